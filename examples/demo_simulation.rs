@@ -1,4 +1,7 @@
-use phenomenological_rendezvous::sim::{run_simulation, SimulationConfig};
+use phenomenological_rendezvous::matching::DimensionWeights;
+use phenomenological_rendezvous::sim::{
+    run_simulation, SimulationBackend, SimulationConfig, SimulationDetail,
+};
 use phenomenological_rendezvous::SemanticRendezvousToken;
 
 fn main() {
@@ -9,6 +12,10 @@ fn main() {
         window_size: 1,
         apply_geo_filter: true,
         geo_filter_factor: 1e6,
+        backend: SimulationBackend::Cpu,
+        detail: SimulationDetail::Summary,
+        metrics: Vec::new(),
+        weights: DimensionWeights::default(),
     };
 
     let srt = SemanticRendezvousToken::from_bytes([1u8; 32]);
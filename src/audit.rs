@@ -0,0 +1,307 @@
+//! Hash-chained audit log of match events.
+//!
+//! Each entry commits to the hash of the entry before it, so editing,
+//! dropping, or reordering any recorded event breaks every hash after it. The
+//! log is written as JSON lines and can be checked with [`verify_log`].
+//!
+//! The chain only provides tamper evidence relative to a trusted final hash;
+//! it is not signed and does not prove who produced the log.
+
+use std::fmt;
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::Result;
+use crate::hex;
+
+/// Previous-entry hash used by the first entry in a log.
+pub const GENESIS_HASH: [u8; 32] = [0u8; 32];
+
+/// A single matching decision recorded in the audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MatchEvent {
+    /// Position of the observation in the measured stream.
+    pub index: u64,
+    /// Distance between measured and target patterns in normalized space,
+    /// as the matcher computed it (see
+    /// [`Matcher::explain`](crate::matching::Matcher::explain)).
+    pub distance: f32,
+    /// Whether the matcher reported a stable match after this observation.
+    pub matched: bool,
+}
+
+/// An audit log entry chaining a match event to its predecessor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AuditEntry {
+    /// Zero-based position of the entry in the log.
+    pub sequence: u64,
+    /// Hex-encoded hash of the previous entry.
+    pub prev_hash: String,
+    /// The recorded event.
+    pub event: MatchEvent,
+    /// Hex-encoded hash committing to this entry and its predecessor.
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// Compute the hash this entry should carry.
+    ///
+    /// The hash is SHA-256 over the previous hash bytes, the big-endian
+    /// sequence number, and the JSON encoding of the event.
+    fn expected_hash(
+        sequence: u64,
+        prev_hash: &[u8; 32],
+        event: &MatchEvent,
+    ) -> Result<[u8; 32]> {
+        let mut hasher = Sha256::new();
+        hasher.update(prev_hash);
+        hasher.update(sequence.to_be_bytes());
+        hasher.update(serde_json::to_vec(event)?);
+        Ok(hasher.finalize().into())
+    }
+}
+
+/// Append-only writer for hash-chained match events.
+///
+/// Entries are written as one JSON object per line.
+#[derive(Debug)]
+pub struct AuditLog<W: Write> {
+    /// Destination for serialized entries.
+    writer: W,
+    /// Sequence number assigned to the next entry.
+    next_sequence: u64,
+    /// Hash of the most recently written entry.
+    head: [u8; 32],
+}
+
+impl<W: Write> AuditLog<W> {
+    /// Start a new audit log writing to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            next_sequence: 0,
+            head: GENESIS_HASH,
+        }
+    }
+
+    /// Append an event, returning the entry that was written.
+    ///
+    /// Events with a NaN or infinite distance are rejected, since JSON would
+    /// write them as `null` and the entry could never verify.
+    pub fn append(&mut self, event: MatchEvent) -> Result<AuditEntry> {
+        if !event.distance.is_finite() {
+            return Err(AuditError::NonFiniteDistance { index: event.index }.into());
+        }
+        let hash = AuditEntry::expected_hash(self.next_sequence, &self.head, &event)?;
+        let entry = AuditEntry {
+            sequence: self.next_sequence,
+            prev_hash: hex::encode(&self.head),
+            event,
+            hash: hex::encode(&hash),
+        };
+        serde_json::to_writer(&mut self.writer, &entry)?;
+        self.writer.write_all(b"\n")?;
+
+        self.next_sequence += 1;
+        self.head = hash;
+        Ok(entry)
+    }
+
+    /// Hex-encoded hash of the most recent entry (the genesis hash if empty).
+    pub fn head_hash(&self) -> String {
+        hex::encode(&self.head)
+    }
+
+    /// Flush buffered entries and return the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Summary of a successfully verified audit log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditSummary {
+    /// Number of entries in the log.
+    pub entries: u64,
+    /// Hex-encoded hash of the last entry (the genesis hash if empty).
+    pub head_hash: String,
+}
+
+/// Verify a sequence of entries, checking sequence numbers and hash links.
+pub fn verify_entries<'a, I>(entries: I) -> Result<AuditSummary>
+where
+    I: IntoIterator<Item = &'a AuditEntry>,
+{
+    let mut head = GENESIS_HASH;
+    let mut count = 0u64;
+
+    for entry in entries {
+        if entry.sequence != count {
+            return Err(AuditError::SequenceGap {
+                expected: count,
+                found: entry.sequence,
+            }
+            .into());
+        }
+        if entry.prev_hash != hex::encode(&head) {
+            return Err(AuditError::BrokenChain {
+                sequence: entry.sequence,
+            }
+            .into());
+        }
+        let expected = AuditEntry::expected_hash(entry.sequence, &head, &entry.event)?;
+        if entry.hash != hex::encode(&expected) {
+            return Err(AuditError::HashMismatch {
+                sequence: entry.sequence,
+            }
+            .into());
+        }
+        head = expected;
+        count += 1;
+    }
+
+    Ok(AuditSummary {
+        entries: count,
+        head_hash: hex::encode(&head),
+    })
+}
+
+/// Read a JSON-lines audit log and verify its hash chain.
+///
+/// Blank lines are ignored.
+pub fn verify_log<R: BufRead>(reader: R) -> Result<AuditSummary> {
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(serde_json::from_str::<AuditEntry>(&line)?);
+    }
+    verify_entries(&entries)
+}
+
+/// Errors returned when an event cannot be logged or a log fails
+/// verification.
+#[derive(Debug)]
+pub enum AuditError {
+    /// An entry's sequence number did not follow its predecessor.
+    SequenceGap {
+        /// Sequence number that was expected.
+        expected: u64,
+        /// Sequence number that was found.
+        found: u64,
+    },
+    /// An entry's `prev_hash` did not match the preceding entry's hash.
+    BrokenChain {
+        /// Sequence number of the offending entry.
+        sequence: u64,
+    },
+    /// An entry's `hash` did not match its contents.
+    HashMismatch {
+        /// Sequence number of the offending entry.
+        sequence: u64,
+    },
+    /// An event passed to [`AuditLog::append`] had a NaN or infinite
+    /// distance.
+    NonFiniteDistance {
+        /// Stream index of the rejected event.
+        index: u64,
+    },
+}
+
+impl fmt::Display for AuditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SequenceGap { expected, found } => {
+                write!(f, "expected audit entry {expected}, found {found}")
+            }
+            Self::BrokenChain { sequence } => {
+                write!(f, "audit entry {sequence} does not link to its predecessor")
+            }
+            Self::HashMismatch { sequence } => {
+                write!(f, "audit entry {sequence} hash does not match its contents")
+            }
+            Self::NonFiniteDistance { index } => {
+                write!(f, "match event {index} has a non-finite distance")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuditError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    fn sample_log() -> Vec<u8> {
+        let mut log = AuditLog::new(Vec::new());
+        for index in 0..4 {
+            log.append(MatchEvent {
+                index,
+                distance: 0.1 * index as f32,
+                matched: index >= 2,
+            })
+            .expect("append");
+        }
+        log.finish().expect("finish")
+    }
+
+    #[test]
+    fn written_log_verifies() {
+        let bytes = sample_log();
+        let summary = verify_log(bytes.as_slice()).expect("valid log");
+        assert_eq!(summary.entries, 4);
+    }
+
+    #[test]
+    fn edited_event_is_detected() {
+        let text = String::from_utf8(sample_log()).expect("utf8");
+        let tampered = text.replacen("\"matched\":false", "\"matched\":true", 1);
+        let err = verify_log(tampered.as_bytes()).expect_err("tampered log");
+        assert!(matches!(
+            err,
+            Error::Audit(AuditError::HashMismatch { sequence: 0 })
+        ));
+    }
+
+    #[test]
+    fn dropped_entry_is_detected() {
+        let text = String::from_utf8(sample_log()).expect("utf8");
+        let without_second: Vec<&str> = text
+            .lines()
+            .enumerate()
+            .filter(|(i, _)| *i != 1)
+            .map(|(_, line)| line)
+            .collect();
+        let err = verify_log(without_second.join("\n").as_bytes()).expect_err("gap");
+        assert!(matches!(
+            err,
+            Error::Audit(AuditError::SequenceGap {
+                expected: 1,
+                found: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn non_finite_distance_is_rejected() {
+        let mut log = AuditLog::new(Vec::new());
+        let err = log
+            .append(MatchEvent {
+                index: 7,
+                distance: f32::NAN,
+                matched: false,
+            })
+            .expect_err("NaN distance");
+        assert!(matches!(err, Error::Audit(AuditError::NonFiniteDistance { index: 7 })));
+        assert!(log.finish().expect("finish").is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+//! Structure-of-arrays pattern containers.
+//!
+//! [`PatternBatch`] stores each dimension of many patterns in its own
+//! contiguous `Vec<f32>`, so normalization and distance computations run as
+//! tight per-column loops the compiler can vectorize, and columns can be
+//! handed to GPU buffers or statistics code without reshuffling.
+
+use crate::matching::DimensionWeights;
+use crate::pattern::{
+    normalized_to_array, pattern_from_array, pattern_to_array, DimensionInfo, NormalizedPattern,
+    SubmodalityPattern, DIMENSIONS,
+};
+
+/// Raw patterns stored column by column, in field declaration order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PatternBatch {
+    /// One column per dimension, all of equal length.
+    columns: [Vec<f32>; 9],
+}
+
+impl PatternBatch {
+    /// Create an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty batch with room for `capacity` patterns.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            columns: std::array::from_fn(|_| Vec::with_capacity(capacity)),
+        }
+    }
+
+    /// Number of patterns in the batch.
+    pub fn len(&self) -> usize {
+        self.columns[0].len()
+    }
+
+    /// Whether the batch holds no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append a pattern.
+    pub fn push(&mut self, pattern: &SubmodalityPattern) {
+        for (column, value) in self.columns.iter_mut().zip(pattern_to_array(pattern)) {
+            column.push(value);
+        }
+    }
+
+    /// The pattern at `index`, if any.
+    pub fn get(&self, index: usize) -> Option<SubmodalityPattern> {
+        (index < self.len()).then(|| pattern_from_array(self.columns.each_ref().map(|c| c[index])))
+    }
+
+    /// Values of dimension `dimension` (in field declaration order).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dimension` is 9 or more.
+    pub fn column(&self, dimension: usize) -> &[f32] {
+        &self.columns[dimension]
+    }
+
+    /// All nine columns in field declaration order.
+    pub fn columns(&self) -> &[Vec<f32>; 9] {
+        &self.columns
+    }
+
+    /// Iterate over the patterns in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = SubmodalityPattern> + '_ {
+        (0..self.len()).filter_map(|index| self.get(index))
+    }
+
+    /// Normalize every pattern, column by column.
+    ///
+    /// Produces the same values as [`SubmodalityPattern::normalize`].
+    pub fn normalize(&self) -> NormalizedBatch {
+        let columns = std::array::from_fn(|dimension| {
+            let DimensionInfo { min, max, .. } = DIMENSIONS[dimension];
+            let span = max - min;
+            self.columns[dimension]
+                .iter()
+                .map(|value| ((value - min) / span).clamp(0.0, 1.0))
+                .collect()
+        });
+        NormalizedBatch { columns }
+    }
+}
+
+impl From<&[SubmodalityPattern]> for PatternBatch {
+    fn from(patterns: &[SubmodalityPattern]) -> Self {
+        let mut batch = Self::with_capacity(patterns.len());
+        for pattern in patterns {
+            batch.push(pattern);
+        }
+        batch
+    }
+}
+
+impl FromIterator<SubmodalityPattern> for PatternBatch {
+    fn from_iter<I: IntoIterator<Item = SubmodalityPattern>>(iter: I) -> Self {
+        let mut batch = Self::new();
+        for pattern in iter {
+            batch.push(&pattern);
+        }
+        batch
+    }
+}
+
+impl<'a> FromIterator<&'a SubmodalityPattern> for PatternBatch {
+    fn from_iter<I: IntoIterator<Item = &'a SubmodalityPattern>>(iter: I) -> Self {
+        let mut batch = Self::new();
+        for pattern in iter {
+            batch.push(pattern);
+        }
+        batch
+    }
+}
+
+/// Normalized patterns stored column by column, produced by
+/// [`PatternBatch::normalize`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NormalizedBatch {
+    /// One column per dimension, all of equal length, with values in `[0, 1]`.
+    columns: [Vec<f32>; 9],
+}
+
+impl NormalizedBatch {
+    /// Number of patterns in the batch.
+    pub fn len(&self) -> usize {
+        self.columns[0].len()
+    }
+
+    /// Whether the batch holds no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Values of dimension `dimension` (in field declaration order).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dimension` is 9 or more.
+    pub fn column(&self, dimension: usize) -> &[f32] {
+        &self.columns[dimension]
+    }
+
+    /// Euclidean distance from every pattern to `target`.
+    ///
+    /// Matches [`euclidean_distance`](crate::matching::euclidean_distance)
+    /// applied to each pattern in turn.
+    pub fn distances(&self, target: &NormalizedPattern) -> Vec<f32> {
+        self.weighted_distances(target, &DimensionWeights::default())
+    }
+
+    /// Weighted distance from every pattern to `target`.
+    ///
+    /// Matches [`weighted_distance`](crate::matching::weighted_distance)
+    /// applied to each pattern in turn.
+    pub fn weighted_distances(
+        &self,
+        target: &NormalizedPattern,
+        weights: &DimensionWeights,
+    ) -> Vec<f32> {
+        let mut sums = self.squared_distances(target, weights);
+        for sum in &mut sums {
+            *sum = sum.sqrt();
+        }
+        sums
+    }
+
+    /// Number of patterns within `epsilon` of `target`.
+    pub fn count_within(&self, target: &NormalizedPattern, epsilon: f32) -> usize {
+        self.count_within_weighted(target, &DimensionWeights::default(), epsilon)
+    }
+
+    /// Number of patterns within weighted distance `epsilon` of `target`.
+    pub fn count_within_weighted(
+        &self,
+        target: &NormalizedPattern,
+        weights: &DimensionWeights,
+        epsilon: f32,
+    ) -> usize {
+        let limit = epsilon * epsilon;
+        self.squared_distances(target, weights)
+            .into_iter()
+            .filter(|&sum| sum <= limit)
+            .count()
+    }
+
+    /// Weighted squared distances, accumulated one column at a time.
+    fn squared_distances(
+        &self,
+        target: &NormalizedPattern,
+        weights: &DimensionWeights,
+    ) -> Vec<f32> {
+        let mut sums = vec![0.0f32; self.len()];
+        for ((column, center), weight) in self
+            .columns
+            .iter()
+            .zip(normalized_to_array(target))
+            .zip(weights.values())
+        {
+            for (sum, value) in sums.iter_mut().zip(column) {
+                let diff = value - center;
+                *sum += weight * diff * diff;
+            }
+        }
+        sums
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::{euclidean_distance, weighted_distance};
+    use crate::pattern::sampling::random_pattern;
+
+    #[test]
+    fn batch_round_trips_patterns() {
+        let mut rng = rand::thread_rng();
+        let patterns: Vec<_> = (0..5).map(|_| random_pattern(&mut rng)).collect();
+        let batch = PatternBatch::from(patterns.as_slice());
+        assert_eq!(batch.len(), 5);
+        assert_eq!(batch.column(4)[2], patterns[2].tempo);
+        assert_eq!(batch.iter().collect::<Vec<_>>(), patterns);
+        assert_eq!(batch.get(5), None);
+    }
+
+    #[test]
+    fn kernels_match_scalar_implementation() {
+        let mut rng = rand::thread_rng();
+        let mut patterns: Vec<_> = (0..100).map(|_| random_pattern(&mut rng)).collect();
+        patterns[0].pitch = 1e6;
+        let target = random_pattern(&mut rng).normalize();
+        let normalized = patterns.iter().collect::<PatternBatch>().normalize();
+        let distances = normalized.distances(&target);
+        for (pattern, distance) in patterns.iter().zip(&distances) {
+            assert_eq!(*distance, euclidean_distance(&pattern.normalize(), &target));
+        }
+        let within = distances.iter().filter(|&&d| d <= 1.0).count();
+        assert_eq!(normalized.count_within(&target, 1.0), within);
+        assert!(PatternBatch::new().normalize().is_empty());
+    }
+
+    #[test]
+    fn weighted_kernels_match_scalar_implementation() {
+        let mut rng = rand::thread_rng();
+        let patterns: Vec<_> = (0..50).map(|_| random_pattern(&mut rng)).collect();
+        let target = random_pattern(&mut rng).normalize();
+        let weights =
+            DimensionWeights::new([2.0, 0.5, 1.0, 0.0, 1.0, 3.0, 1.0, 1.0, 0.25]).expect("weights");
+        let normalized = patterns.iter().collect::<PatternBatch>().normalize();
+        let distances = normalized.weighted_distances(&target, &weights);
+        for (pattern, distance) in patterns.iter().zip(&distances) {
+            let expected = weighted_distance(&pattern.normalize(), &target, &weights);
+            assert!((distance - expected).abs() < 1e-6);
+        }
+        let within = distances.iter().filter(|&&d| d <= 0.8).count();
+        assert_eq!(normalized.count_within_weighted(&target, &weights, 0.8), within);
+    }
+}
@@ -0,0 +1,460 @@
+//! Per-deployment sensor ranges.
+//!
+//! [`SubmodalityPattern::normalize`] maps raw values into `[0, 1]` using the
+//! reference ranges in [`crate::pattern`]. Sensors rarely match those exactly
+//! (a thermal camera might read -5 to 60 °C), so a [`CalibrationProfile`]
+//! overrides the range of any subset of dimensions and is passed to
+//! [`SubmodalityPattern::normalize_with`]. Profiles serialize to JSON so they
+//! can be stored and shipped alongside the datasets recorded with them.
+//!
+//! Each range also carries a [`NormalizationPolicy`]. Linear mapping over
+//! 20–20000 Hz leaves the musically meaningful pitch range in a sliver of
+//! `[0, 1]`, so pitch (and tempo) can be mapped logarithmically or on a mel
+//! scale instead. The policy is part of the serialized profile, so peers
+//! that share a profile also share the mapping.
+
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::pattern::{
+    normalized_from_array, normalized_to_array, pattern_from_array, pattern_to_array,
+    DimensionInfo, NormalizedPattern, PatternFieldError, SubmodalityPattern, DIMENSIONS,
+};
+
+/// How raw values within a [`DimensionRange`] map into `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum NormalizationPolicy {
+    /// `(value - min) / (max - min)`.
+    #[default]
+    Linear,
+    /// `ln(value / min) / ln(max / min)`; requires `min > 0`.
+    Logarithmic,
+    /// Linear on the mel scale, `2595 * log10(1 + value / 700)`: close to
+    /// linear below 700 and logarithmic above. Requires `min > -700`.
+    Mel,
+}
+
+impl NormalizationPolicy {
+    /// Map `value` from `[min, max]` into `[0, 1]`, clamping out-of-range
+    /// input.
+    pub fn apply(&self, value: f32, min: f32, max: f32) -> f32 {
+        let fraction = match self {
+            Self::Linear => (value - min) / (max - min),
+            Self::Logarithmic => (value.max(min) / min).ln() / (max / min).ln(),
+            Self::Mel => (mel(value.max(min)) - mel(min)) / (mel(max) - mel(min)),
+        };
+        fraction.clamp(0.0, 1.0)
+    }
+
+    /// Map `fraction` in `[0, 1]` back into `[min, max]`; the inverse of
+    /// [`apply`](Self::apply). Out-of-range fractions are clamped first.
+    pub fn invert(&self, fraction: f32, min: f32, max: f32) -> f32 {
+        let fraction = fraction.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => min + fraction * (max - min),
+            Self::Logarithmic => min * (max / min).powf(fraction),
+            Self::Mel => mel_inverse(mel(min) + fraction * (mel(max) - mel(min))),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn is_linear(&self) -> bool {
+        *self == Self::Linear
+    }
+}
+
+fn mel(value: f32) -> f32 {
+    2595.0 * (1.0 + value / 700.0).log10()
+}
+
+fn mel_inverse(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+/// Raw value range of one dimension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DimensionRange {
+    /// Raw value mapped to `0.0`.
+    pub min: f32,
+    /// Raw value mapped to `1.0`.
+    pub max: f32,
+    /// Mapping between `min` and `max`; omitted from JSON when linear.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "NormalizationPolicy::is_linear")
+    )]
+    pub policy: NormalizationPolicy,
+}
+
+impl DimensionRange {
+    /// Create a linear range; see [`CalibrationProfile::validate`] for the
+    /// rules.
+    pub fn new(min: f32, max: f32) -> Self {
+        Self {
+            min,
+            max,
+            policy: NormalizationPolicy::Linear,
+        }
+    }
+
+    /// Return this range with a different normalization policy.
+    pub fn with_policy(mut self, policy: NormalizationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Map a raw value into `[0, 1]` under this range's policy.
+    pub fn normalize(&self, value: f32) -> f32 {
+        self.policy.apply(value, self.min, self.max)
+    }
+
+    /// Map a normalized value back into raw units under this range's policy.
+    pub fn denormalize(&self, fraction: f32) -> f32 {
+        self.policy.invert(fraction, self.min, self.max)
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        let domain = match self.policy {
+            NormalizationPolicy::Linear => true,
+            NormalizationPolicy::Logarithmic => self.min > 0.0,
+            NormalizationPolicy::Mel => self.min > -700.0,
+        };
+        domain && self.min.is_finite() && self.max.is_finite() && self.min < self.max
+    }
+}
+
+/// Range overrides for some or all pattern dimensions.
+///
+/// Dimensions without an override keep the reference range, so the default
+/// profile normalizes exactly like [`SubmodalityPattern::normalize`].
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CalibrationProfile {
+    /// Free-form label, such as the sensor rig the profile was measured on.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub name: Option<String>,
+    /// Range of `brightness`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub brightness: Option<DimensionRange>,
+    /// Range of `color_temp`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub color_temp: Option<DimensionRange>,
+    /// Range of `focal_distance`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub focal_distance: Option<DimensionRange>,
+    /// Range of `volume`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub volume: Option<DimensionRange>,
+    /// Range of `tempo`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub tempo: Option<DimensionRange>,
+    /// Range of `pitch`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub pitch: Option<DimensionRange>,
+    /// Range of `temperature`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub temperature: Option<DimensionRange>,
+    /// Range of `movement`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub movement: Option<DimensionRange>,
+    /// Range of `arousal`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub arousal: Option<DimensionRange>,
+}
+
+impl CalibrationProfile {
+    /// Create a profile that overrides nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Effective ranges in field declaration order, falling back to the
+    /// reference ranges for dimensions without an override.
+    pub fn ranges(&self) -> [DimensionRange; 9] {
+        let overrides = self.overrides();
+        std::array::from_fn(|i| {
+            let DimensionInfo { min, max, .. } = DIMENSIONS[i];
+            overrides[i].unwrap_or(DimensionRange::new(min, max))
+        })
+    }
+
+    /// Return this profile with `policy` applied to the named dimension,
+    /// keeping its current (overridden or reference) range.
+    ///
+    /// Logarithmic tempo needs a positive minimum, so override the tempo
+    /// range first.
+    pub fn with_policy(
+        mut self,
+        dimension: &str,
+        policy: NormalizationPolicy,
+    ) -> Result<Self, CalibrationError> {
+        let index = DIMENSIONS
+            .iter()
+            .position(|info| info.key == dimension)
+            .ok_or_else(|| CalibrationError::UnknownDimension(dimension.to_string()))?;
+        let range = self.ranges()[index].with_policy(policy);
+        *self.override_mut(index) = Some(range);
+        Ok(self)
+    }
+
+    /// Check that every override is finite with `min < max` and within the
+    /// domain of its policy.
+    pub fn validate(&self) -> Result<(), CalibrationError> {
+        match self
+            .overrides()
+            .iter()
+            .zip(DIMENSIONS)
+            .find(|(range, _)| range.is_some_and(|range| !range.is_valid()))
+        {
+            Some((_, info)) => Err(CalibrationError::InvalidRange(info.key)),
+            None => Ok(()),
+        }
+    }
+
+    fn overrides(&self) -> [Option<DimensionRange>; 9] {
+        [
+            self.brightness,
+            self.color_temp,
+            self.focal_distance,
+            self.volume,
+            self.tempo,
+            self.pitch,
+            self.temperature,
+            self.movement,
+            self.arousal,
+        ]
+    }
+
+    fn override_mut(&mut self, index: usize) -> &mut Option<DimensionRange> {
+        match index {
+            0 => &mut self.brightness,
+            1 => &mut self.color_temp,
+            2 => &mut self.focal_distance,
+            3 => &mut self.volume,
+            4 => &mut self.tempo,
+            5 => &mut self.pitch,
+            6 => &mut self.temperature,
+            7 => &mut self.movement,
+            8 => &mut self.arousal,
+            _ => unreachable!("patterns have nine dimensions"),
+        }
+    }
+}
+
+impl SubmodalityPattern {
+    /// [`validate`](Self::validate) against the ranges in `profile`.
+    pub fn validate_with(
+        &self,
+        profile: &CalibrationProfile,
+    ) -> Result<(), Vec<PatternFieldError>> {
+        self.check_ranges(profile.ranges().map(|range| (range.min, range.max)))
+    }
+
+    /// Normalize into `[0, 1]` using the ranges and policies in `profile`.
+    ///
+    /// Values outside a range are clamped. Call
+    /// [`CalibrationProfile::validate`] on profiles loaded from untrusted
+    /// files first; an empty or non-finite range yields meaningless output.
+    pub fn normalize_with(&self, profile: &CalibrationProfile) -> NormalizedPattern {
+        let raw = pattern_to_array(self);
+        let ranges = profile.ranges();
+        normalized_from_array(std::array::from_fn(|i| ranges[i].normalize(raw[i])))
+    }
+}
+
+impl NormalizedPattern {
+    /// Map back into raw units using the ranges and policies in `profile`;
+    /// the inverse of [`SubmodalityPattern::normalize_with`].
+    ///
+    /// Use it to render a derived target for display, actuation, or logging
+    /// on a calibrated rig. Values outside `[0, 1]` are clamped first.
+    pub fn denormalize_with(&self, profile: &CalibrationProfile) -> SubmodalityPattern {
+        let normalized = normalized_to_array(self);
+        let ranges = profile.ranges();
+        pattern_from_array(std::array::from_fn(|i| {
+            ranges[i].denormalize(normalized[i])
+        }))
+    }
+}
+
+/// Errors returned when validating a [`CalibrationProfile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CalibrationError {
+    /// The named dimension's range is empty, inverted, non-finite, or
+    /// outside the domain of its policy.
+    InvalidRange(&'static str),
+    /// No pattern dimension has this name.
+    UnknownDimension(String),
+}
+
+impl fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRange(name) => write!(
+                f,
+                "calibration range for '{name}' must be finite with min < max and within its policy's domain"
+            ),
+            Self::UnknownDimension(name) => write!(f, "unknown pattern dimension '{name}'"),
+        }
+    }
+}
+
+impl std::error::Error for CalibrationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_profile_matches_reference_normalization() {
+        let pattern = SubmodalityPattern {
+            brightness: 0.4,
+            color_temp: 6500.0,
+            tempo: 150.0,
+            pitch: 3000.0,
+            temperature: 50.0,
+            ..SubmodalityPattern::zeros()
+        };
+        assert_eq!(
+            pattern.normalize_with(&CalibrationProfile::new()),
+            pattern.normalize()
+        );
+    }
+
+    #[test]
+    fn overrides_change_only_their_dimension() {
+        let profile = CalibrationProfile {
+            temperature: Some(DimensionRange::new(-5.0, 60.0)),
+            ..CalibrationProfile::new()
+        };
+        assert_eq!(profile.validate(), Ok(()));
+        let pattern = SubmodalityPattern {
+            temperature: 27.5,
+            ..SubmodalityPattern::zeros()
+        };
+        let calibrated = pattern.normalize_with(&profile);
+        assert!((calibrated.temperature - 0.5).abs() < 1e-6);
+        assert_eq!(
+            NormalizedPattern {
+                temperature: calibrated.temperature,
+                ..pattern.normalize()
+            },
+            calibrated
+        );
+
+        let inverted = CalibrationProfile {
+            pitch: Some(DimensionRange::new(100.0, 100.0)),
+            ..CalibrationProfile::new()
+        };
+        assert_eq!(
+            inverted.validate(),
+            Err(CalibrationError::InvalidRange("pitch"))
+        );
+    }
+
+    #[test]
+    fn policies_spread_the_musical_pitch_range() {
+        let profile = CalibrationProfile::new()
+            .with_policy("pitch", NormalizationPolicy::Logarithmic)
+            .expect("pitch");
+        assert_eq!(profile.validate(), Ok(()));
+        let a4 = SubmodalityPattern::zeros();
+        let a5 = SubmodalityPattern {
+            pitch: 880.0,
+            ..a4.clone()
+        };
+        let linear = a5.normalize().pitch - a4.normalize().pitch;
+        let octave = a5.normalize_with(&profile).pitch - a4.normalize_with(&profile).pitch;
+        assert!(linear < 0.03);
+        assert!((octave - 0.1).abs() < 1e-3);
+
+        let mel = DimensionRange::new(20.0, 20_000.0).with_policy(NormalizationPolicy::Mel);
+        assert_eq!(mel.normalize(20.0), 0.0);
+        assert_eq!(mel.normalize(20_000.0), 1.0);
+        assert!(mel.normalize(440.0) > 0.13);
+
+        assert_eq!(
+            CalibrationProfile::new()
+                .with_policy("tempo", NormalizationPolicy::Logarithmic)
+                .and_then(|profile| profile.validate()),
+            Err(CalibrationError::InvalidRange("tempo"))
+        );
+        let tempo = CalibrationProfile {
+            tempo: Some(DimensionRange::new(30.0, 300.0)),
+            ..CalibrationProfile::new()
+        }
+        .with_policy("tempo", NormalizationPolicy::Logarithmic)
+        .expect("tempo");
+        assert_eq!(tempo.validate(), Ok(()));
+        assert_eq!(tempo.ranges()[4].min, 30.0);
+        assert_eq!(
+            CalibrationProfile::new().with_policy("smell", NormalizationPolicy::Mel),
+            Err(CalibrationError::UnknownDimension("smell".into()))
+        );
+    }
+
+    #[test]
+    fn denormalize_with_inverts_every_policy() {
+        let profile = CalibrationProfile {
+            temperature: Some(DimensionRange::new(-5.0, 60.0)),
+            tempo: Some(
+                DimensionRange::new(30.0, 300.0).with_policy(NormalizationPolicy::Logarithmic),
+            ),
+            pitch: Some(DimensionRange::new(20.0, 20_000.0).with_policy(NormalizationPolicy::Mel)),
+            ..CalibrationProfile::new()
+        };
+        let pattern = SubmodalityPattern {
+            temperature: 41.0,
+            tempo: 120.0,
+            pitch: 1000.0,
+            ..SubmodalityPattern::zeros()
+        };
+        let restored = pattern.normalize_with(&profile).denormalize_with(&profile);
+        assert!((restored.temperature - 41.0).abs() < 1e-4);
+        assert!((restored.tempo - 120.0).abs() < 1e-3);
+        assert!((restored.pitch - 1000.0).abs() < 1e-2);
+        assert_eq!(restored.brightness, pattern.brightness);
+        assert_eq!(
+            pattern
+                .normalize()
+                .denormalize_with(&CalibrationProfile::new()),
+            pattern.normalize().denormalize()
+        );
+        let range = DimensionRange::new(30.0, 300.0).with_policy(NormalizationPolicy::Logarithmic);
+        assert_eq!(range.denormalize(-1.0), 30.0);
+        assert!((range.denormalize(0.5) - 94.868_33).abs() < 1e-3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn profiles_serialize_only_overrides() {
+        let json = r#"{"name": "thermal-rig", "temperature": {"min": -5.0, "max": 60.0}}"#;
+        let profile: CalibrationProfile = serde_json::from_str(json).expect("parse");
+        assert_eq!(profile.name.as_deref(), Some("thermal-rig"));
+        assert_eq!(profile.temperature, Some(DimensionRange::new(-5.0, 60.0)));
+        assert_eq!(profile.pitch, None);
+        assert_eq!(
+            serde_json::to_string(&profile).expect("serialize"),
+            r#"{"name":"thermal-rig","temperature":{"min":-5.0,"max":60.0}}"#
+        );
+
+        let json = r#"{"pitch": {"min": 20.0, "max": 20000.0, "policy": "mel"}}"#;
+        let profile: CalibrationProfile = serde_json::from_str(json).expect("parse");
+        assert_eq!(
+            profile.pitch.map(|range| range.policy),
+            Some(NormalizationPolicy::Mel)
+        );
+        assert!(serde_json::to_string(&profile)
+            .expect("serialize")
+            .contains(r#""policy":"mel""#));
+    }
+}
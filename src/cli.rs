@@ -1,16 +1,55 @@
 //! CLI scaffolding for offline testing.
 
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use clap::{Parser, Subcommand};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json::json;
 
-use phenomenological_rendezvous::matching::{MatchingConfig, Matcher};
-use phenomenological_rendezvous::pattern::SubmodalityPattern;
-use phenomenological_rendezvous::sim::{run_simulation, SimulationConfig};
-use phenomenological_rendezvous::srt::{pattern_from_srt, SemanticRendezvousToken};
+use phenomenological_rendezvous::audit::{AuditLog, MatchEvent};
+use phenomenological_rendezvous::calibration::CalibrationProfile;
+use phenomenological_rendezvous::conformance;
+use phenomenological_rendezvous::error::{Error, ResultExt};
+use phenomenological_rendezvous::diagnostics::{self, Severity};
+use phenomenological_rendezvous::datasets::{
+    load_trajectories, DatasetMapping, PatternTrajectory, PeerModel,
+};
+use phenomenological_rendezvous::hex;
+use phenomenological_rendezvous::ingest::{self, read_csv_patterns, CsvOptions, JsonMode, NumberMode};
+#[cfg(feature = "viz")]
+use phenomenological_rendezvous::matching::euclidean_distance;
+use phenomenological_rendezvous::matching::{
+    DimensionWeights, DuplicatePolicy, DurationMatcher, MatchingConfig, Matcher,
+};
+use phenomenological_rendezvous::pattern::series_codec::{
+    read_series, Compression, SeriesReader, SeriesWriter,
+};
+use phenomenological_rendezvous::pattern::{
+    migrate, SanitizePolicy, SubmodalityPattern, TimedPattern, DIMENSIONS,
+};
+use phenomenological_rendezvous::registry::DimensionRegistry;
+use phenomenological_rendezvous::series::Interpolation;
+use phenomenological_rendezvous::sim::{
+    run_simulation_with_prior, run_sweep, try_run_simulation, DistanceMetric, MetricSpec,
+    SimulationBackend, SimulationConfig, SimulationDetail,
+};
+use phenomenological_rendezvous::srt::armor::{ArmorError, ArmoredToken};
+#[cfg(feature = "tui")]
+use phenomenological_rendezvous::srt::oracle::TimeOracle;
+#[cfg(any(feature = "viz", feature = "tui"))]
+use phenomenological_rendezvous::srt::pattern_from_srt;
+use phenomenological_rendezvous::srt::provider::{CommandProvider, ProviderError};
+use phenomenological_rendezvous::srt::record::{RecordError, SrtRecord};
+use phenomenological_rendezvous::srt::target::TargetError;
+use phenomenological_rendezvous::srt::{
+    context_salt, DerivationAlgorithm, EncodedTarget, SemanticRendezvousToken, SrtProvider,
+};
+#[cfg(feature = "viz")]
+use phenomenological_rendezvous::viz;
 
 /// Command-line interface for Phenomenological Rendezvous experiments.
 #[derive(Debug, Parser)]
@@ -24,45 +63,182 @@ pub struct CliArgs {
 pub enum Commands {
     /// Derive a target pattern from SRT + salt and write JSON output.
     #[command(
-        long_about = "Derive a target pattern from an SRT and salt, then write JSON output.\n\nExample:\n  phenorv encode-target --srt-hex <HEX> --salt-string \"oracle-state\" --output target.json"
+        long_about = "Derive a target pattern from an SRT and salt, then write JSON output.\n\nThe output carries the salt and an HMAC tag keyed by the SRT, which match-stream --target checks before using the file.\n\nExample:\n  phenorv encode-target --srt-hex <HEX> --salt-string \"oracle-state\" --output target.json"
     )]
     EncodeTarget {
         /// SRT hex string (64 hex chars).
-        #[arg(long)]
-        srt_hex: String,
+        #[arg(long, required_unless_present_any = ["srt_command", "srt_record"])]
+        srt_hex: Option<String>,
+        /// Command that prints the HMAC-SHA256 of stdin under the SRT, for
+        /// tokens held in a keystore. Only the default algorithm is supported.
+        #[arg(long, conflicts_with_all = ["srt_hex", "srt_record"])]
+        srt_command: Option<String>,
+        /// JSON SrtRecord file; refused if expired, not yet valid, or revoked.
+        #[arg(long, conflicts_with = "srt_hex")]
+        srt_record: Option<PathBuf>,
         /// Salt as hex string.
         #[arg(long, conflicts_with = "salt_string")]
         salt_hex: Option<String>,
         /// Salt as UTF-8 string.
         #[arg(long)]
         salt_string: Option<String>,
+        /// Keyed PRF used to derive the target; recorded in the output.
+        #[arg(long, value_enum, default_value_t = AlgorithmKind::HmacSha256)]
+        algorithm: AlgorithmKind,
+        /// Application context label that separates this deployment's targets.
+        #[arg(long)]
+        context: Option<String>,
         /// Output file (defaults to stdout).
         #[arg(long)]
         output: Option<PathBuf>,
     },
     /// Match a stream of measured patterns against a derived target.
     #[command(
-        long_about = "Match a JSONL stream of SubmodalityPattern values against a derived target.\n\nExample:\n  phenorv match-stream --srt-hex <HEX> --salt-string \"oracle-state\" --epsilon 0.1 --window-size 3 --input examples/measured_example.jsonl"
+        long_about = "Match a JSONL stream of SubmodalityPattern values against a derived target.\n\nInput can be a file, stdin, a named pipe, or (with the `serial` feature) a serial device via --serial and --baud.\n\nExample:\n  phenorv match-stream --srt-hex <HEX> --salt-string \"oracle-state\" --epsilon 0.1 --window-size 3 --input examples/measured_example.jsonl"
     )]
     MatchStream {
         /// SRT hex string (64 hex chars).
-        #[arg(long)]
-        srt_hex: String,
+        #[arg(long, required_unless_present_any = ["srt_command", "srt_record"])]
+        srt_hex: Option<String>,
+        /// Command that prints the HMAC-SHA256 of stdin under the SRT, for
+        /// tokens held in a keystore. Only the default algorithm is supported.
+        #[arg(long, conflicts_with_all = ["srt_hex", "srt_record"])]
+        srt_command: Option<String>,
+        /// JSON SrtRecord file; refused if expired, not yet valid, or revoked.
+        #[arg(long, conflicts_with = "srt_hex")]
+        srt_record: Option<PathBuf>,
         /// Salt as hex string.
         #[arg(long, conflicts_with = "salt_string")]
         salt_hex: Option<String>,
         /// Salt as UTF-8 string.
         #[arg(long)]
         salt_string: Option<String>,
+        /// Target file written by encode-target, used instead of deriving the
+        /// target. Its tag is verified against the SRT; the salt and
+        /// algorithm are read from the file.
+        #[arg(
+            long = "target",
+            value_name = "PATH",
+            conflicts_with_all = ["salt_hex", "salt_string", "context"]
+        )]
+        target_file: Option<PathBuf>,
         /// Matching threshold in normalized space.
+        #[arg(long, required_unless_present = "derive_tolerances")]
+        epsilon: Option<f32>,
+        /// Match each dimension against tolerances derived from the SRT and
+        /// salt instead of a single epsilon.
+        #[arg(long, conflicts_with = "epsilon")]
+        derive_tolerances: bool,
+        /// Keyed PRF used to derive the target (and tolerances).
+        #[arg(long, value_enum, default_value_t = AlgorithmKind::HmacSha256)]
+        algorithm: AlgorithmKind,
+        /// Application context label that separates this deployment's targets.
         #[arg(long)]
-        epsilon: f32,
+        context: Option<String>,
         /// Number of consecutive samples required to match.
-        #[arg(long)]
-        window_size: usize,
+        #[arg(long, required_unless_present = "hold_ms")]
+        window_size: Option<usize>,
+        /// Require matches to hold for this many milliseconds instead of a
+        /// number of samples. Input lines must then be TimedPattern entries.
+        #[arg(long, conflicts_with = "window_size")]
+        hold_ms: Option<u64>,
         /// Input JSONL file with SubmodalityPattern entries. Use "-" for stdin.
+        /// Named pipes (FIFOs) are read as live streams.
+        #[cfg_attr(feature = "serial", arg(long, required_unless_present = "serial"))]
+        #[cfg_attr(not(feature = "serial"), arg(long, required = true))]
+        input: Option<PathBuf>,
+        /// Serial device emitting newline-delimited JSON patterns.
+        #[cfg(feature = "serial")]
+        #[arg(long, conflicts_with = "input")]
+        serial: Option<String>,
+        /// Serial baud rate.
+        #[cfg(feature = "serial")]
+        #[arg(long, default_value_t = 115_200, requires = "serial")]
+        baud: u32,
+        /// Optional file to record a hash-chained audit log of match events.
+        #[arg(long)]
+        audit_log: Option<PathBuf>,
+        /// How to treat exactly repeated observations (a frozen sensor).
+        #[arg(long, value_enum)]
+        duplicates: Option<DuplicateKind>,
+        /// Consecutive repeats tolerated by `--duplicates cap` or `flag`.
+        #[arg(long, default_value_t = 2)]
+        max_repeats: usize,
+        /// Reject unknown or missing JSON fields, non-finite numbers, and
+        /// pattern values outside the reference ranges; with `--sanitize`,
+        /// only in the fields the policy keeps.
+        #[arg(long)]
+        strict: bool,
+        /// Add a per-dimension diff against the target to each non-matching
+        /// line, weighted as the matcher weights it, largest contribution
+        /// first.
+        #[arg(long)]
+        explain: bool,
+        /// Print the measured pattern and its diff against the target as
+        /// aligned tables on stderr for each non-matching line.
+        #[arg(long)]
+        explain_table: bool,
+        /// How to treat non-finite or out-of-range fields instead of silently
+        /// clamping them; offending fields are listed in `"out_of_range"`.
+        #[arg(long, value_enum)]
+        sanitize: Option<SanitizeKind>,
+    },
+    /// Convert delimited pattern data (e.g. CSV exports) into JSONL.
+    #[command(
+        long_about = "Convert delimited pattern data into JSONL SubmodalityPattern records.\n\nThe header row must name all nine pattern fields; other columns are ignored.\n\nExample:\n  phenorv convert --input export.csv --delimiter ';' --locale-tolerant --output measured.jsonl"
+    )]
+    Convert {
+        /// Input delimited text file. Use "-" for stdin.
+        #[arg(long)]
+        input: PathBuf,
+        /// Field delimiter.
+        #[arg(long, default_value_t = ',')]
+        delimiter: char,
+        /// Accept comma decimals, thousands separators, and unit suffixes.
+        #[arg(long)]
+        locale_tolerant: bool,
+        /// Output file (defaults to stdout).
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Record a JSONL stream of timestamped patterns as a compact binary series.
+    #[command(
+        long_about = "Encode TimedPattern JSONL records (a `timestamp` in milliseconds plus the nine pattern fields) in the delta-encoded series format.\n\nValues are stored losslessly; `replay` turns the file back into JSONL.\n\nExample:\n  phenorv record --input session.jsonl --output session.prvs --zstd"
+    )]
+    Record {
+        /// Input JSONL file with TimedPattern entries. Use "-" for stdin.
+        #[arg(long)]
+        input: PathBuf,
+        /// Output series file.
+        #[arg(long)]
+        output: PathBuf,
+        /// Compress the records with zstd (requires the `zstd` feature).
+        #[arg(long)]
+        zstd: bool,
+        /// Reject unknown or missing JSON fields and non-finite numbers.
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Replay a recorded series as JSONL.
+    #[command(
+        long_about = "Decode a series written by `record` into TimedPattern JSONL records.\n\nWith --realtime, samples are emitted at their recorded pace, so the output can drive `match-stream --input -` like a live sensor. With --resample-ms, the series is first resampled to a fixed interval using --interpolation.\n\nExample:\n  phenorv replay --input session.prvs --resample-ms 50 --interpolation catmull-rom --realtime"
+    )]
+    Replay {
+        /// Input series file. Use "-" for stdin.
         #[arg(long)]
         input: PathBuf,
+        /// Output file (defaults to stdout).
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Wait between samples for the time between their timestamps.
+        #[arg(long)]
+        realtime: bool,
+        /// Resample to this fixed interval in milliseconds before emitting.
+        #[arg(long)]
+        resample_ms: Option<u64>,
+        /// Interpolation used between recorded samples when resampling.
+        #[arg(long, value_enum, default_value_t = InterpolationKind::Linear, requires = "resample_ms")]
+        interpolation: InterpolationKind,
     },
     /// Run a Monte Carlo simulation for collision and false rendezvous rates.
     #[command(
@@ -99,7 +275,369 @@ pub enum Commands {
         /// Geographic filter factor (e.g., 1e6).
         #[arg(long, default_value_t = 1e6)]
         geo_filter_factor: f32,
+        /// Optional JSON peer model (from `fit-prior`) to sample peers from.
+        #[arg(long)]
+        prior: Option<PathBuf>,
+        /// Hardware for uniform peer sampling; `gpu` needs the `gpu` feature.
+        #[arg(long, value_enum, default_value_t = BackendKind::Cpu)]
+        backend: BackendKind,
+        /// Include per-trial records (distances, match counts, nearest misses).
+        #[arg(long, value_enum, default_value_t = DetailKind::Summary)]
+        detail: DetailKind,
+        /// Keep a uniform sample of at most this many trial records (implies
+        /// `--detail trials`).
+        #[arg(long)]
+        sample_trials: Option<usize>,
+        /// Also evaluate these unweighted metrics on the same sampled peers
+        /// (comma-separated); weighted sets can be given in `--config`.
+        #[arg(long, value_enum, value_delimiter = ',')]
+        metrics: Vec<MetricKind>,
+        /// Reject unknown or missing JSON fields and non-finite numbers.
+        #[arg(long)]
+        strict: bool,
     },
+    /// Sweep epsilon and window size, reporting simulation results per cell.
+    #[command(
+        long_about = "Run the Monte Carlo simulation over a grid of epsilon and window-size values.\n\nExample:\n  phenorv sweep --srt-hex <HEX> --salt-string \"oracle-state\" --epsilons 0.05,0.1,0.2 --window-sizes 1,3,5"
+    )]
+    Sweep {
+        /// SRT hex string (64 hex chars).
+        #[arg(long)]
+        srt_hex: String,
+        /// Salt as hex string.
+        #[arg(long, conflicts_with = "salt_string")]
+        salt_hex: Option<String>,
+        /// Salt as UTF-8 string.
+        #[arg(long)]
+        salt_string: Option<String>,
+        /// Comma-separated epsilon values.
+        #[arg(long, value_delimiter = ',', required = true)]
+        epsilons: Vec<f32>,
+        /// Comma-separated window sizes.
+        #[arg(long, value_delimiter = ',', required = true)]
+        window_sizes: Vec<usize>,
+        /// Number of peers per trial.
+        #[arg(long, default_value_t = 1000)]
+        num_peers: usize,
+        /// Number of trials per grid cell.
+        #[arg(long, default_value_t = 100)]
+        num_trials: usize,
+        /// Optional SVG heatmap output.
+        #[cfg(feature = "viz")]
+        #[arg(long)]
+        svg: Option<PathBuf>,
+    },
+    /// Render an SVG chart of a measured stream's distance to the target.
+    #[cfg(feature = "viz")]
+    #[command(
+        long_about = "Render distances between a JSONL stream and the derived target as an SVG chart.\n\nExample:\n  phenorv visualize --srt-hex <HEX> --salt-string \"oracle-state\" --epsilon 0.1 --input examples/measured_example.jsonl --output distance.svg"
+    )]
+    Visualize {
+        /// SRT hex string (64 hex chars).
+        #[arg(long)]
+        srt_hex: String,
+        /// Salt as hex string.
+        #[arg(long, conflicts_with = "salt_string")]
+        salt_hex: Option<String>,
+        /// Salt as UTF-8 string.
+        #[arg(long)]
+        salt_string: Option<String>,
+        /// Matching threshold drawn on the chart.
+        #[arg(long)]
+        epsilon: f32,
+        /// Input JSONL file with SubmodalityPattern entries. Use "-" for stdin.
+        #[arg(long)]
+        input: PathBuf,
+        /// Chart type.
+        #[arg(long, value_enum, default_value_t = ChartKind::Distance)]
+        kind: ChartKind,
+        /// Number of histogram bins.
+        #[arg(long, default_value_t = 20)]
+        bins: usize,
+        /// Output SVG file.
+        #[arg(long)]
+        output: PathBuf,
+        /// Reject unknown or missing JSON fields and non-finite numbers.
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Print metadata for the nine pattern dimensions as JSON.
+    #[command(
+        long_about = "Print each pattern dimension's key, display name, unit, range, and whether it is circular, in field order, so UIs can build forms and validation without hardcoding field names.\n\nExample:\n  phenorv dimensions"
+    )]
+    Dimensions,
+    /// Print JSON Schemas for serialized types.
+    #[cfg(feature = "schemars")]
+    #[command(
+        long_about = "Print JSON Schemas for patterns, configs, results, and protocol messages.\n\nWithout --type, prints an object mapping every type name to its schema.\n\nExample:\n  phenorv schema --type SubmodalityPattern"
+    )]
+    Schema {
+        /// Print only the schema for this type name.
+        #[arg(long = "type")]
+        type_name: Option<String>,
+    },
+    /// Verify the implementation against conformance vector bundles.
+    #[command(
+        long_about = "Load every JSON vector bundle in a directory and check derivation, normalization, distance, and match decisions.\n\nPrints a JSON report and exits non-zero if any vector fails.\n\nExample:\n  phenorv conformance --dir vectors/"
+    )]
+    Conformance {
+        /// Directory containing `*.json` vector bundles.
+        #[arg(long, default_value = "vectors")]
+        dir: PathBuf,
+    },
+    /// Fit a peer model to a dataset for use as a simulation prior.
+    #[command(
+        long_about = "Load a delimited dataset through a JSON column mapping and fit a per-dimension peer model.\n\nExample:\n  phenorv fit-prior --input dataset.csv --mapping mapping.json --output prior.json"
+    )]
+    FitPrior {
+        /// Input delimited text file. Use "-" for stdin. With the `columnar`
+        /// feature, `.parquet` and `.arrow`/`.ipc` files are read as columnar data.
+        #[arg(long)]
+        input: PathBuf,
+        /// JSON dataset mapping describing columns, delimiter, and grouping.
+        #[arg(long)]
+        mapping: PathBuf,
+        /// Output file (defaults to stdout).
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Check an SRT, matching parameters, and profiles for common mistakes.
+    #[command(
+        long_about = "Inspect an SRT, matching parameters, a calibration profile, a dimension registry, and a fitted prior, and print actionable warnings.\n\nEpsilon is cross-checked against a quick false-match simulation. Exits non-zero if any check reports an error.\n\nExample:\n  phenorv doctor --srt-hex <HEX> --epsilon 0.1 --window-size 3 --calibration rig.json --prior prior.json"
+    )]
+    Doctor {
+        /// SRT hex string (64 hex chars).
+        #[arg(long)]
+        srt_hex: Option<String>,
+        /// Matching threshold in normalized space.
+        #[arg(long, default_value_t = 0.1)]
+        epsilon: f32,
+        /// Consecutive observations required.
+        #[arg(long, default_value_t = 3)]
+        window_size: usize,
+        /// JSON MatchingConfig file; overrides --epsilon and --window-size.
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// JSON CalibrationProfile (sensor ranges) to validate.
+        #[arg(long)]
+        calibration: Option<PathBuf>,
+        /// JSON DimensionRegistry (range profile) to validate.
+        #[arg(long)]
+        registry: Option<PathBuf>,
+        /// JSON PeerModel produced by `fit-prior` to validate.
+        #[arg(long)]
+        prior: Option<PathBuf>,
+        /// Reject unknown or missing JSON fields and non-finite numbers.
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Generate a fresh SRT from the operating system's random source.
+    #[command(
+        long_about = "Generate a new random SRT and print it as 64 hex characters.\n\nShare the token with the peer over a secure channel; use export-token for an armored copy.\n\nExample:\n  phenorv generate-token --output token.hex"
+    )]
+    GenerateToken {
+        /// Output file (defaults to stdout).
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Write an SRT in ASCII-armored text form.
+    #[command(
+        long_about = "Write an SRT as a checksummed \"BEGIN PHENORV TOKEN\" block that can be pasted into emails and config files.\n\nExample:\n  phenorv export-token --srt-hex <HEX> --header Label=alice-bob --output token.asc"
+    )]
+    ExportToken {
+        /// SRT hex string (64 hex chars).
+        #[arg(long)]
+        srt_hex: String,
+        /// Informational header as NAME=VALUE; may be repeated.
+        #[arg(long = "header")]
+        headers: Vec<String>,
+        /// Output file (defaults to stdout).
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Render an SRT as a QR code for scanning by the peer.
+    #[cfg(feature = "qr")]
+    #[command(
+        long_about = "Render an SRT as a QR code. Without --output the code is drawn in the terminal; with --output an SVG file is written. The token fingerprint is printed to stderr so both people can compare it.\n\nExample:\n  phenorv qr-token --srt-hex <64 hex chars> --output token.svg"
+    )]
+    QrToken {
+        /// SRT hex string (64 hex chars).
+        #[arg(long)]
+        srt_hex: String,
+        /// SVG output file (defaults to a terminal rendering on stdout).
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Read an ASCII-armored SRT and print its hex form and headers.
+    #[command(
+        long_about = "Parse an armored SRT, verify its checksum, and print its hex form and headers as JSON.\n\nExample:\n  phenorv import-token --input token.asc"
+    )]
+    ImportToken {
+        /// File containing the armored token. Use "-" for stdin.
+        #[arg(long)]
+        input: PathBuf,
+    },
+    /// Show a live terminal dashboard while matching a stream.
+    #[cfg(feature = "tui")]
+    #[command(
+        long_about = "Match a live JSONL stream and show per-dimension distances, window fill, match transitions, and the salt epoch countdown.\n\nWith --epoch-length, the salt is the time-oracle epoch salt and the target rotates at each epoch boundary. Press q or Esc to quit.\n\nExample:\n  phenorv tui --srt-hex <HEX> --epoch-length 600 --epsilon 0.1 --window-size 3 --input sensor.fifo"
+    )]
+    Tui {
+        /// SRT hex string (64 hex chars).
+        #[arg(long)]
+        srt_hex: String,
+        /// Salt as hex string.
+        #[arg(long, conflicts_with_all = ["salt_string", "epoch_length"])]
+        salt_hex: Option<String>,
+        /// Salt as UTF-8 string.
+        #[arg(long, conflicts_with = "epoch_length")]
+        salt_string: Option<String>,
+        /// Derive the salt from time-oracle epochs of this many seconds.
+        #[arg(long)]
+        epoch_length: Option<u64>,
+        /// Matching threshold in normalized space.
+        #[arg(long, default_value_t = 0.1)]
+        epsilon: f32,
+        /// Number of consecutive samples required to match.
+        #[arg(long, default_value_t = 3)]
+        window_size: usize,
+        /// Input JSONL stream of SubmodalityPattern entries (file, named
+        /// pipe, or "-" for stdin).
+        #[arg(long)]
+        input: PathBuf,
+    },
+}
+
+/// Simulation backends selectable from the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum BackendKind {
+    /// Sample peers on the CPU.
+    Cpu,
+    /// Sample peers in a GPU compute shader.
+    Gpu,
+}
+
+impl From<BackendKind> for SimulationBackend {
+    fn from(kind: BackendKind) -> Self {
+        match kind {
+            BackendKind::Cpu => Self::Cpu,
+            BackendKind::Gpu => Self::Gpu,
+        }
+    }
+}
+
+/// Duplicate-observation policies available to `match-stream`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DuplicateKind {
+    /// Drop repeated observations.
+    Ignore,
+    /// Record repeats beyond `--max-repeats` as misses.
+    Cap,
+    /// Report `"frozen": true` after `--max-repeats` repeats.
+    Flag,
+}
+
+/// Sanitization policies available to `match-stream`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SanitizeKind {
+    /// Clamp to the reference range; NaN fields are ignored.
+    Clamp,
+    /// Record samples with any bad field as misses.
+    RejectSample,
+    /// Ignore bad fields and match on the rest.
+    MarkMissing,
+}
+
+impl From<SanitizeKind> for SanitizePolicy {
+    fn from(kind: SanitizeKind) -> Self {
+        match kind {
+            SanitizeKind::Clamp => SanitizePolicy::Clamp,
+            SanitizeKind::RejectSample => SanitizePolicy::RejectSample,
+            SanitizeKind::MarkMissing => SanitizePolicy::MarkMissing,
+        }
+    }
+}
+
+/// Interpolation methods available to `replay --interpolation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum InterpolationKind {
+    /// Straight lines between samples.
+    Linear,
+    /// Catmull-Rom spline; smooth but may overshoot.
+    CatmullRom,
+    /// Monotone cubic spline; smooth without overshoot.
+    MonotoneCubic,
+}
+
+impl From<InterpolationKind> for Interpolation {
+    fn from(kind: InterpolationKind) -> Self {
+        match kind {
+            InterpolationKind::Linear => Interpolation::Linear,
+            InterpolationKind::CatmullRom => Interpolation::CatmullRom,
+            InterpolationKind::MonotoneCubic => Interpolation::MonotoneCubic,
+        }
+    }
+}
+
+/// Distance metrics available to `simulate --metrics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum MetricKind {
+    /// Euclidean distance.
+    Euclidean,
+    /// Sum of absolute differences.
+    Manhattan,
+    /// Largest absolute difference.
+    Chebyshev,
+}
+
+impl From<MetricKind> for DistanceMetric {
+    fn from(kind: MetricKind) -> Self {
+        match kind {
+            MetricKind::Euclidean => Self::Euclidean,
+            MetricKind::Manhattan => Self::Manhattan,
+            MetricKind::Chebyshev => Self::Chebyshev,
+        }
+    }
+}
+
+/// Target derivation algorithms available to `encode-target` and `match-stream`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AlgorithmKind {
+    /// HMAC-SHA256 (the original scheme).
+    HmacSha256,
+    /// Keyed BLAKE3.
+    Blake3,
+    /// HMAC-SHA3-256.
+    HmacSha3,
+}
+
+impl From<AlgorithmKind> for DerivationAlgorithm {
+    fn from(kind: AlgorithmKind) -> Self {
+        match kind {
+            AlgorithmKind::HmacSha256 => Self::HmacSha256,
+            AlgorithmKind::Blake3 => Self::Blake3Keyed,
+            AlgorithmKind::HmacSha3 => Self::HmacSha3_256,
+        }
+    }
+}
+
+/// Output detail levels available to `simulate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DetailKind {
+    /// Aggregate counts and probabilities only.
+    Summary,
+    /// Add a record per trial (or a sample with `--sample-trials`).
+    Trials,
+}
+
+/// Chart types available to `visualize`.
+#[cfg(feature = "viz")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ChartKind {
+    /// Distance per observation with the epsilon band.
+    Distance,
+    /// Histogram of distances.
+    Histogram,
 }
 
 pub fn run() -> Result<(), CliError> {
@@ -108,14 +646,18 @@ pub fn run() -> Result<(), CliError> {
     match args.command {
         Commands::EncodeTarget {
             srt_hex,
+            srt_command,
+            srt_record,
             salt_hex,
             salt_string,
+            algorithm,
+            context,
             output,
         } => {
-            let srt = SemanticRendezvousToken::from_hex(&srt_hex)?;
-            let salt = resolve_salt(salt_hex, salt_string)?;
-            let pattern = pattern_from_srt(&srt, &salt);
-            let json = serde_json::to_string_pretty(&pattern)?;
+            let provider = srt_provider(srt_hex, srt_command, srt_record)?;
+            let salt = scope_salt(context, resolve_salt(salt_hex, salt_string)?);
+            let target = EncodedTarget::seal(provider.as_ref(), &salt, algorithm.into())?;
+            let json = serde_json::to_string_pretty(&target)?;
 
             match output {
                 Some(path) => {
@@ -132,36 +674,235 @@ pub fn run() -> Result<(), CliError> {
         }
         Commands::MatchStream {
             srt_hex,
+            srt_command,
+            srt_record,
             salt_hex,
             salt_string,
+            target_file,
             epsilon,
+            derive_tolerances,
+            algorithm,
+            context,
             window_size,
+            hold_ms,
             input,
+            #[cfg(feature = "serial")]
+            serial,
+            #[cfg(feature = "serial")]
+            baud,
+            audit_log,
+            duplicates,
+            max_repeats,
+            strict,
+            explain,
+            explain_table,
+            sanitize,
         } => {
-            let srt = SemanticRendezvousToken::from_hex(&srt_hex)?;
-            let salt = resolve_salt(salt_hex, salt_string)?;
-            let target = pattern_from_srt(&srt, &salt);
-            let mut matcher = Matcher::new(MatchingConfig::new(epsilon, window_size));
+            let provider = srt_provider(srt_hex, srt_command, srt_record)?;
+            let (salt, algorithm, target) = match target_file {
+                Some(path) => {
+                    let encoded: EncodedTarget =
+                        serde_json::from_reader(BufReader::new(File::open(path)?))?;
+                    encoded.verify(provider.as_ref())?;
+                    let salt = encoded.salt_bytes().expect("verified salt is valid hex");
+                    (salt, encoded.target.algorithm, encoded.target.pattern)
+                }
+                None => {
+                    let salt = scope_salt(context, resolve_salt(salt_hex, salt_string)?);
+                    let algorithm = DerivationAlgorithm::from(algorithm);
+                    let target = provider.derive_pattern_with(&salt, algorithm)?;
+                    (salt, algorithm, target)
+                }
+            };
+            let config = MatchingConfig::builder()
+                .epsilon(epsilon.unwrap_or_default())
+                .window_size(window_size.unwrap_or(1))
+                .build()?;
+            let mut builder = Matcher::builder(config);
+            if derive_tolerances {
+                builder = builder.tolerances(provider.derive_tolerances_with(&salt, algorithm)?);
+            }
+            if let Some(kind) = duplicates {
+                builder = builder.duplicate_policy(match kind {
+                    DuplicateKind::Ignore => DuplicatePolicy::Ignore,
+                    DuplicateKind::Cap => DuplicatePolicy::Cap(max_repeats),
+                    DuplicateKind::Flag => DuplicatePolicy::Flag(max_repeats),
+                });
+            }
+            if let Some(kind) = sanitize {
+                builder = builder.sanitize(kind.into());
+            }
+            let mut timed = match hold_ms {
+                Some(hold) => Some(DurationMatcher::from_builder(
+                    builder.clone(),
+                    Duration::from_millis(hold),
+                )?),
+                None => None,
+            };
+            let mut matcher = builder.build()?;
+            let mut audit = match audit_log {
+                Some(path) => Some(AuditLog::new(File::create(path)?)),
+                None => None,
+            };
 
-            let reader: Box<dyn BufRead> = if input.as_os_str() == "-" {
-                Box::new(BufReader::new(io::stdin().lock()))
-            } else {
-                Box::new(BufReader::new(File::open(input)?))
+            #[cfg(feature = "serial")]
+            let reader = match (serial, input) {
+                (Some(device), _) => open_serial(&device, baud)?,
+                (None, Some(input)) => open_input(&input)?,
+                (None, None) => unreachable!("clap requires --input or --serial"),
             };
+            #[cfg(not(feature = "serial"))]
+            let reader = open_input(&input.expect("clap requires --input"))?;
 
+            let policy = sanitize.map(SanitizePolicy::from);
             for (index, line) in reader.lines().enumerate() {
                 let line = line?;
                 if line.trim().is_empty() {
                     continue;
                 }
-                let measured: SubmodalityPattern = serde_json::from_str(&line)?;
-                let matched = matcher.observe(&measured, &target);
-                let output = json!({
+                // Explain before observing, so drift correction learned from
+                // this sample does not change the distance it was judged by.
+                let (measured, mut diff, matched) = match timed.as_mut() {
+                    Some(timed) => {
+                        let measured: TimedPattern = read_pattern(&line, strict)?;
+                        check_pattern(&measured.pattern, strict, policy, index)?;
+                        let diff = timed.matcher().explain(&measured.pattern, &target);
+                        let matched = timed.observe(&measured, &target)?;
+                        (measured.pattern, diff, matched)
+                    }
+                    None => {
+                        let measured: SubmodalityPattern = read_pattern(&line, strict)?;
+                        check_pattern(&measured, strict, policy, index)?;
+                        let diff = matcher.explain(&measured, &target);
+                        let matched = matcher.observe(&measured, &target);
+                        (measured, diff, matched)
+                    }
+                };
+                if let Some(audit) = audit.as_mut() {
+                    audit.append(MatchEvent {
+                        index: index as u64,
+                        distance: diff.distance,
+                        matched,
+                    })?;
+                }
+                let mut output = json!({
                     "index": index,
                     "match": matched,
                 });
+                if duplicates == Some(DuplicateKind::Flag) {
+                    let frozen = match timed.as_ref() {
+                        Some(timed) => timed.matcher().is_frozen(),
+                        None => matcher.is_frozen(),
+                    };
+                    output["frozen"] = json!(frozen);
+                }
+                if let (Some(_), Err(errors)) = (sanitize, measured.validate()) {
+                    let fields: Vec<&str> = errors.iter().map(|err| err.field()).collect();
+                    output["out_of_range"] = json!(fields);
+                }
+                if (explain || explain_table) && !matched {
+                    diff.sort_by_contribution();
+                    if explain {
+                        output["diff"] = serde_json::to_value(&diff)?;
+                    }
+                    if explain_table {
+                        eprintln!("line {}:\n{measured}\n\n{diff}\n", index + 1);
+                    }
+                }
                 println!("{}", output);
             }
+
+            if let Some(audit) = audit {
+                audit.finish()?;
+            }
+        }
+        Commands::Convert {
+            input,
+            delimiter,
+            locale_tolerant,
+            output,
+        } => {
+            let options = CsvOptions {
+                delimiter,
+                mode: if locale_tolerant {
+                    NumberMode::LocaleTolerant
+                } else {
+                    NumberMode::Strict
+                },
+            };
+            let patterns = read_csv_patterns(open_input(&input)?, &options)
+                .with_context(|| format!("reading {}", input.display()))?;
+
+            let mut out: Box<dyn Write> = match output {
+                Some(path) => Box::new(File::create(path)?),
+                None => Box::new(io::stdout().lock()),
+            };
+            for pattern in &patterns {
+                serde_json::to_writer(&mut out, &migrate::versioned(pattern))?;
+                out.write_all(b"\n")?;
+            }
+            out.flush()?;
+        }
+        Commands::Record {
+            input,
+            output,
+            zstd,
+            strict,
+        } => {
+            let compression = if zstd {
+                Compression::Zstd
+            } else {
+                Compression::None
+            };
+            let file =
+                File::create(&output).with_context(|| format!("creating {}", output.display()))?;
+            let mut writer = SeriesWriter::new(io::BufWriter::new(file), compression)?;
+            for (index, line) in open_input(&input)?.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let sample: TimedPattern = read_pattern(&line, strict)?;
+                check_pattern(&sample.pattern, strict, None, index)?;
+                writer.write(&sample)?;
+            }
+            writer.finish()?;
+        }
+        Commands::Replay {
+            input,
+            output,
+            realtime,
+            resample_ms,
+            interpolation,
+        } => {
+            let mut out: Box<dyn Write> = match output {
+                Some(path) => Box::new(File::create(path)?),
+                None => Box::new(io::stdout().lock()),
+            };
+            let samples: Box<
+                dyn Iterator<Item = phenomenological_rendezvous::Result<TimedPattern>>,
+            > = match resample_ms {
+                Some(ms) => {
+                    let series = read_series(open_input(&input)?)?
+                        .resample_with(Duration::from_millis(ms), interpolation.into())
+                        .map_err(Error::from)?;
+                    Box::new(series.samples().to_vec().into_iter().map(Ok))
+                }
+                None => Box::new(SeriesReader::new(open_input(&input)?)?),
+            };
+            let mut previous: Option<u64> = None;
+            for sample in samples {
+                let sample = sample?;
+                if let (true, Some(previous)) = (realtime, previous) {
+                    out.flush()?;
+                    let wait = sample.timestamp.saturating_sub(previous);
+                    std::thread::sleep(Duration::from_millis(wait));
+                }
+                previous = Some(sample.timestamp);
+                serde_json::to_writer(&mut out, &migrate::versioned(&sample))?;
+                out.write_all(b"\n")?;
+            }
+            out.flush()?;
         }
         Commands::Simulate {
             srt_hex,
@@ -174,13 +915,19 @@ pub fn run() -> Result<(), CliError> {
             window_size,
             apply_geo_filter,
             geo_filter_factor,
+            prior,
+            backend,
+            detail,
+            sample_trials,
+            metrics,
+            strict,
         } => {
             let srt = SemanticRendezvousToken::from_hex(&srt_hex)?;
             let salt = resolve_salt(salt_hex, salt_string)?;
 
             let config = if let Some(path) = config {
                 let text = std::fs::read_to_string(path)?;
-                serde_json::from_str(&text)?
+                ingest::from_json(&text, json_mode(strict))?
             } else {
                 SimulationConfig {
                     num_peers,
@@ -189,94 +936,625 @@ pub fn run() -> Result<(), CliError> {
                     window_size,
                     apply_geo_filter,
                     geo_filter_factor,
+                    backend: backend.into(),
+                    detail: match (detail, sample_trials) {
+                        (_, Some(size)) => SimulationDetail::Sample(size),
+                        (DetailKind::Trials, None) => SimulationDetail::Trials,
+                        (DetailKind::Summary, None) => SimulationDetail::Summary,
+                    },
+                    metrics: metrics
+                        .into_iter()
+                        .map(|kind| {
+                            let name = clap::ValueEnum::to_possible_value(&kind)
+                                .expect("no variants are skipped");
+                            MetricSpec::new(name.get_name(), kind.into())
+                        })
+                        .collect(),
+                    weights: DimensionWeights::default(),
                 }
             };
+            config.validate()?;
 
-            let result = run_simulation(&config, &srt, &salt);
+            let result = match prior {
+                Some(path) => {
+                    let model: PeerModel =
+                        ingest::from_json(&std::fs::read_to_string(path)?, json_mode(strict))?;
+                    run_simulation_with_prior(&config, &srt, &salt, &model)
+                }
+                None => try_run_simulation(&config, &srt, &salt)?,
+            };
             let output = serde_json::to_string_pretty(&result)?;
             println!("{output}");
         }
+        Commands::Sweep {
+            srt_hex,
+            salt_hex,
+            salt_string,
+            epsilons,
+            window_sizes,
+            num_peers,
+            num_trials,
+            #[cfg(feature = "viz")]
+            svg,
+        } => {
+            let srt = SemanticRendezvousToken::from_hex(&srt_hex)?;
+            let salt = resolve_salt(salt_hex, salt_string)?;
+            let base = SimulationConfig {
+                num_peers,
+                num_trials,
+                epsilon: 0.0,
+                window_size: 0,
+                apply_geo_filter: false,
+                geo_filter_factor: 1.0,
+                backend: SimulationBackend::Cpu,
+                detail: SimulationDetail::Summary,
+                metrics: Vec::new(),
+                weights: DimensionWeights::default(),
+            };
+            let sweep = run_sweep(&base, &srt, &salt, &epsilons, &window_sizes);
+
+            #[cfg(feature = "viz")]
+            if let Some(path) = svg {
+                std::fs::write(path, viz::sweep_heatmap_svg(&sweep)?)?;
+            }
+            println!("{}", serde_json::to_string_pretty(&sweep)?);
+        }
+        #[cfg(feature = "viz")]
+        Commands::Visualize {
+            srt_hex,
+            salt_hex,
+            salt_string,
+            epsilon,
+            input,
+            kind,
+            bins,
+            output,
+            strict,
+        } => {
+            let srt = SemanticRendezvousToken::from_hex(&srt_hex)?;
+            let salt = resolve_salt(salt_hex, salt_string)?;
+            let target = pattern_from_srt(&srt, &salt).normalize();
+
+            let mut distances = Vec::new();
+            for line in open_input(&input)?.lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let measured: SubmodalityPattern = read_pattern(&line, strict)?;
+                distances.push(euclidean_distance(&measured.normalize(), &target));
+            }
+
+            let svg = match kind {
+                ChartKind::Distance => viz::distance_over_time_svg(&distances, epsilon)?,
+                ChartKind::Histogram => viz::distance_histogram_svg(&distances, bins, Some(epsilon))?,
+            };
+            std::fs::write(output, svg)?;
+        }
+        Commands::Dimensions => {
+            println!("{}", serde_json::to_string_pretty(&DIMENSIONS)?);
+        }
+        #[cfg(feature = "schemars")]
+        Commands::Schema { type_name } => {
+            let output = match type_name {
+                Some(name) => {
+                    let schema = phenomenological_rendezvous::schema::schema_by_name(&name)
+                        .ok_or_else(|| {
+                            Error::InvalidConfig(format!("no schema for type '{name}'"))
+                        })?;
+                    serde_json::to_string_pretty(&schema)?
+                }
+                None => serde_json::to_string_pretty(
+                    &phenomenological_rendezvous::schema::all_schemas(),
+                )?,
+            };
+            println!("{output}");
+        }
+        Commands::Conformance { dir } => {
+            let report = conformance::run_dir(&dir)?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            if !report.all_passed() {
+                return Err(CliError::ConformanceFailed(report.failed));
+            }
+        }
+        Commands::Doctor {
+            srt_hex,
+            epsilon,
+            window_size,
+            config,
+            calibration,
+            registry,
+            prior,
+            strict,
+        } => {
+            let mode = json_mode(strict);
+            let config = match config {
+                Some(path) => ingest::from_json(&std::fs::read_to_string(path)?, mode)?,
+                None => MatchingConfig::new(epsilon, window_size),
+            };
+            let mut findings = diagnostics::check_matching(&config);
+            if let Some(srt_hex) = srt_hex {
+                findings.extend(diagnostics::check_srt(&srt_hex));
+            }
+            if let Some(path) = calibration {
+                let profile: CalibrationProfile =
+                    ingest::from_json(&std::fs::read_to_string(path)?, mode)?;
+                findings.extend(diagnostics::check_calibration(&profile));
+            }
+            if let Some(path) = registry {
+                let registry: DimensionRegistry =
+                    ingest::from_json(&std::fs::read_to_string(path)?, mode)?;
+                findings.extend(diagnostics::check_registry(&registry));
+            }
+            if let Some(path) = prior {
+                let model: PeerModel = ingest::from_json(&std::fs::read_to_string(path)?, mode)?;
+                findings.extend(diagnostics::check_peer_model(&model));
+            }
+
+            if findings.is_empty() {
+                println!("no problems found");
+            }
+            for finding in &findings {
+                println!("{finding}");
+            }
+            let errors = findings
+                .iter()
+                .filter(|finding| finding.severity == Severity::Error)
+                .count();
+            if errors > 0 {
+                return Err(CliError::DoctorFailed(errors));
+            }
+        }
+        Commands::ExportToken {
+            srt_hex,
+            headers,
+            output,
+        } => {
+            let mut armored = ArmoredToken::new(SemanticRendezvousToken::from_hex(&srt_hex)?);
+            for header in headers {
+                let (name, value) = header
+                    .split_once('=')
+                    .ok_or_else(|| ArmorError::InvalidHeader(header.clone()))?;
+                armored = armored.with_header(name, value)?;
+            }
+            match output {
+                Some(path) => std::fs::write(path, armored.to_string())?,
+                None => print!("{armored}"),
+            }
+        }
+        #[cfg(feature = "qr")]
+        Commands::QrToken { srt_hex, output } => {
+            let srt = SemanticRendezvousToken::from_hex(&srt_hex)?;
+            match output {
+                Some(path) => std::fs::write(path, srt.to_qr_svg())?,
+                None => print!("{}", srt.to_qr_text()),
+            }
+            eprintln!("fingerprint: {}", srt.fingerprint());
+        }
+        Commands::GenerateToken { output } => {
+            let srt = SemanticRendezvousToken::generate();
+            match output {
+                Some(path) => std::fs::write(path, format!("{srt}\n"))?,
+                None => println!("{srt}"),
+            }
+            eprintln!("fingerprint: {}", srt.fingerprint());
+        }
+        Commands::ImportToken { input } => {
+            let mut text = String::new();
+            open_input(&input)?.read_to_string(&mut text)?;
+            let armored = ArmoredToken::parse(&text)?;
+            let headers: serde_json::Map<String, serde_json::Value> = armored
+                .headers()
+                .map(|(name, value)| (name.to_string(), json!(value)))
+                .collect();
+            let output = json!({
+                "srt_hex": armored.token().to_string(),
+                "fingerprint": armored.token().fingerprint().to_string(),
+                "headers": headers,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        #[cfg(feature = "tui")]
+        Commands::Tui {
+            srt_hex,
+            salt_hex,
+            salt_string,
+            epoch_length,
+            epsilon,
+            window_size,
+            input,
+        } => {
+            let srt = SemanticRendezvousToken::from_hex(&srt_hex)?;
+            let salt = match epoch_length {
+                Some(secs) => SaltSource::Epochs(TimeOracle::new(secs, 0).map_err(Error::from)?),
+                None => SaltSource::Fixed(resolve_salt(salt_hex, salt_string)?),
+            };
+            let config = MatchingConfig::builder()
+                .epsilon(epsilon)
+                .window_size(window_size)
+                .build()?;
+            run_tui(&srt, &salt, config, input)?;
+        }
+        Commands::FitPrior {
+            input,
+            mapping,
+            output,
+        } => {
+            let mapping: DatasetMapping = std::fs::read_to_string(&mapping)
+                .map_err(Error::from)
+                .and_then(|text| Ok(serde_json::from_str(&text)?))
+                .with_context(|| format!("reading mapping {}", mapping.display()))?;
+            let trajectories = load_dataset(&input, &mapping)
+                .with_context(|| format!("reading {}", input.display()))?;
+            let model = PeerModel::fit(&trajectories)?;
+            let json = serde_json::to_string_pretty(&model)?;
+
+            match output {
+                Some(path) => {
+                    let mut file = File::create(path)?;
+                    file.write_all(json.as_bytes())?;
+                    file.write_all(b"\n")?;
+                }
+                None => println!("{json}"),
+            }
+        }
     }
 
     Ok(())
 }
 
+fn load_dataset(
+    input: &Path,
+    mapping: &DatasetMapping,
+) -> phenomenological_rendezvous::Result<Vec<PatternTrajectory>> {
+    #[cfg(feature = "columnar")]
+    {
+        use phenomenological_rendezvous::datasets::columnar;
+        match input.extension().and_then(|ext| ext.to_str()) {
+            Some("parquet") => {
+                return columnar::load_trajectories_parquet(File::open(input)?, mapping);
+            }
+            Some("arrow" | "ipc") => {
+                return columnar::load_trajectories_ipc(File::open(input)?, mapping);
+            }
+            _ => {}
+        }
+    }
+    let reader: Box<dyn BufRead> = if input.as_os_str() == "-" {
+        Box::new(BufReader::new(io::stdin().lock()))
+    } else {
+        Box::new(BufReader::new(File::open(input)?))
+    };
+    load_trajectories(reader, mapping)
+}
+
+fn open_input(input: &Path) -> Result<Box<dyn BufRead>, CliError> {
+    if input.as_os_str() == "-" {
+        Ok(Box::new(BufReader::new(io::stdin().lock())))
+    } else {
+        let file = File::open(input).with_context(|| format!("opening {}", input.display()))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// Where the `tui` command gets its salt.
+#[cfg(feature = "tui")]
+enum SaltSource {
+    /// A salt given on the command line.
+    Fixed(Vec<u8>),
+    /// The current time-oracle epoch salt, rotating at each boundary.
+    Epochs(TimeOracle),
+}
+
+/// Run the matching dashboard until the user quits.
+///
+/// Input is read on a background thread so the display keeps refreshing (and
+/// the epoch countdown keeps running) while the stream is idle.
+#[cfg(feature = "tui")]
+fn run_tui(
+    srt: &SemanticRendezvousToken,
+    salt: &SaltSource,
+    config: MatchingConfig,
+    input: PathBuf,
+) -> Result<(), CliError> {
+    use std::sync::mpsc;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use phenomenological_rendezvous::srt::oracle::epoch_salt;
+    use phenomenological_rendezvous::tui::{Dashboard, EpochStatus};
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let read = || -> Result<(), CliError> {
+            for line in open_input(&input)?.lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let measured: SubmodalityPattern = read_pattern(&line, false)?;
+                if sender.send(Ok(Some(measured))).is_err() {
+                    return Ok(());
+                }
+            }
+            Ok(())
+        };
+        let _ = sender.send(read().map(|()| None));
+    });
+
+    let unix_now = || {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0)
+    };
+    let mut epoch = None;
+    let mut target = match salt {
+        SaltSource::Fixed(salt) => pattern_from_srt(srt, salt),
+        SaltSource::Epochs(_) => SubmodalityPattern::zeros(),
+    };
+    let mut matcher = Matcher::new(config);
+    let mut dashboard = Dashboard::new(config.epsilon, config.window_size);
+
+    let mut terminal = ratatui::init();
+    let result = (|| -> io::Result<()> {
+        loop {
+            if let SaltSource::Epochs(oracle) = salt {
+                let now = unix_now();
+                let current = oracle.epoch_at(now);
+                if epoch != Some(current) {
+                    epoch = Some(current);
+                    target = pattern_from_srt(srt, &epoch_salt(current));
+                    matcher = Matcher::new(config);
+                }
+                dashboard.set_epoch(EpochStatus {
+                    epoch: current,
+                    secs_remaining: oracle.secs_until_next_epoch(now),
+                });
+            }
+            while let Ok(message) = receiver.try_recv() {
+                match message {
+                    Ok(Some(measured)) => {
+                        let matched = matcher.observe(&measured, &target);
+                        dashboard.record(
+                            &measured.normalize(),
+                            &target.normalize(),
+                            matched,
+                            matcher.window(),
+                        );
+                    }
+                    Ok(None) => dashboard.set_status("stream ended"),
+                    Err(err) => dashboard.set_status(format!("input error: {err}")),
+                }
+            }
+            terminal.draw(|frame| dashboard.render(frame))?;
+            if event::poll(Duration::from_millis(100))? {
+                match event::read()? {
+                    Event::Key(key)
+                        if key.kind == KeyEventKind::Press
+                            && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) =>
+                    {
+                        return Ok(());
+                    }
+                    _ => {}
+                }
+            }
+        }
+    })();
+    ratatui::restore();
+    Ok(result?)
+}
+
+/// Open a serial device for newline-delimited reads.
+///
+/// Reads block until data arrives; the port's read timeout only bounds each
+/// underlying read and is retried, so idle sensors do not end the stream.
+#[cfg(feature = "serial")]
+fn open_serial(device: &str, baud: u32) -> Result<Box<dyn BufRead>, CliError> {
+    let port = serialport::new(device, baud)
+        .timeout(std::time::Duration::from_secs(1))
+        .open()
+        .map_err(io::Error::from)
+        .with_context(|| format!("opening serial device {device}"))?;
+    Ok(Box::new(BufReader::new(RetryOnTimeout(port))))
+}
+
+/// Reader adapter that retries reads failing with `TimedOut`.
+#[cfg(feature = "serial")]
+struct RetryOnTimeout<R>(R);
+
+#[cfg(feature = "serial")]
+impl<R: Read> Read for RetryOnTimeout<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.0.read(buf) {
+                Err(err) if err.kind() == io::ErrorKind::TimedOut => continue,
+                result => return result,
+            }
+        }
+    }
+}
+
+fn json_mode(strict: bool) -> JsonMode {
+    if strict {
+        JsonMode::Strict
+    } else {
+        JsonMode::Lenient
+    }
+}
+
+/// Parse a JSON pattern record, upgrading it from older schema versions.
+fn read_pattern<T>(line: &str, strict: bool) -> Result<T, CliError>
+where
+    T: DeserializeOwned + Serialize,
+{
+    let record = migrate::migrate(serde_json::from_str(line)?)?;
+    Ok(ingest::from_json_value(record, json_mode(strict))?)
+}
+
 fn resolve_salt(salt_hex: Option<String>, salt_string: Option<String>) -> Result<Vec<u8>, CliError> {
     match (salt_hex, salt_string) {
-        (Some(hex), None) => parse_hex_bytes(&hex),
+        (Some(hex), None) => Ok(hex::decode(hex.trim()).map_err(Error::from)?),
         (None, Some(text)) => Ok(text.into_bytes()),
-        (None, None) => Err(CliError::MissingSalt),
-        (Some(_), Some(_)) => Err(CliError::ConflictingSalt),
+        (None, None) => Err(invalid_args(
+            "missing salt (provide --salt-hex or --salt-string)",
+        )),
+        (Some(_), Some(_)) => Err(invalid_args(
+            "provide only one of --salt-hex or --salt-string",
+        )),
     }
 }
 
-fn parse_hex_bytes(input: &str) -> Result<Vec<u8>, CliError> {
-    let trimmed = input.trim();
-    if trimmed.len() % 2 != 0 {
-        return Err(CliError::InvalidHexLength(trimmed.len()));
-    }
-    let mut bytes = Vec::with_capacity(trimmed.len() / 2);
-    let mut iter = trimmed.as_bytes().chunks(2);
-    while let Some(chunk) = iter.next() {
-        let hi = decode_hex_nibble(chunk[0])?;
-        let lo = decode_hex_nibble(chunk[1])?;
-        bytes.push((hi << 4) | lo);
+/// In strict mode, reject a streamed pattern with out-of-range fields.
+///
+/// With a sanitize policy only the fields it keeps are checked: dropped
+/// fields and rejected samples never reach the distance, but a clamped one
+/// would quietly hide a broken sensor.
+fn check_pattern(
+    pattern: &SubmodalityPattern,
+    strict: bool,
+    sanitize: Option<SanitizePolicy>,
+    index: usize,
+) -> Result<(), CliError> {
+    if !strict {
+        return Ok(());
     }
-    Ok(bytes)
+    let kept = match sanitize.map(|policy| pattern.sanitize(policy)) {
+        None => [true; 9],
+        Some(Ok(sanitized)) => sanitized.present(),
+        Some(Err(_)) => [false; 9],
+    };
+    pattern
+        .validate()
+        .or_else(|errors| {
+            let errors: Vec<_> = errors
+                .into_iter()
+                .filter(|err| {
+                    DIMENSIONS
+                        .iter()
+                        .zip(kept)
+                        .any(|(dimension, kept)| kept && dimension.key == err.field())
+                })
+                .collect();
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(errors)
+            }
+        })
+        .with_context(|| format!("line {}", index + 1))?;
+    Ok(())
 }
 
-fn decode_hex_nibble(byte: u8) -> Result<u8, CliError> {
-    match byte {
-        b'0'..=b'9' => Ok(byte - b'0'),
-        b'a'..=b'f' => Ok(byte - b'a' + 10),
-        b'A'..=b'F' => Ok(byte - b'A' + 10),
-        _ => Err(CliError::InvalidHexCharacter(byte as char)),
+/// Resolve the SRT source: a hex token, a validated record, or an external
+/// HMAC command.
+fn srt_provider(
+    srt_hex: Option<String>,
+    srt_command: Option<String>,
+    srt_record: Option<PathBuf>,
+) -> Result<Box<dyn SrtProvider>, CliError> {
+    if let Some(hex) = srt_hex {
+        return Ok(Box::new(SemanticRendezvousToken::from_hex(&hex)?));
     }
+    if let Some(path) = srt_record {
+        let record: SrtRecord = serde_json::from_reader(BufReader::new(File::open(&path)?))
+            .with_context(|| format!("reading {}", path.display()))?;
+        record.token_now()?;
+        return Ok(Box::new(record));
+    }
+    srt_command
+        .as_deref()
+        .and_then(CommandProvider::from_command_line)
+        .map(|provider| Box::new(provider) as Box<dyn SrtProvider>)
+        .ok_or_else(|| {
+            invalid_args("missing SRT (provide --srt-hex, --srt-record, or --srt-command)")
+        })
 }
 
-#[derive(Debug)]
-pub enum CliError {
-    MissingSalt,
-    ConflictingSalt,
-    InvalidHexLength(usize),
-    InvalidHexCharacter(char),
-    SrtError(phenomenological_rendezvous::srt::SrtParseError),
-    Io(std::io::Error),
-    Json(serde_json::Error),
-}
-
-impl std::fmt::Display for CliError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::MissingSalt => write!(f, "missing salt (provide --salt-hex or --salt-string)"),
-            Self::ConflictingSalt => {
-                write!(f, "provide only one of --salt-hex or --salt-string")
-            }
-            Self::InvalidHexLength(len) => write!(f, "invalid hex length: {len}"),
-            Self::InvalidHexCharacter(ch) => write!(f, "invalid hex character: '{ch}'"),
-            Self::SrtError(err) => write!(f, "{err}"),
-            Self::Io(err) => write!(f, "{err}"),
-            Self::Json(err) => write!(f, "{err}"),
-        }
+/// An unusable combination of command-line arguments.
+fn invalid_args(message: &str) -> CliError {
+    Error::InvalidConfig(message.to_string()).into()
+}
+
+/// Mix an optional application context label into a salt.
+fn scope_salt(context: Option<String>, salt: Vec<u8>) -> Vec<u8> {
+    match context {
+        Some(context) => context_salt(&context, &salt),
+        None => salt,
     }
 }
 
-impl std::error::Error for CliError {}
+/// Errors reported by the CLI: any crate error, plus failed checks.
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    /// Some conformance vectors did not reproduce.
+    #[error("{0} conformance vector(s) failed")]
+    ConformanceFailed(usize),
+    /// `doctor` reported error-level findings.
+    #[error("doctor found {0} error(s)")]
+    DoctorFailed(usize),
+    /// A library operation failed.
+    #[error(transparent)]
+    Core(#[from] Error),
+}
 
 impl From<std::io::Error> for CliError {
     fn from(err: std::io::Error) -> Self {
-        Self::Io(err)
+        Self::Core(err.into())
     }
 }
 
 impl From<serde_json::Error> for CliError {
     fn from(err: serde_json::Error) -> Self {
-        Self::Json(err)
+        Self::Core(err.into())
+    }
+}
+
+impl From<migrate::MigrateError> for CliError {
+    fn from(err: migrate::MigrateError) -> Self {
+        Self::Core(err.into())
+    }
+}
+
+impl From<ArmorError> for CliError {
+    fn from(err: ArmorError) -> Self {
+        Self::Core(err.into())
     }
 }
 
 impl From<phenomenological_rendezvous::srt::SrtParseError> for CliError {
     fn from(err: phenomenological_rendezvous::srt::SrtParseError) -> Self {
-        Self::SrtError(err)
+        Self::Core(err.into())
+    }
+}
+
+impl From<RecordError> for CliError {
+    fn from(err: RecordError) -> Self {
+        Self::Core(err.into())
+    }
+}
+
+impl From<ProviderError> for CliError {
+    fn from(err: ProviderError) -> Self {
+        Self::Core(err.into())
+    }
+}
+
+impl From<TargetError> for CliError {
+    fn from(err: TargetError) -> Self {
+        Self::Core(err.into())
+    }
+}
+
+impl From<phenomenological_rendezvous::datasets::DatasetError> for CliError {
+    fn from(err: phenomenological_rendezvous::datasets::DatasetError) -> Self {
+        Self::Core(err.into())
+    }
+}
+
+#[cfg(feature = "viz")]
+impl From<viz::VizError> for CliError {
+    fn from(err: viz::VizError) -> Self {
+        Self::Core(err.into())
     }
 }
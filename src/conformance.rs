@@ -0,0 +1,345 @@
+//! Conformance harness for ports of the protocol.
+//!
+//! A vector bundle is a JSON file listing inputs and the outputs this
+//! reference implementation produces for SRT derivation, normalization,
+//! distance, and match decisions. [`run_dir`] loads every `*.json` bundle in
+//! a directory, checks each vector, and returns a [`ConformanceReport`] that
+//! serializes to a machine-readable pass/fail summary. Implementations in
+//! other languages can certify by producing the same outputs for the bundles
+//! shipped in `vectors/`.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, ResultExt};
+use crate::hex;
+use crate::matching::{euclidean_distance, Matcher, MatchingConfig};
+use crate::pattern::{
+    normalized_to_array, pattern_to_array, NormalizedPattern, SubmodalityPattern,
+};
+use crate::srt::{pattern_from_srt, SemanticRendezvousToken};
+
+/// Tolerance used when a bundle does not specify one.
+pub const DEFAULT_TOLERANCE: f32 = 1e-5;
+
+fn default_tolerance() -> f32 {
+    DEFAULT_TOLERANCE
+}
+
+/// A named set of test vectors.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VectorBundle {
+    /// Bundle name reported alongside each case.
+    pub name: String,
+    /// Tolerance for floating-point comparisons, relative to the expected
+    /// value's magnitude when that exceeds one and absolute otherwise.
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f32,
+    /// SRT + salt to target pattern vectors.
+    #[serde(default)]
+    pub derivation: Vec<DerivationVector>,
+    /// Raw to normalized pattern vectors.
+    #[serde(default)]
+    pub normalization: Vec<NormalizationVector>,
+    /// Normalized distance vectors.
+    #[serde(default)]
+    pub distance: Vec<DistanceVector>,
+    /// Smoothed match decision vectors.
+    #[serde(default)]
+    pub matching: Vec<MatchVector>,
+}
+
+/// Expected target pattern for an SRT and salt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DerivationVector {
+    /// SRT as 64 hex characters.
+    pub srt: String,
+    /// Salt as hex.
+    pub salt: String,
+    /// Expected derived pattern.
+    pub expected: SubmodalityPattern,
+}
+
+/// Expected normalization of a raw pattern.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NormalizationVector {
+    /// Raw input pattern.
+    pub pattern: SubmodalityPattern,
+    /// Expected normalized pattern.
+    pub expected: NormalizedPattern,
+}
+
+/// Expected distance between two normalized patterns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DistanceVector {
+    /// First pattern.
+    pub a: NormalizedPattern,
+    /// Second pattern.
+    pub b: NormalizedPattern,
+    /// Expected Euclidean distance.
+    pub expected: f32,
+}
+
+/// Expected match decisions for a measured stream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchVector {
+    /// Matching threshold in normalized space.
+    pub epsilon: f32,
+    /// Consecutive observations required.
+    pub window_size: usize,
+    /// Target pattern.
+    pub target: SubmodalityPattern,
+    /// Measured patterns in stream order.
+    pub measured: Vec<SubmodalityPattern>,
+    /// Expected `Matcher::observe` result after each measurement.
+    pub expected: Vec<bool>,
+}
+
+/// Category of a checked vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorKind {
+    /// [`DerivationVector`].
+    Derivation,
+    /// [`NormalizationVector`].
+    Normalization,
+    /// [`DistanceVector`].
+    Distance,
+    /// [`MatchVector`].
+    Matching,
+}
+
+/// Outcome of one vector.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseResult {
+    /// Bundle the vector came from.
+    pub bundle: String,
+    /// Vector category.
+    pub kind: VectorKind,
+    /// Index of the vector within its category.
+    pub index: usize,
+    /// Whether the output matched the expectation.
+    pub passed: bool,
+    /// Description of the mismatch, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Pass/fail summary over one or more bundles.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConformanceReport {
+    /// Number of passing vectors.
+    pub passed: usize,
+    /// Number of failing vectors.
+    pub failed: usize,
+    /// Per-vector results in bundle order.
+    pub cases: Vec<CaseResult>,
+}
+
+impl ConformanceReport {
+    /// Whether every vector passed.
+    pub fn all_passed(&self) -> bool {
+        self.failed == 0
+    }
+
+    fn push(&mut self, case: CaseResult) {
+        if case.passed {
+            self.passed += 1;
+        } else {
+            self.failed += 1;
+        }
+        self.cases.push(case);
+    }
+}
+
+/// Load every `*.json` bundle in `dir`, sorted by file name.
+pub fn load_bundles(dir: impl AsRef<Path>) -> Result<Vec<VectorBundle>> {
+    let dir = dir.as_ref();
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            fs::read_to_string(path)
+                .map_err(crate::Error::from)
+                .and_then(|text| Ok(serde_json::from_str(&text)?))
+                .with_context(|| format!("loading bundle {}", path.display()))
+        })
+        .collect()
+}
+
+/// Check every vector in `bundles`.
+pub fn run_bundles(bundles: &[VectorBundle]) -> ConformanceReport {
+    let mut report = ConformanceReport::default();
+    for bundle in bundles {
+        for case in run_bundle(bundle) {
+            report.push(case);
+        }
+    }
+    report
+}
+
+/// Load and check every bundle in `dir`.
+pub fn run_dir(dir: impl AsRef<Path>) -> Result<ConformanceReport> {
+    Ok(run_bundles(&load_bundles(dir)?))
+}
+
+/// Check every vector in one bundle.
+pub fn run_bundle(bundle: &VectorBundle) -> Vec<CaseResult> {
+    let tol = bundle.tolerance;
+    let mut cases = Vec::new();
+    let mut record = |kind, index, outcome: std::result::Result<(), String>| {
+        cases.push(CaseResult {
+            bundle: bundle.name.clone(),
+            kind,
+            index,
+            passed: outcome.is_ok(),
+            detail: outcome.err(),
+        });
+    };
+
+    for (index, vector) in bundle.derivation.iter().enumerate() {
+        record(VectorKind::Derivation, index, check_derivation(vector, tol));
+    }
+    for (index, vector) in bundle.normalization.iter().enumerate() {
+        let actual = vector.pattern.normalize();
+        record(
+            VectorKind::Normalization,
+            index,
+            compare(
+                &normalized_to_array(&actual),
+                &normalized_to_array(&vector.expected),
+                tol,
+            ),
+        );
+    }
+    for (index, vector) in bundle.distance.iter().enumerate() {
+        let actual = euclidean_distance(&vector.a, &vector.b);
+        let outcome = if within(actual, vector.expected, tol) {
+            Ok(())
+        } else {
+            Err(format!("expected {}, got {actual}", vector.expected))
+        };
+        record(VectorKind::Distance, index, outcome);
+    }
+    for (index, vector) in bundle.matching.iter().enumerate() {
+        record(VectorKind::Matching, index, check_matching(vector));
+    }
+    cases
+}
+
+fn check_derivation(vector: &DerivationVector, tol: f32) -> std::result::Result<(), String> {
+    let srt = SemanticRendezvousToken::from_hex(&vector.srt).map_err(|err| err.to_string())?;
+    let salt = hex::decode(&vector.salt).map_err(|err| format!("salt: {err}"))?;
+    let actual = pattern_from_srt(&srt, &salt);
+    compare(
+        &pattern_to_array(&actual),
+        &pattern_to_array(&vector.expected),
+        tol,
+    )
+}
+
+fn check_matching(vector: &MatchVector) -> std::result::Result<(), String> {
+    let config = MatchingConfig::new(vector.epsilon, vector.window_size);
+    config.validate().map_err(|err| err.to_string())?;
+    if vector.measured.len() != vector.expected.len() {
+        return Err(format!(
+            "{} measurements but {} expected decisions",
+            vector.measured.len(),
+            vector.expected.len()
+        ));
+    }
+    let mut matcher = Matcher::new(config);
+    for (step, (measured, &expected)) in vector.measured.iter().zip(&vector.expected).enumerate() {
+        let actual = matcher.observe(measured, &vector.target);
+        if actual != expected {
+            return Err(format!("step {step}: expected {expected}, got {actual}"));
+        }
+    }
+    Ok(())
+}
+
+fn within(actual: f32, expected: f32, tol: f32) -> bool {
+    (actual - expected).abs() <= tol * expected.abs().max(1.0)
+}
+
+/// Compare field by field, reporting the first value outside `tol`.
+fn compare(actual: &[f32; 9], expected: &[f32; 9], tol: f32) -> std::result::Result<(), String> {
+    for (field, (a, e)) in actual.iter().zip(expected).enumerate() {
+        if !within(*a, *e, tol) {
+            return Err(format!("field {field}: expected {e}, got {a}"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle() -> VectorBundle {
+        let srt = SemanticRendezvousToken::from_bytes([7u8; 32]);
+        let target = pattern_from_srt(&srt, b"salt");
+        VectorBundle {
+            name: "unit".to_string(),
+            tolerance: DEFAULT_TOLERANCE,
+            derivation: vec![DerivationVector {
+                srt: srt.to_string(),
+                salt: hex::encode(b"salt"),
+                expected: target.clone(),
+            }],
+            normalization: vec![NormalizationVector {
+                pattern: target.clone(),
+                expected: target.normalize(),
+            }],
+            distance: vec![DistanceVector {
+                a: target.normalize(),
+                b: target.normalize(),
+                expected: 0.0,
+            }],
+            matching: vec![MatchVector {
+                epsilon: 0.1,
+                window_size: 2,
+                target: target.clone(),
+                measured: vec![target.clone(); 3],
+                expected: vec![false, true, true],
+            }],
+        }
+    }
+
+    #[test]
+    fn reference_outputs_pass() {
+        let report = run_bundles(&[bundle()]);
+        assert_eq!(report.passed, 4);
+        assert!(report.all_passed());
+    }
+
+    #[test]
+    fn mismatches_are_reported() {
+        let mut bundle = bundle();
+        bundle.distance[0].expected = 0.5;
+        bundle.matching[0].expected[0] = true;
+        let report = run_bundles(&[bundle]);
+        assert_eq!(report.failed, 2);
+        let failed: Vec<VectorKind> = report
+            .cases
+            .iter()
+            .filter(|case| !case.passed)
+            .map(|case| case.kind)
+            .collect();
+        assert_eq!(failed, vec![VectorKind::Distance, VectorKind::Matching]);
+        assert!(report.cases[3]
+            .detail
+            .as_deref()
+            .unwrap()
+            .contains("step 0"));
+    }
+}
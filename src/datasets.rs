@@ -0,0 +1,366 @@
+//! Empirical priors from published datasets.
+//!
+//! Affective and ambient-sensing datasets rarely use this crate's field names
+//! or units. A [`DatasetMapping`] describes which columns feed which
+//! dimensions (with a linear rescaling into native units), how rows are
+//! grouped into per-subject [`PatternTrajectory`] values, and how cells are
+//! parsed. Fitted [`PeerModel`]s summarize the trajectories so simulations can
+//! sample peers from observed distributions instead of uniform ranges.
+
+#[cfg(feature = "columnar")]
+pub mod columnar;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::BufRead;
+
+use rand::{Rng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::ingest::{parse_value, split_row, IngestError, NumberMode};
+use crate::pattern::sampling::PatternDistribution;
+use crate::pattern::{pattern_from_array, pattern_to_array, stats, SubmodalityPattern, DIMENSIONS};
+
+/// Source column and linear rescaling for one dimension.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ColumnSpec {
+    /// Header name of the source column.
+    pub column: String,
+    /// Multiplier applied to the parsed value.
+    #[cfg_attr(feature = "serde", serde(default = "default_scale"))]
+    pub scale: f32,
+    /// Offset added after scaling.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub offset: f32,
+}
+
+#[cfg(feature = "serde")]
+fn default_scale() -> f32 {
+    1.0
+}
+
+/// Mapping from a dataset's columns onto pattern dimensions.
+///
+/// Dimensions not listed in `dimensions` take their value from
+/// `SubmodalityPattern::zeros()`, so datasets covering only a few
+/// submodalities (e.g. arousal from self-reports) can still be loaded.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DatasetMapping {
+    /// Column identifying the subject or session; rows sharing a value form
+    /// one trajectory. If absent, every row belongs to a single trajectory.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub id_column: Option<String>,
+    /// Per-dimension column specs keyed by `SubmodalityPattern` field name.
+    pub dimensions: BTreeMap<String, ColumnSpec>,
+    /// Field delimiter.
+    #[cfg_attr(feature = "serde", serde(default = "default_delimiter"))]
+    pub delimiter: char,
+    /// Parse cells with locale-tolerant number rules.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub locale_tolerant: bool,
+}
+
+#[cfg(feature = "serde")]
+fn default_delimiter() -> char {
+    ','
+}
+
+/// An ordered sequence of patterns from one subject or session.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PatternTrajectory {
+    /// Subject or session identifier.
+    pub id: String,
+    /// Samples in file order.
+    pub samples: Vec<SubmodalityPattern>,
+}
+
+/// Load trajectories from delimited text using a column mapping.
+///
+/// Trajectories are returned in order of first appearance of their id.
+pub fn load_trajectories<R: BufRead>(
+    reader: R,
+    mapping: &DatasetMapping,
+) -> Result<Vec<PatternTrajectory>> {
+    let fields = mapped_fields(mapping)?;
+    let mode = if mapping.locale_tolerant {
+        NumberMode::LocaleTolerant
+    } else {
+        NumberMode::Strict
+    };
+
+    let mut lines = reader
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line));
+    let header = loop {
+        match lines.next() {
+            Some((_, line)) => {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    break split_row(&line, mapping.delimiter);
+                }
+            }
+            None => return Ok(Vec::new()),
+        }
+    };
+    let find_column = |name: &str| {
+        header
+            .iter()
+            .position(|cell| cell.trim() == name)
+            .ok_or_else(|| Error::from(IngestError::MissingColumn(name.to_string())))
+    };
+
+    let id_index = mapping.id_column.as_deref().map(find_column).transpose()?;
+    let mut sources: Vec<(usize, usize, &ColumnSpec)> = Vec::new();
+    for (field, spec) in fields {
+        sources.push((field, find_column(&spec.column)?, spec));
+    }
+
+    let baseline = pattern_to_array(&SubmodalityPattern::zeros());
+    let mut trajectories: Vec<PatternTrajectory> = Vec::new();
+    for (line_number, line) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let cells = split_row(&line, mapping.delimiter);
+        let cell = |index: usize| {
+            cells
+                .get(index)
+                .ok_or(Error::from(IngestError::ShortRow(line_number)))
+        };
+
+        let mut values = baseline;
+        for &(field, column, spec) in &sources {
+            let raw = parse_value(cell(column)?, DIMENSIONS[field].key, mode).map_err(|err| {
+                IngestError::Row {
+                    line: line_number,
+                    source: Box::new(err),
+                }
+            })?;
+            values[field] = raw * spec.scale + spec.offset;
+        }
+        let sample = pattern_from_array(values);
+
+        let id = match id_index {
+            Some(index) => cell(index)?.trim().to_string(),
+            None => String::from(DEFAULT_TRAJECTORY_ID),
+        };
+        push_sample(&mut trajectories, id, sample);
+    }
+    Ok(trajectories)
+}
+
+/// Trajectory id used when the mapping has no `id_column`.
+const DEFAULT_TRAJECTORY_ID: &str = "default";
+
+/// Resolve mapped dimension names to `DIMENSIONS` indices.
+fn mapped_fields(mapping: &DatasetMapping) -> Result<Vec<(usize, &ColumnSpec)>> {
+    mapping
+        .dimensions
+        .iter()
+        .map(|(name, spec)| {
+            DIMENSIONS
+                .iter()
+                .position(|info| info.key == name)
+                .map(|field| (field, spec))
+                .ok_or_else(|| DatasetError::UnknownDimension(name.clone()).into())
+        })
+        .collect()
+}
+
+/// Append a sample to the trajectory with `id`, creating it if needed.
+fn push_sample(
+    trajectories: &mut Vec<PatternTrajectory>,
+    id: String,
+    sample: SubmodalityPattern,
+) {
+    match trajectories.iter_mut().find(|t| t.id == id) {
+        Some(trajectory) => trajectory.samples.push(sample),
+        None => trajectories.push(PatternTrajectory {
+            id,
+            samples: vec![sample],
+        }),
+    }
+}
+
+/// Independent per-dimension Gaussian model of peer patterns.
+///
+/// Like the uniform sampler in `sim`, this assumes dimensions are
+/// independent; it only replaces the marginal distributions with ones fitted
+/// to data. Samples are clamped to each dimension's raw range.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PeerModel {
+    /// Per-dimension means, in raw units.
+    pub mean: SubmodalityPattern,
+    /// Per-dimension standard deviations, in raw units.
+    pub std_dev: SubmodalityPattern,
+    /// Number of samples the model was fitted on.
+    pub sample_count: usize,
+}
+
+impl PeerModel {
+    /// Fit a model to every sample in `trajectories`.
+    ///
+    /// Requires at least two samples. Standard deviations use the unbiased
+    /// (n - 1) estimator.
+    pub fn fit(trajectories: &[PatternTrajectory]) -> Result<Self, DatasetError> {
+        let samples = || trajectories.iter().flat_map(|t| t.samples.iter());
+        let n = samples().count();
+        if n < 2 {
+            return Err(DatasetError::NotEnoughSamples(n));
+        }
+        let mean = stats::mean(samples()).expect("at least two samples");
+        let variance = stats::variance(samples()).expect("at least two samples");
+        Ok(Self {
+            mean,
+            std_dev: pattern_from_array(pattern_to_array(&variance).map(f32::sqrt)),
+            sample_count: n,
+        })
+    }
+
+    /// Draw a random pattern from the model.
+    ///
+    /// Dimensions are independent Gaussians clamped to the reference ranges,
+    /// like a `pattern::sampling::Gaussian` around the mean.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> SubmodalityPattern {
+        self.mean.jitter(rng, &self.std_dev)
+    }
+}
+
+impl PatternDistribution for PeerModel {
+    fn sample(&self, rng: &mut dyn RngCore) -> SubmodalityPattern {
+        PeerModel::sample(self, rng)
+    }
+}
+
+/// Errors returned while loading datasets or fitting models.
+#[derive(Debug)]
+pub enum DatasetError {
+    /// The mapping referenced a dimension that is not a pattern field.
+    UnknownDimension(String),
+    /// Too few samples were available to fit a model.
+    NotEnoughSamples(usize),
+    /// A mapped column held a null value.
+    NullValue {
+        /// Column containing the null.
+        column: String,
+        /// Zero-based row index within its record batch.
+        row: usize,
+    },
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownDimension(name) => write!(f, "unknown dimension '{name}' in mapping"),
+            Self::NotEnoughSamples(n) => {
+                write!(f, "need at least 2 samples to fit a peer model, got {n}")
+            }
+            Self::NullValue { column, row } => {
+                write!(f, "null value in column '{column}' at row {row}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DatasetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::{AROUSAL_MAX, AROUSAL_MIN};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn sam_mapping() -> DatasetMapping {
+        // Self-assessment manikin arousal on a 1..=9 scale, rescaled to [0, 1].
+        let mut dimensions = BTreeMap::new();
+        dimensions.insert(
+            "arousal".to_string(),
+            ColumnSpec {
+                column: "sam_arousal".to_string(),
+                scale: 0.125,
+                offset: -0.125,
+            },
+        );
+        dimensions.insert(
+            "temperature".to_string(),
+            ColumnSpec {
+                column: "room_c".to_string(),
+                scale: 1.0,
+                offset: 0.0,
+            },
+        );
+        DatasetMapping {
+            id_column: Some("subject".to_string()),
+            dimensions,
+            delimiter: ';',
+            locale_tolerant: true,
+        }
+    }
+
+    const DATA: &str = "\
+subject;sam_arousal;room_c
+s1;1;21,0
+s2;9;23,0
+s1;5;22,0
+";
+
+    #[test]
+    fn rows_are_grouped_and_rescaled() {
+        let trajectories = load_trajectories(DATA.as_bytes(), &sam_mapping()).expect("load");
+        assert_eq!(trajectories.len(), 2);
+        assert_eq!(trajectories[0].id, "s1");
+        assert_eq!(trajectories[0].samples.len(), 2);
+        assert_eq!(trajectories[0].samples[0].arousal, 0.0);
+        assert_eq!(trajectories[0].samples[1].arousal, 0.5);
+        assert_eq!(trajectories[1].samples[0].arousal, 1.0);
+        assert_eq!(trajectories[1].samples[0].temperature, 23.0);
+        assert_eq!(trajectories[1].samples[0].pitch, SubmodalityPattern::zeros().pitch);
+    }
+
+    #[test]
+    fn fitted_model_reflects_data() {
+        let trajectories = load_trajectories(DATA.as_bytes(), &sam_mapping()).expect("load");
+        let model = PeerModel::fit(&trajectories).expect("fit");
+        assert_eq!(model.sample_count, 3);
+        assert!((model.mean.temperature - 22.0).abs() < 1e-5);
+        assert!((model.std_dev.temperature - 1.0).abs() < 1e-5);
+        assert_eq!(model.std_dev.pitch, 0.0);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let sample = model.sample(&mut rng);
+            assert_eq!(sample.pitch, model.mean.pitch);
+            assert!((AROUSAL_MIN..=AROUSAL_MAX).contains(&sample.arousal));
+        }
+    }
+
+    #[test]
+    fn mapping_rejects_unknown_dimension() {
+        let mut mapping = sam_mapping();
+        mapping.dimensions.insert(
+            "valence".to_string(),
+            ColumnSpec {
+                column: "sam_valence".to_string(),
+                scale: 1.0,
+                offset: 0.0,
+            },
+        );
+        let err = load_trajectories(DATA.as_bytes(), &mapping).expect_err("unknown");
+        assert!(
+            matches!(err, Error::Dataset(DatasetError::UnknownDimension(name)) if name == "valence")
+        );
+    }
+}
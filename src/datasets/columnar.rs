@@ -0,0 +1,228 @@
+//! Columnar (Arrow IPC and Parquet) dataset loading.
+//!
+//! Uses the same [`DatasetMapping`] as delimited loading. Mapped columns may
+//! have any numeric Arrow type and are cast to `f32`; the id column may have
+//! any type castable to a string. `delimiter` and `locale_tolerant` are
+//! ignored because columnar files carry typed values.
+
+use std::fs::File;
+use std::io::{Read, Seek};
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::Float32Type;
+use arrow_array::{Array, RecordBatch};
+use arrow_schema::{ArrowError, DataType};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+use super::{
+    mapped_fields, push_sample, DatasetError, DatasetMapping, PatternTrajectory,
+    DEFAULT_TRAJECTORY_ID,
+};
+use crate::error::{Error, Result};
+use crate::ingest::IngestError;
+use crate::pattern::{pattern_from_array, pattern_to_array, SubmodalityPattern};
+
+/// Load trajectories from an Arrow IPC file.
+///
+/// Trajectories are returned in order of first appearance of their id.
+pub fn load_trajectories_ipc<R: Read + Seek>(
+    reader: R,
+    mapping: &DatasetMapping,
+) -> Result<Vec<PatternTrajectory>> {
+    let batches = arrow_ipc::reader::FileReader::try_new(reader, None)?;
+    trajectories_from_batches(batches, mapping)
+}
+
+/// Load trajectories from a Parquet file.
+///
+/// Trajectories are returned in order of first appearance of their id.
+pub fn load_trajectories_parquet(
+    file: File,
+    mapping: &DatasetMapping,
+) -> Result<Vec<PatternTrajectory>> {
+    let batches = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+    trajectories_from_batches(batches, mapping)
+}
+
+/// Load trajectories from a sequence of record batches.
+///
+/// Rows are processed in batch order; rows from different batches that share
+/// an id are appended to the same trajectory.
+pub fn trajectories_from_batches<I>(
+    batches: I,
+    mapping: &DatasetMapping,
+) -> Result<Vec<PatternTrajectory>>
+where
+    I: IntoIterator<Item = std::result::Result<RecordBatch, ArrowError>>,
+{
+    let fields = mapped_fields(mapping)?;
+    let baseline = pattern_to_array(&SubmodalityPattern::zeros());
+    let mut trajectories: Vec<PatternTrajectory> = Vec::new();
+
+    for batch in batches {
+        let batch = batch?;
+        let ids = match mapping.id_column.as_deref() {
+            Some(name) => Some((name, cast_column(&batch, name, &DataType::Utf8)?)),
+            None => None,
+        };
+        let mut sources = Vec::with_capacity(fields.len());
+        for &(field, spec) in &fields {
+            let column = cast_column(&batch, &spec.column, &DataType::Float32)?;
+            sources.push((field, spec, column));
+        }
+
+        for row in 0..batch.num_rows() {
+            let mut values = baseline;
+            for (field, spec, column) in &sources {
+                if column.is_null(row) {
+                    return Err(null_value(&spec.column, row));
+                }
+                let raw = column.as_primitive::<Float32Type>().value(row);
+                values[*field] = raw * spec.scale + spec.offset;
+            }
+            let id = match &ids {
+                Some((name, column)) => {
+                    if column.is_null(row) {
+                        return Err(null_value(name, row));
+                    }
+                    column.as_string::<i32>().value(row).trim().to_string()
+                }
+                None => String::from(DEFAULT_TRAJECTORY_ID),
+            };
+            push_sample(&mut trajectories, id, pattern_from_array(values));
+        }
+    }
+    Ok(trajectories)
+}
+
+/// Look up a column by name and cast it to `data_type`.
+fn cast_column(
+    batch: &RecordBatch,
+    name: &str,
+    data_type: &DataType,
+) -> Result<arrow_array::ArrayRef> {
+    let column = batch
+        .column_by_name(name)
+        .ok_or_else(|| IngestError::MissingColumn(name.to_string()))?;
+    Ok(arrow_cast::cast(column, data_type)?)
+}
+
+fn null_value(column: &str, row: usize) -> Error {
+    DatasetError::NullValue {
+        column: column.to_string(),
+        row,
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    use arrow_array::{Float64Array, Int32Array, StringArray};
+    use arrow_schema::{Field, Schema};
+
+    use super::*;
+    use crate::datasets::ColumnSpec;
+
+    fn mapping() -> DatasetMapping {
+        DatasetMapping {
+            id_column: Some("subject".to_string()),
+            dimensions: BTreeMap::from([
+                (
+                    "arousal".to_string(),
+                    ColumnSpec {
+                        column: "sam_arousal".to_string(),
+                        scale: 0.125,
+                        offset: -0.125,
+                    },
+                ),
+                (
+                    "tempo".to_string(),
+                    ColumnSpec {
+                        column: "hr".to_string(),
+                        scale: 1.0,
+                        offset: 0.0,
+                    },
+                ),
+            ]),
+            delimiter: ',',
+            locale_tolerant: false,
+        }
+    }
+
+    fn batch(arousal: Vec<Option<f64>>) -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("subject", DataType::Utf8, false),
+            Field::new("sam_arousal", DataType::Float64, true),
+            Field::new("hr", DataType::Int32, false),
+        ]);
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(StringArray::from(vec!["s1", "s2", "s1"])),
+                Arc::new(Float64Array::from(arousal)),
+                Arc::new(Int32Array::from(vec![60, 72, 64])),
+            ],
+        )
+        .expect("batch")
+    }
+
+    fn check(trajectories: &[PatternTrajectory]) {
+        assert_eq!(trajectories.len(), 2);
+        assert_eq!(trajectories[0].id, "s1");
+        assert_eq!(trajectories[0].samples.len(), 2);
+        assert_eq!(trajectories[0].samples[1].tempo, 64.0);
+        assert_eq!(trajectories[1].samples[0].arousal, 0.5);
+    }
+
+    #[test]
+    fn reads_ipc_file() {
+        let batch = batch(vec![Some(1.0), Some(5.0), Some(9.0)]);
+        let mut writer =
+            arrow_ipc::writer::FileWriter::try_new(Vec::new(), &batch.schema()).expect("writer");
+        writer.write(&batch).expect("write");
+        let bytes = writer.into_inner().expect("finish");
+
+        let trajectories = load_trajectories_ipc(Cursor::new(bytes), &mapping()).expect("load");
+        check(&trajectories);
+    }
+
+    #[test]
+    fn reads_parquet_file() {
+        let batch = batch(vec![Some(1.0), Some(5.0), Some(9.0)]);
+        let path =
+            std::env::temp_dir().join(format!("phenorv-columnar-{}.parquet", std::process::id()));
+        let file = File::create(&path).expect("create");
+        let mut writer =
+            parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None).expect("writer");
+        writer.write(&batch).expect("write");
+        writer.close().expect("close");
+
+        let trajectories =
+            load_trajectories_parquet(File::open(&path).expect("open"), &mapping()).expect("load");
+        std::fs::remove_file(&path).expect("cleanup");
+        check(&trajectories);
+    }
+
+    #[test]
+    fn nulls_and_missing_columns_are_reported() {
+        let err =
+            trajectories_from_batches([Ok(batch(vec![Some(1.0), None, Some(9.0)]))], &mapping())
+                .expect_err("null");
+        assert!(matches!(
+            err,
+            Error::Dataset(DatasetError::NullValue { ref column, row: 1 }) if column == "sam_arousal"
+        ));
+
+        let mut missing = mapping();
+        missing.id_column = Some("session".to_string());
+        let err = trajectories_from_batches([Ok(batch(vec![Some(1.0); 3]))], &missing)
+            .expect_err("missing");
+        assert!(
+            matches!(err, Error::Ingest(IngestError::MissingColumn(name)) if name == "session")
+        );
+    }
+}
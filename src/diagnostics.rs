@@ -0,0 +1,338 @@
+//! Configuration health checks.
+//!
+//! Each check inspects one input (an SRT, a matching config, a calibration
+//! profile, a dimension registry, or a fitted peer model) and returns [`Finding`]s describing
+//! problems and how to fix them. An empty list means nothing looked wrong.
+//! The `phenorv doctor` command runs these checks on its arguments.
+
+use std::fmt;
+
+use crate::calibration::CalibrationProfile;
+use crate::datasets::PeerModel;
+use crate::matching::MatchingConfig;
+use crate::pattern::{pattern_to_array, DimensionInfo, DIMENSIONS};
+use crate::registry::DimensionRegistry;
+use crate::sim::false_match_rate;
+use crate::srt::SemanticRendezvousToken;
+
+/// Shannon entropy, in bits per byte, below which an SRT is flagged.
+///
+/// Uniformly random 32-byte tokens average about 4.85 bits per byte (at most
+/// 5 bits are observable in 32 bytes) and essentially never fall below 4.
+pub const MIN_SRT_BYTE_ENTROPY: f64 = 4.0;
+
+/// Random pairs sampled when estimating the false-match rate of a config.
+pub const FALSE_MATCH_SAMPLES: usize = 20_000;
+
+/// Per-observation false-match rate above which epsilon is flagged as loose.
+pub const MAX_FALSE_MATCH_RATE: f64 = 1e-3;
+
+/// Epsilon below which genuine peers are unlikely to match through sensor
+/// noise.
+pub const MIN_PRACTICAL_EPSILON: f32 = 0.02;
+
+/// Samples below which a fitted peer model is flagged as unreliable.
+pub const MIN_MODEL_SAMPLES: usize = 30;
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Likely to degrade privacy or matching, but usable.
+    Warning,
+    /// Invalid; the input will be rejected or behave incorrectly.
+    Error,
+}
+
+/// A single problem found by a check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// What was checked, e.g. `srt` or `epsilon`.
+    pub subject: String,
+    /// Description of the problem and suggested fix.
+    pub message: String,
+}
+
+impl Finding {
+    fn warning(subject: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            subject: subject.to_string(),
+            message: message.into(),
+        }
+    }
+
+    fn error(subject: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            subject: subject.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{level} [{}]: {}", self.subject, self.message)
+    }
+}
+
+/// Shannon entropy of the SRT's bytes, in bits per byte.
+pub fn srt_byte_entropy(srt: &SemanticRendezvousToken) -> f64 {
+    let mut counts = [0usize; 256];
+    for &byte in srt.as_bytes() {
+        counts[usize::from(byte)] += 1;
+    }
+    let total = srt.as_bytes().len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let count = count as f64;
+            count / total * (total / count).log2()
+        })
+        .sum()
+}
+
+/// Check that an SRT parses and looks randomly generated.
+pub fn check_srt(srt_hex: &str) -> Vec<Finding> {
+    let srt = match SemanticRendezvousToken::from_hex(srt_hex) {
+        Ok(srt) => srt,
+        Err(err) => {
+            return vec![Finding::error(
+                "srt",
+                format!("{err}; an SRT is exactly 64 hex characters (32 bytes)"),
+            )]
+        }
+    };
+    let entropy = srt_byte_entropy(&srt);
+    if entropy < MIN_SRT_BYTE_ENTROPY {
+        return vec![Finding::warning(
+            "srt",
+            format!(
+                "byte entropy is {entropy:.2} bits/byte (expected about 4.85); \
+                 the token looks hand-made or patterned, so regenerate it from a \
+                 cryptographic random source"
+            ),
+        )];
+    }
+    Vec::new()
+}
+
+/// Check matching parameters, estimating the false-match rate by simulation.
+pub fn check_matching(config: &MatchingConfig) -> Vec<Finding> {
+    if let Err(err) = config.validate() {
+        return vec![Finding::error("matching", err.to_string())];
+    }
+    let mut findings = Vec::new();
+    if config.window_size <= 1 {
+        findings.push(Finding::warning(
+            "window_size",
+            format!(
+                "a window of {} applies no temporal smoothing; single noisy samples \
+                 can trigger rendezvous, so use 3 or more",
+                config.window_size
+            ),
+        ));
+    } else if config.window_size > 30 {
+        findings.push(Finding::warning(
+            "window_size",
+            format!(
+                "a window of {} requires a long run of consecutive matches and may \
+                 never trigger with realistic sensor dropouts",
+                config.window_size
+            ),
+        ));
+    }
+    if config.epsilon < MIN_PRACTICAL_EPSILON {
+        findings.push(Finding::warning(
+            "epsilon",
+            format!(
+                "epsilon {} is below typical sensor noise ({MIN_PRACTICAL_EPSILON}); \
+                 genuine peers may never match",
+                config.epsilon
+            ),
+        ));
+    }
+    let rate = false_match_rate(config.epsilon, &config.weights, FALSE_MATCH_SAMPLES);
+    if rate > MAX_FALSE_MATCH_RATE {
+        findings.push(Finding::warning(
+            "epsilon",
+            format!(
+                "about {:.3}% of random observations fall within epsilon {}; \
+                 lower epsilon (see sim::solve_epsilon) to reduce false rendezvous",
+                rate * 100.0,
+                config.epsilon
+            ),
+        ));
+    }
+    findings
+}
+
+/// Check a calibration profile shipped with a dataset or sensor rig.
+///
+/// Every invalid override is reported, not just the first. An override that
+/// does not overlap the reference range at all is flagged as a likely unit
+/// mistake (say, a color temperature in mireds instead of kelvin).
+pub fn check_calibration(profile: &CalibrationProfile) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (range, info) in profile.ranges().iter().zip(DIMENSIONS) {
+        if !range.is_valid() {
+            findings.push(Finding::error(
+                "calibration",
+                format!(
+                    "range {}..={} for '{}' must be finite with min < max and within \
+                     the domain of its {:?} policy",
+                    range.min, range.max, info.key, range.policy
+                ),
+            ));
+        } else if range.max < info.min || range.min > info.max {
+            let unit = info.unit.map(|unit| format!(" {unit}")).unwrap_or_default();
+            findings.push(Finding::warning(
+                "calibration",
+                format!(
+                    "range {}..={} for '{}' does not overlap the reference range \
+                     {}..={}{unit}; check that the profile uses the same units",
+                    range.min, range.max, info.key, info.min, info.max
+                ),
+            ));
+        }
+    }
+    findings
+}
+
+/// Check a dimension registry (range profile) loaded from outside the crate.
+///
+/// Individual dimensions are validated when the registry is built or
+/// deserialized, so this only flags registries that cannot yield useful
+/// distances.
+pub fn check_registry(registry: &DimensionRegistry) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if registry.is_empty() {
+        findings.push(Finding::error("registry", "the registry has no dimensions"));
+    } else if registry.iter().all(|spec| spec.weight == 0.0) {
+        findings.push(Finding::error(
+            "registry",
+            "every dimension has weight 0, so all patterns are at distance 0",
+        ));
+    }
+    findings
+}
+
+/// Check a fitted peer model used as a simulation prior.
+pub fn check_peer_model(model: &PeerModel) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let means = pattern_to_array(&model.mean);
+    let std_devs = pattern_to_array(&model.std_dev);
+    for ((mean, std_dev), info) in means.iter().zip(std_devs).zip(DIMENSIONS) {
+        let DimensionInfo { key, min, max, .. } = info;
+        if !mean.is_finite() || !std_dev.is_finite() || std_dev < 0.0 {
+            findings.push(Finding::error(
+                "prior",
+                format!("{key} has an invalid mean or standard deviation"),
+            ));
+        } else if *mean < min || *mean > max {
+            findings.push(Finding::warning(
+                "prior",
+                format!(
+                    "{key} mean {mean} is outside its range {min}..={max}; \
+                     check the dataset mapping's scale and offset"
+                ),
+            ));
+        }
+    }
+    if model.sample_count < MIN_MODEL_SAMPLES {
+        findings.push(Finding::warning(
+            "prior",
+            format!(
+                "fitted on only {} samples; refit on at least {MIN_MODEL_SAMPLES}",
+                model.sample_count
+            ),
+        ));
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calibration::{DimensionRange, NormalizationPolicy};
+    use crate::pattern::SubmodalityPattern;
+    use crate::registry::DimensionSpec;
+
+    #[test]
+    fn srt_checks_flag_bad_tokens() {
+        assert_eq!(check_srt(&"00".repeat(32))[0].severity, Severity::Warning);
+        assert_eq!(check_srt("abc")[0].severity, Severity::Error);
+        let random: String = (0u8..32)
+            .map(|i| format!("{:02x}", i.wrapping_mul(97).wrapping_add(13)))
+            .collect();
+        assert!(check_srt(&random).is_empty());
+    }
+
+    #[test]
+    fn matching_checks_flag_loose_and_unsmoothed_configs() {
+        let subjects = |config| -> Vec<String> {
+            check_matching(&config)
+                .into_iter()
+                .map(|finding| finding.subject)
+                .collect()
+        };
+        assert!(subjects(MatchingConfig::new(0.1, 3)).is_empty());
+        assert_eq!(
+            subjects(MatchingConfig::new(1.0, 1)),
+            ["window_size", "epsilon"]
+        );
+        assert_eq!(
+            check_matching(&MatchingConfig::new(-1.0, 3))[0].severity,
+            Severity::Error
+        );
+    }
+
+    #[test]
+    fn calibration_checks_report_every_bad_range() {
+        assert!(check_calibration(&CalibrationProfile::new()).is_empty());
+        let profile = CalibrationProfile {
+            brightness: Some(DimensionRange::new(1.0, 0.0)),
+            color_temp: Some(DimensionRange::new(100.0, 500.0)),
+            pitch: Some(
+                DimensionRange::new(0.0, 20_000.0).with_policy(NormalizationPolicy::Logarithmic),
+            ),
+            ..CalibrationProfile::new()
+        };
+        let severities: Vec<_> = check_calibration(&profile)
+            .into_iter()
+            .map(|finding| finding.severity)
+            .collect();
+        assert_eq!(
+            severities,
+            [Severity::Error, Severity::Warning, Severity::Error]
+        );
+    }
+
+    #[test]
+    fn registry_and_prior_checks() {
+        let mut registry = DimensionRegistry::core();
+        assert!(check_registry(&registry).is_empty());
+        registry = DimensionRegistry::new();
+        assert_eq!(check_registry(&registry).len(), 1);
+        registry
+            .register(DimensionSpec::linear("x", 0.0, 1.0).with_weight(0.0))
+            .expect("register");
+        assert_eq!(check_registry(&registry)[0].severity, Severity::Error);
+
+        let model = PeerModel {
+            mean: SubmodalityPattern::zeros(),
+            std_dev: SubmodalityPattern::zeros(),
+            sample_count: 5,
+        };
+        let findings = check_peer_model(&model);
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].message.contains("5 samples"));
+    }
+}
@@ -0,0 +1,640 @@
+//! Allocation-free core shared by the `std` and `no_std` builds.
+//!
+//! This module builds without the `std` feature (`default-features = false`),
+//! so a microcontroller next to the sensors can derive its target and match
+//! against it. It holds the pattern types and their normalization, the
+//! HMAC-SHA256 derivation of a target from an SRT, and [`MatchWindow`], the
+//! sliding window of match results. Nothing here allocates: a window's storage
+//! is supplied by the caller, so a fixed-size array sized with
+//! [`window_blocks`] works.
+//!
+//! The `std` modules are built on these items rather than beside them:
+//! `pattern` and `srt` re-export the types and derivation, and
+//! `matching::Matcher` records its results in a `Vec`-backed [`MatchWindow`].
+//! A `no_std` matcher pushes `distance(&measured, &target) <= epsilon` for
+//! each normalized reading into [`MatchWindow::record`], which makes the same
+//! decisions as an unweighted `matching::Matcher` with the same window size.
+
+use hmac::{Hmac, Mac};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// Minimum brightness (normalized).
+pub const BRIGHTNESS_MIN: f32 = 0.0;
+/// Maximum brightness (normalized).
+pub const BRIGHTNESS_MAX: f32 = 1.0;
+/// Minimum color temperature (Kelvin).
+pub const COLOR_TEMP_MIN: f32 = 2000.0;
+/// Maximum color temperature (Kelvin).
+pub const COLOR_TEMP_MAX: f32 = 10_000.0;
+/// Minimum focal distance (normalized).
+pub const FOCAL_DISTANCE_MIN: f32 = 0.0;
+/// Maximum focal distance (normalized).
+pub const FOCAL_DISTANCE_MAX: f32 = 1.0;
+/// Minimum volume (normalized).
+pub const VOLUME_MIN: f32 = 0.0;
+/// Maximum volume (normalized).
+pub const VOLUME_MAX: f32 = 1.0;
+/// Minimum tempo (BPM).
+pub const TEMPO_MIN: f32 = 0.0;
+/// Maximum tempo (BPM).
+pub const TEMPO_MAX: f32 = 300.0;
+/// Minimum pitch (Hz).
+pub const PITCH_MIN: f32 = 20.0;
+/// Maximum pitch (Hz).
+pub const PITCH_MAX: f32 = 20_000.0;
+/// Minimum temperature (Celsius).
+pub const TEMPERATURE_MIN: f32 = 10.0;
+/// Maximum temperature (Celsius).
+pub const TEMPERATURE_MAX: f32 = 40.0;
+/// Minimum movement (normalized).
+pub const MOVEMENT_MIN: f32 = 0.0;
+/// Maximum movement (normalized).
+pub const MOVEMENT_MAX: f32 = 1.0;
+/// Minimum arousal (normalized).
+pub const AROUSAL_MIN: f32 = 0.0;
+/// Maximum arousal (normalized).
+pub const AROUSAL_MAX: f32 = 1.0;
+
+/// Description of one core dimension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DimensionInfo {
+    /// Field name in `SubmodalityPattern`, CSV headers, and dimension
+    /// registries. JSON records since schema version 2 spell `color_temp` as
+    /// `color_temperature_k` (see `pattern::migrate::FIELD_RENAMES`).
+    pub key: &'static str,
+    /// Human-readable name for labels.
+    pub name: &'static str,
+    /// Unit of raw values, or `None` for dimensions already in `[0, 1]`.
+    pub unit: Option<&'static str>,
+    /// Lower bound of the reference range.
+    pub min: f32,
+    /// Upper bound of the reference range.
+    pub max: f32,
+    /// Whether the range wraps around, so `min` and `max` are adjacent (as
+    /// for a hue angle). None of the core dimensions is circular.
+    pub circular: bool,
+}
+
+impl DimensionInfo {
+    const fn linear(
+        key: &'static str,
+        name: &'static str,
+        unit: Option<&'static str>,
+        min: f32,
+        max: f32,
+    ) -> Self {
+        Self {
+            key,
+            name,
+            unit,
+            min,
+            max,
+            circular: false,
+        }
+    }
+
+    /// Whether `value` is finite and within the reference range.
+    pub fn contains(&self, value: f32) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+
+    /// Normalize a raw value into `[0, 1]`, clamping out-of-range input.
+    pub fn normalize(&self, value: f32) -> f32 {
+        ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+    }
+}
+
+/// Every core dimension, in field declaration order.
+pub const DIMENSIONS: [DimensionInfo; 9] = [
+    DimensionInfo::linear(
+        "brightness",
+        "Brightness",
+        None,
+        BRIGHTNESS_MIN,
+        BRIGHTNESS_MAX,
+    ),
+    DimensionInfo::linear(
+        "color_temp",
+        "Color temperature",
+        Some("K"),
+        COLOR_TEMP_MIN,
+        COLOR_TEMP_MAX,
+    ),
+    DimensionInfo::linear(
+        "focal_distance",
+        "Focal distance",
+        None,
+        FOCAL_DISTANCE_MIN,
+        FOCAL_DISTANCE_MAX,
+    ),
+    DimensionInfo::linear("volume", "Volume", None, VOLUME_MIN, VOLUME_MAX),
+    DimensionInfo::linear("tempo", "Tempo", Some("BPM"), TEMPO_MIN, TEMPO_MAX),
+    DimensionInfo::linear("pitch", "Pitch", Some("Hz"), PITCH_MIN, PITCH_MAX),
+    DimensionInfo::linear(
+        "temperature",
+        "Temperature",
+        Some("°C"),
+        TEMPERATURE_MIN,
+        TEMPERATURE_MAX,
+    ),
+    DimensionInfo::linear("movement", "Movement", None, MOVEMENT_MIN, MOVEMENT_MAX),
+    DimensionInfo::linear("arousal", "Arousal", None, AROUSAL_MIN, AROUSAL_MAX),
+];
+
+/// A submodality pattern as described in the paper.
+///
+/// This mirrors the SubmodalityPattern pseudo-code and keeps raw values in
+/// their natural units. Normalization to `[0, 1]` is handled separately.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SubmodalityPattern {
+    /// Brightness, normalized to `[0.0, 1.0]`.
+    pub brightness: f32,
+    /// Color temperature in Kelvin (2000–10000); `color_temperature_k` in
+    /// JSON since schema version 2 (see `pattern::migrate`).
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "color_temperature_k", alias = "color_temp")
+    )]
+    pub color_temp: f32,
+    /// Focal distance, normalized to `[0.0, 1.0]`.
+    pub focal_distance: f32,
+    /// Volume, normalized to `[0.0, 1.0]`.
+    pub volume: f32,
+    /// Tempo in BPM (0–300).
+    pub tempo: f32,
+    /// Pitch in Hertz (20–20000).
+    pub pitch: f32,
+    /// Temperature in Celsius.
+    pub temperature: f32,
+    /// Movement, normalized to `[0.0, 1.0]`.
+    pub movement: f32,
+    /// Arousal, normalized to `[0.0, 1.0]`.
+    pub arousal: f32,
+}
+
+impl SubmodalityPattern {
+    /// Create a neutral baseline pattern for initialization and testing.
+    ///
+    /// "Neutral" means unit-range fields are centered or zeroed, and absolute
+    /// scale fields are set to commonly used midpoints. This is a placeholder
+    /// baseline and should be replaced with domain-specific defaults later.
+    pub fn zeros() -> Self {
+        Self {
+            brightness: 0.5,
+            color_temp: 6500.0,
+            focal_distance: 0.5,
+            volume: 0.5,
+            tempo: 0.0,
+            pitch: 440.0,
+            temperature: 20.0,
+            movement: 0.0,
+            arousal: 0.0,
+        }
+    }
+
+    /// Normalize this pattern into `[0, 1]` ranges for distance calculations.
+    ///
+    /// The normalization uses fixed min/max ranges for each dimension. These
+    /// ranges are reference defaults and may need tuning or calibration in
+    /// real deployments based on sensors and user populations.
+    ///
+    /// Temperature normalization assumes a `10..=40` Celsius operating window
+    /// as a placeholder until domain-specific bounds are defined.
+    pub fn normalize(&self) -> NormalizedPattern {
+        normalized_from_array(normalize(&pattern_to_array(self)))
+    }
+}
+
+/// A fully normalized submodality pattern with values in `[0, 1]`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct NormalizedPattern {
+    /// Normalized brightness.
+    pub brightness: f32,
+    /// Normalized color temperature; `color_temperature_k` in JSON.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "color_temperature_k", alias = "color_temp")
+    )]
+    pub color_temp: f32,
+    /// Normalized focal distance.
+    pub focal_distance: f32,
+    /// Normalized volume.
+    pub volume: f32,
+    /// Normalized tempo.
+    pub tempo: f32,
+    /// Normalized pitch.
+    pub pitch: f32,
+    /// Normalized temperature.
+    pub temperature: f32,
+    /// Normalized movement.
+    pub movement: f32,
+    /// Normalized arousal.
+    pub arousal: f32,
+}
+
+impl NormalizedPattern {
+    /// Map back into raw units using the reference ranges; the inverse of
+    /// [`SubmodalityPattern::normalize`] for in-range patterns.
+    ///
+    /// Values outside `[0, 1]` are clamped first, so the result always lies
+    /// within the reference ranges.
+    pub fn denormalize(&self) -> SubmodalityPattern {
+        let values = normalized_to_array(self);
+        pattern_from_array(core::array::from_fn(|i| {
+            let DimensionInfo { min, max, .. } = DIMENSIONS[i];
+            min + clamp01(values[i]) * (max - min)
+        }))
+    }
+}
+
+/// Normalize raw values into `[0, 1]` using the reference ranges, clamping
+/// out-of-range values.
+pub fn normalize(raw: &[f32; 9]) -> [f32; 9] {
+    core::array::from_fn(|i| {
+        DIMENSIONS[i].normalize(raw[i])
+    })
+}
+
+/// Euclidean distance between two normalized patterns.
+pub fn distance(a: &[f32; 9], b: &[f32; 9]) -> f32 {
+    let sum: f32 = a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum();
+    libm::sqrtf(sum)
+}
+
+pub(crate) fn clamp01(value: f32) -> f32 {
+    value.clamp(0.0, 1.0)
+}
+
+/// Field values in declaration order (`DIMENSIONS` order).
+pub(crate) fn pattern_to_array(pattern: &SubmodalityPattern) -> [f32; 9] {
+    [
+        pattern.brightness,
+        pattern.color_temp,
+        pattern.focal_distance,
+        pattern.volume,
+        pattern.tempo,
+        pattern.pitch,
+        pattern.temperature,
+        pattern.movement,
+        pattern.arousal,
+    ]
+}
+
+/// Inverse of [`pattern_to_array`].
+pub(crate) fn pattern_from_array(values: [f32; 9]) -> SubmodalityPattern {
+    SubmodalityPattern {
+        brightness: values[0],
+        color_temp: values[1],
+        focal_distance: values[2],
+        volume: values[3],
+        tempo: values[4],
+        pitch: values[5],
+        temperature: values[6],
+        movement: values[7],
+        arousal: values[8],
+    }
+}
+
+/// Normalized values in declaration order.
+pub(crate) fn normalized_to_array(pattern: &NormalizedPattern) -> [f32; 9] {
+    [
+        pattern.brightness,
+        pattern.color_temp,
+        pattern.focal_distance,
+        pattern.volume,
+        pattern.tempo,
+        pattern.pitch,
+        pattern.temperature,
+        pattern.movement,
+        pattern.arousal,
+    ]
+}
+
+/// Inverse of [`normalized_to_array`].
+pub(crate) fn normalized_from_array(values: [f32; 9]) -> NormalizedPattern {
+    NormalizedPattern {
+        brightness: values[0],
+        color_temp: values[1],
+        focal_distance: values[2],
+        volume: values[3],
+        tempo: values[4],
+        pitch: values[5],
+        temperature: values[6],
+        movement: values[7],
+        arousal: values[8],
+    }
+}
+
+impl From<SubmodalityPattern> for [f32; 9] {
+    fn from(pattern: SubmodalityPattern) -> Self {
+        pattern_to_array(&pattern)
+    }
+}
+
+impl From<NormalizedPattern> for [f32; 9] {
+    fn from(pattern: NormalizedPattern) -> Self {
+        normalized_to_array(&pattern)
+    }
+}
+
+/// Map a 16-bit integer into a floating-point range `[min, max]`.
+///
+/// `val` is interpreted as an unsigned 16-bit sample, where `0` maps to `min`
+/// and `u16::MAX` maps to `max`.
+pub fn quantize_u16_to_range(val: u16, min: f32, max: f32) -> f32 {
+    let fraction = f32::from(val) / f32::from(u16::MAX);
+    min + (max - min) * fraction
+}
+
+/// Map a value in `[min, max]` to the nearest 16-bit sample; the inverse of
+/// [`quantize_u16_to_range`].
+///
+/// Values outside the range are clamped and NaN maps to `0`.
+pub fn range_to_u16(value: f32, min: f32, max: f32) -> u16 {
+    let fraction = ((value - min) / (max - min)).clamp(0.0, 1.0);
+    libm::roundf(fraction * f32::from(u16::MAX)) as u16
+}
+
+/// A Semantic Rendezvous Token (SRT).
+///
+/// An SRT is a shared secret key used for HMAC-based derivation of target
+/// patterns during rendezvous. We treat it as an opaque 32-byte value and do
+/// not attempt to derive it from passwords or other human inputs here.
+///
+/// With the `serde` feature, SRTs serialize as 64-character hex strings in
+/// human-readable formats such as JSON (matching the CLI's `--srt-hex`) and
+/// as raw bytes otherwise. The 32-element byte arrays written by earlier
+/// versions are still accepted when deserializing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticRendezvousToken(pub(crate) [u8; 32]);
+
+impl SemanticRendezvousToken {
+    /// Create an SRT from raw 32-byte input.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Borrow the underlying bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+/// Derive a `SubmodalityPattern` from an SRT and salt (oracle-state).
+///
+/// This uses HMAC-SHA256 with the SRT as key and `salt` as the message; see
+/// `srt::pattern_from_srt_with` for other algorithms.
+/// The resulting 32-byte digest is partitioned into 16-bit chunks:
+///
+/// - `digest[0..2]`  -> brightness
+/// - `digest[2..4]`  -> color_temp
+/// - `digest[4..6]`  -> focal_distance
+/// - `digest[6..8]`  -> volume
+/// - `digest[8..10]` -> tempo
+/// - `digest[10..12]` -> pitch
+/// - `digest[12..14]` -> temperature
+/// - `digest[14..16]` -> movement
+/// - `digest[16..18]` -> arousal
+/// - `digest[18..27]` -> per-dimension tolerances (see `srt::tolerances_from_srt`)
+/// - `digest[27..31]` -> confirmation tag (see `srt::confirmation_tag_from_srt`)
+///
+/// `digest[31]` is reserved for future extensions.
+///
+/// `salt` is an oracle-state or context binding, and should be provided in the
+/// same format for all peers that need to rendezvous.
+pub fn pattern_from_srt(
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+) -> SubmodalityPattern {
+    pattern_from_digest(&hmac_sha256(srt, salt))
+}
+
+/// HMAC-SHA256 of `salt` keyed by the SRT; the default derivation digest.
+pub(crate) fn hmac_sha256(srt: &SemanticRendezvousToken, salt: &[u8]) -> [u8; 32] {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(srt.as_bytes()).expect("HMAC can take a 32-byte key");
+    mac.update(salt);
+    mac.finalize().into_bytes().into()
+}
+
+/// Map `digest[0..18]` onto pattern fields as documented on [`pattern_from_srt`].
+pub(crate) fn pattern_from_digest(digest: &[u8; 32]) -> SubmodalityPattern {
+    pattern_from_array(core::array::from_fn(|i| {
+        let DimensionInfo { min, max, .. } = DIMENSIONS[i];
+        quantize_u16_to_range(u16::from_be_bytes([digest[2 * i], digest[2 * i + 1]]), min, max)
+    }))
+}
+
+/// Number of observations packed into each bitmask block.
+const BLOCK_BITS: usize = u64::BITS as usize;
+
+/// Number of `u64` blocks a [`MatchWindow`] of `capacity` results needs, for
+/// sizing array storage: `[0; window_blocks(N)]`.
+pub const fn window_blocks(capacity: usize) -> usize {
+    capacity.div_ceil(BLOCK_BITS)
+}
+
+/// Fixed-capacity sliding window of boolean match results.
+///
+/// Results are packed into `u64` blocks used as a ring buffer, and the number
+/// of set bits is kept up to date as results enter and leave, so pushing and
+/// m-of-n evaluation are constant time regardless of capacity. This keeps
+/// windows of hundreds of samples cheap for high-rate sensors.
+///
+/// The blocks live in caller-supplied storage `S`: an array such as
+/// `[u64; window_blocks(N)]` on `no_std` targets, or the `Vec<u64>` that
+/// `MatchWindow::new` allocates with the `std` feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchWindow<S> {
+    /// Packed results; bit `i % 64` of block `i / 64` holds slot `i`.
+    blocks: S,
+    /// Maximum number of results held.
+    capacity: usize,
+    /// Number of results currently held.
+    len: usize,
+    /// Slot the next result is written to.
+    next: usize,
+    /// Number of `true` results currently held.
+    matches: usize,
+}
+
+impl<S: AsRef<[u64]> + AsMut<[u64]>> MatchWindow<S> {
+    /// Create an empty window holding up to `capacity` results in `blocks`,
+    /// which is cleared first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `blocks` holds fewer than [`window_blocks`]`(capacity)`
+    /// blocks.
+    pub fn with_storage(mut blocks: S, capacity: usize) -> Self {
+        assert!(
+            blocks.as_ref().len() >= window_blocks(capacity),
+            "a window of {capacity} results needs {} blocks",
+            window_blocks(capacity)
+        );
+        blocks.as_mut().fill(0);
+        Self {
+            blocks,
+            capacity,
+            len: 0,
+            next: 0,
+            matches: 0,
+        }
+    }
+
+    /// Record a result, evicting the oldest one if the window is full.
+    ///
+    /// Does nothing if the capacity is zero.
+    pub fn push(&mut self, within: bool) {
+        if self.capacity == 0 {
+            return;
+        }
+        let block = &mut self.blocks.as_mut()[self.next / BLOCK_BITS];
+        let mask = 1u64 << (self.next % BLOCK_BITS);
+        if self.len == self.capacity {
+            if *block & mask != 0 {
+                self.matches -= 1;
+            }
+        } else {
+            self.len += 1;
+        }
+        if within {
+            *block |= mask;
+            self.matches += 1;
+        } else {
+            *block &= !mask;
+        }
+        self.next = (self.next + 1) % self.capacity;
+    }
+
+    /// Record a result and report whether the window is stable: full, with
+    /// every result `true`.
+    ///
+    /// A zero-capacity window judges each result on its own.
+    pub fn record(&mut self, within: bool) -> bool {
+        if self.capacity == 0 {
+            return within;
+        }
+        self.push(within);
+        self.is_full() && self.all()
+    }
+
+    /// Maximum number of results held.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of results currently held.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no results have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the window holds `capacity` results.
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+
+    /// Number of `true` results in the window.
+    pub fn matches(&self) -> usize {
+        self.matches
+    }
+
+    /// Whether at least `required` of the held results are `true`.
+    pub fn at_least(&self, required: usize) -> bool {
+        self.matches >= required
+    }
+
+    /// Whether every held result is `true`.
+    pub fn all(&self) -> bool {
+        self.matches == self.len
+    }
+
+    /// Count `true` results by popcount over the packed blocks.
+    ///
+    /// Always equals [`matches`](Self::matches); useful for checking a
+    /// window restored from elsewhere.
+    pub fn popcount(&self) -> usize {
+        self.blocks
+            .as_ref()
+            .iter()
+            .map(|block| block.count_ones() as usize)
+            .sum()
+    }
+
+    /// Forget every recorded result.
+    pub fn clear(&mut self) {
+        self.blocks.as_mut().fill(0);
+        self.len = 0;
+        self.next = 0;
+        self.matches = 0;
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::matching::{Matcher, MatchingConfig};
+    use crate::pattern::pattern_from_array;
+
+    #[test]
+    fn array_windows_evict_oldest_results() {
+        let mut window = MatchWindow::with_storage([0; window_blocks(3)], 3);
+        assert!(window.is_empty() && window.all());
+        for within in [true, false, true, true] {
+            window.push(within);
+        }
+        assert!(window.is_full());
+        assert_eq!(window.matches(), 2);
+        assert!(!window.all());
+        assert!(window.record(true));
+        window.clear();
+        assert!(window.is_empty());
+        assert_eq!(window.popcount(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs 2 blocks")]
+    fn undersized_storage_is_rejected() {
+        MatchWindow::with_storage([0; 1], 65);
+    }
+
+    #[test]
+    fn decisions_match_the_std_matcher() {
+        let mut rng = StdRng::seed_from_u64(93);
+        let srt = SemanticRendezvousToken::from_bytes([7; 32]);
+        let target = pattern_from_srt(&srt, b"oracle-state");
+        let normalized: [f32; 9] = target.normalize().into();
+        let mut matcher = Matcher::new(MatchingConfig::new(0.05, 3));
+        let mut window = MatchWindow::with_storage([0; window_blocks(3)], 3);
+        let mut single = MatchWindow::with_storage([0; 0], 0);
+        let mut matches = 0;
+        for _ in 0..2000 {
+            let measured = target.jitter(
+                &mut rng,
+                &pattern_from_array(DIMENSIONS.map(|info| 0.015 * (info.max - info.min))),
+            );
+            let within = distance(&measured.normalize().into(), &normalized) <= 0.05;
+            let expected = matcher.observe(&measured, &target);
+            assert_eq!(window.record(within), expected);
+            assert_eq!(single.record(within), within);
+            matches += usize::from(expected);
+        }
+        assert!(matches > 100 && matches < 1900, "matches: {matches}");
+    }
+}
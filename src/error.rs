@@ -0,0 +1,276 @@
+//! Crate-level error type.
+//!
+//! Each module keeps a precise error enum describing its own failures.
+//! [`Error`] wraps all of them, together with I/O and encoding failures, so
+//! callers combining several modules can propagate a single type with `?`.
+
+use std::io;
+
+#[cfg(feature = "serde")]
+use crate::audit::AuditError;
+use crate::calibration::CalibrationError;
+use crate::datasets::DatasetError;
+use crate::hex::HexError;
+use crate::ingest::IngestError;
+use crate::matching::ObservationError;
+use crate::privacy::PrivacyError;
+use crate::protocol::descriptor::DescriptorError;
+use crate::protocol::group::GroupError;
+use crate::protocol::version::NegotiationError;
+use crate::registry::RegistryError;
+use crate::pattern::series_codec::SeriesCodecError;
+use crate::pattern::stats::StatsError;
+use crate::series::SeriesError;
+use crate::srt::armor::ArmorError;
+use crate::srt::commitment::CommitmentError;
+use crate::srt::group::GroupTokenError;
+use crate::srt::keychain::KeychainError;
+use crate::srt::oracle::OracleError;
+use crate::srt::path::PathError;
+use crate::srt::provider::ProviderError;
+use crate::srt::ratchet::RatchetError;
+use crate::srt::record::RecordError;
+use crate::srt::target::TargetError;
+use crate::srt::uri::UriError;
+use crate::srt::{SrtParseError, SubkeyError};
+use crate::trace::TraceError;
+
+/// Convenience alias for results using [`Error`].
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Any error produced by this crate.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// An SRT could not be parsed.
+    #[error(transparent)]
+    Srt(#[from] SrtParseError),
+    /// A subkey could not be derived from an SRT.
+    #[error(transparent)]
+    Subkey(#[from] SubkeyError),
+    /// An armored SRT could not be parsed.
+    #[error(transparent)]
+    Armor(#[from] ArmorError),
+    /// An SRT ratchet was asked to move backwards.
+    #[error(transparent)]
+    Ratchet(#[from] RatchetError),
+    /// An SRT derivation path could not be parsed.
+    #[error(transparent)]
+    Path(#[from] PathError),
+    /// A `phenorv://` link could not be parsed.
+    #[error(transparent)]
+    Uri(#[from] UriError),
+    /// An SRT record is expired, not yet valid, or revoked.
+    #[error(transparent)]
+    Record(#[from] RecordError),
+    /// An SRT mnemonic could not be decoded.
+    #[cfg(feature = "mnemonic")]
+    #[error(transparent)]
+    Mnemonic(#[from] crate::srt::mnemonic::MnemonicError),
+    /// A scanned SRT QR payload was rejected.
+    #[cfg(feature = "qr")]
+    #[error(transparent)]
+    Qr(#[from] crate::srt::qr::QrError),
+    /// X25519 key agreement failed.
+    #[cfg(feature = "agreement")]
+    #[error(transparent)]
+    Agreement(#[from] crate::srt::agreement::AgreementError),
+    /// A keychain operation failed or its contents could not be decoded.
+    #[error(transparent)]
+    Keychain(#[from] KeychainError),
+    /// An encrypted envelope could not be opened.
+    #[cfg(feature = "encryption")]
+    #[error(transparent)]
+    Envelope(#[from] crate::srt::envelope::EnvelopeError),
+    /// An external SRT provider failed to derive a digest.
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    /// A group token could not be derived.
+    #[error(transparent)]
+    GroupToken(#[from] GroupTokenError),
+    /// A target file failed verification.
+    #[error(transparent)]
+    Target(#[from] TargetError),
+    /// A pattern commitment did not match its opening.
+    #[error(transparent)]
+    Commitment(#[from] CommitmentError),
+    /// A hex string could not be decoded.
+    #[error(transparent)]
+    Hex(#[from] HexError),
+    /// A pattern failed validation.
+    #[error("invalid pattern: {0}")]
+    InvalidPattern(String),
+    /// A configuration value was rejected.
+    #[error("invalid configuration: {0}")]
+    InvalidConfig(String),
+    /// A timestamped observation was stale or out of order.
+    #[error(transparent)]
+    Observation(#[from] ObservationError),
+    /// A salt oracle rejected its input.
+    #[error(transparent)]
+    Oracle(#[from] OracleError),
+    /// A dimension registry operation failed.
+    #[error(transparent)]
+    Registry(#[from] RegistryError),
+    /// A calibration profile has an invalid range.
+    #[error(transparent)]
+    Calibration(#[from] CalibrationError),
+    /// A pattern series is out of order or cannot be resampled.
+    #[error(transparent)]
+    Series(#[from] SeriesError),
+    /// An encoded pattern series was malformed.
+    #[error(transparent)]
+    SeriesCodec(#[from] SeriesCodecError),
+    /// Too few patterns or an invalid percentile for a statistic.
+    #[error(transparent)]
+    Stats(#[from] StatsError),
+    /// Delimited input could not be ingested.
+    #[error(transparent)]
+    Ingest(#[from] IngestError),
+    /// A binary trace was malformed.
+    #[error(transparent)]
+    Trace(#[from] TraceError),
+    /// A dataset could not be loaded or fitted.
+    #[error(transparent)]
+    Dataset(#[from] DatasetError),
+    /// A dataset could not be anonymized.
+    #[error(transparent)]
+    Privacy(#[from] PrivacyError),
+    /// An audit log failed verification.
+    #[cfg(feature = "serde")]
+    #[error(transparent)]
+    Audit(#[from] AuditError),
+    /// Peers could not agree on a protocol configuration.
+    #[error(transparent)]
+    Negotiation(#[from] NegotiationError),
+    /// A rendezvous descriptor could not be encoded or decoded.
+    #[error(transparent)]
+    Descriptor(#[from] DescriptorError),
+    /// A multi-party rendezvous group was misused.
+    #[error(transparent)]
+    Group(#[from] GroupError),
+    /// A chart could not be rendered.
+    #[cfg(feature = "viz")]
+    #[error(transparent)]
+    Viz(#[from] crate::viz::VizError),
+    /// The GPU simulation backend failed.
+    #[cfg(feature = "gpu")]
+    #[error(transparent)]
+    Gpu(#[from] crate::sim::gpu::GpuError),
+    /// The MQTT transport failed.
+    #[cfg(feature = "mqtt")]
+    #[error(transparent)]
+    Mqtt(#[from] crate::transport::mqtt::MqttError),
+    /// A signed session transcript failed verification.
+    #[cfg(feature = "transcript")]
+    #[error(transparent)]
+    Transcript(#[from] crate::transcript::TranscriptError),
+    /// An Arrow IPC file or record batch could not be read.
+    #[cfg(feature = "columnar")]
+    #[error(transparent)]
+    Arrow(#[from] arrow_schema::ArrowError),
+    /// A Parquet file could not be read.
+    #[cfg(feature = "columnar")]
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+    /// A protobuf message could not be converted to a native type.
+    #[cfg(feature = "protobuf")]
+    #[error(transparent)]
+    Proto(#[from] crate::proto::ProtoError),
+    /// A JSON pattern record could not be upgraded to the current schema.
+    #[cfg(feature = "serde")]
+    #[error(transparent)]
+    Migrate(#[from] crate::pattern::migrate::MigrateError),
+    /// A value could not be encoded or decoded as JSON.
+    #[cfg(feature = "serde")]
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// Reading or writing failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Another error, annotated with what was being attempted.
+    ///
+    /// Displays only the context; the wrapped error is its
+    /// [`source`](std::error::Error::source).
+    #[error("{context}")]
+    Context {
+        /// Description of the failed operation.
+        context: String,
+        /// The underlying error.
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Wrap this error with a description of what was being attempted.
+    pub fn context(self, context: impl Into<String>) -> Self {
+        Self::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// The innermost error, with any context layers removed.
+    pub fn root(&self) -> &Error {
+        match self {
+            Self::Context { source, .. } => source.root(),
+            other => other,
+        }
+    }
+}
+
+/// Attach context to fallible results.
+pub trait ResultExt<T> {
+    /// Convert the error into [`Error`] and wrap it with `context`.
+    fn context(self, context: impl Into<String>) -> Result<T>;
+
+    /// Like [`ResultExt::context`], building the message only on failure.
+    fn with_context<F, S>(self, context: F) -> Result<T>
+    where
+        F: FnOnce() -> S,
+        S: Into<String>;
+}
+
+impl<T, E: Into<Error>> ResultExt<T> for std::result::Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|err| err.into().context(context))
+    }
+
+    fn with_context<F, S>(self, context: F) -> Result<T>
+    where
+        F: FnOnce() -> S,
+        S: Into<String>,
+    {
+        self.map_err(|err| err.into().context(context()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_wraps_and_unwraps() {
+        let result: std::result::Result<(), _> = Err(DatasetError::NotEnoughSamples(1));
+        let err = result.context("fitting prior").expect_err("error");
+        assert_eq!(err.to_string(), "fitting prior");
+        let source = std::error::Error::source(&err).expect("source");
+        assert_eq!(
+            source.to_string(),
+            "need at least 2 samples to fit a peer model, got 1"
+        );
+        assert!(matches!(
+            err.root(),
+            Error::Dataset(DatasetError::NotEnoughSamples(1))
+        ));
+    }
+
+    #[test]
+    fn module_errors_convert() {
+        let err: Error = io::Error::other("disk").into();
+        assert!(matches!(err, Error::Io(_)));
+        let err: Error = NegotiationError::NoCommonScheme.into();
+        assert_eq!(err.to_string(), "no common SRT scheme");
+    }
+}
@@ -0,0 +1,62 @@
+//! Lowercase hex helpers shared by modules that serialize digests and by
+//! callers parsing hex salts.
+
+use std::fmt;
+
+/// Encode bytes as a lowercase hex string.
+pub fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Decode a hex string of even length, accepting either case.
+pub fn decode(hex: &str) -> Result<Vec<u8>, HexError> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(HexError::OddLength(hex.len()));
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| Ok((nibble(pair[0])? << 4) | nibble(pair[1])?))
+        .collect()
+}
+
+fn nibble(byte: u8) -> Result<u8, HexError> {
+    match byte {
+        b'0'..=b'9' => Ok(byte - b'0'),
+        b'a'..=b'f' => Ok(byte - b'a' + 10),
+        b'A'..=b'F' => Ok(byte - b'A' + 10),
+        _ => Err(HexError::InvalidCharacter(byte as char)),
+    }
+}
+
+/// Errors returned by [`decode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexError {
+    /// The input has an odd number of characters.
+    OddLength(usize),
+    /// The input contains a non-hex character.
+    InvalidCharacter(char),
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OddLength(len) => write!(f, "invalid hex length: {len}"),
+            Self::InvalidCharacter(ch) => write!(f, "invalid hex character: '{ch}'"),
+        }
+    }
+}
+
+impl std::error::Error for HexError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_either_case_and_rejects_bad_input() {
+        assert_eq!(decode("00Ff7a"), Ok(vec![0x00, 0xff, 0x7a]));
+        assert_eq!(encode(&[0x00, 0xff, 0x7a]), "00ff7a");
+        assert_eq!(decode("abc"), Err(HexError::OddLength(3)));
+        assert_eq!(decode("zz"), Err(HexError::InvalidCharacter('z')));
+    }
+}
@@ -0,0 +1,538 @@
+//! Ingestion of externally produced pattern data.
+//!
+//! Field data often arrives as spreadsheet exports rather than JSONL. This
+//! module reads delimited text into `SubmodalityPattern` values and, in
+//! [`NumberMode::LocaleTolerant`], accepts the number formats those exports
+//! commonly use.
+//!
+//! Locale-tolerant normalization rules:
+//!
+//! 1. Surrounding whitespace is trimmed and a trailing unit suffix is split
+//!    off (see [`parse_value`] for the units accepted per dimension).
+//! 2. Spaces, non-breaking spaces, and apostrophes inside the number are
+//!    treated as thousands separators and removed.
+//! 3. If both `.` and `,` occur, whichever appears last is the decimal
+//!    separator and the other is a thousands separator.
+//! 4. If only one of them occurs and it occurs more than once, it is a
+//!    thousands separator (`1.000.000`).
+//! 5. A single occurrence followed by exactly three digits, with a non-zero
+//!    integer part, is a thousands separator (`6.500` and `6,500` both parse
+//!    as 6500). Any other single occurrence is the decimal separator (`6,5`,
+//!    `0,500`, and `20.25` parse as 6.5, 0.5, and 20.25).
+//!
+//! Rule 5 means a value such as `1,250` meant as 1.25 must be written with a
+//! different number of decimals (`1,25`) or read in strict mode.
+//!
+//! JSON documents (patterns, configs, profiles) can be read with
+//! [`from_json`]; [`JsonMode::Strict`] rejects unknown fields, omitted fields,
+//! and numbers that overflow to infinity, so a typo such as `"brightnes"` in
+//! a hand-edited file fails instead of silently falling back to a default.
+
+use std::fmt;
+use std::io::BufRead;
+
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(feature = "serde")]
+use serde_json::Value;
+
+use crate::error::Result;
+#[cfg(feature = "serde")]
+use crate::pattern::migrate::FIELD_RENAMES;
+use crate::pattern::{SubmodalityPattern, DIMENSIONS};
+
+/// How numeric cells are parsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberMode {
+    /// Plain Rust float syntax with `.` as the decimal separator and no units.
+    #[default]
+    Strict,
+    /// Accept locale-specific separators and unit suffixes.
+    LocaleTolerant,
+}
+
+/// Options for reading delimited pattern data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Field delimiter, typically `,` or `;`.
+    pub delimiter: char,
+    /// Numeric parsing mode.
+    pub mode: NumberMode,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            mode: NumberMode::Strict,
+        }
+    }
+}
+
+/// Parse a numeric cell for a pattern dimension.
+///
+/// In strict mode `text` must be a plain float. In locale-tolerant mode the
+/// separator rules from the module docs apply and an optional unit suffix is
+/// accepted and converted to the dimension's native unit:
+///
+/// - `color_temp`: `K`
+/// - `tempo`: `bpm`
+/// - `pitch`: `Hz`, `kHz` (multiplied by 1000)
+/// - `temperature`: `°C`, `C`, `degC`
+/// - unit-range dimensions (`brightness`, `focal_distance`, `volume`,
+///   `movement`, `arousal`): `%` (divided by 100)
+///
+/// Unit matching is case-insensitive.
+pub fn parse_value(text: &str, dimension: &str, mode: NumberMode) -> Result<f32, IngestError> {
+    let trimmed = text.trim();
+    match mode {
+        NumberMode::Strict => trimmed
+            .parse()
+            .map_err(|_| IngestError::InvalidNumber(text.to_string())),
+        NumberMode::LocaleTolerant => {
+            let split = trimmed
+                .find(|ch: char| !(ch.is_ascii_digit() || "+-.,' \u{a0}\u{202f}".contains(ch)))
+                .unwrap_or(trimmed.len());
+            let (number, unit) = trimmed.split_at(split);
+            let value = parse_locale_number(number)
+                .ok_or_else(|| IngestError::InvalidNumber(text.to_string()))?;
+            let scale = unit_scale(dimension, unit.trim()).ok_or_else(|| {
+                IngestError::UnsupportedUnit {
+                    dimension: dimension.to_string(),
+                    unit: unit.trim().to_string(),
+                }
+            })?;
+            Ok(value * scale)
+        }
+    }
+}
+
+fn parse_locale_number(text: &str) -> Option<f32> {
+    let digits: String = text
+        .chars()
+        .filter(|ch| !matches!(ch, ' ' | '\u{a0}' | '\u{202f}' | '\''))
+        .collect();
+    if digits.is_empty() {
+        return None;
+    }
+
+    let last_dot = digits.rfind('.');
+    let last_comma = digits.rfind(',');
+    let decimal = match (last_dot, last_comma) {
+        (Some(dot), Some(comma)) => Some(if dot > comma { '.' } else { ',' }),
+        (Some(index), None) | (None, Some(index)) => {
+            let separator = digits.as_bytes()[index] as char;
+            let (integer, fraction) = (&digits[..index], &digits[index + 1..]);
+            let integer_digits = integer.trim_start_matches(['+', '-']);
+            let repeated = integer.contains(separator);
+            let grouped = fraction.len() == 3
+                && !integer_digits.is_empty()
+                && integer_digits.trim_start_matches('0').len() == integer_digits.len();
+            if repeated || grouped {
+                None
+            } else {
+                Some(separator)
+            }
+        }
+        (None, None) => None,
+    };
+
+    let canonical: String = digits
+        .chars()
+        .filter_map(|ch| match ch {
+            '.' | ',' if Some(ch) == decimal => Some('.'),
+            '.' | ',' => None,
+            other => Some(other),
+        })
+        .collect();
+    canonical.parse().ok()
+}
+
+fn unit_scale(dimension: &str, unit: &str) -> Option<f32> {
+    if unit.is_empty() {
+        return Some(1.0);
+    }
+    let unit = unit.to_ascii_lowercase();
+    match (dimension, unit.as_str()) {
+        ("color_temp", "k") => Some(1.0),
+        ("tempo", "bpm") => Some(1.0),
+        ("pitch", "hz") => Some(1.0),
+        ("pitch", "khz") => Some(1000.0),
+        ("temperature", "°c" | "c" | "degc") => Some(1.0),
+        ("brightness" | "focal_distance" | "volume" | "movement" | "arousal", "%") => Some(0.01),
+        _ => None,
+    }
+}
+
+/// Read delimited rows into patterns.
+///
+/// The first non-empty line is a header naming the columns. All nine
+/// `SubmodalityPattern` field names must be present; other columns are
+/// ignored. Fields may be wrapped in double quotes, which is required when a
+/// comma decimal separator is used with a comma delimiter.
+pub fn read_csv_patterns<R: BufRead>(
+    reader: R,
+    options: &CsvOptions,
+) -> Result<Vec<SubmodalityPattern>> {
+    let mut lines = reader
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line));
+    let header = loop {
+        match lines.next() {
+            Some((_, line)) => {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    break split_row(&line, options.delimiter);
+                }
+            }
+            None => return Ok(Vec::new()),
+        }
+    };
+
+    let mut columns = [0usize; 9];
+    for (slot, info) in columns.iter_mut().zip(DIMENSIONS) {
+        *slot = header
+            .iter()
+            .position(|name| name.trim() == info.key)
+            .ok_or_else(|| IngestError::MissingColumn(info.key.to_string()))?;
+    }
+
+    let mut patterns = Vec::new();
+    for (line_number, line) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let cells = split_row(&line, options.delimiter);
+        let mut values = [0.0f32; 9];
+        for ((value, &column), info) in values.iter_mut().zip(&columns).zip(DIMENSIONS) {
+            let cell = cells.get(column).ok_or(IngestError::ShortRow(line_number))?;
+            *value = parse_value(cell, info.key, options.mode).map_err(|err| IngestError::Row {
+                line: line_number,
+                source: Box::new(err),
+            })?;
+        }
+        patterns.push(SubmodalityPattern {
+            brightness: values[0],
+            color_temp: values[1],
+            focal_distance: values[2],
+            volume: values[3],
+            tempo: values[4],
+            pitch: values[5],
+            temperature: values[6],
+            movement: values[7],
+            arousal: values[8],
+        });
+    }
+    Ok(patterns)
+}
+
+/// How JSON input is deserialized.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonMode {
+    /// Serde defaults: unknown fields are ignored and fields with defaults
+    /// may be omitted.
+    #[default]
+    Lenient,
+    /// Reject unknown fields, omitted fields, and non-finite numbers.
+    Strict,
+}
+
+/// Deserialize a JSON document.
+///
+/// In strict mode every key the target type ignores is reported as unknown.
+/// The parsed value is then serialized again and compared with the input:
+/// non-null keys that appear only in the output were filled from defaults,
+/// and numbers that became `null` overflowed to infinity or NaN. Keys a type
+/// skips when serializing (such as uniform matching weights) may be given
+/// explicitly or omitted, and a field may be given under the old name of a
+/// [`FIELD_RENAMES`] entry.
+#[cfg(feature = "serde")]
+pub fn from_json<T>(text: &str, mode: JsonMode) -> Result<T>
+where
+    T: DeserializeOwned + Serialize,
+{
+    match mode {
+        JsonMode::Lenient => Ok(serde_json::from_str(text)?),
+        JsonMode::Strict => from_json_value(serde_json::from_str(text)?, mode),
+    }
+}
+
+/// [`from_json`] for a document that has already been parsed, such as a
+/// record passed through [`migrate`](crate::pattern::migrate::migrate).
+#[cfg(feature = "serde")]
+pub fn from_json_value<T>(input: Value, mode: JsonMode) -> Result<T>
+where
+    T: DeserializeOwned + Serialize,
+{
+    match mode {
+        JsonMode::Lenient => Ok(serde_json::from_value(input)?),
+        JsonMode::Strict => {
+            let mut unknown = None;
+            let value: T = serde_ignored::deserialize(&input, |path| {
+                unknown.get_or_insert_with(|| path.to_string());
+            })?;
+            if let Some(path) = unknown {
+                return Err(IngestError::UnknownField(path).into());
+            }
+            check_round_trip(&input, &serde_json::to_value(&value)?, "")?;
+            Ok(value)
+        }
+    }
+}
+
+/// Compare a JSON input with its re-serialized form for [`JsonMode::Strict`].
+#[cfg(feature = "serde")]
+fn check_round_trip(input: &Value, parsed: &Value, path: &str) -> Result<(), IngestError> {
+    let child = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match (input, parsed) {
+        (Value::Object(input), Value::Object(parsed)) => {
+            for (key, value) in input {
+                let renamed = FIELD_RENAMES
+                    .iter()
+                    .find(|rename| rename.old == key)
+                    .and_then(|rename| parsed.get(rename.new));
+                if let Some(parsed) = parsed.get(key).or(renamed) {
+                    check_round_trip(value, parsed, &child(key))?;
+                }
+            }
+            let given = |key: &str| {
+                input.contains_key(key)
+                    || FIELD_RENAMES
+                        .iter()
+                        .any(|rename| rename.new == key && input.contains_key(rename.old))
+            };
+            if let Some(key) = parsed
+                .iter()
+                .find(|(key, value)| !value.is_null() && !given(key))
+                .map(|(key, _)| key)
+            {
+                return Err(IngestError::MissingField(child(key)));
+            }
+        }
+        (Value::Array(input), Value::Array(parsed)) => {
+            for (index, (input, parsed)) in input.iter().zip(parsed).enumerate() {
+                check_round_trip(input, parsed, &format!("{path}[{index}]"))?;
+            }
+        }
+        (Value::Number(number), Value::Null) => {
+            return Err(IngestError::NonFinite(format!("{path} = {number}")));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Split a delimited row, honoring double-quoted fields and `""` escapes.
+pub(crate) fn split_row(line: &str, delimiter: char) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ch if ch == delimiter && !in_quotes => cells.push(std::mem::take(&mut current)),
+            ch => current.push(ch),
+        }
+    }
+    cells.push(current);
+    cells
+}
+
+/// Errors returned while ingesting external data.
+#[derive(Debug)]
+pub enum IngestError {
+    /// A cell could not be parsed as a number.
+    InvalidNumber(String),
+    /// A unit suffix is not accepted for the dimension.
+    UnsupportedUnit {
+        /// Dimension the cell belongs to.
+        dimension: String,
+        /// Unit suffix that was found.
+        unit: String,
+    },
+    /// The header lacked a required column.
+    MissingColumn(String),
+    /// A data row had fewer cells than the header requires.
+    ShortRow(usize),
+    /// A cell in a data row failed to parse.
+    Row {
+        /// One-based line number of the row.
+        line: usize,
+        /// The underlying parse failure.
+        source: Box<IngestError>,
+    },
+    /// A strict JSON document contained a field the type does not have.
+    UnknownField(String),
+    /// A strict JSON document omitted a field.
+    MissingField(String),
+    /// A strict JSON document contained a number that is not finite once
+    /// converted.
+    NonFinite(String),
+}
+
+impl fmt::Display for IngestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNumber(text) => write!(f, "invalid number '{text}'"),
+            Self::UnsupportedUnit { dimension, unit } => {
+                write!(f, "unit '{unit}' is not supported for {dimension}")
+            }
+            Self::MissingColumn(name) => write!(f, "missing column '{name}'"),
+            Self::ShortRow(line) => write!(f, "line {line}: too few columns"),
+            Self::Row { line, source } => write!(f, "line {line}: {source}"),
+            Self::UnknownField(path) => write!(f, "unknown field '{path}'"),
+            Self::MissingField(path) => write!(f, "missing field '{path}'"),
+            Self::NonFinite(value) => write!(f, "number is not finite: {value}"),
+        }
+    }
+}
+
+impl std::error::Error for IngestError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    fn tolerant(text: &str, dimension: &str) -> f32 {
+        parse_value(text, dimension, NumberMode::LocaleTolerant).expect("parse")
+    }
+
+    #[test]
+    fn locale_separators_are_normalized() {
+        assert_eq!(tolerant("0,75", "brightness"), 0.75);
+        assert_eq!(tolerant("0,500", "brightness"), 0.5);
+        assert_eq!(tolerant("6.500", "color_temp"), 6500.0);
+        assert_eq!(tolerant("6,500", "color_temp"), 6500.0);
+        assert_eq!(tolerant("6.500,5 K", "color_temp"), 6500.5);
+        assert_eq!(tolerant("6,500.5K", "color_temp"), 6500.5);
+        assert_eq!(tolerant("12 000", "pitch"), 12000.0);
+        assert_eq!(tolerant("1.000.000", "pitch"), 1_000_000.0);
+    }
+
+    #[test]
+    fn unit_suffixes_are_converted() {
+        assert_eq!(tolerant("440 Hz", "pitch"), 440.0);
+        assert_eq!(tolerant("1,5kHz", "pitch"), 1500.0);
+        assert_eq!(tolerant("21,5 °C", "temperature"), 21.5);
+        assert_eq!(tolerant("50%", "volume"), 0.5);
+        assert!(matches!(
+            parse_value("440 K", "pitch", NumberMode::LocaleTolerant),
+            Err(IngestError::UnsupportedUnit { .. })
+        ));
+    }
+
+    #[test]
+    fn strict_mode_rejects_locale_formats() {
+        assert!(parse_value("0,5", "brightness", NumberMode::Strict).is_err());
+        assert!(parse_value("440 Hz", "pitch", NumberMode::Strict).is_err());
+        assert_eq!(parse_value(" 0.5 ", "brightness", NumberMode::Strict).unwrap(), 0.5);
+    }
+
+    #[test]
+    fn reads_semicolon_export() {
+        let data = "\
+timestamp;brightness;color_temp;focal_distance;volume;tempo;pitch;temperature;movement;arousal
+t0;0,5;6.500 K;0,5;50 %;120 bpm;440 Hz;20,5 °C;0;0,1
+";
+        let options = CsvOptions {
+            delimiter: ';',
+            mode: NumberMode::LocaleTolerant,
+        };
+        let patterns = read_csv_patterns(data.as_bytes(), &options).expect("read");
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].color_temp, 6500.0);
+        assert_eq!(patterns[0].volume, 0.5);
+        assert_eq!(patterns[0].temperature, 20.5);
+    }
+
+    #[test]
+    fn quoted_cells_may_contain_delimiter() {
+        let data = "\
+brightness,color_temp,focal_distance,volume,tempo,pitch,temperature,movement,arousal
+\"0,5\",6500,\"0,5\",\"0,5\",0,440,20,0,0
+";
+        let options = CsvOptions {
+            delimiter: ',',
+            mode: NumberMode::LocaleTolerant,
+        };
+        let patterns = read_csv_patterns(data.as_bytes(), &options).expect("read");
+        assert_eq!(patterns[0], SubmodalityPattern::zeros());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn strict_json_rejects_typos_and_defaults() {
+        use crate::sim::SimulationConfig;
+
+        let pattern = serde_json::to_string(&SubmodalityPattern::zeros()).expect("json");
+        let parsed: SubmodalityPattern = from_json(&pattern, JsonMode::Strict).expect("strict");
+        assert_eq!(parsed, SubmodalityPattern::zeros());
+
+        let typo = pattern.replace("\"brightness\"", "\"brightnes\"");
+        assert!(from_json::<SubmodalityPattern>(&typo, JsonMode::Strict).is_err());
+        let extra = pattern.replace('{', "{\"brightnes\":0.5,");
+        assert!(from_json::<SubmodalityPattern>(&extra, JsonMode::Lenient).is_ok());
+        assert!(matches!(
+            from_json::<SubmodalityPattern>(&extra, JsonMode::Strict),
+            Err(Error::Ingest(IngestError::UnknownField(name))) if name == "brightnes"
+        ));
+        let huge = pattern.replace("\"pitch\":440.0", "\"pitch\":1e39");
+        assert!(matches!(
+            from_json::<SubmodalityPattern>(&huge, JsonMode::Strict),
+            Err(Error::Ingest(IngestError::NonFinite(_)))
+        ));
+
+        let config = r#"{"num_peers": 1, "num_trials": 1, "epsilon": 0.1, "window_size": 1,
+            "apply_geo_filter": false, "geo_filter_factor": 1.0}"#;
+        assert!(from_json::<SimulationConfig>(config, JsonMode::Lenient).is_ok());
+        assert!(matches!(
+            from_json::<SimulationConfig>(config, JsonMode::Strict),
+            Err(Error::Ingest(IngestError::MissingField(name))) if name == "backend"
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn strict_json_accepts_explicit_defaults_of_skipped_fields() {
+        use crate::calibration::CalibrationProfile;
+        use crate::matching::MatchingConfig;
+
+        let config = r#"{"epsilon": 0.1, "window_size": 3,
+            "weights": [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]}"#;
+        let parsed: MatchingConfig = from_json(config, JsonMode::Strict).expect("strict");
+        assert_eq!(parsed, MatchingConfig::new(0.1, 3));
+
+        let profile = r#"{"pitch": {"min": 20.0, "max": 20000.0, "policy": "linear"}}"#;
+        assert!(from_json::<CalibrationProfile>(profile, JsonMode::Strict).is_ok());
+        let typo = r#"{"pitch": {"min": 20.0, "max": 20000.0, "polcy": "linear"}}"#;
+        assert!(matches!(
+            from_json::<CalibrationProfile>(typo, JsonMode::Strict),
+            Err(Error::Ingest(IngestError::UnknownField(_)))
+        ));
+    }
+
+    #[test]
+    fn missing_column_is_reported() {
+        let data = "brightness,color_temp\n0.5,6500\n";
+        let err = read_csv_patterns(data.as_bytes(), &CsvOptions::default()).expect_err("missing");
+        assert!(
+            matches!(err, Error::Ingest(IngestError::MissingColumn(name)) if name == "focal_distance")
+        );
+    }
+}
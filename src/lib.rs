@@ -1,12 +1,103 @@
 //! Reference implementation of the Phenomenological Rendezvous protocol.
 //!
 //! This crate provides SRT encoding, submodality patterns, matching logic,
-//! and simulation tools.
+//! simulation tools, a runtime dimension registry, a hash-chained audit log
+//! for match events, transport-facing beacon messages, and protocol version
+//! negotiation.
+//!
+//! Optional features:
+//!
+//! - `std` (default): everything outside `embedded`. Without it the crate is
+//!   `#![no_std]` and exposes only `embedded`: the pattern types and their
+//!   normalization, HMAC-SHA256 target derivation from an SRT, and the
+//!   allocation-free `MatchWindow`, for microcontrollers next to the sensors.
+//!   The `std` modules re-export and build on these. Every other feature
+//!   enables `std`.
+//! - `serde` (default): `Serialize`/`Deserialize` for data types, JSON I/O,
+//!   the `audit` and `conformance` modules, and the `phenorv` binary. Build
+//!   with `default-features = false, features = ["std"]` for FFI builds that
+//!   only need the core types.
+//! - `mqtt`: MQTT publisher/subscriber adapter in `transport::mqtt`.
+//! - `viz`: SVG charts in `viz`, plus the `visualize` CLI command.
+//! - `schemars`: JSON Schemas for serialized types in `schema`, plus the
+//!   `schema` CLI command.
+//! - `columnar`: Arrow IPC and Parquet dataset loading in `datasets::columnar`.
+//! - `mmap`: memory-mapped `trace::BinaryTraceReader` for large binary traces.
+//! - `transcript`: Ed25519-signed session transcripts in `transcript`.
+//! - `gpu`: `wgpu` compute-shader simulation backend in `sim::gpu`.
+//! - `serial`: serial-device input for the `match-stream` CLI command.
+//! - `tui`: terminal matching dashboard in `tui`, plus the `tui` CLI command.
+//! - `mnemonic`: BIP39 word encoding of SRTs in `srt::mnemonic`.
+//! - `qr`: QR code rendering and payload parsing for SRTs in `srt::qr`, plus
+//!   the `qr-token` CLI command.
+//! - `agreement`: X25519 key agreement that establishes an SRT in
+//!   `srt::agreement`.
+//! - `encryption`: passphrase-encrypted token export in `srt::envelope` and
+//!   encrypted export of `srt::keychain::SrtKeychain`.
+//! - `nalgebra`, `ndarray`: conversions between `NormalizedPattern` and
+//!   `nalgebra::SVector<f32, 9>` or `ndarray::Array1<f32>`.
+//! - `protobuf`: `prost` message types for `proto/phenorv.proto` in `proto`.
+//! - `zstd`: zstd compression of delta-encoded series in
+//!   `pattern::series_codec`.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod embedded;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "std")]
 pub mod srt;
+#[cfg(feature = "std")]
 pub mod pattern;
+#[cfg(feature = "std")]
+pub mod calibration;
+#[cfg(feature = "std")]
+pub mod series;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
 pub mod matching;
+#[cfg(feature = "std")]
+pub mod scheduler;
+#[cfg(feature = "std")]
+pub mod registry;
+#[cfg(feature = "std")]
+pub mod ingest;
+#[cfg(feature = "std")]
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod datasets;
+#[cfg(feature = "std")]
 pub mod sim;
+#[cfg(feature = "std")]
+pub mod diagnostics;
+#[cfg(feature = "std")]
+pub mod privacy;
+#[cfg(feature = "serde")]
+pub mod audit;
+#[cfg(feature = "serde")]
+pub mod conformance;
+#[cfg(feature = "transcript")]
+pub mod transcript;
+#[cfg(feature = "std")]
+pub mod transport;
+#[cfg(feature = "std")]
+pub mod protocol;
+#[cfg(feature = "protobuf")]
+pub mod proto;
+#[cfg(feature = "viz")]
+pub mod viz;
+#[cfg(feature = "schemars")]
+pub mod schema;
+#[cfg(feature = "tui")]
+pub mod tui;
+
+#[cfg(feature = "std")]
+pub mod hex;
 
+#[cfg(feature = "std")]
+pub use error::{Error, Result};
+#[cfg(feature = "std")]
 pub use pattern::{NormalizedPattern, SubmodalityPattern};
+#[cfg(feature = "std")]
 pub use srt::SemanticRendezvousToken;
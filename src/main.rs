@@ -2,7 +2,13 @@ mod cli;
 
 fn main() {
     if let Err(err) = cli::run() {
-        eprintln!("{err}");
+        let mut message = err.to_string();
+        let mut source = std::error::Error::source(&err);
+        while let Some(cause) = source {
+            message.push_str(&format!(": {cause}"));
+            source = cause.source();
+        }
+        eprintln!("{message}");
         std::process::exit(1);
     }
 }
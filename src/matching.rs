@@ -1,22 +1,162 @@
 //! Pattern matching and rendezvous logic.
 
-use crate::pattern::{NormalizedPattern, SubmodalityPattern};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::embedded;
+use crate::error::{Error, Result};
+use crate::pattern::diff::PatternDiff;
+use crate::pattern::{
+    normalized_from_array, normalized_to_array, MeasuredPattern, NormalizedPattern, SanitizePolicy,
+    SubmodalityPattern, TimedPattern,
+};
+use crate::registry::{DimensionRegistry, DynPattern, PatternExt, RegistryError};
+use crate::srt::provider::ProviderError;
+use crate::srt::{DerivationSpec, SrtProvider};
+
+pub use crate::embedded::MatchWindow;
 
 /// Compute Euclidean distance in normalized 9D submodality space.
 ///
 /// Inputs must already be normalized to `[0, 1]` ranges.
 pub fn euclidean_distance(a: &NormalizedPattern, b: &NormalizedPattern) -> f32 {
-    let mut sum = 0.0;
-    sum += (a.brightness - b.brightness).powi(2);
-    sum += (a.color_temp - b.color_temp).powi(2);
-    sum += (a.focal_distance - b.focal_distance).powi(2);
-    sum += (a.volume - b.volume).powi(2);
-    sum += (a.tempo - b.tempo).powi(2);
-    sum += (a.pitch - b.pitch).powi(2);
-    sum += (a.temperature - b.temperature).powi(2);
-    sum += (a.movement - b.movement).powi(2);
-    sum += (a.arousal - b.arousal).powi(2);
-    sum.sqrt()
+    embedded::distance(&normalized_to_array(a), &normalized_to_array(b))
+}
+
+/// Euclidean distance over the dimensions marked in `present`, rescaled to
+/// the full nine dimensions.
+///
+/// The sum of squared differences is multiplied by `9 / k` for `k` present
+/// dimensions, so a measurement off by the same amount in every dimension
+/// gets the same distance however many sensors it has, and one epsilon
+/// serves every device. Returns `None` when no dimension is present.
+pub fn partial_distance(
+    a: &NormalizedPattern,
+    b: &NormalizedPattern,
+    present: [bool; 9],
+) -> Option<f32> {
+    masked_distance(a, b, present, [1.0; 9], &DimensionWeights::default())
+}
+
+/// Euclidean distance with each squared difference multiplied by its weight.
+///
+/// Uniform weights of `1.0` give exactly [`euclidean_distance`].
+pub fn weighted_distance(
+    a: &NormalizedPattern,
+    b: &NormalizedPattern,
+    weights: &DimensionWeights,
+) -> f32 {
+    normalized_to_array(a)
+        .into_iter()
+        .zip(normalized_to_array(b))
+        .zip(weights.0)
+        .map(|((a, b), weight)| weight * (a - b).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Weighted distance over the `mask`ed dimensions, each divided by its
+/// `scale`, with the sum rescaled from the masked weight to the total weight.
+fn masked_distance(
+    a: &NormalizedPattern,
+    b: &NormalizedPattern,
+    mask: [bool; 9],
+    scale: [f32; 9],
+    weights: &DimensionWeights,
+) -> Option<f32> {
+    let present_weight: f32 = mask
+        .into_iter()
+        .zip(weights.0)
+        .filter(|(present, _)| *present)
+        .map(|(_, weight)| weight)
+        .sum();
+    if present_weight == 0.0 {
+        return None;
+    }
+    let sum: f32 = normalized_to_array(a)
+        .into_iter()
+        .zip(normalized_to_array(b))
+        .zip(mask.into_iter().zip(scale))
+        .zip(weights.0)
+        .filter(|((_, (present, _)), _)| *present)
+        .map(|(((a, b), (_, scale)), weight)| weight * ((a - b) / scale).powi(2))
+        .sum();
+    Some((sum * weights.total() / present_weight).sqrt())
+}
+
+/// Relative importance of each dimension in distance calculations.
+///
+/// Noisy estimates such as arousal can be down-weighted so they move the
+/// distance less than precise ones such as brightness. Each squared
+/// difference is multiplied by its weight; the default weights are all
+/// `1.0`, which is plain Euclidean distance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "[f32; 9]"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DimensionWeights([f32; 9]);
+
+impl Default for DimensionWeights {
+    fn default() -> Self {
+        Self([1.0; 9])
+    }
+}
+
+impl DimensionWeights {
+    /// Create weights in field declaration order.
+    ///
+    /// See [`validate`](Self::validate) for the rules.
+    pub fn new(values: [f32; 9]) -> Result<Self> {
+        let weights = Self(values);
+        weights.validate()?;
+        Ok(weights)
+    }
+
+    /// Weights in field declaration order.
+    pub fn values(&self) -> [f32; 9] {
+        self.0
+    }
+
+    /// Whether every weight is `1.0`.
+    pub fn is_uniform(&self) -> bool {
+        self.0 == [1.0; 9]
+    }
+
+    /// Check that every weight is finite and non-negative and at least one
+    /// is positive.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(value) = self.0.iter().find(|v| !(v.is_finite() && **v >= 0.0)) {
+            return Err(Error::InvalidConfig(format!(
+                "weights must be finite and non-negative, got {value}"
+            )));
+        }
+        if self.total() == 0.0 {
+            return Err(Error::InvalidConfig(
+                "at least one weight must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Largest weighted distance between two normalized patterns.
+    pub fn max_distance(&self) -> f32 {
+        self.total().sqrt()
+    }
+
+    fn total(&self) -> f32 {
+        self.0.iter().sum()
+    }
+}
+
+impl TryFrom<[f32; 9]> for DimensionWeights {
+    type Error = Error;
+
+    fn try_from(values: [f32; 9]) -> Result<Self> {
+        Self::new(values)
+    }
 }
 
 /// Configuration for matching behavior.
@@ -24,11 +164,21 @@ pub fn euclidean_distance(a: &NormalizedPattern, b: &NormalizedPattern) -> f32 {
 /// Assumes a static epsilon and a fixed temporal window, which are simple
 /// baselines meant for experimentation rather than adaptive production use.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawMatchingConfig"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MatchingConfig {
     /// Matching threshold in normalized 9D space.
     pub epsilon: f32,
-    /// Number of consecutive observations required within `epsilon`.
+    /// Number of consecutive observations required within `epsilon`; 0
+    /// disables the window, so each observation is judged on its own.
     pub window_size: usize,
+    /// Per-dimension distance weights; uniform unless set.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "DimensionWeights::is_uniform")
+    )]
+    pub weights: DimensionWeights,
 }
 
 impl MatchingConfig {
@@ -37,60 +187,1125 @@ impl MatchingConfig {
         Self {
             epsilon,
             window_size,
+            weights: DimensionWeights::default(),
+        }
+    }
+
+    /// Return this config with different distance weights.
+    pub fn with_weights(mut self, weights: DimensionWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Start building a validated config.
+    pub fn builder() -> MatchingConfigBuilder {
+        MatchingConfigBuilder::default()
+    }
+
+    /// Check that `epsilon` is finite and non-negative and the weights are
+    /// valid. Every `window_size` is accepted, including 0.
+    pub fn validate(&self) -> Result<()> {
+        check_epsilon(self.epsilon)?;
+        self.weights.validate()
+    }
+}
+
+/// Unchecked wire form of [`MatchingConfig`], validated on conversion.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawMatchingConfig {
+    epsilon: f32,
+    window_size: usize,
+    #[serde(default)]
+    weights: DimensionWeights,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawMatchingConfig> for MatchingConfig {
+    type Error = Error;
+
+    fn try_from(raw: RawMatchingConfig) -> Result<Self> {
+        MatchingConfig::builder()
+            .epsilon(raw.epsilon)
+            .window_size(raw.window_size)
+            .weights(raw.weights)
+            .build()
+    }
+}
+
+/// Builder for [`MatchingConfig`] that rejects invalid combinations.
+///
+/// Defaults to `epsilon = 0.1` and `window_size = 3`, matching the CLI.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchingConfigBuilder {
+    epsilon: f32,
+    window_size: usize,
+    weights: DimensionWeights,
+}
+
+impl Default for MatchingConfigBuilder {
+    fn default() -> Self {
+        Self {
+            epsilon: 0.1,
+            window_size: 3,
+            weights: DimensionWeights::default(),
         }
     }
 }
 
+impl MatchingConfigBuilder {
+    /// Set the matching threshold in normalized space.
+    pub fn epsilon(mut self, epsilon: f32) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Set the number of consecutive observations required.
+    pub fn window_size(mut self, window_size: usize) -> Self {
+        self.window_size = window_size;
+        self
+    }
+
+    /// Set the per-dimension distance weights.
+    pub fn weights(mut self, weights: DimensionWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Validate and build the config.
+    pub fn build(self) -> Result<MatchingConfig> {
+        let config = MatchingConfig::new(self.epsilon, self.window_size).with_weights(self.weights);
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Reject NaN, infinite, or negative thresholds.
+pub(crate) fn check_epsilon(epsilon: f32) -> Result<()> {
+    if !epsilon.is_finite() || epsilon < 0.0 {
+        return Err(Error::InvalidConfig(format!(
+            "epsilon must be finite and non-negative, got {epsilon}"
+        )));
+    }
+    Ok(())
+}
+
+/// Per-dimension matching tolerances in normalized units.
+///
+/// A measurement matches when it lies inside the axis-aligned ellipsoid with
+/// these half-widths around the target, i.e. when
+/// [`scaled_distance`](Self::scaled_distance) is at most 1. Equal tolerances
+/// of `epsilon` reproduce the Euclidean `epsilon` ball.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "[f32; 9]"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Tolerances([f32; 9]);
+
+impl Tolerances {
+    /// Create tolerances in field declaration order.
+    ///
+    /// Every value must be finite and positive.
+    pub fn new(values: [f32; 9]) -> Result<Self> {
+        if let Some(value) = values.iter().find(|v| !(v.is_finite() && **v > 0.0)) {
+            return Err(Error::InvalidConfig(format!(
+                "tolerances must be finite and positive, got {value}"
+            )));
+        }
+        Ok(Self(values))
+    }
+
+    /// Tolerances in field declaration order.
+    pub fn values(&self) -> [f32; 9] {
+        self.0
+    }
+
+    /// Distance with each dimension divided by its tolerance.
+    pub fn scaled_distance(&self, a: &NormalizedPattern, b: &NormalizedPattern) -> f32 {
+        normalized_to_array(a)
+            .iter()
+            .zip(normalized_to_array(b))
+            .zip(self.0)
+            .map(|((a, b), tolerance)| ((a - b) / tolerance).powi(2))
+            .sum::<f32>()
+            .sqrt()
+    }
+}
+
+impl TryFrom<[f32; 9]> for Tolerances {
+    type Error = Error;
+
+    fn try_from(values: [f32; 9]) -> Result<Self> {
+        Self::new(values)
+    }
+}
+
 /// Matcher that performs temporal smoothing over recent observations.
 ///
 /// This matcher assumes measured patterns arrive as a time-ordered stream and
 /// that each observation is comparable to the target pattern without additional
-/// context such as sensor calibration or quality scores.
+/// context such as sensor calibration or quality scores. The `with_*`
+/// constructors enable one option each; [`Matcher::builder`] combines them.
 #[derive(Debug, Clone)]
 pub struct Matcher {
     /// Matching behavior configuration.
     config: MatchingConfig,
     /// Sliding window of recent match results.
-    window: Vec<bool>,
+    window: MatchWindow<Vec<u64>>,
+    /// Drift compensation state, if enabled.
+    drift: Option<DriftCompensator>,
+    /// Per-dimension tolerances replacing `epsilon`, if set.
+    tolerances: Option<Tolerances>,
+    /// Handling of exactly repeated observations, if enabled.
+    duplicates: Option<DuplicatePolicy>,
+    /// Most recent raw observation, tracked when `duplicates` is set.
+    last: Option<MeasuredPattern>,
+    /// Consecutive repeats of `last` after its first occurrence.
+    repeats: usize,
+    /// Dimensions excluded from distance calculations, in field order.
+    ignored: [bool; 9],
+    /// Handling of non-finite and out-of-range fields, if enabled.
+    sanitize: Option<SanitizePolicy>,
 }
 
 impl Matcher {
+    /// Start building a matcher that combines several options.
+    pub fn builder(config: MatchingConfig) -> MatcherBuilder {
+        MatcherBuilder::new(config)
+    }
+
     /// Create a matcher with the provided configuration.
     pub fn new(config: MatchingConfig) -> Self {
         Self {
             config,
-            window: Vec::with_capacity(config.window_size),
+            window: MatchWindow::new(config.window_size),
+            drift: None,
+            tolerances: None,
+            duplicates: None,
+            last: None,
+            repeats: 0,
+            ignored: [false; 9],
+            sanitize: None,
         }
     }
 
+    /// Create a matcher that tests each dimension against its own tolerance.
+    ///
+    /// `config.epsilon` is ignored by [`observe`](Self::observe); see
+    /// [`Tolerances`] for the match condition. Tolerances can be derived from
+    /// the SRT with [`tolerances_from_srt`](crate::srt::tolerances_from_srt).
+    pub fn with_tolerances(config: MatchingConfig, tolerances: Tolerances) -> Self {
+        let mut matcher = Self::new(config);
+        matcher.tolerances = Some(tolerances);
+        matcher
+    }
+
+    /// Create a matcher that skips the dimensions `spec` marks as ignored.
+    ///
+    /// Ignored dimensions contribute nothing to the distance, so `epsilon`
+    /// applies to the remaining dimensions only. Use with targets from
+    /// [`pattern_from_srt_with_spec`](crate::srt::pattern_from_srt_with_spec).
+    pub fn with_derivation_spec(config: MatchingConfig, spec: &DerivationSpec) -> Self {
+        let mut matcher = Self::new(config);
+        matcher.ignored = spec.ignored();
+        matcher
+    }
+
+    /// Dimensions excluded from matching, in field declaration order.
+    pub fn ignored(&self) -> [bool; 9] {
+        self.ignored
+    }
+
+    /// Per-dimension tolerances, if set.
+    pub fn tolerances(&self) -> Option<&Tolerances> {
+        self.tolerances.as_ref()
+    }
+
+    /// Recent within-threshold results.
+    pub fn window(&self) -> &MatchWindow<Vec<u64>> {
+        &self.window
+    }
+
+    /// Create a matcher that detects a frozen sensor.
+    ///
+    /// A stuck sensor repeating a value within epsilon would otherwise fill
+    /// the window and report a trivially stable match. See
+    /// [`DuplicatePolicy`] for the available responses.
+    pub fn with_duplicate_policy(config: MatchingConfig, policy: DuplicatePolicy) -> Self {
+        let mut matcher = Self::new(config);
+        matcher.duplicates = Some(policy);
+        matcher
+    }
+
+    /// Number of consecutive observations identical to the one before them.
+    ///
+    /// Always 0 unless a [`DuplicatePolicy`] is set.
+    pub fn repeats(&self) -> usize {
+        self.repeats
+    }
+
+    /// Whether a [`DuplicatePolicy::Flag`] limit has been reached.
+    pub fn is_frozen(&self) -> bool {
+        matches!(self.duplicates, Some(DuplicatePolicy::Flag(limit)) if self.repeats >= limit)
+    }
+
+    /// Return this matcher with `policy` applied to every measurement passed
+    /// to [`observe`](Self::observe).
+    ///
+    /// Rejected samples count as misses, so a sensor stuck out of range
+    /// cannot hold a lock; samples with dimensions marked missing are
+    /// matched on the rest, as by [`observe_partial`](Self::observe_partial).
+    /// Combines with every constructor.
+    pub fn sanitizing(mut self, policy: SanitizePolicy) -> Self {
+        self.sanitize = Some(policy);
+        self
+    }
+
+    /// Policy applied to out-of-range measurements, if set.
+    pub fn sanitize_policy(&self) -> Option<SanitizePolicy> {
+        self.sanitize
+    }
+
+    /// Create a matcher that compensates for slow sensor drift.
+    ///
+    /// See [`DriftConfig`] for how the correction is estimated and bounded.
+    pub fn with_drift(config: MatchingConfig, drift: DriftConfig) -> Result<Self> {
+        drift.validate()?;
+        let mut matcher = Self::new(config);
+        matcher.drift = Some(DriftCompensator::new(drift));
+        Ok(matcher)
+    }
+
+    /// Current drift correction, if drift compensation is enabled.
+    pub fn drift(&self) -> Option<&DriftCompensator> {
+        self.drift.as_ref()
+    }
+
+    /// Value [`observe`](Self::observe) compares the distance against: `1.0`
+    /// with per-dimension tolerances, otherwise `config.epsilon`.
+    pub fn threshold(&self) -> f32 {
+        match self.tolerances {
+            Some(_) => 1.0,
+            None => self.config.epsilon,
+        }
+    }
+
+    /// Per-dimension breakdown of the distance [`observe`](Self::observe)
+    /// would compute for `measured`, without updating the matcher.
+    ///
+    /// Deltas are taken after drift correction, and each carries the weight
+    /// the matcher gives its dimension: the config weight, `1 / tolerance²`
+    /// with tolerances, or `0` for a dimension the derivation spec ignores.
+    /// The resulting distance is compared against
+    /// [`threshold`](Self::threshold).
+    pub fn explain(
+        &self,
+        measured: &SubmodalityPattern,
+        target: &SubmodalityPattern,
+    ) -> PatternDiff {
+        let offset = self.drift.as_ref().map_or([0.0; 9], DriftCompensator::offset);
+        let weights = match &self.tolerances {
+            Some(tolerances) => tolerances.values().map(|t| 1.0 / (t * t)),
+            None => self.config.weights.values(),
+        };
+        let weights = std::array::from_fn(|i| if self.ignored[i] { 0.0 } else { weights[i] });
+        measured.diff(target).corrected(offset).weighted(weights)
+    }
+
     /// Observe a new measurement and return whether a match is stable.
     ///
-    /// This normalizes both patterns, computes distance, and records whether
-    /// the distance is within `epsilon`. It returns `true` only when the most
-    /// recent `window_size` observations are all within `epsilon`.
+    /// This normalizes both patterns, computes the distance weighted by
+    /// `config.weights`, and records whether it is within `epsilon`. It
+    /// returns `true` only when the most recent `window_size` observations
+    /// are all within `epsilon`, or within the per-dimension tolerances when
+    /// set. With drift compensation enabled, the measurement is corrected by
+    /// the current drift estimate first. With a [`DuplicatePolicy`], exact
+    /// repeats of the previous measurement are handled by the policy before
+    /// any distance is computed, and with a [`SanitizePolicy`] out-of-range
+    /// fields are handled after that; a sample left with missing dimensions
+    /// is then judged as by [`observe_partial`](Self::observe_partial).
     pub fn observe(
         &mut self,
         measured: &SubmodalityPattern,
         target: &SubmodalityPattern,
+    ) -> bool {
+        self.observe_measured(MeasuredPattern::from(measured), target)
+    }
+
+    /// Observe a measurement that may lack some dimensions.
+    ///
+    /// Only dimensions that are present (and not ignored by a
+    /// [`DerivationSpec`]) are compared, using [`partial_distance`] or the
+    /// per-dimension tolerances rescaled the same way, so missing sensors
+    /// neither help nor hurt the match. A measurement with no usable
+    /// dimension counts as outside epsilon. The duplicate and sanitize
+    /// policies apply as in [`observe`](Self::observe), the latter to the
+    /// present fields only, and a stable match updates the drift estimate of
+    /// the present dimensions.
+    pub fn observe_partial(
+        &mut self,
+        measured: &MeasuredPattern,
+        target: &SubmodalityPattern,
+    ) -> bool {
+        self.observe_measured(measured.clone(), target)
+    }
+
+    /// Pipeline shared by [`observe`](Self::observe) and
+    /// [`observe_partial`](Self::observe_partial): duplicate policy, sanitize
+    /// policy, then the distance over whichever dimensions remain.
+    fn observe_measured(&mut self, measured: MeasuredPattern, target: &SubmodalityPattern) -> bool {
+        if let Some(policy) = self.duplicates {
+            if self.last.as_ref() == Some(&measured) {
+                self.repeats += 1;
+            } else {
+                self.repeats = 0;
+                self.last = Some(measured.clone());
+            }
+            match policy {
+                DuplicatePolicy::Ignore
+                    if self.repeats > 0 && self.repeats >= self.config.window_size =>
+                {
+                    return self.record(false);
+                }
+                DuplicatePolicy::Ignore if self.repeats > 0 => {
+                    return self.window.is_full() && self.window.all();
+                }
+                DuplicatePolicy::Cap(limit) if self.repeats > limit => return self.record(false),
+                _ => {}
+            }
+        }
+        let measured = match self.sanitize.map(|policy| measured.sanitize(policy)) {
+            None => measured,
+            Some(Err(_)) => return self.record(false),
+            Some(Ok(sanitized)) => sanitized,
+        };
+        if measured.present_count() == 9 {
+            self.judge_complete(&measured.fill_from(target), target)
+        } else {
+            self.judge_partial(&measured, target)
+        }
+    }
+
+    /// Judge a measurement with every dimension present.
+    fn judge_complete(
+        &mut self,
+        measured: &SubmodalityPattern,
+        target: &SubmodalityPattern,
     ) -> bool {
         let measured_norm = measured.normalize();
         let target_norm = target.normalize();
-        let distance = euclidean_distance(&measured_norm, &target_norm);
-        let within = distance <= self.config.epsilon;
+        let mut corrected = match &self.drift {
+            Some(drift) => drift.correct(&measured_norm),
+            None => measured_norm.clone(),
+        };
+        if self.ignored.contains(&true) {
+            let mut values = normalized_to_array(&corrected);
+            let target_values = normalized_to_array(&target_norm);
+            for (i, value) in values.iter_mut().enumerate() {
+                if self.ignored[i] {
+                    *value = target_values[i];
+                }
+            }
+            corrected = normalized_from_array(values);
+        }
+        let within = match &self.tolerances {
+            Some(tolerances) => tolerances.scaled_distance(&corrected, &target_norm) <= 1.0,
+            None => {
+                weighted_distance(&corrected, &target_norm, &self.config.weights)
+                    <= self.config.epsilon
+            }
+        };
+        let stable = self.record(within);
+        if let (true, Some(drift)) = (stable, self.drift.as_mut()) {
+            drift.update(&measured_norm, &target_norm, [true; 9]);
+        }
+        stable
+    }
+
+    /// Judge a measurement over its present, non-ignored dimensions.
+    fn judge_partial(&mut self, measured: &MeasuredPattern, target: &SubmodalityPattern) -> bool {
+        let present = measured.present();
+        let mask: [bool; 9] = std::array::from_fn(|i| present[i] && !self.ignored[i]);
+        let measured_norm = measured.fill_from(target).normalize();
+        let corrected = match &self.drift {
+            Some(drift) => drift.correct(&measured_norm),
+            None => measured_norm.clone(),
+        };
+        let target_norm = target.normalize();
+        // Tolerances replace the weights, as they do in `judge_complete`.
+        let (scale, weights, threshold) = match &self.tolerances {
+            Some(tolerances) => (tolerances.values(), DimensionWeights::default(), 1.0),
+            None => ([1.0; 9], self.config.weights, self.config.epsilon),
+        };
+        // Rescale to the non-ignored weight rather than the total, so a
+        // complete measurement gets exactly the distance `judge_complete`
+        // computes.
+        let expected: f32 = (0..9)
+            .filter(|&i| !self.ignored[i])
+            .map(|i| weights.0[i])
+            .sum();
+        let within = masked_distance(&corrected, &target_norm, mask, scale, &weights)
+            .is_some_and(|distance| distance * (expected / weights.total()).sqrt() <= threshold);
+        let stable = self.record(within);
+        if let (true, Some(drift)) = (stable, self.drift.as_mut()) {
+            drift.update(&measured_norm, &target_norm, present);
+        }
+        stable
+    }
+
+    /// Observe a measurement against the target an [`SrtProvider`] derives
+    /// for `salt`.
+    ///
+    /// Lets the matcher run with an opaque provider such as a keystore
+    /// instead of a raw token. The target is derived on every call; derive it
+    /// once with [`SrtProvider::derive_pattern`] and use
+    /// [`observe`](Self::observe) when the provider is slow. The window is
+    /// left unchanged if derivation fails.
+    pub fn observe_derived<P: SrtProvider + ?Sized>(
+        &mut self,
+        measured: &SubmodalityPattern,
+        provider: &P,
+        salt: &[u8],
+    ) -> Result<bool, ProviderError> {
+        let target = provider.derive_pattern(salt)?;
+        Ok(self.observe(measured, &target))
+    }
+
+    /// Observe a measurement over registry-defined dimensions.
+    ///
+    /// Compares the weighted distance from [`DimensionRegistry::distance`]
+    /// against `epsilon` and records it in the window, so custom dimensions
+    /// and weights are honored. None of the per-pattern options apply here.
+    /// Returns an error without updating the window if either pattern does
+    /// not match the registry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matcher was built with tolerances, ignored dimensions,
+    /// drift compensation, or a duplicate or sanitize policy.
+    pub fn observe_dyn(
+        &mut self,
+        registry: &DimensionRegistry,
+        measured: &DynPattern,
+        target: &DynPattern,
+    ) -> Result<bool, RegistryError> {
+        self.assert_no_options("observe_dyn");
+        let distance = registry.distance(measured, target)?;
+        Ok(self.record(distance <= self.config.epsilon))
+    }
+
+    /// Observe a measurement that may carry extension dimensions.
+    ///
+    /// Compares the weighted distance from
+    /// [`DimensionRegistry::extended_distance`], which adds every extension
+    /// both patterns supply, against `epsilon` and records it in the window.
+    /// None of the per-pattern options apply here. Returns an error without
+    /// updating the window if an extension is not registered.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matcher was built with tolerances, ignored dimensions,
+    /// drift compensation, or a duplicate or sanitize policy.
+    pub fn observe_ext(
+        &mut self,
+        registry: &DimensionRegistry,
+        measured: &PatternExt,
+        target: &PatternExt,
+    ) -> Result<bool, RegistryError> {
+        self.assert_no_options("observe_ext");
+        let distance = registry.extended_distance(measured, target, &self.config.weights)?;
+        Ok(self.record(distance <= self.config.epsilon))
+    }
+
+    /// Reject options that the registry entry points would silently skip.
+    fn assert_no_options(&self, entry: &str) {
+        assert!(
+            self.tolerances.is_none()
+                && !self.ignored.contains(&true)
+                && self.drift.is_none()
+                && self.duplicates.is_none()
+                && self.sanitize.is_none(),
+            "{entry} does not support tolerances, ignored dimensions, drift, \
+             duplicate or sanitize policies"
+        );
+    }
+
+    /// Push a within-epsilon result into the window and report stability.
+    fn record(&mut self, within: bool) -> bool {
+        self.window.record(within)
+    }
+}
+
+/// Builder for [`Matcher`] that combines tolerances, ignored dimensions,
+/// duplicate handling, drift compensation, and sanitizing.
+///
+/// Each option behaves as it does with the matching `with_*` constructor.
+#[derive(Debug, Clone)]
+pub struct MatcherBuilder {
+    config: MatchingConfig,
+    tolerances: Option<Tolerances>,
+    ignored: [bool; 9],
+    duplicates: Option<DuplicatePolicy>,
+    drift: Option<DriftConfig>,
+    sanitize: Option<SanitizePolicy>,
+}
+
+impl MatcherBuilder {
+    /// Start from `config` with every option disabled.
+    pub fn new(config: MatchingConfig) -> Self {
+        Self {
+            config,
+            tolerances: None,
+            ignored: [false; 9],
+            duplicates: None,
+            drift: None,
+            sanitize: None,
+        }
+    }
+
+    /// Test each dimension against its own tolerance instead of `epsilon`.
+    pub fn tolerances(mut self, tolerances: Tolerances) -> Self {
+        self.tolerances = Some(tolerances);
+        self
+    }
+
+    /// Skip the dimensions `spec` marks as ignored.
+    pub fn derivation_spec(mut self, spec: &DerivationSpec) -> Self {
+        self.ignored = spec.ignored();
+        self
+    }
+
+    /// Handle exactly repeated observations with `policy`.
+    pub fn duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicates = Some(policy);
+        self
+    }
+
+    /// Compensate for slow sensor drift.
+    pub fn drift(mut self, drift: DriftConfig) -> Self {
+        self.drift = Some(drift);
+        self
+    }
+
+    /// Apply `policy` to every measurement before matching.
+    pub fn sanitize(mut self, policy: SanitizePolicy) -> Self {
+        self.sanitize = Some(policy);
+        self
+    }
+
+    /// Validate the config and drift parameters and build the matcher.
+    pub fn build(self) -> Result<Matcher> {
+        self.config.validate()?;
+        let drift = match self.drift {
+            Some(drift) => {
+                drift.validate()?;
+                Some(DriftCompensator::new(drift))
+            }
+            None => None,
+        };
+        Ok(Matcher {
+            drift,
+            tolerances: self.tolerances,
+            duplicates: self.duplicates,
+            ignored: self.ignored,
+            sanitize: self.sanitize,
+            ..Matcher::new(self.config)
+        })
+    }
+}
+
+/// How a [`Matcher`] treats observations identical to the previous one.
+///
+/// Real sensors jitter, so exactly repeated consecutive readings usually mean
+/// a frozen sensor or a stalled pipeline replaying its last value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum DuplicatePolicy {
+    /// Drop repeats, leaving the window unchanged, until a window's worth
+    /// of them has been seen in a row; from then on repeats are recorded as
+    /// misses, so a frozen sensor loses its lock as fast as it gained it.
+    Ignore,
+    /// Count at most this many consecutive repeats; later ones are recorded
+    /// as misses, so a frozen sensor loses its lock.
+    Cap(usize),
+    /// Record repeats normally but report [`Matcher::is_frozen`] once this
+    /// many consecutive repeats have been seen.
+    Flag(usize),
+}
+
+/// Parameters for [`Matcher::with_drift`].
+///
+/// While the matcher is locked (reporting a stable match), it tracks the
+/// residual between measured and target patterns as an exponential moving
+/// average per normalized dimension, and subtracts that estimate from later
+/// measurements. Updates only happen while locked, so an unrelated peer cannot
+/// pull the correction toward itself, and each dimension's correction is
+/// clamped to `max_correction` so drift cannot walk the matcher arbitrarily
+/// far from the target. The correction is kept when the lock is lost.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DriftConfig {
+    /// Weight of each new residual in the moving average, in `(0, 1]`.
+    pub rate: f32,
+    /// Largest correction applied to any dimension, in normalized units.
+    pub max_correction: f32,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        Self {
+            rate: 0.05,
+            max_correction: 0.05,
+        }
+    }
+}
+
+impl DriftConfig {
+    /// Check that `rate` is in `(0, 1]` and `max_correction` is finite and
+    /// non-negative.
+    pub fn validate(&self) -> Result<()> {
+        if !(self.rate > 0.0 && self.rate <= 1.0) {
+            return Err(Error::InvalidConfig(format!(
+                "drift rate must be in (0, 1], got {}",
+                self.rate
+            )));
+        }
+        if !self.max_correction.is_finite() || self.max_correction < 0.0 {
+            return Err(Error::InvalidConfig(format!(
+                "max_correction must be finite and non-negative, got {}",
+                self.max_correction
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Running per-dimension drift estimate used by a [`Matcher`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriftCompensator {
+    /// Adaptation parameters.
+    config: DriftConfig,
+    /// Estimated offset of measurements from the target, in field order.
+    offset: [f32; 9],
+}
+
+impl DriftCompensator {
+    fn new(config: DriftConfig) -> Self {
+        Self {
+            config,
+            offset: [0.0; 9],
+        }
+    }
+
+    /// Estimated per-dimension offset in normalized units, in field order.
+    pub fn offset(&self) -> [f32; 9] {
+        self.offset
+    }
+
+    /// Forget the current estimate.
+    pub fn reset(&mut self) {
+        self.offset = [0.0; 9];
+    }
+
+    /// Subtract the estimated offset from a measurement.
+    fn correct(&self, measured: &NormalizedPattern) -> NormalizedPattern {
+        let mut values = normalized_to_array(measured);
+        for (value, offset) in values.iter_mut().zip(self.offset) {
+            *value -= offset;
+        }
+        normalized_from_array(values)
+    }
+
+    /// Move the estimate toward the residual of a locked observation, in
+    /// the dimensions marked in `present` only.
+    fn update(
+        &mut self,
+        measured: &NormalizedPattern,
+        target: &NormalizedPattern,
+        present: [bool; 9],
+    ) {
+        let limit = self.config.max_correction;
+        let residuals = normalized_to_array(measured)
+            .into_iter()
+            .zip(normalized_to_array(target))
+            .map(|(m, t)| m - t);
+        for ((offset, residual), present) in self.offset.iter_mut().zip(residuals).zip(present) {
+            if !present {
+                continue;
+            }
+            *offset += self.config.rate * (residual - *offset);
+            *offset = offset.clamp(-limit, limit);
+        }
+    }
+}
+
+impl MatchWindow<Vec<u64>> {
+    /// Create an empty heap-backed window holding up to `capacity` results.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_storage(vec![0; embedded::window_blocks(capacity)], capacity)
+    }
+}
+
+/// Identifier of a stream registered with a [`QuorumMatcher`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StreamId(usize);
+
+/// Declares a collective rendezvous when enough streams are stable together.
+///
+/// Each registered stream (typically one peer's sensor feed) has its own
+/// smoothed [`Matcher`]. A stream counts toward the quorum while its most
+/// recent observation was stable and that observation is no older than the
+/// shared `window`; the quorum is reached when at least `threshold` streams
+/// count at once. Time is passed in explicitly, as in the scheduler.
+#[derive(Debug, Clone)]
+pub struct QuorumMatcher {
+    /// Per-stream matching configuration.
+    config: MatchingConfig,
+    /// Minimum number of simultaneously stable streams.
+    threshold: usize,
+    /// Maximum age of a stable observation that still counts.
+    window: Duration,
+    /// Matcher and time of the latest stable observation for each stream.
+    streams: Vec<(Matcher, Option<Instant>)>,
+}
+
+impl QuorumMatcher {
+    /// Create a matcher requiring `threshold` streams stable within `window`.
+    pub fn new(config: MatchingConfig, threshold: usize, window: Duration) -> Result<Self> {
+        config.validate()?;
+        if threshold == 0 {
+            return Err(Error::InvalidConfig(
+                "quorum threshold must be at least 1".to_string(),
+            ));
+        }
+        Ok(Self {
+            config,
+            threshold,
+            window,
+            streams: Vec::new(),
+        })
+    }
+
+    /// Register a new stream and return its id.
+    pub fn add_stream(&mut self) -> StreamId {
+        self.streams.push((Matcher::new(self.config), None));
+        StreamId(self.streams.len() - 1)
+    }
+
+    /// Number of registered streams.
+    pub fn stream_count(&self) -> usize {
+        self.streams.len()
+    }
+
+    /// Observe a measurement on `stream` taken at `now` and report whether
+    /// the quorum is reached.
+    ///
+    /// Fails with [`ObservationError::UnknownStream`] if `stream` was not
+    /// returned by this matcher's [`add_stream`](Self::add_stream).
+    pub fn observe(
+        &mut self,
+        stream: StreamId,
+        measured: &SubmodalityPattern,
+        target: &SubmodalityPattern,
+        now: Instant,
+    ) -> Result<bool> {
+        let (matcher, stable_at) = self
+            .streams
+            .get_mut(stream.0)
+            .ok_or(ObservationError::UnknownStream(stream))?;
+        *stable_at = matcher.observe(measured, target).then_some(now);
+        Ok(self.has_quorum(now))
+    }
+
+    /// Number of streams whose latest observation was stable within the
+    /// window ending at `now`.
+    pub fn stable_count(&self, now: Instant) -> usize {
+        self.streams
+            .iter()
+            .filter(|(_, stable_at)| {
+                stable_at.is_some_and(|at| now.saturating_duration_since(at) <= self.window)
+            })
+            .count()
+    }
+
+    /// Whether at least `threshold` streams are stable at `now`.
+    pub fn has_quorum(&self, now: Instant) -> bool {
+        self.stable_count(now) >= self.threshold
+    }
+}
+
+/// Matcher that steps through a sequence of targets (a moving target).
+///
+/// Each target must be matched stably, as by [`Matcher::observe`], before the
+/// next becomes current; the window starts empty for every target. Targets
+/// typically come from
+/// [`pattern_sequence_from_srt`](crate::srt::pattern_sequence_from_srt).
+#[derive(Debug, Clone)]
+pub struct SequenceMatcher {
+    /// Matching behavior configuration.
+    config: MatchingConfig,
+    /// Matcher for the current target.
+    matcher: Matcher,
+    /// Targets in the order they must be matched.
+    targets: Vec<SubmodalityPattern>,
+    /// Index of the current target.
+    position: usize,
+}
+
+impl SequenceMatcher {
+    /// Create a matcher over `targets`, which must not be empty.
+    pub fn new(config: MatchingConfig, targets: Vec<SubmodalityPattern>) -> Result<Self> {
+        config.validate()?;
+        if targets.is_empty() {
+            return Err(Error::InvalidConfig(
+                "target sequence must not be empty".to_string(),
+            ));
+        }
+        Ok(Self {
+            config,
+            matcher: Matcher::new(config),
+            targets,
+            position: 0,
+        })
+    }
+
+    /// Observe a measurement against the current target.
+    ///
+    /// Returns `true` when the observation completes a stable match on the
+    /// current target, after which the next target becomes current. Once the
+    /// sequence is complete, further observations are ignored and return
+    /// `false`.
+    pub fn observe(&mut self, measured: &SubmodalityPattern) -> bool {
+        let Some(target) = self.targets.get(self.position) else {
+            return false;
+        };
+        if !self.matcher.observe(measured, target) {
+            return false;
+        }
+        self.position += 1;
+        self.matcher = Matcher::new(self.config);
+        true
+    }
+
+    /// Target currently being matched, or `None` once complete.
+    pub fn current_target(&self) -> Option<&SubmodalityPattern> {
+        self.targets.get(self.position)
+    }
+
+    /// Number of targets matched so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Whether every target in the sequence has been matched.
+    pub fn is_complete(&self) -> bool {
+        self.position == self.targets.len()
+    }
+}
+
+/// Matcher for timestamped observations that may arrive late or out of order.
+///
+/// Observations are held in a small reorder buffer and released to an inner
+/// [`Matcher`] in timestamp order once the buffer is full, so a delayed
+/// sensor batch cannot shuffle the consecutive-window semantics. Observations
+/// older than `max_age` on arrival, or older than one already released, are
+/// rejected. Buffered observations are matched against the target passed to
+/// the call that releases them.
+#[derive(Debug, Clone)]
+pub struct TimedMatcher {
+    /// Matcher receiving observations in timestamp order.
+    matcher: Matcher,
+    /// Maximum age of an observation on arrival.
+    max_age: Duration,
+    /// Number of observations held back for reordering.
+    reorder_capacity: usize,
+    /// Held observations, sorted by timestamp.
+    buffer: Vec<(Instant, SubmodalityPattern)>,
+    /// Timestamp of the most recently released observation.
+    last_released: Option<Instant>,
+}
+
+impl TimedMatcher {
+    /// Create a matcher; a `reorder_capacity` of zero releases every
+    /// observation immediately.
+    pub fn new(config: MatchingConfig, max_age: Duration, reorder_capacity: usize) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            matcher: Matcher::new(config),
+            max_age,
+            reorder_capacity,
+            buffer: Vec::with_capacity(reorder_capacity + 1),
+            last_released: None,
+        })
+    }
+
+    /// Observe a measurement taken at `timestamp`, received at `now`.
+    ///
+    /// Returns the match decision for the observation released from the
+    /// buffer, or `None` while the buffer is still filling.
+    pub fn observe_at(
+        &mut self,
+        measured: &SubmodalityPattern,
+        target: &SubmodalityPattern,
+        timestamp: Instant,
+        now: Instant,
+    ) -> Result<Option<bool>> {
+        let age = now.saturating_duration_since(timestamp);
+        if age > self.max_age {
+            return Err(ObservationError::Stale { age }.into());
+        }
+        if self.last_released.is_some_and(|last| timestamp < last) {
+            return Err(ObservationError::OutOfOrder.into());
+        }
+        let position = self.buffer.partition_point(|(at, _)| *at <= timestamp);
+        self.buffer.insert(position, (timestamp, measured.clone()));
+        if self.buffer.len() > self.reorder_capacity {
+            return Ok(Some(self.release(target)));
+        }
+        Ok(None)
+    }
+
+    /// Release every buffered observation and return their decisions in
+    /// timestamp order.
+    pub fn flush(&mut self, target: &SubmodalityPattern) -> Vec<bool> {
+        (0..self.buffer.len())
+            .map(|_| self.release(target))
+            .collect()
+    }
+
+    /// Number of observations waiting in the reorder buffer.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Feed the oldest buffered observation to the matcher.
+    fn release(&mut self, target: &SubmodalityPattern) -> bool {
+        let (timestamp, measured) = self.buffer.remove(0);
+        self.last_released = Some(timestamp);
+        self.matcher.observe(&measured, target)
+    }
+}
 
-        if self.config.window_size == 0 {
-            return within;
+/// Matcher whose window is a length of time rather than a sample count.
+///
+/// A match is stable once consecutive observations have stayed within
+/// `epsilon` for at least `hold`, measured from the first observation of the
+/// run, so the decision does not depend on the sensor's sample rate. A miss
+/// ends the run, and so does a gap longer than `hold` between observations,
+/// since nothing is known about the interval in between. Observations must
+/// arrive in timestamp order; `config.window_size` is not used.
+#[derive(Debug, Clone)]
+pub struct DurationMatcher {
+    /// Matcher making per-observation decisions (window size zero).
+    matcher: Matcher,
+    /// How long observations must stay within threshold.
+    hold: Duration,
+    /// Timestamp of the first observation of the current run, if any.
+    run_start: Option<u64>,
+    /// Timestamp of the most recent observation.
+    last: Option<u64>,
+}
+
+impl DurationMatcher {
+    /// Create a matcher requiring `hold` of continuous matches.
+    pub fn new(config: MatchingConfig, hold: Duration) -> Result<Self> {
+        Self::from_builder(Matcher::builder(config), hold)
+    }
+
+    /// Create a matcher requiring `hold` of continuous matches, with the
+    /// options set on `builder`. The builder's window size is replaced by 0.
+    pub fn from_builder(mut builder: MatcherBuilder, hold: Duration) -> Result<Self> {
+        builder.config.window_size = 0;
+        Ok(Self {
+            matcher: builder.build()?,
+            hold,
+            run_start: None,
+            last: None,
+        })
+    }
+
+    /// Matcher making the per-observation decisions.
+    pub fn matcher(&self) -> &Matcher {
+        &self.matcher
+    }
+
+    /// Observe a timed measurement and return whether a match is stable.
+    ///
+    /// Fails with [`ObservationError::OutOfOrder`] without changing state if
+    /// `measured` is older than the previous observation.
+    pub fn observe(
+        &mut self,
+        measured: &TimedPattern,
+        target: &SubmodalityPattern,
+    ) -> Result<bool> {
+        let now = measured.timestamp;
+        if let Some(last) = self.last {
+            if now < last {
+                return Err(ObservationError::OutOfOrder.into());
+            }
+            if Duration::from_millis(now - last) > self.hold {
+                self.run_start = None;
+            }
         }
+        self.last = Some(now);
+        if !self.matcher.observe(&measured.pattern, target) {
+            self.run_start = None;
+            return Ok(false);
+        }
+        let start = *self.run_start.get_or_insert(now);
+        Ok(Duration::from_millis(now - start) >= self.hold)
+    }
 
-        if self.window.len() == self.config.window_size {
-            self.window.remove(0);
+    /// Length of the current run of matches, or zero after a miss.
+    pub fn held(&self) -> Duration {
+        match (self.run_start, self.last) {
+            (Some(start), Some(last)) => Duration::from_millis(last - start),
+            _ => Duration::ZERO,
         }
-        self.window.push(within);
+    }
 
-        self.window.len() == self.config.window_size && self.window.iter().all(|v| *v)
+    /// Required length of a run of matches.
+    pub fn hold(&self) -> Duration {
+        self.hold
     }
 }
 
+/// Reasons a timestamped observation is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObservationError {
+    /// The observation was older than the staleness bound on arrival.
+    Stale {
+        /// Age of the observation when it arrived.
+        age: Duration,
+    },
+    /// An observation with a later timestamp was already matched.
+    OutOfOrder,
+    /// The stream is not registered with this [`QuorumMatcher`].
+    UnknownStream(StreamId),
+}
+
+impl fmt::Display for ObservationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stale { age } => write!(f, "observation is stale ({age:?} old)"),
+            Self::OutOfOrder => {
+                write!(
+                    f,
+                    "observation arrived after a later one was already matched"
+                )
+            }
+            Self::UnknownStream(stream) => {
+                write!(f, "stream {} is not registered with this matcher", stream.0)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ObservationError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,6 +1368,293 @@ mod tests {
         assert!(matcher.observe(&measured, &target));
     }
 
+    #[test]
+    fn match_window_tracks_counts_across_blocks() {
+        let mut window = MatchWindow::new(150);
+        for i in 0..400 {
+            window.push(i % 3 != 0);
+            assert_eq!(window.matches(), window.popcount());
+        }
+        assert!(window.is_full());
+        assert_eq!(window.len(), 150);
+        assert_eq!(window.matches(), 100);
+        assert!(window.at_least(100));
+        assert!(!window.at_least(101));
+        assert!(!window.all());
+
+        for _ in 0..150 {
+            window.push(true);
+        }
+        assert!(window.all());
+        window.clear();
+        assert!(window.is_empty());
+        assert_eq!(window.popcount(), 0);
+    }
+
+    #[test]
+    fn large_windows_require_every_observation() {
+        let mut matcher = Matcher::new(MatchingConfig::new(0.05, 500));
+        let target = SubmodalityPattern::zeros();
+        for _ in 0..499 {
+            assert!(!matcher.observe(&target, &target));
+        }
+        assert!(matcher.observe(&target, &target));
+        assert!(!matcher.observe(&max_pattern(), &target));
+    }
+
+    #[test]
+    fn drift_compensation_keeps_slow_drift_locked() {
+        let config = MatchingConfig::new(0.05, 3);
+        let drift = DriftConfig {
+            rate: 0.2,
+            max_correction: 0.1,
+        };
+        let mut plain = Matcher::new(config);
+        let mut adaptive = Matcher::with_drift(config, drift).expect("valid");
+        let target = SubmodalityPattern::zeros();
+        let mut measured = target.clone();
+        let mut plain_locked = 0;
+        let mut adaptive_locked = 0;
+        for _ in 0..100 {
+            measured.brightness += 0.001;
+            plain_locked += usize::from(plain.observe(&measured, &target));
+            adaptive_locked += usize::from(adaptive.observe(&measured, &target));
+        }
+        // Total drift is 0.1; only the compensated matcher stays locked.
+        assert!(plain_locked < 60);
+        assert_eq!(adaptive_locked, 98);
+        let offset = adaptive.drift().expect("drift enabled").offset();
+        assert!(offset[0] > 0.05 && offset[0] <= 0.1);
+
+        // Corrections are bounded, so a large jump still breaks the lock.
+        measured.brightness += 0.3;
+        assert!(!adaptive.observe(&measured, &target));
+        assert!(Matcher::with_drift(config, DriftConfig { rate: 0.0, ..drift }).is_err());
+    }
+
+    #[test]
+    fn tolerances_apply_per_dimension() {
+        let mut values = [0.01; 9];
+        values[0] = 0.3;
+        let tolerances = Tolerances::new(values).expect("valid");
+        let mut matcher = Matcher::with_tolerances(MatchingConfig::new(0.0, 1), tolerances);
+        let target = min_pattern();
+        let mut measured = min_pattern();
+        measured.brightness = BRIGHTNESS_MIN + 0.2 * (BRIGHTNESS_MAX - BRIGHTNESS_MIN);
+        assert!(matcher.observe(&measured, &target));
+        measured.color_temp = COLOR_TEMP_MIN + 0.2 * (COLOR_TEMP_MAX - COLOR_TEMP_MIN);
+        assert!(!matcher.observe(&measured, &target));
+
+        let ball = Tolerances::new([0.5; 9]).expect("valid");
+        let (a, b) = (measured.normalize(), target.normalize());
+        assert!((ball.scaled_distance(&a, &b) * 0.5 - euclidean_distance(&a, &b)).abs() < 1e-6);
+        assert!(Tolerances::new([0.0; 9]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn tolerance_deserialization_checks_values() {
+        let ball = Tolerances::new([0.5; 9]).unwrap();
+        let json = serde_json::to_value(ball).unwrap();
+        assert_eq!(serde_json::from_value::<Tolerances>(json).unwrap(), ball);
+        let zero = serde_json::json!([0.5, 0.5, 0.5, 0.0, 0.5, 0.5, 0.5, 0.5, 0.5]);
+        assert!(serde_json::from_value::<Tolerances>(zero).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_deserialization_checks_values() {
+        let config = MatchingConfig::new(0.2, 0);
+        let json = serde_json::to_value(config).unwrap();
+        assert_eq!(serde_json::from_value::<MatchingConfig>(json).unwrap(), config);
+        let negative = serde_json::json!({ "epsilon": -0.1, "window_size": 3 });
+        assert!(serde_json::from_value::<MatchingConfig>(negative).is_err());
+        // serde_json writes a NaN epsilon as null.
+        let nan = serde_json::to_value(MatchingConfig::new(f32::NAN, 3)).unwrap();
+        assert!(serde_json::from_value::<MatchingConfig>(nan).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn weight_deserialization_checks_values() {
+        let weights = DimensionWeights::new([2.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0]).unwrap();
+        let json = serde_json::to_value(weights).unwrap();
+        assert_eq!(serde_json::from_value::<DimensionWeights>(json).unwrap(), weights);
+        let negative = serde_json::json!([1.0, 1.0, 1.0, -1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+        assert!(serde_json::from_value::<DimensionWeights>(negative).is_err());
+        let zero = serde_json::json!([0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(serde_json::from_value::<DimensionWeights>(zero).is_err());
+    }
+
+    #[test]
+    fn ignored_dimensions_do_not_affect_matching() {
+        use crate::srt::{DerivationSpec, DimensionSource};
+
+        let spec = DerivationSpec {
+            pitch: DimensionSource::Ignored,
+            ..DerivationSpec::default()
+        };
+        let mut matcher = Matcher::with_derivation_spec(MatchingConfig::new(0.1, 1), &spec);
+        let target = min_pattern();
+        let mut measured = min_pattern();
+        measured.pitch = PITCH_MAX;
+        assert!(matcher.observe(&measured, &target));
+        assert!(!Matcher::new(MatchingConfig::new(0.1, 1)).observe(&measured, &target));
+        measured.volume = VOLUME_MAX;
+        assert!(!matcher.observe(&measured, &target));
+    }
+
+    #[test]
+    fn provider_targets_match_like_token_targets() {
+        use crate::srt::{pattern_from_srt, SemanticRendezvousToken};
+
+        let srt = SemanticRendezvousToken::from_bytes([3u8; 32]);
+        let target = pattern_from_srt(&srt, b"salt");
+        let mut matcher = Matcher::new(MatchingConfig::new(0.05, 2));
+        assert_eq!(matcher.observe_derived(&target, &srt, b"salt"), Ok(false));
+        assert_eq!(matcher.observe_derived(&target, &srt, b"salt"), Ok(true));
+        assert_eq!(matcher.observe_derived(&target, &srt, b"other"), Ok(false));
+    }
+
+    #[test]
+    fn builder_combines_options() {
+        let config = MatchingConfig::new(0.1, 2);
+        let target = SubmodalityPattern::zeros();
+        let mut matcher = Matcher::builder(config)
+            .tolerances(Tolerances::new([0.5; 9]).expect("valid"))
+            .duplicate_policy(DuplicatePolicy::Cap(0))
+            .build()
+            .expect("valid");
+        let mut measured = target.clone();
+        measured.brightness = 0.3;
+        assert!(!matcher.observe(&measured, &target));
+        measured.brightness = 0.2;
+        assert!(matcher.observe(&measured, &target));
+        assert!(!matcher.observe(&measured, &target));
+        assert!(matcher.tolerances().is_some());
+
+        let bad_drift = DriftConfig {
+            max_correction: -1.0,
+            ..DriftConfig::default()
+        };
+        assert!(Matcher::builder(config).drift(bad_drift).build().is_err());
+    }
+
+    #[test]
+    fn explanation_matches_observed_distance() {
+        use crate::pattern::sampling::{Gaussian, PatternDistribution};
+        use crate::srt::DimensionSource;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let config = MatchingConfig::new(0.15, 1);
+        let weights = DimensionWeights::new([2.0, 1.0, 0.5, 1.0, 1.0, 1.0, 0.0, 1.0, 3.0]).unwrap();
+        let spec = DerivationSpec {
+            pitch: DimensionSource::Ignored,
+            ..DerivationSpec::default()
+        };
+        let matchers = [
+            Matcher::new(config.with_weights(weights)),
+            Matcher::with_tolerances(config, Tolerances::new([0.1; 9]).unwrap()),
+            Matcher::with_derivation_spec(config, &spec),
+            Matcher::with_drift(config, DriftConfig::default()).unwrap(),
+        ];
+        let target = SubmodalityPattern::zeros();
+        let readings = Gaussian::normalized(target.clone(), 0.04);
+        let mut rng = StdRng::seed_from_u64(1556);
+        for mut matcher in matchers {
+            for _ in 0..200 {
+                let measured = readings.sample(&mut rng);
+                let diff = matcher.explain(&measured, &target);
+                let within = matcher.observe(&measured, &target);
+                assert_eq!(diff.distance <= matcher.threshold(), within);
+            }
+        }
+        let diff = Matcher::with_derivation_spec(config, &spec).explain(
+            &SubmodalityPattern {
+                pitch: 5000.0,
+                ..target.clone()
+            },
+            &target,
+        );
+        assert_eq!(diff.distance, 0.0);
+        assert_eq!(diff.get("pitch").expect("pitch").weight, 0.0);
+    }
+
+    #[test]
+    fn duplicate_policies_handle_frozen_sensor() {
+        let config = MatchingConfig::new(0.1, 3);
+        let target = SubmodalityPattern::zeros();
+        let observe_frozen = |matcher: &mut Matcher| -> Vec<bool> {
+            (0..6).map(|_| matcher.observe(&target, &target)).collect()
+        };
+
+        let mut plain = Matcher::new(config);
+        assert_eq!(observe_frozen(&mut plain), [false, false, true, true, true, true]);
+
+        let mut ignore = Matcher::with_duplicate_policy(config, DuplicatePolicy::Ignore);
+        assert!(!observe_frozen(&mut ignore).contains(&true));
+        assert_eq!(ignore.repeats(), 5);
+
+        // A locked matcher keeps its lock through brief repeats but loses it
+        // once the sensor has been frozen for a whole window.
+        let mut ignore = Matcher::with_duplicate_policy(config, DuplicatePolicy::Ignore);
+        let mut moved = target.clone();
+        for step in 0..3 {
+            moved.brightness = target.brightness + 0.001 * step as f32;
+            ignore.observe(&moved, &target);
+        }
+        let frozen: Vec<bool> = (0..4).map(|_| ignore.observe(&moved, &target)).collect();
+        assert_eq!(frozen, [true, true, false, false]);
+
+        let mut cap = Matcher::with_duplicate_policy(config, DuplicatePolicy::Cap(3));
+        assert_eq!(observe_frozen(&mut cap), [false, false, true, true, false, false]);
+
+        let mut flag = Matcher::with_duplicate_policy(config, DuplicatePolicy::Flag(2));
+        observe_frozen(&mut flag);
+        assert!(flag.is_frozen());
+        let mut moved = target.clone();
+        moved.brightness += 0.001;
+        assert!(flag.observe(&moved, &target));
+        assert!(!flag.is_frozen());
+    }
+
+    #[test]
+    fn ignore_policy_with_zero_window_judges_fresh_samples() {
+        let config = MatchingConfig::new(0.1, 0);
+        let target = SubmodalityPattern::zeros();
+        let mut ignore = Matcher::with_duplicate_policy(config, DuplicatePolicy::Ignore);
+        let mut moved = target.clone();
+        let fresh: Vec<bool> = (0..3)
+            .map(|step| {
+                moved.brightness = target.brightness + 0.001 * step as f32;
+                ignore.observe(&moved, &target)
+            })
+            .collect();
+        assert_eq!(fresh, [true, true, true]);
+        // Without a window to fill, any repeat counts as a miss.
+        assert!(!ignore.observe(&moved, &target));
+    }
+
+    #[test]
+    fn registry_entry_points_judge_against_epsilon() {
+        let registry = DimensionRegistry::core();
+        let target = SubmodalityPattern::zeros();
+        let mut matcher = Matcher::new(MatchingConfig::new(0.1, 1));
+        let dyn_target = DynPattern::from(&target);
+        assert_eq!(matcher.observe_dyn(&registry, &dyn_target, &dyn_target), Ok(true));
+        let ext_target = PatternExt::from(target);
+        assert_eq!(matcher.observe_ext(&registry, &ext_target, &ext_target), Ok(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "observe_ext does not support")]
+    fn registry_entry_points_reject_unsupported_options() {
+        let config = MatchingConfig::new(0.1, 1);
+        let mut matcher = Matcher::with_duplicate_policy(config, DuplicatePolicy::Ignore);
+        let target = PatternExt::from(SubmodalityPattern::zeros());
+        let _ = matcher.observe_ext(&DimensionRegistry::core(), &target, &target);
+    }
+
     #[test]
     fn epsilon_affects_match_behavior() {
         let measured = SubmodalityPattern::zeros();
@@ -165,4 +1667,327 @@ mod tests {
         assert!(!strict.observe(&measured, &target));
         assert!(loose.observe(&measured, &target));
     }
+
+    #[test]
+    fn builder_rejects_invalid_values() {
+        let config = MatchingConfig::builder()
+            .epsilon(0.2)
+            .window_size(4)
+            .build()
+            .expect("valid");
+        assert_eq!(config, MatchingConfig::new(0.2, 4));
+        assert!(MatchingConfig::builder().epsilon(f32::NAN).build().is_err());
+        assert!(MatchingConfig::builder().epsilon(-0.1).build().is_err());
+        let unwindowed = MatchingConfig::builder()
+            .window_size(0)
+            .build()
+            .expect("window 0 judges each observation alone");
+        let mut matcher = Matcher::new(unwindowed);
+        let target = SubmodalityPattern::zeros();
+        assert!(matcher.observe(&target, &target));
+    }
+
+    #[test]
+    fn sequence_matcher_steps_through_targets() {
+        let first = SubmodalityPattern::zeros();
+        let mut second = first.clone();
+        second.brightness = 1.0;
+        let config = MatchingConfig::new(0.05, 2);
+        let mut matcher =
+            SequenceMatcher::new(config, vec![first.clone(), second.clone()]).expect("matcher");
+
+        assert!(!matcher.observe(&first));
+        assert!(matcher.observe(&first));
+        assert_eq!(matcher.position(), 1);
+        assert_eq!(matcher.current_target(), Some(&second));
+        // The window restarts, and the old target no longer matches.
+        assert!(!matcher.observe(&first));
+        assert!(!matcher.observe(&second));
+        assert!(matcher.observe(&second));
+        assert!(matcher.is_complete());
+        assert!(!matcher.observe(&second));
+        assert!(SequenceMatcher::new(config, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn quorum_requires_threshold_streams_within_window() {
+        let config = MatchingConfig::new(0.05, 1);
+        let mut quorum = QuorumMatcher::new(config, 2, Duration::from_secs(5)).expect("quorum");
+        let streams: Vec<StreamId> = (0..3).map(|_| quorum.add_stream()).collect();
+        let target = SubmodalityPattern::zeros();
+        let start = Instant::now();
+
+        assert!(!quorum.observe(streams[0], &target, &target, start).unwrap());
+        assert!(!quorum
+            .observe(streams[1], &max_pattern(), &target, start)
+            .unwrap());
+        let later = start + Duration::from_secs(3);
+        assert!(quorum.observe(streams[2], &target, &target, later).unwrap());
+        assert_eq!(quorum.stable_count(later), 2);
+
+        let stale = start + Duration::from_secs(6);
+        assert!(!quorum.has_quorum(stale));
+
+        let mut other = QuorumMatcher::new(config, 1, Duration::from_secs(5)).expect("quorum");
+        other.add_stream();
+        assert!(matches!(
+            other.observe(streams[2], &target, &target, later),
+            Err(Error::Observation(ObservationError::UnknownStream(id))) if id == streams[2]
+        ));
+        assert!(QuorumMatcher::new(config, 0, Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn timed_matcher_reorders_and_rejects_late_samples() {
+        let config = MatchingConfig::new(0.05, 2);
+        let mut matcher = TimedMatcher::new(config, Duration::from_secs(10), 1).expect("matcher");
+        let target = SubmodalityPattern::zeros();
+        let far = max_pattern();
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        // The far sample at t=1 arrives after the t=2 sample but is still
+        // matched first, so the window only fills at t=3.
+        assert_eq!(
+            matcher
+                .observe_at(&target, &target, at(2), at(2))
+                .expect("t2"),
+            None
+        );
+        assert_eq!(
+            matcher.observe_at(&far, &target, at(1), at(2)).expect("t1"),
+            Some(false)
+        );
+        assert_eq!(
+            matcher
+                .observe_at(&target, &target, at(3), at(3))
+                .expect("t3"),
+            Some(false)
+        );
+        assert!(matches!(
+            matcher.observe_at(&target, &target, at(1), at(3)),
+            Err(Error::Observation(ObservationError::OutOfOrder))
+        ));
+        assert!(matches!(
+            matcher.observe_at(&target, &target, at(4), at(20)),
+            Err(Error::Observation(ObservationError::Stale { .. }))
+        ));
+        assert_eq!(matcher.flush(&target), vec![true]);
+        assert_eq!(matcher.buffered(), 0);
+    }
+
+    #[test]
+    fn partial_observations_compare_present_dimensions_only() {
+        let target = SubmodalityPattern::zeros();
+        let full = SubmodalityPattern {
+            brightness: target.brightness + 0.03,
+            volume: target.volume + 0.03,
+            ..target.clone()
+        };
+        let distance = euclidean_distance(&full.normalize(), &target.normalize());
+        let complete = MeasuredPattern::from(&full);
+        assert_eq!(
+            partial_distance(&full.normalize(), &target.normalize(), complete.present()),
+            Some(distance)
+        );
+
+        // Dropping an unchanged sensor rescales, dropping a deviating one
+        // removes its contribution entirely.
+        let no_thermometer = MeasuredPattern {
+            temperature: None,
+            ..complete.clone()
+        };
+        let no_volume = MeasuredPattern {
+            volume: None,
+            ..complete.clone()
+        };
+        let mut matcher = Matcher::new(MatchingConfig::new(distance + 1e-4, 1));
+        assert!(matcher.observe_partial(&complete, &target));
+        assert!(!matcher.observe_partial(&no_thermometer, &target));
+        assert!(matcher.observe_partial(&no_volume, &target));
+
+        assert_eq!(
+            partial_distance(&full.normalize(), &target.normalize(), [false; 9]),
+            None
+        );
+        assert!(!matcher.observe_partial(&MeasuredPattern::default(), &target));
+    }
+
+    #[test]
+    fn sanitize_policies_keep_stuck_sensors_from_matching() {
+        let target = SubmodalityPattern {
+            color_temp: COLOR_TEMP_MAX,
+            ..SubmodalityPattern::zeros()
+        };
+        let stuck = SubmodalityPattern {
+            color_temp: 99_999.0,
+            ..target.clone()
+        };
+        let config = MatchingConfig::new(0.05, 2);
+        let observe_twice = |mut matcher: Matcher| {
+            matcher.observe(&stuck, &target);
+            matcher.observe(&stuck, &target)
+        };
+        assert!(observe_twice(Matcher::new(config)));
+        assert!(observe_twice(
+            Matcher::new(config).sanitizing(SanitizePolicy::Clamp)
+        ));
+        let rejecting = Matcher::new(config).sanitizing(SanitizePolicy::RejectSample);
+        assert_eq!(
+            rejecting.sanitize_policy(),
+            Some(SanitizePolicy::RejectSample)
+        );
+        assert!(!observe_twice(rejecting));
+
+        // Marking the stuck dimension missing matches on the others, so the
+        // result no longer depends on the bogus value.
+        let mut masking = Matcher::new(config).sanitizing(SanitizePolicy::MarkMissing);
+        let off_target = SubmodalityPattern {
+            color_temp: 99_999.0,
+            brightness: 0.9,
+            ..target.clone()
+        };
+        masking.observe(&off_target, &target);
+        assert!(!masking.observe(&off_target, &target));
+        assert!(observe_twice(masking));
+    }
+
+    #[test]
+    fn partial_samples_share_the_policy_pipeline() {
+        let target = SubmodalityPattern::zeros();
+        let config = MatchingConfig::new(0.05, 2);
+        let drift = DriftConfig {
+            rate: 0.5,
+            max_correction: 0.1,
+        };
+        // A stuck thermometer is dropped, and the drift estimate still
+        // learns from the brightness offset but not from the missing field.
+        let mut matcher = Matcher::builder(config)
+            .drift(drift)
+            .sanitize(SanitizePolicy::MarkMissing)
+            .build()
+            .expect("valid");
+        let measured = SubmodalityPattern {
+            brightness: target.brightness + 0.02,
+            temperature: 99.0,
+            ..target.clone()
+        };
+        for _ in 0..3 {
+            matcher.observe(&measured, &target);
+        }
+        let offset = matcher.drift().expect("drift enabled").offset();
+        assert!(offset[0] > 0.01, "offset: {offset:?}");
+        assert_eq!(offset[6], 0.0);
+
+        // Repeated partial samples count as duplicates.
+        let mut capped = Matcher::builder(config)
+            .duplicate_policy(DuplicatePolicy::Cap(1))
+            .build()
+            .expect("valid");
+        let partial = MeasuredPattern {
+            temperature: None,
+            ..MeasuredPattern::from(&target)
+        };
+        assert!(!capped.observe_partial(&partial, &target));
+        assert!(capped.observe_partial(&partial, &target));
+        assert!(!capped.observe_partial(&partial, &target));
+        assert_eq!(capped.repeats(), 2);
+
+        // Sanitizing applies to the present fields of partial samples.
+        let mut rejecting = Matcher::new(config).sanitizing(SanitizePolicy::RejectSample);
+        let stuck = MeasuredPattern {
+            color_temp: Some(99_999.0),
+            ..partial.clone()
+        };
+        rejecting.observe_partial(&stuck, &target);
+        assert!(!rejecting.observe_partial(&stuck, &target));
+        rejecting.observe_partial(&partial, &target);
+        assert!(rejecting.observe_partial(&partial, &target));
+    }
+
+    #[test]
+    fn weights_discount_noisy_dimensions() {
+        let target = SubmodalityPattern::zeros();
+        let noisy = SubmodalityPattern {
+            arousal: 0.4,
+            ..target.clone()
+        };
+        let (a, b) = (noisy.normalize(), target.normalize());
+        assert_eq!(
+            weighted_distance(&a, &b, &DimensionWeights::default()),
+            euclidean_distance(&a, &b)
+        );
+        let mut values = [1.0; 9];
+        values[8] = 0.25;
+        let weights = DimensionWeights::new(values).expect("weights");
+        assert!((weighted_distance(&a, &b, &weights) - 0.2).abs() < 1e-6);
+
+        let config = MatchingConfig::builder()
+            .epsilon(0.3)
+            .window_size(1)
+            .weights(weights)
+            .build()
+            .expect("config");
+        assert!(!Matcher::new(MatchingConfig::new(0.3, 1)).observe(&noisy, &target));
+        assert!(Matcher::new(config).observe(&noisy, &target));
+        let partial = MeasuredPattern::from(&noisy);
+        assert!(Matcher::new(config).observe_partial(&partial, &target));
+
+        assert!(DimensionWeights::new([0.0; 9]).is_err());
+        values[0] = -1.0;
+        assert!(DimensionWeights::new(values).is_err());
+        values[0] = f32::NAN;
+        assert!(MatchingConfig::new(0.1, 3)
+            .with_weights(DimensionWeights(values))
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn duration_window_ignores_sample_rate() {
+        let target = SubmodalityPattern::zeros();
+        let off = SubmodalityPattern {
+            brightness: 1.0,
+            ..target.clone()
+        };
+        let at = |ms: u64, pattern: &SubmodalityPattern| TimedPattern::new(ms, pattern.clone());
+        let config = MatchingConfig::new(0.1, 1);
+
+        // A fast sensor and a slow one lock after the same two seconds.
+        for step in [100, 500] {
+            let mut matcher = DurationMatcher::new(config, Duration::from_secs(2)).unwrap();
+            let decisions: Vec<bool> = (0..=3000 / step)
+                .map(|i| matcher.observe(&at(i * step, &target), &target).unwrap())
+                .collect();
+            let first = decisions.iter().position(|stable| *stable).unwrap() as u64;
+            assert_eq!(first * step, 2000);
+            assert!(decisions[first as usize..].iter().all(|stable| *stable));
+        }
+
+        let mut matcher = DurationMatcher::new(config, Duration::from_secs(1)).unwrap();
+        assert!(!matcher.observe(&at(0, &target), &target).unwrap());
+        assert!(!matcher.observe(&at(600, &off), &target).unwrap());
+        assert_eq!(matcher.held(), Duration::ZERO);
+        assert!(!matcher.observe(&at(700, &target), &target).unwrap());
+        // A gap longer than the hold restarts the run.
+        assert!(!matcher.observe(&at(2000, &target), &target).unwrap());
+        assert!(matcher.observe(&at(3000, &target), &target).unwrap());
+        assert_eq!(matcher.held(), Duration::from_secs(1));
+        assert!(matches!(
+            matcher.observe(&at(2500, &target), &target),
+            Err(Error::Observation(ObservationError::OutOfOrder))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn uniform_weights_are_omitted_from_config_json() {
+        let config = MatchingConfig::new(0.1, 3);
+        let json = serde_json::to_string(&config).expect("serialize");
+        assert_eq!(json, r#"{"epsilon":0.1,"window_size":3}"#);
+        let weighted = config.with_weights(DimensionWeights::new([2.0; 9]).expect("weights"));
+        let json = serde_json::to_string(&weighted).expect("serialize");
+        let restored: MatchingConfig = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(restored, weighted);
+    }
 }
@@ -1,159 +1,896 @@
 //! Submodality pattern definitions and helpers.
 
+pub mod diff;
+pub mod dimensions;
+pub mod lsh;
+#[cfg(feature = "serde")]
+pub mod migrate;
+pub mod sampling;
+pub mod series_codec;
+pub mod stats;
+pub mod table;
+pub mod units;
+
+use std::f32::consts::TAU;
+use std::fmt;
+
+use rand::Rng;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-/// Minimum brightness (normalized).
-pub const BRIGHTNESS_MIN: f32 = 0.0;
-/// Maximum brightness (normalized).
-pub const BRIGHTNESS_MAX: f32 = 1.0;
-/// Minimum color temperature (Kelvin).
-pub const COLOR_TEMP_MIN: f32 = 2000.0;
-/// Maximum color temperature (Kelvin).
-pub const COLOR_TEMP_MAX: f32 = 10_000.0;
-/// Minimum focal distance (normalized).
-pub const FOCAL_DISTANCE_MIN: f32 = 0.0;
-/// Maximum focal distance (normalized).
-pub const FOCAL_DISTANCE_MAX: f32 = 1.0;
-/// Minimum volume (normalized).
-pub const VOLUME_MIN: f32 = 0.0;
-/// Maximum volume (normalized).
-pub const VOLUME_MAX: f32 = 1.0;
-/// Minimum tempo (BPM).
-pub const TEMPO_MIN: f32 = 0.0;
-/// Maximum tempo (BPM).
-pub const TEMPO_MAX: f32 = 300.0;
-/// Minimum pitch (Hz).
-pub const PITCH_MIN: f32 = 20.0;
-/// Maximum pitch (Hz).
-pub const PITCH_MAX: f32 = 20_000.0;
-/// Minimum temperature (Celsius).
-pub const TEMPERATURE_MIN: f32 = 10.0;
-/// Maximum temperature (Celsius).
-pub const TEMPERATURE_MAX: f32 = 40.0;
-/// Minimum movement (normalized).
-pub const MOVEMENT_MIN: f32 = 0.0;
-/// Maximum movement (normalized).
-pub const MOVEMENT_MAX: f32 = 1.0;
-/// Minimum arousal (normalized).
-pub const AROUSAL_MIN: f32 = 0.0;
-/// Maximum arousal (normalized).
-pub const AROUSAL_MAX: f32 = 1.0;
-
-/// A submodality pattern as described in the paper.
+use crate::calibration::CalibrationProfile;
+use crate::error::{Error, Result};
+
+pub use dimensions::{dimension, DimensionInfo, DIMENSIONS};
+pub use crate::embedded::{
+    quantize_u16_to_range, range_to_u16, NormalizedPattern, SubmodalityPattern, AROUSAL_MAX,
+    AROUSAL_MIN, BRIGHTNESS_MAX, BRIGHTNESS_MIN, COLOR_TEMP_MAX, COLOR_TEMP_MIN,
+    FOCAL_DISTANCE_MAX, FOCAL_DISTANCE_MIN, MOVEMENT_MAX, MOVEMENT_MIN, PITCH_MAX, PITCH_MIN,
+    TEMPERATURE_MAX, TEMPERATURE_MIN, TEMPO_MAX, TEMPO_MIN, VOLUME_MAX, VOLUME_MIN,
+};
+
+/// Length of [`SubmodalityPattern::to_compact_bytes`] output.
+pub const COMPACT_LEN: usize = 18;
+
+pub(crate) use crate::embedded::{
+    clamp01, normalized_from_array, normalized_to_array, pattern_from_array, pattern_to_array,
+};
+
+impl SubmodalityPattern {
+    /// Check every field against the reference ranges.
+    ///
+    /// [`normalize`](Self::normalize) clamps out-of-range values and passes
+    /// NaN through, so a faulty sensor can look like a plausible reading.
+    /// This reports every non-finite or out-of-range field instead.
+    pub fn validate(&self) -> std::result::Result<(), Vec<PatternFieldError>> {
+        self.check_ranges(DIMENSIONS.map(|info| (info.min, info.max)))
+    }
+
+    /// Apply `policy` to non-finite and out-of-range fields.
+    ///
+    /// Returns the sanitized measurement, with any dimensions the policy
+    /// dropped left missing, or every offending field when the policy
+    /// rejects the sample. Match the result with
+    /// [`Matcher::observe_partial`](crate::matching::Matcher::observe_partial),
+    /// or set the policy on the matcher with
+    /// [`Matcher::sanitizing`](crate::matching::Matcher::sanitizing).
+    pub fn sanitize(
+        &self,
+        policy: SanitizePolicy,
+    ) -> std::result::Result<MeasuredPattern, Vec<PatternFieldError>> {
+        let errors = match self.validate() {
+            Ok(()) => return Ok(MeasuredPattern::from(self)),
+            Err(errors) => errors,
+        };
+        let values = pattern_to_array(self);
+        let sanitized: [Option<f32>; 9] = match policy {
+            SanitizePolicy::RejectSample => return Err(errors),
+            SanitizePolicy::Clamp => std::array::from_fn(|i| {
+                let DimensionInfo { min, max, .. } = DIMENSIONS[i];
+                (!values[i].is_nan()).then(|| values[i].clamp(min, max))
+            }),
+            SanitizePolicy::MarkMissing => {
+                std::array::from_fn(|i| DIMENSIONS[i].contains(values[i]).then_some(values[i]))
+            }
+        };
+        Ok(measured_from_array(sanitized))
+    }
+
+    /// [`validate`](Self::validate) against arbitrary ranges in field order.
+    pub(crate) fn check_ranges(
+        &self,
+        ranges: [(f32, f32); 9],
+    ) -> std::result::Result<(), Vec<PatternFieldError>> {
+        let errors: Vec<PatternFieldError> = pattern_to_array(self)
+            .into_iter()
+            .zip(DIMENSIONS.map(|info| info.key).into_iter().zip(ranges))
+            .filter_map(|(value, (field, (min, max)))| {
+                if !value.is_finite() {
+                    Some(PatternFieldError::NonFinite { field, value })
+                } else if !(min..=max).contains(&value) {
+                    Some(PatternFieldError::OutOfRange {
+                        field,
+                        value,
+                        min,
+                        max,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Encode as nine big-endian `u16`s, for BLE advertisements, LoRa
+    /// payloads, and other size-constrained links.
+    ///
+    /// Each value is clamped to its reference range and mapped onto
+    /// `0..=u16::MAX` with [`range_to_u16`], so the round-trip error is at
+    /// most half a step, `(max - min) / 131070`, per dimension. NaN encodes
+    /// as the range minimum. The layout matches the digest bytes
+    /// [`pattern_from_srt`](crate::srt::pattern_from_srt) reads, so derived
+    /// targets round-trip exactly.
+    pub fn to_compact_bytes(&self) -> [u8; COMPACT_LEN] {
+        let mut bytes = [0u8; COMPACT_LEN];
+        for ((chunk, value), info) in bytes
+            .chunks_exact_mut(2)
+            .zip(pattern_to_array(self))
+            .zip(DIMENSIONS)
+        {
+            chunk.copy_from_slice(&range_to_u16(value, info.min, info.max).to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Decode [`to_compact_bytes`](Self::to_compact_bytes) output.
+    pub fn from_compact_bytes(bytes: &[u8; COMPACT_LEN]) -> SubmodalityPattern {
+        pattern_from_array(std::array::from_fn(|i| {
+            let DimensionInfo { min, max, .. } = DIMENSIONS[i];
+            let value = u16::from_be_bytes([bytes[2 * i], bytes[2 * i + 1]]);
+            quantize_u16_to_range(value, min, max)
+        }))
+    }
+
+    /// Grid cell of this pattern with `bits_per_dim` bits per dimension,
+    /// after [`normalize`](Self::normalize).
+    ///
+    /// `bits_per_dim` must be in `1..=16`. See
+    /// [`PatternCell::max_intra_cell_distance`] for choosing epsilon.
+    pub fn quantize_to_cells(&self, bits_per_dim: u8) -> Result<PatternCell> {
+        check_cell_bits(bits_per_dim)?;
+        let cell = cell_of(&normalized_to_array(&self.normalize()), 1 << bits_per_dim);
+        Ok(PatternCell {
+            bits: bits_per_dim,
+            coords: cell.map(|index| index as u16),
+        })
+    }
+
+    /// Start building a pattern from [`zeros`](Self::zeros).
+    pub fn builder() -> SubmodalityPatternBuilder {
+        SubmodalityPatternBuilder::default()
+    }
+
+    /// Linear interpolation from `self` (at `t = 0`) to `other` (at `t = 1`).
+    ///
+    /// `t` is not clamped, so values outside `[0, 1]` extrapolate.
+    pub fn lerp(&self, other: &SubmodalityPattern, t: f32) -> SubmodalityPattern {
+        let a = pattern_to_array(self);
+        let b = pattern_to_array(other);
+        pattern_from_array(std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t))
+    }
+
+    /// Weighted mean of `(pattern, weight)` pairs.
+    ///
+    /// Weights are relative and need not sum to one, but must be finite and
+    /// non-negative with a positive total.
+    pub fn blend(weighted: &[(&SubmodalityPattern, f32)]) -> Result<SubmodalityPattern> {
+        if let Some((_, weight)) = weighted
+            .iter()
+            .find(|(_, weight)| !(weight.is_finite() && *weight >= 0.0))
+        {
+            return Err(Error::InvalidConfig(format!(
+                "blend weights must be finite and non-negative, got {weight}"
+            )));
+        }
+        let total: f32 = weighted.iter().map(|(_, weight)| weight).sum();
+        if total <= 0.0 {
+            return Err(Error::InvalidConfig(
+                "blend needs at least one positive weight".to_string(),
+            ));
+        }
+        let mut sum = [0.0f32; 9];
+        for (pattern, weight) in weighted {
+            for (acc, value) in sum.iter_mut().zip(pattern_to_array(pattern)) {
+                *acc += value * weight;
+            }
+        }
+        Ok(pattern_from_array(sum.map(|value| value / total)))
+    }
+
+    /// Add independent Gaussian noise to every dimension.
+    ///
+    /// `sigma` holds the standard deviation of each dimension in raw units
+    /// (so `sigma.color_temp` is in Kelvin). Results are clamped to the
+    /// reference ranges, as a real sensor's readings would be.
+    pub fn jitter<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        sigma: &SubmodalityPattern,
+    ) -> SubmodalityPattern {
+        let values = pattern_to_array(self);
+        let sigma = pattern_to_array(sigma);
+        pattern_from_array(std::array::from_fn(|i| {
+            let DimensionInfo { min, max, .. } = DIMENSIONS[i];
+            (values[i] + sigma[i] * standard_normal(rng)).clamp(min, max)
+        }))
+    }
+}
+
+/// Builder for [`SubmodalityPattern`] that range-checks on
+/// [`build`](Self::build).
 ///
-/// This mirrors the SubmodalityPattern pseudo-code and keeps raw values in
-/// their natural units. Normalization to `[0, 1]` is handled separately.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct SubmodalityPattern {
-    /// Brightness, normalized to `[0.0, 1.0]`.
-    pub brightness: f32,
-    /// Color temperature in Kelvin (2000–10000).
-    pub color_temp: f32,
-    /// Focal distance, normalized to `[0.0, 1.0]`.
-    pub focal_distance: f32,
-    /// Volume, normalized to `[0.0, 1.0]`.
-    pub volume: f32,
-    /// Tempo in BPM (0–300).
-    pub tempo: f32,
-    /// Pitch in Hertz (20–20000).
-    pub pitch: f32,
-    /// Temperature in Celsius.
-    pub temperature: f32,
-    /// Movement, normalized to `[0.0, 1.0]`.
-    pub movement: f32,
-    /// Arousal, normalized to `[0.0, 1.0]`.
-    pub arousal: f32,
+/// Unset fields keep the value of the base pattern, [`SubmodalityPattern::zeros`]
+/// unless the builder was started with [`from_pattern`](Self::from_pattern).
+/// Values are checked against the reference ranges, or against a
+/// [`CalibrationProfile`]'s ranges when one is set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmodalityPatternBuilder {
+    pattern: SubmodalityPattern,
+    profile: Option<CalibrationProfile>,
 }
 
-impl SubmodalityPattern {
-    /// Create a neutral baseline pattern for initialization and testing.
+impl Default for SubmodalityPatternBuilder {
+    fn default() -> Self {
+        Self::from_pattern(SubmodalityPattern::zeros())
+    }
+}
+
+impl SubmodalityPatternBuilder {
+    /// Start from `base` instead of `zeros()`, e.g. a subject's baseline.
+    pub fn from_pattern(base: SubmodalityPattern) -> Self {
+        Self {
+            pattern: base,
+            profile: None,
+        }
+    }
+
+    /// Check values against `profile`'s ranges instead of the reference
+    /// ranges.
+    pub fn profile(mut self, profile: CalibrationProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Set brightness.
+    pub fn brightness(mut self, value: f32) -> Self {
+        self.pattern.brightness = value;
+        self
+    }
+
+    /// Set color temperature in Kelvin.
+    pub fn color_temp(mut self, value: f32) -> Self {
+        self.pattern.color_temp = value;
+        self
+    }
+
+    /// Set focal distance.
+    pub fn focal_distance(mut self, value: f32) -> Self {
+        self.pattern.focal_distance = value;
+        self
+    }
+
+    /// Set volume.
+    pub fn volume(mut self, value: f32) -> Self {
+        self.pattern.volume = value;
+        self
+    }
+
+    /// Set tempo in BPM.
+    pub fn tempo(mut self, value: f32) -> Self {
+        self.pattern.tempo = value;
+        self
+    }
+
+    /// Set pitch in Hz.
+    pub fn pitch(mut self, value: f32) -> Self {
+        self.pattern.pitch = value;
+        self
+    }
+
+    /// Set temperature in °C.
+    pub fn temperature(mut self, value: f32) -> Self {
+        self.pattern.temperature = value;
+        self
+    }
+
+    /// Set movement.
+    pub fn movement(mut self, value: f32) -> Self {
+        self.pattern.movement = value;
+        self
+    }
+
+    /// Set arousal.
+    pub fn arousal(mut self, value: f32) -> Self {
+        self.pattern.arousal = value;
+        self
+    }
+
+    /// Set the visual dimensions: brightness, color temperature, and focal
+    /// distance.
+    pub fn with_visual(self, brightness: f32, color_temp: f32, focal_distance: f32) -> Self {
+        self.brightness(brightness)
+            .color_temp(color_temp)
+            .focal_distance(focal_distance)
+    }
+
+    /// Set the auditory dimensions: volume, tempo, and pitch.
+    pub fn with_auditory(self, volume: f32, tempo: f32, pitch: f32) -> Self {
+        self.volume(volume).tempo(tempo).pitch(pitch)
+    }
+
+    /// Set the kinesthetic dimensions: temperature, movement, and arousal.
+    pub fn with_kinesthetic(self, temperature: f32, movement: f32, arousal: f32) -> Self {
+        self.temperature(temperature)
+            .movement(movement)
+            .arousal(arousal)
+    }
+
+    /// Range-check and build the pattern.
     ///
-    /// "Neutral" means unit-range fields are centered or zeroed, and absolute
-    /// scale fields are set to commonly used midpoints. This is a placeholder
-    /// baseline and should be replaced with domain-specific defaults later.
-    pub fn zeros() -> Self {
+    /// Fails with [`Error::InvalidPattern`] listing every non-finite or
+    /// out-of-range field.
+    pub fn build(self) -> Result<SubmodalityPattern> {
+        let checked = match &self.profile {
+            Some(profile) => self.pattern.validate_with(profile),
+            None => self.pattern.validate(),
+        };
+        checked?;
+        Ok(self.pattern)
+    }
+}
+
+/// A field rejected by [`SubmodalityPattern::validate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PatternFieldError {
+    /// The value is NaN or infinite.
+    NonFinite {
+        /// Field name.
+        field: &'static str,
+        /// Offending value.
+        value: f32,
+    },
+    /// The value lies outside the field's reference range.
+    OutOfRange {
+        /// Field name.
+        field: &'static str,
+        /// Offending value.
+        value: f32,
+        /// Smallest accepted value.
+        min: f32,
+        /// Largest accepted value.
+        max: f32,
+    },
+}
+
+impl PatternFieldError {
+    /// Name of the offending field.
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::NonFinite { field, .. } | Self::OutOfRange { field, .. } => field,
+        }
+    }
+}
+
+impl fmt::Display for PatternFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonFinite { field, value } => write!(f, "{field} is {value}"),
+            Self::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => write!(f, "{field} = {value} is outside {min}..={max}"),
+        }
+    }
+}
+
+impl std::error::Error for PatternFieldError {}
+
+/// How [`SubmodalityPattern::sanitize`] treats non-finite and out-of-range
+/// fields.
+///
+/// [`normalize`](SubmodalityPattern::normalize) silently clamps, so a stuck
+/// sensor reading 9999 K looks like a plausible 10000 K. The other policies
+/// keep such readings from distorting distances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SanitizePolicy {
+    /// Clamp to the nearest bound of the reference range, as `normalize`
+    /// does; NaN fields cannot be clamped and are marked missing.
+    #[default]
+    Clamp,
+    /// Reject the whole sample if any field is bad.
+    RejectSample,
+    /// Mark only the bad fields missing and keep the rest.
+    MarkMissing,
+}
+
+impl From<Vec<PatternFieldError>> for Error {
+    fn from(errors: Vec<PatternFieldError>) -> Self {
+        Error::InvalidPattern(join_field_errors(&errors))
+    }
+}
+
+fn join_field_errors(errors: &[PatternFieldError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Serde adapter that rejects patterns failing
+/// [`SubmodalityPattern::validate`].
+///
+/// Use with `#[serde(with = "phenomenological_rendezvous::pattern::deny_invalid")]`
+/// on a `SubmodalityPattern` field, or call [`deny_invalid::deserialize`]
+/// directly on a deserializer. The error message lists every bad field.
+#[cfg(feature = "serde")]
+pub mod deny_invalid {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{join_field_errors, SubmodalityPattern};
+
+    /// Serialize the pattern unchanged.
+    pub fn serialize<S: Serializer>(
+        pattern: &SubmodalityPattern,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        pattern.serialize(serializer)
+    }
+
+    /// Deserialize a pattern and validate it.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<SubmodalityPattern, D::Error> {
+        let pattern = SubmodalityPattern::deserialize(deserializer)?;
+        pattern.validate().map_err(|errors| {
+            D::Error::custom(format!("invalid pattern: {}", join_field_errors(&errors)))
+        })?;
+        Ok(pattern)
+    }
+}
+
+/// Sample a standard normal variate using the Box-Muller transform.
+pub(crate) fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f32 {
+    let u1: f32 = 1.0 - rng.r#gen::<f32>();
+    let u2: f32 = rng.r#gen();
+    (-2.0 * u1.ln()).sqrt() * (TAU * u2).cos()
+}
+
+impl TryFrom<[f32; 9]> for SubmodalityPattern {
+    type Error = Error;
+
+    /// Build a pattern from raw values in field declaration order; every
+    /// value must be finite.
+    fn try_from(values: [f32; 9]) -> Result<Self> {
+        if let Some(value) = values.iter().find(|value| !value.is_finite()) {
+            return Err(Error::InvalidConfig(format!(
+                "pattern values must be finite, got {value}"
+            )));
+        }
+        Ok(pattern_from_array(values))
+    }
+}
+
+impl TryFrom<[f32; 9]> for NormalizedPattern {
+    type Error = Error;
+
+    /// Build a pattern from normalized values in field declaration order;
+    /// every value must lie in `[0, 1]`.
+    fn try_from(values: [f32; 9]) -> Result<Self> {
+        if let Some(value) = values.iter().find(|value| !(0.0..=1.0).contains(*value)) {
+            return Err(Error::InvalidConfig(format!(
+                "normalized values must lie in [0, 1], got {value}"
+            )));
+        }
+        Ok(normalized_from_array(values))
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<&NormalizedPattern> for nalgebra::SVector<f32, 9> {
+    fn from(pattern: &NormalizedPattern) -> Self {
+        Self::from(normalized_to_array(pattern))
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl TryFrom<&nalgebra::SVector<f32, 9>> for NormalizedPattern {
+    type Error = Error;
+
+    fn try_from(vector: &nalgebra::SVector<f32, 9>) -> Result<Self> {
+        Self::try_from(<[f32; 9]>::from(*vector))
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl From<&NormalizedPattern> for ndarray::Array1<f32> {
+    fn from(pattern: &NormalizedPattern) -> Self {
+        Self::from(normalized_to_array(pattern).to_vec())
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl TryFrom<ndarray::ArrayView1<'_, f32>> for NormalizedPattern {
+    type Error = Error;
+
+    /// Convert a length-9 view, such as a row of a sample matrix.
+    fn try_from(view: ndarray::ArrayView1<'_, f32>) -> Result<Self> {
+        let values: [f32; 9] = view
+            .iter()
+            .copied()
+            .collect::<Vec<f32>>()
+            .try_into()
+            .map_err(|values: Vec<f32>| {
+                Error::InvalidConfig(format!(
+                    "expected 9 normalized values, got {}",
+                    values.len()
+                ))
+            })?;
+        Self::try_from(values)
+    }
+}
+
+/// A measurement in which any dimension may be missing.
+///
+/// Devices without a sensor for some dimension (no thermometer, no
+/// accelerometer) leave it `None`; in JSON the field may be omitted or
+/// `null`. Match these with
+/// [`Matcher::observe_partial`](crate::matching::Matcher::observe_partial).
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MeasuredPattern {
+    /// Brightness, if measured.
+    pub brightness: Option<f32>,
+    /// Color temperature, if measured; `color_temperature_k` in JSON.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "color_temperature_k", alias = "color_temp")
+    )]
+    pub color_temp: Option<f32>,
+    /// Focal distance, if measured.
+    pub focal_distance: Option<f32>,
+    /// Volume, if measured.
+    pub volume: Option<f32>,
+    /// Tempo, if measured.
+    pub tempo: Option<f32>,
+    /// Pitch, if measured.
+    pub pitch: Option<f32>,
+    /// Temperature, if measured.
+    pub temperature: Option<f32>,
+    /// Movement, if measured.
+    pub movement: Option<f32>,
+    /// Arousal, if measured.
+    pub arousal: Option<f32>,
+}
+
+impl MeasuredPattern {
+    /// Which dimensions are present, in field declaration order.
+    pub fn present(&self) -> [bool; 9] {
+        measured_to_array(self).map(|value| value.is_some())
+    }
+
+    /// Number of present dimensions.
+    pub fn present_count(&self) -> usize {
+        self.present().iter().filter(|present| **present).count()
+    }
+
+    /// Full pattern with missing dimensions taken from `fallback`.
+    pub fn fill_from(&self, fallback: &SubmodalityPattern) -> SubmodalityPattern {
+        let values = measured_to_array(self);
+        let fallback = pattern_to_array(fallback);
+        pattern_from_array(std::array::from_fn(|i| values[i].unwrap_or(fallback[i])))
+    }
+
+    /// Apply `policy` to the present fields as
+    /// [`SubmodalityPattern::sanitize`] does; missing fields stay missing.
+    pub fn sanitize(
+        &self,
+        policy: SanitizePolicy,
+    ) -> std::result::Result<MeasuredPattern, Vec<PatternFieldError>> {
+        let present = self.present();
+        // The baseline is in range, so only present fields can be flagged.
+        let sanitized = self.fill_from(&SubmodalityPattern::zeros()).sanitize(policy)?;
+        let sanitized = measured_to_array(&sanitized);
+        Ok(measured_from_array(std::array::from_fn(|i| {
+            sanitized[i].filter(|_| present[i])
+        })))
+    }
+}
+
+impl From<&SubmodalityPattern> for MeasuredPattern {
+    fn from(pattern: &SubmodalityPattern) -> Self {
         Self {
-            brightness: 0.5,
-            color_temp: 6500.0,
-            focal_distance: 0.5,
-            volume: 0.5,
-            tempo: 0.0,
-            pitch: 440.0,
-            temperature: 20.0,
-            movement: 0.0,
-            arousal: 0.0,
+            brightness: Some(pattern.brightness),
+            color_temp: Some(pattern.color_temp),
+            focal_distance: Some(pattern.focal_distance),
+            volume: Some(pattern.volume),
+            tempo: Some(pattern.tempo),
+            pitch: Some(pattern.pitch),
+            temperature: Some(pattern.temperature),
+            movement: Some(pattern.movement),
+            arousal: Some(pattern.arousal),
         }
     }
+}
+
+/// Inverse of [`measured_to_array`].
+pub(crate) fn measured_from_array(values: [Option<f32>; 9]) -> MeasuredPattern {
+    MeasuredPattern {
+        brightness: values[0],
+        color_temp: values[1],
+        focal_distance: values[2],
+        volume: values[3],
+        tempo: values[4],
+        pitch: values[5],
+        temperature: values[6],
+        movement: values[7],
+        arousal: values[8],
+    }
+}
+
+/// Optional field values in declaration order.
+fn measured_to_array(pattern: &MeasuredPattern) -> [Option<f32>; 9] {
+    [
+        pattern.brightness,
+        pattern.color_temp,
+        pattern.focal_distance,
+        pattern.volume,
+        pattern.tempo,
+        pattern.pitch,
+        pattern.temperature,
+        pattern.movement,
+        pattern.arousal,
+    ]
+}
+
+/// A measurement together with the time it was taken.
+///
+/// `timestamp` is in milliseconds; the origin is up to the recorder (the Unix
+/// epoch for live sensors, zero for synthetic streams) but must be shared by
+/// every pattern in a stream. In JSON the pattern fields sit beside
+/// `timestamp`, so a timed line also parses as a plain [`SubmodalityPattern`].
+/// Match these with [`DurationMatcher`](crate::matching::DurationMatcher).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TimedPattern {
+    /// Time of the measurement in milliseconds.
+    pub timestamp: u64,
+    /// The measured values.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub pattern: SubmodalityPattern,
+}
+
+impl TimedPattern {
+    /// Pair a pattern with its timestamp in milliseconds.
+    pub fn new(timestamp: u64, pattern: SubmodalityPattern) -> Self {
+        Self { timestamp, pattern }
+    }
+}
+
+/// A pattern snapped to an integer lattice, usable as a hash key.
+///
+/// Float patterns cannot implement `Eq` or `Hash`, so indexes and
+/// deduplication key on this type instead. Conversion rules:
+///
+/// 1. Raw values are normalized with [`SubmodalityPattern::normalize`], which
+///    clamps out-of-range values to the nearest bound.
+/// 2. Each normalized value `v` becomes `round(v * (levels - 1))`, so `0.0`
+///    and `1.0` map to the first and last lattice points. NaN maps to `0`.
+/// 3. Converting back yields lattice point `i` at `i / (levels - 1)`.
+///
+/// Two patterns with different `levels` are never equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawQuantizedPattern"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct QuantizedPattern {
+    /// Number of lattice points per dimension.
+    levels: u16,
+    /// Lattice indices in declaration order, each below `levels`.
+    values: [u16; 9],
+}
+
+impl QuantizedPattern {
+    /// Quantize a raw pattern onto `levels` points per dimension.
+    pub fn from_pattern(pattern: &SubmodalityPattern, levels: u16) -> Result<Self> {
+        Self::from_normalized(&pattern.normalize(), levels)
+    }
+
+    /// Quantize a normalized pattern onto `levels` points per dimension.
+    ///
+    /// Values outside `[0, 1]` are clamped first. `levels` must be at least 2.
+    pub fn from_normalized(pattern: &NormalizedPattern, levels: u16) -> Result<Self> {
+        if levels < 2 {
+            return Err(Error::InvalidConfig(format!(
+                "quantization needs at least 2 levels, got {levels}"
+            )));
+        }
+        let steps = f32::from(levels - 1);
+        let values = normalized_to_array(pattern).map(|v| (clamp01(v) * steps).round() as u16);
+        Ok(Self { levels, values })
+    }
+
+    /// Build a pattern from lattice indices.
+    ///
+    /// `levels` must be at least 2 and every index must be below `levels`.
+    pub fn from_values(levels: u16, values: [u16; 9]) -> Result<Self> {
+        if levels < 2 {
+            return Err(Error::InvalidConfig(format!(
+                "quantization needs at least 2 levels, got {levels}"
+            )));
+        }
+        if let Some(value) = values.iter().find(|v| **v >= levels) {
+            return Err(Error::InvalidConfig(format!(
+                "lattice index {value} out of range for {levels} levels"
+            )));
+        }
+        Ok(Self { levels, values })
+    }
+
+    /// Number of lattice points per dimension.
+    pub fn levels(&self) -> u16 {
+        self.levels
+    }
+
+    /// Lattice indices in field declaration order.
+    pub fn values(&self) -> [u16; 9] {
+        self.values
+    }
+
+    /// Lattice point in normalized space.
+    pub fn to_normalized(&self) -> NormalizedPattern {
+        let steps = f32::from(self.levels - 1);
+        normalized_from_array(self.values.map(|i| f32::from(i) / steps))
+    }
+
+    /// Lattice point in raw units.
+    pub fn to_pattern(&self) -> SubmodalityPattern {
+        let mut raw = normalized_to_array(&self.to_normalized());
+        for (value, DimensionInfo { min, max, .. }) in raw.iter_mut().zip(DIMENSIONS) {
+            *value = min + (max - min) * *value;
+        }
+        pattern_from_array(raw)
+    }
+}
+
+/// Unchecked wire form of [`QuantizedPattern`], validated on conversion.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawQuantizedPattern {
+    levels: u16,
+    values: [u16; 9],
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawQuantizedPattern> for QuantizedPattern {
+    type Error = Error;
+
+    fn try_from(raw: RawQuantizedPattern) -> Result<Self> {
+        Self::from_values(raw.levels, raw.values)
+    }
+}
+
+/// Coarse grid cell containing a pattern, for public announcement.
+///
+/// The normalized space is split into `2^bits` equal intervals per
+/// dimension; a pattern's cell is the interval index of each normalized
+/// value (values are clamped, NaN falls in cell `0`). Announcing the cell
+/// reveals only which bucket a pattern lies in. Unlike
+/// [`QuantizedPattern`], which rounds to the nearest lattice point, cells
+/// are half-open intervals with `1.0` folded into the last one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawPatternCell"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PatternCell {
+    /// Bits per dimension, `1..=16`.
+    bits: u8,
+    /// Interval indices in declaration order, each below `2^bits`.
+    coords: [u16; 9],
+}
 
-    /// Normalize this pattern into `[0, 1]` ranges for distance calculations.
+impl PatternCell {
+    /// Largest Euclidean distance between two normalized patterns in the
+    /// same cell at `bits_per_dim`: the cell diagonal, `3 / 2^bits`.
     ///
-    /// The normalization uses fixed min/max ranges for each dimension. These
-    /// ranges are reference defaults and may need tuning or calibration in
-    /// real deployments based on sensors and user populations.
+    /// With an epsilon below this, patterns sharing a cell may still fail to
+    /// match; with a larger one, matches regularly cross cell boundaries.
+    pub fn max_intra_cell_distance(bits_per_dim: u8) -> f32 {
+        3.0 / (1u32 << bits_per_dim.min(16)) as f32
+    }
+
+    /// Build a cell from interval indices.
     ///
-    /// Temperature normalization assumes a `10..=40` Celsius operating window
-    /// as a placeholder until domain-specific bounds are defined.
-    pub fn normalize(&self) -> NormalizedPattern {
-        NormalizedPattern {
-            brightness: clamp01(self.brightness),
-            color_temp: clamp01((self.color_temp - COLOR_TEMP_MIN) / (COLOR_TEMP_MAX - COLOR_TEMP_MIN)),
-            focal_distance: clamp01(self.focal_distance),
-            volume: clamp01(self.volume),
-            tempo: clamp01(self.tempo / TEMPO_MAX),
-            pitch: clamp01((self.pitch - PITCH_MIN) / (PITCH_MAX - PITCH_MIN)),
-            temperature: clamp01((self.temperature - TEMPERATURE_MIN) / (TEMPERATURE_MAX - TEMPERATURE_MIN)),
-            movement: clamp01(self.movement),
-            arousal: clamp01(self.arousal),
-        }
-    }
-}
-
-/// A fully normalized submodality pattern with values in `[0, 1]`.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct NormalizedPattern {
-    /// Normalized brightness.
-    pub brightness: f32,
-    /// Normalized color temperature.
-    pub color_temp: f32,
-    /// Normalized focal distance.
-    pub focal_distance: f32,
-    /// Normalized volume.
-    pub volume: f32,
-    /// Normalized tempo.
-    pub tempo: f32,
-    /// Normalized pitch.
-    pub pitch: f32,
-    /// Normalized temperature.
-    pub temperature: f32,
-    /// Normalized movement.
-    pub movement: f32,
-    /// Normalized arousal.
-    pub arousal: f32,
-}
-
-fn clamp01(value: f32) -> f32 {
-    if value < 0.0 {
-        0.0
-    } else if value > 1.0 {
-        1.0
-    } else {
-        value
-    }
-}
-
-/// Map a 16-bit integer into a floating-point range `[min, max]`.
+    /// `bits` must be in `1..=16` and every index must be below `2^bits`.
+    pub fn from_coords(bits: u8, coords: [u16; 9]) -> Result<Self> {
+        check_cell_bits(bits)?;
+        if let Some(index) = coords.iter().find(|i| u32::from(**i) >= 1 << bits) {
+            return Err(Error::InvalidConfig(format!(
+                "cell index {index} out of range for {bits} bits per dimension"
+            )));
+        }
+        Ok(Self { bits, coords })
+    }
+
+    /// Bits per dimension.
+    pub fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    /// Interval indices in field declaration order.
+    pub fn coords(&self) -> [u16; 9] {
+        self.coords
+    }
+
+    /// Center of the cell in normalized space.
+    pub fn center(&self) -> NormalizedPattern {
+        normalized_from_array(cell_center(&self.coords.map(u32::from), self.cells()))
+    }
+
+    /// Whether a normalized pattern falls in this cell.
+    pub fn contains(&self, pattern: &NormalizedPattern) -> bool {
+        cell_of(&normalized_to_array(pattern), self.cells()) == self.coords.map(u32::from)
+    }
+
+    fn cells(&self) -> u32 {
+        1 << self.bits
+    }
+}
+
+/// Unchecked wire form of [`PatternCell`], validated on conversion.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawPatternCell {
+    bits: u8,
+    coords: [u16; 9],
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawPatternCell> for PatternCell {
+    type Error = Error;
+
+    fn try_from(raw: RawPatternCell) -> Result<Self> {
+        Self::from_coords(raw.bits, raw.coords)
+    }
+}
+
+fn check_cell_bits(bits: u8) -> Result<()> {
+    if !(1..=16).contains(&bits) {
+        return Err(Error::InvalidConfig(format!(
+            "cells need 1 to 16 bits per dimension, got {bits}"
+        )));
+    }
+    Ok(())
+}
+
+/// Interval index of each normalized value on a grid of `bins` equal
+/// intervals per dimension, with `1.0` folded into the last interval.
 ///
-/// `val` is interpreted as an unsigned 16-bit sample, where `0` maps to `min`
-/// and `u16::MAX` maps to `max`.
-pub fn quantize_u16_to_range(val: u16, min: f32, max: f32) -> f32 {
-    let fraction = f32::from(val) / f32::from(u16::MAX);
-    min + (max - min) * fraction
+/// Values are clamped and NaN falls in interval `0`. Shared by
+/// [`PatternCell`] and the anonymization grid in [`crate::privacy`].
+pub(crate) fn cell_of(values: &[f32; 9], bins: u32) -> [u32; 9] {
+    values.map(|v| ((v * bins as f32) as u32).min(bins - 1))
+}
+
+/// Center in normalized space of a [`cell_of`] cell.
+pub(crate) fn cell_center(cell: &[u32; 9], bins: u32) -> [f32; 9] {
+    cell.map(|index| (index as f32 + 0.5) / bins as f32)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
     use super::*;
 
+    #[cfg(feature = "serde")]
     #[test]
     fn pattern_json_round_trip() {
         let pattern = SubmodalityPattern::zeros();
@@ -161,4 +898,361 @@ mod tests {
         let decoded: SubmodalityPattern = serde_json::from_str(&json).expect("deserialize");
         assert_eq!(pattern, decoded);
     }
+
+    #[test]
+    fn lerp_blend_and_jitter() {
+        let a = SubmodalityPattern::zeros();
+        let b = SubmodalityPattern {
+            brightness: 1.0,
+            color_temp: 8500.0,
+            ..a.clone()
+        };
+        let mid = a.lerp(&b, 0.5);
+        assert_eq!(mid.brightness, 0.75);
+        assert_eq!(mid.color_temp, 7500.0);
+        assert_eq!(a.lerp(&b, 0.0), a);
+        assert_eq!(a.lerp(&b, 1.0), b);
+
+        let blended = SubmodalityPattern::blend(&[(&a, 1.0), (&b, 3.0)]).unwrap();
+        assert_eq!(blended.brightness, 0.875);
+        assert_eq!(blended.pitch, a.pitch);
+        assert!(SubmodalityPattern::blend(&[]).is_err());
+        assert!(SubmodalityPattern::blend(&[(&a, -1.0), (&b, 2.0)]).is_err());
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let sigma = SubmodalityPattern {
+            color_temp: 50.0,
+            ..pattern_from_array([0.0; 9])
+        };
+        let samples: Vec<SubmodalityPattern> =
+            (0..2000).map(|_| a.jitter(&mut rng, &sigma)).collect();
+        assert!(samples.iter().all(|s| s.brightness == a.brightness));
+        let mean = samples.iter().map(|s| s.color_temp).sum::<f32>() / samples.len() as f32;
+        assert!((mean - a.color_temp).abs() < 5.0);
+        let wide = SubmodalityPattern {
+            brightness: 10.0,
+            ..sigma
+        };
+        assert!((0..100).all(|_| (0.0..=1.0).contains(&a.jitter(&mut rng, &wide).brightness)));
+    }
+
+    #[test]
+    fn denormalize_inverts_normalize() {
+        let pattern = SubmodalityPattern {
+            brightness: 0.3,
+            color_temp: 4000.0,
+            tempo: 96.0,
+            pitch: 880.0,
+            temperature: 31.0,
+            ..SubmodalityPattern::zeros()
+        };
+        let restored = pattern.normalize().denormalize();
+        for (a, b) in pattern_to_array(&pattern)
+            .iter()
+            .zip(pattern_to_array(&restored))
+        {
+            assert!((a - b).abs() <= a.abs() * 1e-5, "{a} vs {b}");
+        }
+        let clamped = normalized_from_array([1.5; 9]).denormalize();
+        assert_eq!(clamped, pattern_from_array(DIMENSIONS.map(|info| info.max)));
+    }
+
+    #[test]
+    fn sanitize_policies() {
+        let good = SubmodalityPattern::zeros();
+        for policy in [
+            SanitizePolicy::Clamp,
+            SanitizePolicy::RejectSample,
+            SanitizePolicy::MarkMissing,
+        ] {
+            assert_eq!(good.sanitize(policy), Ok(MeasuredPattern::from(&good)));
+        }
+
+        let bad = SubmodalityPattern {
+            color_temp: 99_999.0,
+            pitch: f32::NAN,
+            ..good.clone()
+        };
+        let clamped = bad.sanitize(SanitizePolicy::Clamp).expect("clamp");
+        assert_eq!(clamped.color_temp, Some(COLOR_TEMP_MAX));
+        assert_eq!(clamped.pitch, None);
+        assert_eq!(clamped.brightness, Some(0.5));
+        let errors = bad
+            .sanitize(SanitizePolicy::RejectSample)
+            .expect_err("reject");
+        assert_eq!(errors.len(), 2);
+        let masked = bad.sanitize(SanitizePolicy::MarkMissing).expect("mask");
+        assert_eq!(masked.present_count(), 7);
+        assert_eq!(masked.color_temp, None);
+    }
+
+    #[test]
+    fn validate_reports_every_bad_field() {
+        assert_eq!(SubmodalityPattern::zeros().validate(), Ok(()));
+        let pattern = SubmodalityPattern {
+            brightness: f32::NAN,
+            tempo: 500.0,
+            temperature: 20.0,
+            ..SubmodalityPattern::zeros()
+        };
+        let errors = pattern.validate().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(PatternFieldError::field).collect();
+        assert_eq!(fields, ["brightness", "tempo"]);
+        assert_eq!(
+            errors[1],
+            PatternFieldError::OutOfRange {
+                field: "tempo",
+                value: 500.0,
+                min: TEMPO_MIN,
+                max: TEMPO_MAX,
+            }
+        );
+        assert!(Error::from(errors)
+            .to_string()
+            .starts_with("invalid pattern: brightness is NaN; tempo = 500"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deny_invalid_rejects_out_of_range_fields() {
+        #[derive(Debug, Deserialize)]
+        struct Reading {
+            #[serde(with = "deny_invalid")]
+            pattern: SubmodalityPattern,
+        }
+        let mut json = serde_json::to_value(SubmodalityPattern::zeros()).unwrap();
+        let good = serde_json::json!({ "pattern": json.clone() });
+        let reading: Reading = serde_json::from_value(good).unwrap();
+        assert_eq!(reading.pattern, SubmodalityPattern::zeros());
+        json["volume"] = serde_json::json!(1.5);
+        let err =
+            serde_json::from_value::<Reading>(serde_json::json!({ "pattern": json })).unwrap_err();
+        assert!(err.to_string().contains("volume = 1.5 is outside 0..=1"));
+    }
+
+    #[test]
+    fn builder_groups_fields_and_checks_ranges() {
+        let pattern = SubmodalityPattern::builder()
+            .with_visual(0.8, 3000.0, 0.2)
+            .with_auditory(0.3, 90.0, 220.0)
+            .arousal(0.6)
+            .build()
+            .unwrap();
+        assert_eq!(
+            pattern,
+            SubmodalityPattern {
+                brightness: 0.8,
+                color_temp: 3000.0,
+                focal_distance: 0.2,
+                volume: 0.3,
+                tempo: 90.0,
+                pitch: 220.0,
+                arousal: 0.6,
+                ..SubmodalityPattern::zeros()
+            }
+        );
+
+        let base = SubmodalityPatternBuilder::from_pattern(pattern.clone());
+        assert_eq!(base.clone().build().unwrap(), pattern);
+        let err = base
+            .clone()
+            .temperature(-3.0)
+            .tempo(f32::NAN)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid pattern: tempo is NaN; temperature = -3 is outside 10..=40"
+        );
+        let cold_rig = CalibrationProfile {
+            temperature: Some(crate::calibration::DimensionRange::new(-5.0, 60.0)),
+            ..CalibrationProfile::new()
+        };
+        assert!(base.temperature(-3.0).profile(cold_rig).build().is_ok());
+    }
+
+    #[test]
+    fn compact_bytes_bound_quantization_error() {
+        let mut rng = StdRng::seed_from_u64(18);
+        for _ in 0..1000 {
+            let pattern = sampling::random_pattern(&mut rng);
+            let bytes = pattern.to_compact_bytes();
+            let decoded = SubmodalityPattern::from_compact_bytes(&bytes);
+            for ((a, b), DimensionInfo { min, max, .. }) in pattern_to_array(&pattern)
+                .into_iter()
+                .zip(pattern_to_array(&decoded))
+                .zip(DIMENSIONS)
+            {
+                // Half a quantization step, plus f32 rounding at the range scale.
+                let bound = (max - min) / 131_070.0 + max.abs() * f32::EPSILON * 2.0;
+                assert!((a - b).abs() <= bound, "{a} decoded as {b}");
+            }
+            assert_eq!(decoded.to_compact_bytes(), bytes);
+        }
+
+        let srt = crate::srt::SemanticRendezvousToken::from_bytes([9u8; 32]);
+        let target = crate::srt::pattern_from_srt(&srt, b"salt");
+        assert_eq!(
+            SubmodalityPattern::from_compact_bytes(&target.to_compact_bytes()),
+            target
+        );
+
+        let wild = SubmodalityPattern {
+            brightness: f32::NAN,
+            tempo: 1e6,
+            ..SubmodalityPattern::zeros()
+        };
+        let decoded = SubmodalityPattern::from_compact_bytes(&wild.to_compact_bytes());
+        assert_eq!(decoded.brightness, BRIGHTNESS_MIN);
+        assert_eq!(decoded.tempo, TEMPO_MAX);
+    }
+
+    #[test]
+    fn array_conversions_round_trip() {
+        let pattern = SubmodalityPattern::zeros();
+        let values: [f32; 9] = pattern.clone().into();
+        assert_eq!(values[1], pattern.color_temp);
+        assert_eq!(SubmodalityPattern::try_from(values).unwrap(), pattern);
+        let mut bad = values;
+        bad[4] = f32::NAN;
+        assert!(SubmodalityPattern::try_from(bad).is_err());
+
+        let normalized = pattern.normalize();
+        let values: [f32; 9] = normalized.clone().into();
+        assert_eq!(NormalizedPattern::try_from(values).unwrap(), normalized);
+        assert!(NormalizedPattern::try_from([1.5; 9]).is_err());
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn nalgebra_conversions_round_trip() {
+        let normalized = SubmodalityPattern::zeros().normalize();
+        let vector = nalgebra::SVector::<f32, 9>::from(&normalized);
+        assert_eq!(vector[0], normalized.brightness);
+        assert_eq!(NormalizedPattern::try_from(&vector).unwrap(), normalized);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray_conversions_round_trip() {
+        let normalized = SubmodalityPattern::zeros().normalize();
+        let array = ndarray::Array1::from(&normalized);
+        assert_eq!(array[8], normalized.arousal);
+        assert_eq!(
+            NormalizedPattern::try_from(array.view()).unwrap(),
+            normalized
+        );
+        let short = ndarray::Array1::<f32>::zeros(8);
+        assert!(NormalizedPattern::try_from(short.view()).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn timed_pattern_json_is_flat() {
+        let timed = TimedPattern::new(1_700_000_000_250, SubmodalityPattern::zeros());
+        let json = serde_json::to_value(&timed).expect("serialize");
+        assert_eq!(json["timestamp"], 1_700_000_000_250u64);
+        assert_eq!(json["brightness"], 0.5);
+        let decoded: TimedPattern = serde_json::from_value(json.clone()).expect("deserialize");
+        assert_eq!(decoded, timed);
+        let plain: SubmodalityPattern = serde_json::from_value(json).expect("pattern");
+        assert_eq!(plain, timed.pattern);
+    }
+
+    #[test]
+    fn quantized_patterns_key_hash_sets() {
+        let base = SubmodalityPattern::zeros();
+        let mut nudged = base.clone();
+        nudged.pitch += 0.5;
+        let mut moved = base.clone();
+        moved.arousal = 1.0;
+
+        let keys: HashSet<QuantizedPattern> = [&base, &nudged, &moved]
+            .into_iter()
+            .map(|p| QuantizedPattern::from_pattern(p, 16).expect("quantize"))
+            .collect();
+        assert_eq!(keys.len(), 2);
+        assert_ne!(
+            QuantizedPattern::from_pattern(&base, 16).expect("16"),
+            QuantizedPattern::from_pattern(&base, 17).expect("17")
+        );
+    }
+
+    #[test]
+    fn cells_bucket_nearby_patterns() {
+        let pattern = SubmodalityPattern {
+            brightness: 1.0,
+            pitch: 6000.0,
+            ..SubmodalityPattern::zeros()
+        };
+        let cell = pattern.quantize_to_cells(2).expect("cell");
+        assert_eq!(cell.coords(), [3, 2, 2, 2, 0, 1, 1, 0, 0]);
+        assert!(cell.contains(&pattern.normalize()));
+        assert!(cell.contains(&cell.center()));
+        let nudged = SubmodalityPattern {
+            pitch: 6010.0,
+            ..pattern.clone()
+        };
+        assert_eq!(nudged.quantize_to_cells(2).expect("cell"), cell);
+        assert_ne!(pattern.quantize_to_cells(3).expect("cell"), cell);
+        assert!(pattern.quantize_to_cells(0).is_err());
+        assert!(pattern.quantize_to_cells(17).is_err());
+        assert_eq!(PatternCell::from_coords(2, cell.coords()).expect("cell"), cell);
+        assert!(PatternCell::from_coords(2, [4, 0, 0, 0, 0, 0, 0, 0, 0]).is_err());
+        assert!(PatternCell::from_coords(32, [0; 9]).is_err());
+
+        assert_eq!(PatternCell::max_intra_cell_distance(2), 0.75);
+        let mut rng = StdRng::seed_from_u64(85);
+        for _ in 0..200 {
+            let pattern = sampling::random_pattern(&mut rng);
+            let cell = pattern.quantize_to_cells(3).expect("cell");
+            let distance =
+                crate::matching::euclidean_distance(&pattern.normalize(), &cell.center());
+            assert!(distance <= PatternCell::max_intra_cell_distance(3) / 2.0);
+        }
+    }
+
+    #[test]
+    fn quantization_rules_are_explicit() {
+        let mut pattern = SubmodalityPattern::zeros();
+        pattern.brightness = 2.0;
+        pattern.movement = f32::NAN;
+        let q = QuantizedPattern::from_pattern(&pattern, 5).expect("quantize");
+        assert_eq!(q.values()[0], 4);
+        assert_eq!(q.values()[7], 0);
+        assert_eq!(q.to_normalized().brightness, 1.0);
+        assert_eq!(q.to_pattern().brightness, BRIGHTNESS_MAX);
+        assert!(QuantizedPattern::from_pattern(&pattern, 1).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn quantized_pattern_deserialization_checks_invariants() {
+        let q = QuantizedPattern::from_pattern(&SubmodalityPattern::zeros(), 4).unwrap();
+        let json = serde_json::to_value(q).unwrap();
+        assert_eq!(serde_json::from_value::<QuantizedPattern>(json).unwrap(), q);
+
+        let one_level = serde_json::json!({ "levels": 1, "values": [0, 0, 0, 0, 0, 0, 0, 0, 0] });
+        assert!(serde_json::from_value::<QuantizedPattern>(one_level).is_err());
+        let mut out_of_range = serde_json::to_value(q).unwrap();
+        out_of_range["values"][4] = serde_json::json!(4);
+        assert!(serde_json::from_value::<QuantizedPattern>(out_of_range).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn pattern_cell_deserialization_checks_invariants() {
+        let cell = SubmodalityPattern::zeros().quantize_to_cells(3).unwrap();
+        let json = serde_json::to_value(cell).unwrap();
+        assert_eq!(serde_json::from_value::<PatternCell>(json).unwrap(), cell);
+
+        for bits in [0, 17, 40] {
+            let mut bad = serde_json::to_value(cell).unwrap();
+            bad["bits"] = serde_json::json!(bits);
+            assert!(serde_json::from_value::<PatternCell>(bad).is_err());
+        }
+        let mut out_of_range = serde_json::to_value(cell).unwrap();
+        out_of_range["coords"][2] = serde_json::json!(8);
+        assert!(serde_json::from_value::<PatternCell>(out_of_range).is_err());
+    }
 }
@@ -0,0 +1,155 @@
+//! Per-dimension differences between two patterns.
+//!
+//! When a stream does not match, the total distance alone does not say which
+//! sensor is off. A [`PatternDiff`] breaks the distance down by dimension so
+//! tools can show the dimensions that contribute most first.
+//! [`Matcher::explain`](crate::matching::Matcher::explain) weights the
+//! breakdown the way the matcher does, so the shares add up to the distance
+//! it compared against its threshold.
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use super::{normalized_to_array, pattern_to_array, SubmodalityPattern, DIMENSIONS};
+
+/// Difference in one dimension, `self - other`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DimensionDelta {
+    /// Field name, the key of its [`DIMENSIONS`] entry.
+    pub field: &'static str,
+    /// Difference in raw units.
+    pub raw: f32,
+    /// Difference after normalization.
+    pub normalized: f32,
+    /// Factor the squared normalized difference is multiplied by in the
+    /// distance; `1.0` in a plain [`SubmodalityPattern::diff`].
+    pub weight: f32,
+}
+
+impl DimensionDelta {
+    /// This dimension's share of the squared distance.
+    pub fn contribution(&self) -> f32 {
+        self.weight * self.normalized * self.normalized
+    }
+}
+
+/// Per-dimension breakdown of the distance between two patterns.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PatternDiff {
+    /// Square root of the summed contributions: the Euclidean distance in
+    /// normalized space, as
+    /// [`euclidean_distance`](crate::matching::euclidean_distance) computes
+    /// it, unless the deltas are weighted.
+    pub distance: f32,
+    /// One delta per dimension, in field order until sorted.
+    pub deltas: Vec<DimensionDelta>,
+}
+
+impl PatternDiff {
+    /// Sort deltas by descending contribution, so the dimensions that keep
+    /// two patterns apart come first. Ties keep field order.
+    pub fn sort_by_contribution(&mut self) {
+        self.deltas
+            .sort_by(|a, b| b.contribution().total_cmp(&a.contribution()));
+    }
+
+    /// Delta for the named field.
+    pub fn get(&self, field: &str) -> Option<&DimensionDelta> {
+        self.deltas.iter().find(|delta| delta.field == field)
+    }
+
+    /// Subtract a drift `offset` in normalized units, in field order, from
+    /// the deltas; raw deltas lose the same share of each reference range.
+    pub(crate) fn corrected(mut self, offset: [f32; 9]) -> Self {
+        for delta in &mut self.deltas {
+            let i = field_index(delta.field);
+            let info = &DIMENSIONS[i];
+            delta.normalized -= offset[i];
+            delta.raw -= offset[i] * (info.max - info.min);
+        }
+        self.with_total()
+    }
+
+    /// Set per-dimension weights, in field order, and recompute the distance.
+    pub(crate) fn weighted(mut self, weights: [f32; 9]) -> Self {
+        for delta in &mut self.deltas {
+            delta.weight = weights[field_index(delta.field)];
+        }
+        self.with_total()
+    }
+
+    fn with_total(mut self) -> Self {
+        self.distance = self
+            .deltas
+            .iter()
+            .map(DimensionDelta::contribution)
+            .sum::<f32>()
+            .sqrt();
+        self
+    }
+}
+
+fn field_index(field: &str) -> usize {
+    DIMENSIONS
+        .iter()
+        .position(|info| info.key == field)
+        .expect("deltas are named after pattern fields")
+}
+
+impl SubmodalityPattern {
+    /// Per-dimension differences `self - other`, raw and normalized, with
+    /// the total normalized distance.
+    pub fn diff(&self, other: &SubmodalityPattern) -> PatternDiff {
+        let (raw_a, raw_b) = (pattern_to_array(self), pattern_to_array(other));
+        let norm_a = normalized_to_array(&self.normalize());
+        let norm_b = normalized_to_array(&other.normalize());
+        let deltas: Vec<DimensionDelta> = (0..9)
+            .map(|i| DimensionDelta {
+                field: DIMENSIONS[i].key,
+                raw: raw_a[i] - raw_b[i],
+                normalized: norm_a[i] - norm_b[i],
+                weight: 1.0,
+            })
+            .collect();
+        PatternDiff {
+            distance: 0.0,
+            deltas,
+        }
+        .with_total()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::euclidean_distance;
+
+    #[test]
+    fn diff_explains_the_distance() {
+        let target = SubmodalityPattern::zeros();
+        let measured = SubmodalityPattern {
+            pitch: 2438.0,
+            brightness: 0.6,
+            tempo: 3.0,
+            ..target.clone()
+        };
+        let mut diff = measured.diff(&target);
+        assert_eq!(
+            diff.distance,
+            euclidean_distance(&measured.normalize(), &target.normalize())
+        );
+        let pitch = diff.get("pitch").expect("pitch");
+        assert_eq!(pitch.raw, 1998.0);
+        assert!((pitch.normalized - 0.1).abs() < 1e-6);
+
+        diff.sort_by_contribution();
+        let order: Vec<&str> = diff.deltas.iter().map(|delta| delta.field).collect();
+        assert_eq!(&order[..4], ["brightness", "pitch", "tempo", "color_temp"]);
+        assert!(diff.deltas[1].contribution() > diff.deltas[2].contribution());
+        assert_eq!(target.diff(&target).distance, 0.0);
+    }
+}
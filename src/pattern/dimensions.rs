@@ -0,0 +1,54 @@
+//! Metadata for the nine core dimensions.
+//!
+//! [`DIMENSIONS`] describes every `SubmodalityPattern` field (key, display
+//! name, unit, and range) in declaration order, so forms, tables, and
+//! validation can be generated from it rather than naming each field.
+//! `registry::DimensionRegistry::core` is built from the same table. It is
+//! defined in [`crate::embedded`], whose normalization and target derivation
+//! read their ranges from it in `no_std` builds too.
+
+pub use crate::embedded::{DimensionInfo, DIMENSIONS};
+
+/// Look up a core dimension by key.
+pub fn dimension(key: &str) -> Option<&'static DimensionInfo> {
+    DIMENSIONS.iter().find(|info| info.key == key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::{normalized_to_array, pattern_to_array, SubmodalityPattern};
+
+    #[test]
+    fn table_agrees_with_pattern_layout() {
+        // `Debug` lists the struct's fields in declaration order.
+        let debug = format!("{:?}", SubmodalityPattern::zeros());
+        let fields: Vec<&str> = debug
+            .split(['{', ','])
+            .skip(1)
+            .map(|field| field.split(':').next().unwrap_or_default().trim())
+            .collect();
+        assert_eq!(fields, DIMENSIONS.map(|info| info.key));
+        assert!(DIMENSIONS.iter().all(|info| !info.circular));
+        let pattern = SubmodalityPattern {
+            pitch: 1e6,
+            ..SubmodalityPattern::zeros()
+        };
+        let raw = pattern_to_array(&pattern);
+        let normalized = normalized_to_array(&pattern.normalize());
+        for (i, info) in DIMENSIONS.iter().enumerate() {
+            assert_eq!(info.normalize(raw[i]), normalized[i]);
+            assert_eq!(info.contains(raw[i]), info.key != "pitch");
+        }
+    }
+
+    #[test]
+    fn looks_up_by_key() {
+        assert_eq!(
+            dimension("color_temp").and_then(|info| info.unit),
+            Some("K")
+        );
+        assert_eq!(dimension("pitch").map(|info| info.name), Some("Pitch"));
+        assert!(dimension("valence").is_none());
+    }
+}
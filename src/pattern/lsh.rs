@@ -0,0 +1,150 @@
+//! Locality-sensitive bucket identifiers for normalized patterns.
+//!
+//! Publishing a pattern, or even its [`PatternCell`](super::PatternCell),
+//! reveals where it lies. A bucket from [`LshHasher`] only reveals which side
+//! of each of a few seeded random hyperplanes (through the center of the
+//! normalized cube) the pattern is on, yet nearby patterns usually share it:
+//! each bit agrees with probability `1 - θ/π`, where `θ` is the angle between
+//! the two patterns as seen from the center. Peers announce bucket IDs and
+//! only compare exact patterns with those in the same bucket.
+//!
+//! Hyperplanes are expanded from the seed with SHA-256 and built with plain
+//! IEEE arithmetic (no transcendental functions), so every platform derives
+//! the same planes and the same buckets.
+
+use sha2::{Digest, Sha256};
+
+use super::{normalized_to_array, NormalizedPattern};
+use crate::error::{Error, Result};
+
+/// Domain separator for hyperplane expansion.
+pub const LSH_DOMAIN: &[u8] = b"phenorv-lsh-v1|";
+
+/// Maximum number of hyperplanes, one bucket bit each.
+pub const MAX_PLANES: u8 = 64;
+
+/// Seeded random hyperplanes that map patterns to bucket IDs.
+///
+/// Build once and reuse when bucketing many patterns; the planes depend
+/// only on the seed and plane count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LshHasher {
+    /// Plane normals, one per bucket bit, lowest bit first.
+    normals: Vec<[f32; 9]>,
+}
+
+impl LshHasher {
+    /// Expand `planes` hyperplanes (`1..=64`) from `seed`.
+    ///
+    /// Peers must agree on both; a shared salt or meeting point works as the
+    /// seed.
+    pub fn new(seed: &[u8], planes: u8) -> Result<Self> {
+        if !(1..=MAX_PLANES).contains(&planes) {
+            return Err(Error::InvalidConfig(format!(
+                "LSH needs 1 to {MAX_PLANES} hyperplanes, got {planes}"
+            )));
+        }
+        let seed_len = u32::try_from(seed.len()).expect("seed fits in u32");
+        let coordinates: Vec<f32> = (0u32..)
+            .flat_map(|block| {
+                let digest: [u8; 32] = Sha256::new()
+                    .chain_update(LSH_DOMAIN)
+                    .chain_update(seed_len.to_be_bytes())
+                    .chain_update(seed)
+                    .chain_update(block.to_be_bytes())
+                    .finalize()
+                    .into();
+                digest
+                    .chunks_exact(4)
+                    .map(|chunk| {
+                        let value = i32::from_be_bytes(chunk.try_into().expect("4 bytes"));
+                        value as f32 / 2_147_483_648.0
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .take(9 * usize::from(planes))
+            .collect();
+        let normals = coordinates
+            .chunks_exact(9)
+            .map(|chunk| chunk.try_into().expect("9 coordinates"))
+            .collect();
+        Ok(Self { normals })
+    }
+
+    /// Number of hyperplanes, and so of meaningful bucket bits.
+    pub fn planes(&self) -> u8 {
+        self.normals.len() as u8
+    }
+
+    /// Bucket ID of `pattern`: bit `i` is set when the pattern lies on the
+    /// positive side of plane `i`. Bits at and above [`planes`](Self::planes)
+    /// are zero.
+    pub fn bucket(&self, pattern: &NormalizedPattern) -> u64 {
+        let centered = normalized_to_array(pattern).map(|v| v - 0.5);
+        self.normals
+            .iter()
+            .enumerate()
+            .filter(|(_, normal)| {
+                let dot: f32 = normal.iter().zip(centered).map(|(n, v)| n * v).sum();
+                dot > 0.0
+            })
+            .fold(0u64, |bucket, (index, _)| bucket | (1 << index))
+    }
+}
+
+impl NormalizedPattern {
+    /// Locality-sensitive bucket ID under `planes` hyperplanes expanded from
+    /// `seed`; see [`LshHasher`].
+    pub fn lsh_bucket(&self, seed: &[u8], planes: u8) -> Result<u64> {
+        Ok(LshHasher::new(seed, planes)?.bucket(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::pattern::normalized_from_array;
+
+    fn random_normalized(rng: &mut StdRng) -> NormalizedPattern {
+        normalized_from_array(std::array::from_fn(|_| rng.gen_range(0.0..=1.0)))
+    }
+
+    #[test]
+    fn nearby_patterns_collide_and_distant_ones_rarely_do() {
+        let hasher = LshHasher::new(b"oracle-state", 12).expect("hasher");
+        let mut rng = StdRng::seed_from_u64(86);
+        let (mut near, mut far) = (0, 0);
+        for _ in 0..500 {
+            let pattern = random_normalized(&mut rng);
+            let nudged = normalized_from_array(
+                normalized_to_array(&pattern).map(|v| v + rng.gen_range(-0.005..=0.005)),
+            );
+            let other = random_normalized(&mut rng);
+            let bucket = hasher.bucket(&pattern);
+            assert!(bucket < 1 << 12);
+            near += usize::from(hasher.bucket(&nudged) == bucket);
+            far += usize::from(hasher.bucket(&other) == bucket);
+        }
+        assert!(near > 400, "near collisions: {near}");
+        assert!(far < 25, "far collisions: {far}");
+    }
+
+    #[test]
+    fn buckets_depend_on_seed_and_plane_count() {
+        let pattern = normalized_from_array([0.9, 0.1, 0.7, 0.3, 0.8, 0.2, 0.6, 0.4, 0.95]);
+        let bucket = pattern.lsh_bucket(b"seed", 64).expect("bucket");
+        assert_eq!(pattern.lsh_bucket(b"seed", 64).ok(), Some(bucket));
+        assert_ne!(pattern.lsh_bucket(b"other", 64).ok(), Some(bucket));
+        assert_eq!(
+            pattern.lsh_bucket(b"seed", 8).ok(),
+            Some(bucket & 0xff),
+            "fewer planes keep the leading planes"
+        );
+        assert!(pattern.lsh_bucket(b"seed", 0).is_err());
+        assert!(pattern.lsh_bucket(b"seed", 65).is_err());
+        assert_eq!(LshHasher::new(b"seed", 3).expect("hasher").planes(), 3);
+    }
+}
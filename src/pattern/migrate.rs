@@ -0,0 +1,253 @@
+//! Versioned JSON pattern records.
+//!
+//! Stored JSONL datasets outlive the field names they were written with. A
+//! record may carry a `schema_version`; records without one predate
+//! versioning and are read as version 1. [`migrate`] upgrades a record to
+//! [`PATTERN_SCHEMA_VERSION`] by applying every [`FieldRename`] introduced
+//! since its version, then drops the version field so the record
+//! deserializes (even in strict mode) into the current types.
+//!
+//! Renaming a field means bumping [`PATTERN_SCHEMA_VERSION`], appending the
+//! rename to [`FIELD_RENAMES`], and adding `#[serde(alias = "old")]` to the
+//! field so code that deserializes directly keeps accepting old records.
+//! Version 2 spells `color_temp` as `color_temperature_k`, so the unit is
+//! part of the name.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Schema version written by this crate.
+pub const PATTERN_SCHEMA_VERSION: u32 = 2;
+
+/// Name of the version field in a JSON record.
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// A field renamed in a schema version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldRename {
+    /// Version that introduced the new name.
+    pub since: u32,
+    /// Name used before `since`.
+    pub old: &'static str,
+    /// Name used from `since` on.
+    pub new: &'static str,
+}
+
+/// Every field rename so far, oldest first.
+pub const FIELD_RENAMES: &[FieldRename] = &[FieldRename {
+    since: 2,
+    old: "color_temp",
+    new: "color_temperature_k",
+}];
+
+/// A record tagged with the schema version it is written with.
+///
+/// Serializes as the record's own fields plus [`SCHEMA_VERSION_FIELD`].
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Versioned<'a, T> {
+    /// Version of the record's field names.
+    pub schema_version: u32,
+    /// The record itself.
+    #[serde(flatten)]
+    pub record: &'a T,
+}
+
+/// Upgrade a JSON record to [`PATTERN_SCHEMA_VERSION`] and remove its
+/// `schema_version` field.
+///
+/// Works on any record whose pattern fields sit at the top level, such as
+/// [`SubmodalityPattern`](super::SubmodalityPattern) and
+/// [`TimedPattern`](super::TimedPattern). Rejects records from a newer
+/// version rather than guessing at their fields.
+pub fn migrate(record: Value) -> Result<Value, MigrateError> {
+    migrate_with(record, FIELD_RENAMES)
+}
+
+/// Tag `record` with the current `schema_version`, for writers of datasets
+/// that [`migrate`] will read back.
+pub fn versioned<T: Serialize>(record: &T) -> Versioned<'_, T> {
+    Versioned {
+        schema_version: PATTERN_SCHEMA_VERSION,
+        record,
+    }
+}
+
+fn migrate_with(record: Value, renames: &[FieldRename]) -> Result<Value, MigrateError> {
+    let Value::Object(mut fields) = record else {
+        return Err(MigrateError::NotAnObject);
+    };
+    let version = schema_version(&mut fields)?;
+    let current = renames
+        .iter()
+        .map(|rename| rename.since)
+        .fold(PATTERN_SCHEMA_VERSION, u32::max);
+    if version > current {
+        return Err(MigrateError::NewerVersion {
+            found: version,
+            supported: current,
+        });
+    }
+    for rename in renames.iter().filter(|rename| rename.since > version) {
+        if let Some(value) = fields.remove(rename.old) {
+            if fields.contains_key(rename.new) {
+                return Err(MigrateError::Conflict {
+                    old: rename.old,
+                    new: rename.new,
+                });
+            }
+            fields.insert(rename.new.to_string(), value);
+        }
+    }
+    Ok(Value::Object(fields))
+}
+
+fn schema_version(fields: &mut Map<String, Value>) -> Result<u32, MigrateError> {
+    match fields.remove(SCHEMA_VERSION_FIELD) {
+        None => Ok(1),
+        Some(value) => value
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|&version| version >= 1)
+            .ok_or(MigrateError::InvalidVersion(value)),
+    }
+}
+
+/// Errors returned by [`migrate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MigrateError {
+    /// The record is not a JSON object.
+    NotAnObject,
+    /// `schema_version` is not a positive integer.
+    InvalidVersion(Value),
+    /// The record was written by a newer schema version.
+    NewerVersion {
+        /// Version in the record.
+        found: u32,
+        /// Newest version this crate reads.
+        supported: u32,
+    },
+    /// The record holds a field under both its old and its new name.
+    Conflict {
+        /// Name used before the rename.
+        old: &'static str,
+        /// Name used after the rename.
+        new: &'static str,
+    },
+}
+
+impl fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnObject => write!(f, "pattern record is not a JSON object"),
+            Self::InvalidVersion(value) => {
+                write!(f, "schema_version must be a positive integer, got {value}")
+            }
+            Self::NewerVersion { found, supported } => write!(
+                f,
+                "record uses schema version {found}, newer than the supported {supported}"
+            ),
+            Self::Conflict { old, new } => {
+                write!(f, "record has both `{old}` and its new name `{new}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrateError {}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::ingest::{self, JsonMode};
+    use crate::pattern::SubmodalityPattern;
+
+    const RENAMES: &[FieldRename] = &[FieldRename {
+        since: 3,
+        old: "pitch",
+        new: "pitch_hz",
+    }];
+
+    #[test]
+    fn current_records_round_trip_through_strict_parsing() {
+        let pattern = SubmodalityPattern::zeros();
+        let record = serde_json::to_value(versioned(&pattern)).expect("serialize");
+        assert_eq!(record[SCHEMA_VERSION_FIELD], json!(PATTERN_SCHEMA_VERSION));
+        assert_eq!(record["color_temperature_k"], json!(pattern.color_temp));
+        let migrated = migrate(record).expect("migrate");
+        let parsed: SubmodalityPattern =
+            ingest::from_json(&migrated.to_string(), JsonMode::Strict).expect("parse");
+        assert_eq!(parsed, pattern);
+
+        let unversioned = serde_json::to_value(&pattern).expect("serialize");
+        assert_eq!(migrate(unversioned.clone()), Ok(unversioned));
+    }
+
+    #[test]
+    fn version_one_records_read_in_every_mode() {
+        let pattern = SubmodalityPattern::zeros();
+        let mut old = serde_json::to_value(&pattern).expect("serialize");
+        let fields = old.as_object_mut().expect("object");
+        let kelvin = fields.remove("color_temperature_k").expect("renamed field");
+        fields.insert("color_temp".to_string(), kelvin);
+
+        let text = old.to_string();
+        for mode in [JsonMode::Lenient, JsonMode::Strict] {
+            let parsed: SubmodalityPattern = ingest::from_json(&text, mode).expect("alias");
+            assert_eq!(parsed, pattern);
+        }
+        let migrated = migrate(old).expect("migrate");
+        assert_eq!(migrated, serde_json::to_value(&pattern).expect("serialize"));
+
+        let mut both = migrated.clone();
+        both["color_temp"] = json!(1.0);
+        assert!(ingest::from_json::<SubmodalityPattern>(&both.to_string(), JsonMode::Strict)
+            .is_err());
+    }
+
+    #[test]
+    fn renames_apply_only_to_older_records() {
+        let old = json!({"schema_version": 2, "brightness": 0.5, "pitch": 440.0});
+        assert_eq!(
+            migrate_with(old, RENAMES),
+            Ok(json!({"brightness": 0.5, "pitch_hz": 440.0}))
+        );
+        let new = json!({"schema_version": 3, "pitch_hz": 440.0});
+        assert_eq!(migrate_with(new, RENAMES), Ok(json!({"pitch_hz": 440.0})));
+        let both = json!({"pitch": 1.0, "pitch_hz": 2.0});
+        assert_eq!(
+            migrate_with(both, RENAMES),
+            Err(MigrateError::Conflict {
+                old: "pitch",
+                new: "pitch_hz"
+            })
+        );
+        assert_eq!(
+            migrate(json!({"color_temp": 1.0, "color_temperature_k": 2.0})),
+            Err(MigrateError::Conflict {
+                old: "color_temp",
+                new: "color_temperature_k"
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        assert_eq!(
+            migrate(json!({"schema_version": 7})),
+            Err(MigrateError::NewerVersion {
+                found: 7,
+                supported: PATTERN_SCHEMA_VERSION
+            })
+        );
+        assert!(matches!(
+            migrate(json!({"schema_version": "1"})),
+            Err(MigrateError::InvalidVersion(_))
+        ));
+        assert!(migrate(json!({"schema_version": 0})).is_err());
+        assert_eq!(migrate(json!([1, 2])), Err(MigrateError::NotAnObject));
+    }
+}
@@ -0,0 +1,168 @@
+//! Random pattern distributions.
+//!
+//! A [`PatternDistribution`] draws whole patterns, so the simulator and test
+//! generators share one sampling interface: [`Uniform`] over the reference
+//! ranges (the simulator's default population), [`Gaussian`] around a
+//! baseline, and [`Empirical`] resampling of recorded patterns.
+//! `datasets::PeerModel` is also a distribution. Generated values stay
+//! within the reference ranges; resampled ones are returned as recorded.
+
+use rand::{Rng, RngCore};
+
+use super::stats::StatsError;
+use super::{pattern_from_array, SubmodalityPattern, DIMENSIONS};
+
+/// Source of random patterns.
+///
+/// Takes `&mut dyn RngCore` so distributions can be boxed and chosen at
+/// runtime.
+pub trait PatternDistribution {
+    /// Draw one pattern.
+    fn sample(&self, rng: &mut dyn RngCore) -> SubmodalityPattern;
+
+    /// Draw `len` independent patterns.
+    fn samples(&self, rng: &mut dyn RngCore, len: usize) -> Vec<SubmodalityPattern> {
+        (0..len).map(|_| self.sample(rng)).collect()
+    }
+}
+
+/// Generate a random submodality pattern using uniform sampling per dimension.
+///
+/// This assumes independence and uniform distributions across the allowed
+/// ranges. These assumptions are for exploration only and do not reflect real
+/// sensor distributions. Equivalent to sampling [`Uniform`], but usable with
+/// any `Rng`.
+pub fn random_pattern<R: Rng + ?Sized>(rng: &mut R) -> SubmodalityPattern {
+    pattern_from_array(DIMENSIONS.map(|info| rng.gen_range(info.min..=info.max)))
+}
+
+/// Independent uniform values across each dimension's reference range.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Uniform;
+
+impl PatternDistribution for Uniform {
+    fn sample(&self, rng: &mut dyn RngCore) -> SubmodalityPattern {
+        random_pattern(rng)
+    }
+}
+
+/// Independent Gaussian values around a baseline pattern, clamped to the
+/// reference ranges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gaussian {
+    /// Per-dimension means, in raw units.
+    pub baseline: SubmodalityPattern,
+    /// Per-dimension standard deviations, in raw units.
+    pub std_dev: SubmodalityPattern,
+}
+
+impl Gaussian {
+    /// Gaussian around `baseline` with standard deviations in raw units.
+    pub fn new(baseline: SubmodalityPattern, std_dev: SubmodalityPattern) -> Self {
+        Self { baseline, std_dev }
+    }
+
+    /// Gaussian around `baseline` with the same standard deviation on every
+    /// dimension, expressed as a fraction of each dimension's reference range.
+    pub fn normalized(baseline: SubmodalityPattern, sigma: f32) -> Self {
+        let std_dev = pattern_from_array(DIMENSIONS.map(|info| sigma * (info.max - info.min)));
+        Self::new(baseline, std_dev)
+    }
+}
+
+impl PatternDistribution for Gaussian {
+    fn sample(&self, rng: &mut dyn RngCore) -> SubmodalityPattern {
+        self.baseline.jitter(rng, &self.std_dev)
+    }
+}
+
+/// Recorded patterns drawn uniformly with replacement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Empirical {
+    samples: Vec<SubmodalityPattern>,
+}
+
+impl Empirical {
+    /// Resample `samples`, which must not be empty.
+    pub fn new(samples: Vec<SubmodalityPattern>) -> Result<Self, StatsError> {
+        if samples.is_empty() {
+            return Err(StatsError::NotEnoughSamples { needed: 1, got: 0 });
+        }
+        Ok(Self { samples })
+    }
+
+    /// The recorded patterns.
+    pub fn patterns(&self) -> &[SubmodalityPattern] {
+        &self.samples
+    }
+}
+
+impl PatternDistribution for Empirical {
+    fn sample(&self, rng: &mut dyn RngCore) -> SubmodalityPattern {
+        self.samples[rng.gen_range(0..self.samples.len())].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::pattern::{pattern_to_array, stats};
+
+    fn in_range(pattern: &SubmodalityPattern) -> bool {
+        pattern_to_array(pattern)
+            .iter()
+            .zip(DIMENSIONS)
+            .all(|(value, info)| info.contains(*value))
+    }
+
+    #[test]
+    fn uniform_matches_random_pattern() {
+        let mut a = StdRng::seed_from_u64(96);
+        let mut b = StdRng::seed_from_u64(96);
+        for _ in 0..100 {
+            let pattern = Uniform.sample(&mut a);
+            assert_eq!(pattern, random_pattern(&mut b));
+            assert!(in_range(&pattern));
+        }
+    }
+
+    #[test]
+    fn gaussian_centers_on_baseline() {
+        let mut rng = StdRng::seed_from_u64(97);
+        let baseline = SubmodalityPattern {
+            brightness: 0.5,
+            ..SubmodalityPattern::zeros()
+        };
+        let distribution = Gaussian::normalized(baseline.clone(), 0.02);
+        let samples = distribution.samples(&mut rng, 2000);
+        assert!(samples.iter().all(in_range));
+        let mean = stats::mean(&samples).expect("mean");
+        assert!((mean.brightness - 0.5).abs() < 0.005);
+        assert!((mean.color_temp - baseline.color_temp).abs() < 0.005 * 8000.0);
+        let exact = Gaussian::new(baseline.clone(), pattern_from_array([0.0; 9]));
+        assert_eq!(exact.sample(&mut rng), baseline);
+    }
+
+    #[test]
+    fn empirical_resamples_recorded_patterns() {
+        assert!(matches!(
+            Empirical::new(Vec::new()),
+            Err(StatsError::NotEnoughSamples { needed: 1, got: 0 })
+        ));
+        let recorded = vec![
+            SubmodalityPattern::zeros(),
+            SubmodalityPattern {
+                tempo: 120.0,
+                ..SubmodalityPattern::zeros()
+            },
+        ];
+        let distribution = Empirical::new(recorded.clone()).expect("empirical");
+        let mut rng = StdRng::seed_from_u64(98);
+        let samples = distribution.samples(&mut rng, 200);
+        assert!(samples.iter().all(|sample| recorded.contains(sample)));
+        assert!(recorded.iter().all(|pattern| samples.contains(pattern)));
+    }
+}
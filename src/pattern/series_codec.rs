@@ -0,0 +1,507 @@
+//! Delta-encoded binary format for timestamped pattern series.
+//!
+//! A 50 Hz recording stored as JSON lines costs a few hundred bytes per
+//! sample. This format stores each sample as the difference from the one
+//! before it, which is small for slowly varying sensor data:
+//!
+//! | Offset | Size | Contents                                           |
+//! |--------|------|----------------------------------------------------|
+//! | 0      | 4    | Magic bytes `PRVS`                                 |
+//! | 4      | 2    | Format version, little-endian (currently `1`)      |
+//! | 6      | 1    | Compression: `0` none, `1` zstd                    |
+//! | 7      | 1    | Reserved, `0`                                      |
+//! | 8      | …    | Records, zstd-compressed as one stream if flagged  |
+//!
+//! A record is ten varints (LEB128): the zigzag-encoded change in
+//! `timestamp`, then the zigzag-encoded change in each dimension's value in
+//! `SubmodalityPattern` field order. Values are first mapped to integers
+//! that preserve `f32` ordering, so nearby values have small differences and
+//! decoding is lossless. The first record is relative to zero. Zstd
+//! compression needs the `zstd` feature to write or read.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use super::{pattern_from_array, pattern_to_array, TimedPattern};
+use crate::error::Result;
+use crate::series::PatternSeries;
+
+/// Magic bytes at the start of every encoded series.
+pub const SERIES_MAGIC: [u8; 4] = *b"PRVS";
+
+/// Series format version written by this crate.
+pub const SERIES_VERSION: u16 = 1;
+
+/// Length of the header in bytes.
+pub const SERIES_HEADER_LEN: usize = 8;
+
+/// Compression applied to the records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Records are stored as plain varints.
+    #[default]
+    None,
+    /// Records are compressed as one zstd stream (feature `zstd`).
+    Zstd,
+}
+
+impl Compression {
+    fn flag(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Zstd => 1,
+        }
+    }
+
+    fn from_flag(flag: u8) -> std::result::Result<Self, SeriesCodecError> {
+        match flag {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Zstd),
+            _ => Err(SeriesCodecError::UnknownCompression(flag)),
+        }
+    }
+}
+
+/// Previous sample, as integers, that the next record is relative to.
+#[derive(Debug, Clone, Copy, Default)]
+struct DeltaState {
+    timestamp: u64,
+    values: [i32; 9],
+}
+
+/// Map an `f32` to an `i32` with the same ordering, so close values have
+/// close integers. The mapping is its own inverse.
+fn ordered(bits: i32) -> i32 {
+    if bits < 0 {
+        bits ^ i32::MAX
+    } else {
+        bits
+    }
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+fn push_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+enum Sink<W: Write> {
+    Plain(W),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, W>),
+}
+
+impl<W: Write> Sink<W> {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Self::Plain(writer) => writer,
+            #[cfg(feature = "zstd")]
+            Self::Zstd(encoder) => encoder,
+        }
+    }
+
+    /// End any compressed stream and return the underlying writer.
+    fn into_inner(self) -> io::Result<W> {
+        match self {
+            Self::Plain(writer) => Ok(writer),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(encoder) => encoder.finish(),
+        }
+    }
+}
+
+/// Streaming writer for delta-encoded series.
+pub struct SeriesWriter<W: Write> {
+    sink: Sink<W>,
+    state: DeltaState,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> fmt::Debug for SeriesWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeriesWriter")
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<W: Write> SeriesWriter<W> {
+    /// Write the header and return a writer for records.
+    ///
+    /// Fails with [`SeriesCodecError::CompressionUnavailable`] for
+    /// [`Compression::Zstd`] without the `zstd` feature.
+    pub fn new(mut writer: W, compression: Compression) -> Result<Self> {
+        let mut header = [0u8; SERIES_HEADER_LEN];
+        header[..4].copy_from_slice(&SERIES_MAGIC);
+        header[4..6].copy_from_slice(&SERIES_VERSION.to_le_bytes());
+        header[6] = compression.flag();
+        let sink = match compression {
+            Compression::None => {
+                writer.write_all(&header)?;
+                Sink::Plain(writer)
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                writer.write_all(&header)?;
+                Sink::Zstd(zstd::stream::write::Encoder::new(writer, 0)?)
+            }
+            #[cfg(not(feature = "zstd"))]
+            Compression::Zstd => return Err(SeriesCodecError::CompressionUnavailable.into()),
+        };
+        Ok(Self {
+            sink,
+            state: DeltaState::default(),
+            buf: Vec::with_capacity(10 * 5),
+        })
+    }
+
+    /// Append a sample. Timestamps need not increase, but small steps
+    /// encode smallest.
+    pub fn write(&mut self, sample: &TimedPattern) -> Result<()> {
+        self.buf.clear();
+        let timestamp_delta = sample.timestamp.wrapping_sub(self.state.timestamp) as i64;
+        push_varint(&mut self.buf, zigzag(timestamp_delta));
+        let values = pattern_to_array(&sample.pattern).map(|v| ordered(v.to_bits() as i32));
+        for (value, previous) in values.iter().zip(self.state.values) {
+            push_varint(
+                &mut self.buf,
+                zigzag(i64::from(value.wrapping_sub(previous))),
+            );
+        }
+        self.state = DeltaState {
+            timestamp: sample.timestamp,
+            values,
+        };
+        self.sink.writer().write_all(&self.buf)?;
+        Ok(())
+    }
+
+    /// Finish any compressed stream, flush, and return the underlying writer.
+    pub fn finish(self) -> Result<W> {
+        let mut writer = self.sink.into_inner()?;
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+enum Source<R: Read> {
+    Plain(BufReader<R>),
+    #[cfg(feature = "zstd")]
+    Zstd(BufReader<zstd::stream::read::Decoder<'static, BufReader<R>>>),
+}
+
+impl<R: Read> Source<R> {
+    fn reader(&mut self) -> &mut dyn BufRead {
+        match self {
+            Self::Plain(reader) => reader,
+            #[cfg(feature = "zstd")]
+            Self::Zstd(reader) => reader,
+        }
+    }
+}
+
+/// Streaming reader for delta-encoded series; yields samples in file order.
+pub struct SeriesReader<R: Read> {
+    source: Source<R>,
+    compression: Compression,
+    state: DeltaState,
+    /// Index of the next record, for error messages.
+    record: usize,
+    /// Set after an error so iteration stops.
+    failed: bool,
+}
+
+impl<R: Read> fmt::Debug for SeriesReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeriesReader")
+            .field("compression", &self.compression)
+            .field("record", &self.record)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: Read> SeriesReader<R> {
+    /// Read and validate the header.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut header = [0u8; SERIES_HEADER_LEN];
+        if reader.read_exact(&mut header).is_err() || header[..4] != SERIES_MAGIC {
+            return Err(SeriesCodecError::BadMagic.into());
+        }
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version != SERIES_VERSION {
+            return Err(SeriesCodecError::UnsupportedVersion(version).into());
+        }
+        let compression = Compression::from_flag(header[6])?;
+        let source = match compression {
+            Compression::None => Source::Plain(BufReader::new(reader)),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                Source::Zstd(BufReader::new(zstd::stream::read::Decoder::new(reader)?))
+            }
+            #[cfg(not(feature = "zstd"))]
+            Compression::Zstd => return Err(SeriesCodecError::CompressionUnavailable.into()),
+        };
+        Ok(Self {
+            source,
+            compression,
+            state: DeltaState::default(),
+            record: 0,
+            failed: false,
+        })
+    }
+
+    /// Compression named in the header.
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Read a varint, or `None` at a clean end of input.
+    fn read_varint(&mut self) -> Result<Option<u64>> {
+        let reader = self.source.reader();
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let mut byte = [0u8; 1];
+            match reader.read_exact(&mut byte) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof && shift == 0 => {
+                    return Ok(None)
+                }
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(SeriesCodecError::Truncated {
+                        record: self.record,
+                    }
+                    .into())
+                }
+                Err(err) => return Err(err.into()),
+            }
+            value |= u64::from(byte[0] & 0x7f) << shift;
+            if byte[0] & 0x80 == 0 {
+                return Ok(Some(value));
+            }
+        }
+        Err(SeriesCodecError::InvalidVarint {
+            record: self.record,
+        }
+        .into())
+    }
+
+    fn read_record(&mut self) -> Result<Option<TimedPattern>> {
+        let Some(timestamp_delta) = self.read_varint()? else {
+            return Ok(None);
+        };
+        let mut values = [0i32; 9];
+        for (value, previous) in values.iter_mut().zip(self.state.values) {
+            let delta = self.read_varint()?.ok_or(SeriesCodecError::Truncated {
+                record: self.record,
+            })?;
+            *value = previous.wrapping_add(unzigzag(delta) as i32);
+        }
+        let timestamp = self
+            .state
+            .timestamp
+            .wrapping_add(unzigzag(timestamp_delta) as u64);
+        self.state = DeltaState { timestamp, values };
+        self.record += 1;
+        let pattern = pattern_from_array(values.map(|v| f32::from_bits(ordered(v) as u32)));
+        Ok(Some(TimedPattern::new(timestamp, pattern)))
+    }
+}
+
+impl<R: Read> Iterator for SeriesReader<R> {
+    type Item = Result<TimedPattern>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let record = self.read_record().transpose();
+        self.failed = matches!(record, Some(Err(_)));
+        record
+    }
+}
+
+/// Encode every sample of `series`.
+pub fn write_series<W: Write>(
+    writer: W,
+    series: &PatternSeries,
+    compression: Compression,
+) -> Result<W> {
+    let mut writer = SeriesWriter::new(writer, compression)?;
+    for sample in series.samples() {
+        writer.write(sample)?;
+    }
+    writer.finish()
+}
+
+/// Decode an encoded series; the samples must be in time order.
+pub fn read_series<R: Read>(reader: R) -> Result<PatternSeries> {
+    let samples = SeriesReader::new(reader)?.collect::<Result<Vec<_>>>()?;
+    Ok(PatternSeries::from_samples(samples)?)
+}
+
+/// Errors returned when an encoded series is malformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeriesCodecError {
+    /// The data does not start with [`SERIES_MAGIC`].
+    BadMagic,
+    /// The header names a format version this crate cannot read.
+    UnsupportedVersion(u16),
+    /// The header names an unknown compression scheme.
+    UnknownCompression(u8),
+    /// Zstd compression was requested without the `zstd` feature.
+    CompressionUnavailable,
+    /// The data ends partway through a record.
+    Truncated {
+        /// Zero-based index of the incomplete record.
+        record: usize,
+    },
+    /// A varint is longer than ten bytes.
+    InvalidVarint {
+        /// Zero-based index of the record holding it.
+        record: usize,
+    },
+}
+
+impl fmt::Display for SeriesCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic => write!(f, "not an encoded pattern series (bad magic bytes)"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported series format version {version}")
+            }
+            Self::UnknownCompression(flag) => write!(f, "unknown series compression {flag}"),
+            Self::CompressionUnavailable => {
+                write!(f, "zstd series compression requires the `zstd` feature")
+            }
+            Self::Truncated { record } => write!(f, "series ends inside record {record}"),
+            Self::InvalidVarint { record } => write!(f, "invalid varint in record {record}"),
+        }
+    }
+}
+
+impl std::error::Error for SeriesCodecError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::pattern::SubmodalityPattern;
+
+    fn recording() -> PatternSeries {
+        let samples = (0..500)
+            .map(|i| {
+                let t = i as f32 / 50.0;
+                TimedPattern::new(
+                    1_700_000_000_000 + i * 20,
+                    SubmodalityPattern {
+                        brightness: 0.5 + 0.1 * t.sin(),
+                        tempo: 72.0 + (i % 7) as f32,
+                        temperature: -0.0,
+                        movement: f32::NAN,
+                        ..SubmodalityPattern::zeros()
+                    },
+                )
+            })
+            .collect();
+        PatternSeries::from_samples(samples).expect("ordered")
+    }
+
+    fn assert_same(decoded: &PatternSeries, original: &PatternSeries) {
+        assert_eq!(decoded.len(), original.len());
+        for (a, b) in decoded.samples().iter().zip(original.samples()) {
+            assert_eq!(a.timestamp, b.timestamp);
+            let bits = |p: &SubmodalityPattern| pattern_to_array(p).map(f32::to_bits);
+            assert_eq!(bits(&a.pattern), bits(&b.pattern));
+        }
+    }
+
+    #[test]
+    fn round_trips_losslessly_and_compactly() {
+        let series = recording();
+        let bytes = write_series(Vec::new(), &series, Compression::None).expect("write");
+        assert_same(&read_series(bytes.as_slice()).expect("read"), &series);
+        // Eight bytes of timestamp plus nine `f32`s per sample, uncompressed.
+        let fixed_len = series.len() * (8 + 9 * 4);
+        assert!(
+            bytes.len() * 3 < fixed_len,
+            "{} vs {fixed_len}",
+            bytes.len()
+        );
+
+        let empty =
+            write_series(Vec::new(), &PatternSeries::new(), Compression::None).expect("write");
+        assert_eq!(empty.len(), SERIES_HEADER_LEN);
+        assert!(read_series(empty.as_slice()).expect("read").is_empty());
+    }
+
+    #[test]
+    fn ordered_mapping_preserves_order() {
+        let values = [-1e9f32, -1.0, -0.0, 0.0, 1e-30, 1.0, 6500.0, f32::MAX];
+        let mapped = values.map(|v| ordered(v.to_bits() as i32));
+        assert!(mapped.windows(2).all(|pair| pair[0] < pair[1]));
+        for (value, mapped) in values.iter().zip(mapped) {
+            assert_eq!(
+                f32::from_bits(ordered(mapped) as u32).to_bits(),
+                value.to_bits()
+            );
+        }
+        for value in [0, 1, -1, i64::MAX, i64::MIN] {
+            assert_eq!(unzigzag(zigzag(value)), value);
+        }
+    }
+
+    #[test]
+    fn malformed_series_are_rejected() {
+        let mut bytes = write_series(Vec::new(), &recording(), Compression::None).expect("write");
+        bytes.pop();
+        let results: Vec<_> = SeriesReader::new(bytes.as_slice())
+            .expect("header")
+            .collect();
+        assert_eq!(results.len(), 500);
+        assert!(matches!(
+            results.last(),
+            Some(Err(Error::SeriesCodec(SeriesCodecError::Truncated {
+                record: 499
+            })))
+        ));
+        assert!(matches!(
+            SeriesReader::new(&b"PRVT\x01\x00\x24\x00"[..]),
+            Err(Error::SeriesCodec(SeriesCodecError::BadMagic))
+        ));
+        assert!(matches!(
+            SeriesReader::new(&b"PRVS\x01\x00\x07\x00"[..]),
+            Err(Error::SeriesCodec(SeriesCodecError::UnknownCompression(7)))
+        ));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_round_trips() {
+        let series = recording();
+        let plain = write_series(Vec::new(), &series, Compression::None).expect("plain");
+        let packed = write_series(Vec::new(), &series, Compression::Zstd).expect("zstd");
+        assert!(packed.len() < plain.len());
+        let reader = SeriesReader::new(packed.as_slice()).expect("header");
+        assert_eq!(reader.compression(), Compression::Zstd);
+        assert_same(&read_series(packed.as_slice()).expect("read"), &series);
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    #[test]
+    fn zstd_requires_feature() {
+        assert!(matches!(
+            SeriesWriter::new(Vec::new(), Compression::Zstd),
+            Err(Error::SeriesCodec(SeriesCodecError::CompressionUnavailable))
+        ));
+    }
+}
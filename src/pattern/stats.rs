@@ -0,0 +1,187 @@
+//! Summary statistics over collections of patterns.
+//!
+//! Every function takes any iterator of pattern references, so it works on
+//! slices (`patterns.iter()`) and on series
+//! ([`PatternSeries::patterns`](crate::series::PatternSeries::patterns))
+//! alike. Statistics are per dimension in raw units and accumulate in `f64`.
+
+use std::fmt;
+
+use super::{pattern_from_array, pattern_to_array, SubmodalityPattern};
+
+/// Per-dimension arithmetic mean. Needs at least one pattern.
+pub fn mean<'a>(
+    patterns: impl IntoIterator<Item = &'a SubmodalityPattern>,
+) -> Result<SubmodalityPattern, StatsError> {
+    let samples = collect(patterns, 1)?;
+    Ok(pattern_from_array(means(&samples).map(|m| m as f32)))
+}
+
+/// Per-dimension unbiased (n - 1) sample variance. Needs at least two
+/// patterns.
+pub fn variance<'a>(
+    patterns: impl IntoIterator<Item = &'a SubmodalityPattern>,
+) -> Result<SubmodalityPattern, StatsError> {
+    let matrix = covariance(patterns)?;
+    Ok(pattern_from_array(std::array::from_fn(|i| matrix[i][i])))
+}
+
+/// Unbiased (n - 1) sample covariance matrix, rows and columns in field
+/// declaration order. Needs at least two patterns.
+pub fn covariance<'a>(
+    patterns: impl IntoIterator<Item = &'a SubmodalityPattern>,
+) -> Result<[[f32; 9]; 9], StatsError> {
+    let samples = collect(patterns, 2)?;
+    let means = means(&samples);
+    let mut sums = [[0.0f64; 9]; 9];
+    for sample in &samples {
+        let centered: [f64; 9] = std::array::from_fn(|i| sample[i] - means[i]);
+        for (row, a) in sums.iter_mut().zip(centered) {
+            for (sum, b) in row.iter_mut().zip(centered) {
+                *sum += a * b;
+            }
+        }
+    }
+    let denominator = (samples.len() - 1) as f64;
+    Ok(sums.map(|row| row.map(|sum| (sum / denominator) as f32)))
+}
+
+/// Per-dimension `p`th percentile, `p` in `[0, 100]`.
+///
+/// Interpolates linearly between the closest ranks, so `p = 50` is the
+/// median and `0` and `100` are the minimum and maximum. NaN values sort
+/// last. Needs at least one pattern.
+pub fn percentile<'a>(
+    patterns: impl IntoIterator<Item = &'a SubmodalityPattern>,
+    p: f32,
+) -> Result<SubmodalityPattern, StatsError> {
+    if !(0.0..=100.0).contains(&p) {
+        return Err(StatsError::InvalidPercentile(p));
+    }
+    let samples = collect(patterns, 1)?;
+    let rank = f64::from(p) / 100.0 * (samples.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    let fraction = rank - rank.floor();
+    Ok(pattern_from_array(std::array::from_fn(|i| {
+        let mut column: Vec<f64> = samples.iter().map(|sample| sample[i]).collect();
+        column.sort_by(f64::total_cmp);
+        (column[low] + (column[high] - column[low]) * fraction) as f32
+    })))
+}
+
+fn collect<'a>(
+    patterns: impl IntoIterator<Item = &'a SubmodalityPattern>,
+    needed: usize,
+) -> Result<Vec<[f64; 9]>, StatsError> {
+    let samples: Vec<[f64; 9]> = patterns
+        .into_iter()
+        .map(|pattern| pattern_to_array(pattern).map(f64::from))
+        .collect();
+    if samples.len() < needed {
+        return Err(StatsError::NotEnoughSamples {
+            needed,
+            got: samples.len(),
+        });
+    }
+    Ok(samples)
+}
+
+fn means(samples: &[[f64; 9]]) -> [f64; 9] {
+    let mut sums = [0.0f64; 9];
+    for sample in samples {
+        for (sum, value) in sums.iter_mut().zip(sample) {
+            *sum += value;
+        }
+    }
+    sums.map(|sum| sum / samples.len() as f64)
+}
+
+/// Errors returned by the statistics functions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsError {
+    /// Too few patterns for the statistic.
+    NotEnoughSamples {
+        /// Minimum number of patterns required.
+        needed: usize,
+        /// Number of patterns supplied.
+        got: usize,
+    },
+    /// The percentile is outside `[0, 100]` or NaN.
+    InvalidPercentile(f32),
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEnoughSamples { needed, got } => {
+                write!(f, "need at least {needed} patterns, got {got}")
+            }
+            Self::InvalidPercentile(p) => write!(f, "percentile {p} is outside 0..=100"),
+        }
+    }
+}
+
+impl std::error::Error for StatsError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns() -> Vec<SubmodalityPattern> {
+        [(0.0, 60.0), (0.5, 90.0), (1.0, 120.0), (0.5, 130.0)]
+            .into_iter()
+            .map(|(brightness, tempo)| SubmodalityPattern {
+                brightness,
+                tempo,
+                ..SubmodalityPattern::zeros()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mean_variance_and_covariance() {
+        let patterns = patterns();
+        let mean = mean(&patterns).expect("mean");
+        assert_eq!(mean.brightness, 0.5);
+        assert_eq!(mean.tempo, 100.0);
+        assert_eq!(mean.pitch, 440.0);
+
+        let variance = variance(&patterns).expect("variance");
+        assert!((variance.brightness - 1.0 / 6.0).abs() < 1e-6);
+        assert!((variance.tempo - 1000.0).abs() < 1e-3);
+        assert_eq!(variance.pitch, 0.0);
+
+        let matrix = covariance(&patterns).expect("covariance");
+        assert!((matrix[0][4] - 10.0).abs() < 1e-4);
+        assert_eq!(matrix[0][4], matrix[4][0]);
+        assert_eq!(matrix[5], [0.0; 9]);
+    }
+
+    #[test]
+    fn percentiles_interpolate_between_ranks() {
+        let patterns = patterns();
+        assert_eq!(percentile(&patterns, 0.0).expect("min").tempo, 60.0);
+        assert_eq!(percentile(&patterns, 50.0).expect("median").tempo, 105.0);
+        assert_eq!(percentile(&patterns, 100.0).expect("max").tempo, 130.0);
+        assert_eq!(percentile(&patterns, 50.0).expect("median").brightness, 0.5);
+        assert_eq!(
+            percentile(&patterns, 101.0),
+            Err(StatsError::InvalidPercentile(101.0))
+        );
+        assert!(percentile(&patterns, f32::NAN).is_err());
+    }
+
+    #[test]
+    fn statistics_need_enough_patterns() {
+        let one = [SubmodalityPattern::zeros()];
+        assert_eq!(mean(&one), Ok(SubmodalityPattern::zeros()));
+        assert_eq!(
+            variance(&one),
+            Err(StatsError::NotEnoughSamples { needed: 2, got: 1 })
+        );
+        assert_eq!(
+            mean(&[]),
+            Err(StatsError::NotEnoughSamples { needed: 1, got: 0 })
+        );
+    }
+}
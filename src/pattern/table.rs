@@ -0,0 +1,122 @@
+//! Aligned text tables for patterns and diffs.
+//!
+//! JSON is hard to eyeball while debugging a stream. The [`fmt::Display`]
+//! impls here (and the equivalent `to_table` methods) render one row per
+//! dimension with its unit, so raw and normalized values line up.
+
+use std::fmt;
+
+use super::diff::PatternDiff;
+use super::{dimension, normalized_to_array, pattern_to_array, SubmodalityPattern, DIMENSIONS};
+
+/// Width of the field column, the longest name (`focal_distance`).
+const FIELD_WIDTH: usize = 14;
+
+fn unit_of(field: &str) -> &'static str {
+    dimension(field).and_then(|info| info.unit).unwrap_or("")
+}
+
+impl SubmodalityPattern {
+    /// Render as a table of raw values, units, and normalized values; the
+    /// same text as the `Display` output.
+    pub fn to_table(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for SubmodalityPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<FIELD_WIDTH$}  {:>10}  {:<4}  {:>10}",
+            "field", "value", "unit", "normalized"
+        )?;
+        let raw = pattern_to_array(self);
+        let normalized = normalized_to_array(&self.normalize());
+        for (i, info) in DIMENSIONS.iter().enumerate() {
+            write!(
+                f,
+                "\n{:<FIELD_WIDTH$}  {:>10.3}  {:<4}  {:>10.4}",
+                info.key,
+                raw[i],
+                info.unit.unwrap_or(""),
+                normalized[i]
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl PatternDiff {
+    /// Render as a table of raw and normalized deltas with each dimension's
+    /// share of the squared distance, in the current delta order, followed
+    /// by the total distance; the same text as the `Display` output.
+    pub fn to_table(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for PatternDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<FIELD_WIDTH$}  {:>10}  {:<4}  {:>10}  {:>6}",
+            "field", "raw delta", "unit", "normalized", "share"
+        )?;
+        let total = self.distance * self.distance;
+        for delta in &self.deltas {
+            let share = if total > 0.0 {
+                100.0 * delta.contribution() / total
+            } else {
+                0.0
+            };
+            write!(
+                f,
+                "\n{:<FIELD_WIDTH$}  {:>+10.3}  {:<4}  {:>+10.4}  {:>5.1}%",
+                delta.field,
+                delta.raw,
+                unit_of(delta.field),
+                delta.normalized,
+                share
+            )?;
+        }
+        write!(f, "\ndistance {:.4}", self.distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern_table_aligns_units_and_normalized_values() {
+        let table = SubmodalityPattern::zeros().to_table();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[0], "field                value  unit  normalized");
+        assert_eq!(lines[2], "color_temp        6500.000  K         0.5625");
+        assert_eq!(lines[7], "temperature         20.000  °C        0.3333");
+        assert!(lines
+            .iter()
+            .all(|line| line.chars().count() == lines[0].chars().count()));
+    }
+
+    #[test]
+    fn diff_table_shows_shares_and_distance() {
+        let target = SubmodalityPattern::zeros();
+        let measured = SubmodalityPattern {
+            pitch: 2438.0,
+            ..target.clone()
+        };
+        let mut diff = measured.diff(&target);
+        diff.sort_by_contribution();
+        let table = format!("{diff}");
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(
+            lines[1],
+            "pitch            +1998.000  Hz       +0.1000  100.0%"
+        );
+        assert_eq!(lines.last(), Some(&"distance 0.1000"));
+        assert!(target.diff(&target).to_table().contains("  0.0%"));
+    }
+}
@@ -0,0 +1,152 @@
+//! Unit conversions for the ingestion boundary.
+//!
+//! [`SubmodalityPattern`](super::SubmodalityPattern) stores temperature in
+//! degrees Celsius, color temperature in Kelvin, pitch in Hertz, and
+//! brightness in `[0, 1]`. Sensors and lighting APIs often report
+//! Fahrenheit, mireds, MIDI note numbers, or lux instead; convert with these
+//! helpers before building a pattern.
+
+/// Frequency of MIDI note 69 (A4) in Hertz.
+pub const MIDI_A4_HZ: f32 = 440.0;
+
+/// MIDI note number of A4.
+pub const MIDI_A4_NOTE: f32 = 69.0;
+
+/// Convert degrees Fahrenheit to degrees Celsius.
+pub fn fahrenheit_to_celsius(fahrenheit: f32) -> f32 {
+    (fahrenheit - 32.0) * 5.0 / 9.0
+}
+
+/// Convert degrees Celsius to degrees Fahrenheit.
+pub fn celsius_to_fahrenheit(celsius: f32) -> f32 {
+    celsius * 9.0 / 5.0 + 32.0
+}
+
+/// Convert a color temperature in mireds (micro reciprocal degrees) to Kelvin.
+///
+/// Zero mireds maps to infinity; clamp the result before storing it.
+pub fn mired_to_kelvin(mired: f32) -> f32 {
+    1_000_000.0 / mired
+}
+
+/// Convert a color temperature in Kelvin to mireds.
+pub fn kelvin_to_mired(kelvin: f32) -> f32 {
+    1_000_000.0 / kelvin
+}
+
+/// Convert a MIDI note number to Hertz in twelve-tone equal temperament.
+///
+/// Fractional notes are allowed, so pitch-bent values convert directly.
+pub fn midi_to_hz(note: f32) -> f32 {
+    MIDI_A4_HZ * ((note - MIDI_A4_NOTE) / 12.0).exp2()
+}
+
+/// Convert a frequency in Hertz to a (fractional) MIDI note number.
+///
+/// Round the result to get the nearest note.
+pub fn hz_to_midi(hz: f32) -> f32 {
+    MIDI_A4_NOTE + 12.0 * (hz / MIDI_A4_HZ).log2()
+}
+
+/// How illuminance in lux maps to normalized brightness.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LuxCurve {
+    /// `lux / max_lux`.
+    Linear {
+        /// Illuminance mapped to full brightness.
+        max_lux: f32,
+    },
+    /// `ln(lux / min_lux) / ln(max_lux / min_lux)`, closer to how brightness
+    /// is perceived across indoor and outdoor light levels.
+    Logarithmic {
+        /// Illuminance mapped to zero brightness; must be positive.
+        min_lux: f32,
+        /// Illuminance mapped to full brightness.
+        max_lux: f32,
+    },
+}
+
+impl Default for LuxCurve {
+    /// Logarithmic from 1 lux (deep twilight) to 100 000 lux (direct sun).
+    fn default() -> Self {
+        Self::Logarithmic {
+            min_lux: 1.0,
+            max_lux: 100_000.0,
+        }
+    }
+}
+
+impl LuxCurve {
+    /// Map an illuminance to brightness in `[0, 1]`, clamping out-of-range
+    /// input. NaN maps to `0.0`.
+    pub fn brightness(&self, lux: f32) -> f32 {
+        let fraction = match *self {
+            Self::Linear { max_lux } => lux / max_lux,
+            Self::Logarithmic { min_lux, max_lux } => {
+                (lux.max(min_lux) / min_lux).ln() / (max_lux / min_lux).ln()
+            }
+        };
+        if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Convert illuminance in lux to normalized brightness using `curve`.
+pub fn lux_to_brightness(lux: f32, curve: LuxCurve) -> f32 {
+    curve.brightness(lux)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32, tolerance: f32) -> bool {
+        (a - b).abs() <= tolerance
+    }
+
+    #[test]
+    fn temperature_and_color_temperature() {
+        assert_eq!(fahrenheit_to_celsius(212.0), 100.0);
+        assert_eq!(fahrenheit_to_celsius(-40.0), -40.0);
+        assert!(close(fahrenheit_to_celsius(98.6), 37.0, 1e-4));
+        assert!(close(
+            celsius_to_fahrenheit(fahrenheit_to_celsius(71.3)),
+            71.3,
+            1e-4
+        ));
+
+        assert_eq!(mired_to_kelvin(250.0), 4000.0);
+        assert!(close(kelvin_to_mired(6500.0), 153.846, 1e-3));
+        assert!(close(
+            mired_to_kelvin(kelvin_to_mired(2700.0)),
+            2700.0,
+            1e-2
+        ));
+    }
+
+    #[test]
+    fn midi_notes() {
+        assert_eq!(midi_to_hz(69.0), 440.0);
+        assert_eq!(midi_to_hz(81.0), 880.0);
+        assert!(close(midi_to_hz(60.0), 261.6256, 1e-3));
+        assert!(close(hz_to_midi(261.6256), 60.0, 1e-4));
+        assert_eq!(hz_to_midi(220.0), 57.0);
+    }
+
+    #[test]
+    fn lux_curves() {
+        let linear = LuxCurve::Linear { max_lux: 1000.0 };
+        assert_eq!(lux_to_brightness(250.0, linear), 0.25);
+        assert_eq!(lux_to_brightness(5000.0, linear), 1.0);
+        assert_eq!(lux_to_brightness(-3.0, linear), 0.0);
+
+        let curve = LuxCurve::default();
+        assert_eq!(curve.brightness(0.0), 0.0);
+        assert!(close(curve.brightness(1000.0), 0.6, 1e-6));
+        assert_eq!(curve.brightness(f32::INFINITY), 1.0);
+        assert_eq!(curve.brightness(f32::NAN), 0.0);
+    }
+}
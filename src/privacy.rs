@@ -0,0 +1,296 @@
+//! Privacy-preserving release of measured patterns.
+//!
+//! Raw measured patterns can single out participants, especially when several
+//! submodalities are combined. [`anonymize`] generalizes patterns onto a
+//! uniform grid in normalized space and coarsens the grid until every
+//! published bucket holds at least `k` records (k-anonymity over the pattern
+//! dimensions). It does not protect against attacks using auxiliary data such
+//! as timestamps or locations, which must be removed separately.
+//!
+//! For streams shared one record at a time, such as logs and telemetry,
+//! [`SubmodalityPattern::anonymize`] snaps a single pattern onto the same
+//! grid and can drop the dimensions that identify a person most readily.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::pattern::{
+    cell_center, cell_of, measured_from_array, pattern_from_array, pattern_to_array,
+    DimensionInfo, MeasuredPattern, SubmodalityPattern, DIMENSIONS,
+};
+
+/// Finest grid tried, in bins per dimension.
+pub const MAX_BINS_PER_DIMENSION: u32 = 64;
+
+/// Dimensions that reflect stable personal traits, such as voice pitch and
+/// speech or gait tempo, rather than momentary state. Coarse bins still
+/// narrow these down to a few people, so drop them when sharing widely.
+pub const HIGH_IDENTIFIABILITY_FIELDS: [&str; 2] = ["pitch", "tempo"];
+
+/// A published group of at least `k` generalized records.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Bucket {
+    /// Center of the bucket's grid cell, in raw units.
+    pub center: SubmodalityPattern,
+    /// Number of records generalized into this bucket.
+    pub count: usize,
+}
+
+/// Result of [`anonymize`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AnonymizedDataset {
+    /// Anonymity parameter every bucket satisfies.
+    pub k: usize,
+    /// Grid resolution used, in bins per normalized dimension.
+    pub bins_per_dimension: u32,
+    /// Non-empty buckets, ordered by grid cell.
+    pub buckets: Vec<Bucket>,
+    /// Mean normalized Euclidean distance between each record and its
+    /// bucket center; `0.0` means no information was lost.
+    pub information_loss: f32,
+}
+
+/// Generalize `patterns` so every bucket contains at least `k` records.
+///
+/// The grid starts at [`MAX_BINS_PER_DIMENSION`] bins per dimension and is
+/// halved until the k-anonymity condition holds; at one bin every record
+/// falls in a single bucket. Values outside a dimension's range are clamped
+/// before bucketing, matching `SubmodalityPattern::normalize`.
+pub fn anonymize(patterns: &[SubmodalityPattern], k: usize) -> Result<AnonymizedDataset> {
+    if k == 0 {
+        return Err(PrivacyError::InvalidK.into());
+    }
+    if patterns.len() < k {
+        return Err(PrivacyError::NotEnoughRecords {
+            k,
+            records: patterns.len(),
+        }
+        .into());
+    }
+    let normalized: Vec<[f32; 9]> = patterns.iter().map(normalized_array).collect();
+
+    let mut bins = MAX_BINS_PER_DIMENSION;
+    loop {
+        let mut cells: BTreeMap<[u32; 9], usize> = BTreeMap::new();
+        for values in &normalized {
+            *cells.entry(cell_of(values, bins)).or_default() += 1;
+        }
+        if bins == 1 || cells.values().all(|&count| count >= k) {
+            let loss = normalized
+                .iter()
+                .map(|values| distance(values, &cell_center(&cell_of(values, bins), bins)))
+                .sum::<f32>()
+                / normalized.len() as f32;
+            let buckets = cells
+                .into_iter()
+                .map(|(cell, count)| Bucket {
+                    center: denormalize(&cell_center(&cell, bins)),
+                    count,
+                })
+                .collect();
+            return Ok(AnonymizedDataset {
+                k,
+                bins_per_dimension: bins,
+                buckets,
+                information_loss: loss,
+            });
+        }
+        bins /= 2;
+    }
+}
+
+impl SubmodalityPattern {
+    /// Snap every dimension to the center of its bin, with `bins_per_dim`
+    /// bins across each normalized range (the grid [`anonymize`] uses).
+    ///
+    /// `bins_per_dim` must be in `1..=MAX_BINS_PER_DIMENSION`; values
+    /// outside a dimension's range are clamped first. Binning one record
+    /// does not guarantee k-anonymity; use [`anonymize`] when the whole
+    /// dataset is available.
+    pub fn anonymize(&self, bins_per_dim: u32) -> Result<SubmodalityPattern> {
+        if !(1..=MAX_BINS_PER_DIMENSION).contains(&bins_per_dim) {
+            return Err(PrivacyError::InvalidBins(bins_per_dim).into());
+        }
+        let cell = cell_of(&normalized_array(self), bins_per_dim);
+        Ok(denormalize(&cell_center(&cell, bins_per_dim)))
+    }
+
+    /// [`anonymize`](Self::anonymize), then remove the dimensions named in
+    /// `drop`, for example [`HIGH_IDENTIFIABILITY_FIELDS`].
+    pub fn anonymize_dropping(&self, bins_per_dim: u32, drop: &[&str]) -> Result<MeasuredPattern> {
+        let mut keep = [true; 9];
+        for name in drop {
+            let index = DIMENSIONS
+                .iter()
+                .position(|info| info.key == *name)
+                .ok_or_else(|| PrivacyError::UnknownDimension(name.to_string()))?;
+            keep[index] = false;
+        }
+        let binned = pattern_to_array(&self.anonymize(bins_per_dim)?);
+        Ok(measured_from_array(std::array::from_fn(|i| {
+            Some(binned[i]).filter(|_| keep[i])
+        })))
+    }
+}
+
+fn normalized_array(pattern: &SubmodalityPattern) -> [f32; 9] {
+    let mut values = pattern_to_array(pattern);
+    for (value, info) in values.iter_mut().zip(DIMENSIONS) {
+        *value = info.normalize(*value);
+    }
+    values
+}
+
+fn denormalize(values: &[f32; 9]) -> SubmodalityPattern {
+    let mut raw = *values;
+    for (value, DimensionInfo { min, max, .. }) in raw.iter_mut().zip(DIMENSIONS) {
+        *value = min + (max - min) * *value;
+    }
+    pattern_from_array(raw)
+}
+
+fn distance(a: &[f32; 9], b: &[f32; 9]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Errors returned when a dataset cannot be anonymized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrivacyError {
+    /// `k` was zero.
+    InvalidK,
+    /// Bins per dimension was outside `1..=MAX_BINS_PER_DIMENSION`.
+    InvalidBins(u32),
+    /// A dimension to drop is not a pattern field.
+    UnknownDimension(String),
+    /// Fewer than `k` records were supplied, so no bucket can satisfy `k`.
+    NotEnoughRecords {
+        /// Requested anonymity parameter.
+        k: usize,
+        /// Number of records supplied.
+        records: usize,
+    },
+}
+
+impl fmt::Display for PrivacyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidK => write!(f, "k must be at least 1"),
+            Self::InvalidBins(bins) => write!(
+                f,
+                "bins per dimension must be between 1 and {MAX_BINS_PER_DIMENSION}, got {bins}"
+            ),
+            Self::UnknownDimension(name) => write!(f, "unknown dimension `{name}`"),
+            Self::NotEnoughRecords { k, records } => {
+                write!(f, "cannot form buckets of {k} from {records} records")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PrivacyError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::pattern::{PITCH_MAX, PITCH_MIN};
+
+    fn cluster(arousal: f32, n: usize) -> Vec<SubmodalityPattern> {
+        (0..n)
+            .map(|i| {
+                let mut pattern = SubmodalityPattern::zeros();
+                pattern.arousal = arousal + i as f32 * 0.001;
+                pattern
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_bucket_meets_k() {
+        let mut patterns = cluster(0.1, 5);
+        patterns.extend(cluster(0.9, 3));
+        let released = anonymize(&patterns, 3).expect("anonymize");
+        assert!(released.buckets.iter().all(|b| b.count >= 3));
+        assert_eq!(released.buckets.iter().map(|b| b.count).sum::<usize>(), 8);
+        assert_eq!(released.buckets.len(), 2);
+        assert!(released.information_loss > 0.0);
+    }
+
+    #[test]
+    fn larger_k_coarsens_more() {
+        let mut patterns = cluster(0.1, 5);
+        patterns.extend(cluster(0.9, 3));
+        let fine = anonymize(&patterns, 3).expect("k=3");
+        let coarse = anonymize(&patterns, 5).expect("k=5");
+        assert!(coarse.bins_per_dimension < fine.bins_per_dimension);
+        assert!(coarse.information_loss >= fine.information_loss);
+        assert_eq!(coarse.buckets.len(), 1);
+    }
+
+    #[test]
+    fn single_patterns_snap_to_bin_centers() {
+        let pattern = SubmodalityPattern {
+            brightness: 0.62,
+            pitch: 1e6,
+            ..SubmodalityPattern::zeros()
+        };
+        let binned = pattern.anonymize(4).expect("bins");
+        assert_eq!(binned.brightness, 0.625);
+        assert_eq!(binned.pitch, PITCH_MAX - (PITCH_MAX - PITCH_MIN) / 8.0);
+        assert_eq!(binned.anonymize(4).expect("bins"), binned);
+        let nearby = SubmodalityPattern {
+            brightness: 0.74,
+            ..pattern.clone()
+        };
+        assert_eq!(nearby.anonymize(4).expect("bins"), binned);
+
+        let shared = pattern
+            .anonymize_dropping(4, &HIGH_IDENTIFIABILITY_FIELDS)
+            .expect("drop");
+        assert_eq!((shared.pitch, shared.tempo), (None, None));
+        assert_eq!(shared.brightness, Some(0.625));
+        assert_eq!(shared.present_count(), 7);
+    }
+
+    #[test]
+    fn rejects_invalid_binning() {
+        let pattern = SubmodalityPattern::zeros();
+        assert!(matches!(
+            pattern.anonymize(0),
+            Err(Error::Privacy(PrivacyError::InvalidBins(0)))
+        ));
+        assert!(pattern.anonymize(MAX_BINS_PER_DIMENSION + 1).is_err());
+        assert!(matches!(
+            pattern.anonymize_dropping(4, &["valence"]),
+            Err(Error::Privacy(PrivacyError::UnknownDimension(name))) if name == "valence"
+        ));
+    }
+
+    #[test]
+    fn rejects_impossible_k() {
+        let patterns = cluster(0.5, 2);
+        assert!(matches!(
+            anonymize(&patterns, 0),
+            Err(Error::Privacy(PrivacyError::InvalidK))
+        ));
+        assert!(matches!(
+            anonymize(&patterns, 3),
+            Err(Error::Privacy(PrivacyError::NotEnoughRecords {
+                k: 3,
+                records: 2
+            }))
+        ));
+    }
+}
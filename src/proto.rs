@@ -0,0 +1,285 @@
+//! Protocol Buffers types for patterns, targets, and match events.
+//!
+//! These are the `prost` message types for `proto/phenorv.proto` (package
+//! `phenorv.v1`), written out by hand so that building the crate does not
+//! require `protoc`. Other languages generate their bindings from the
+//! `.proto` file; the wire format is identical.
+//!
+//! Conversions from the native types are infallible. Conversions back use
+//! `TryFrom` where a message field may be absent or an enum value unknown.
+//!
+//! ```
+//! use phenomenological_rendezvous::proto;
+//! use phenomenological_rendezvous::SubmodalityPattern;
+//! use prost::Message;
+//!
+//! let bytes = proto::SubmodalityPattern::from(&SubmodalityPattern::zeros()).encode_to_vec();
+//! let decoded = proto::SubmodalityPattern::decode(bytes.as_slice()).unwrap();
+//! assert_eq!(SubmodalityPattern::from(decoded), SubmodalityPattern::zeros());
+//! ```
+
+use std::fmt;
+
+use crate::pattern;
+use crate::srt;
+
+/// Raw submodality values; see [`pattern::SubmodalityPattern`].
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct SubmodalityPattern {
+    #[prost(float, tag = "1")]
+    pub brightness: f32,
+    #[prost(float, tag = "2")]
+    pub color_temp: f32,
+    #[prost(float, tag = "3")]
+    pub focal_distance: f32,
+    #[prost(float, tag = "4")]
+    pub volume: f32,
+    #[prost(float, tag = "5")]
+    pub tempo: f32,
+    #[prost(float, tag = "6")]
+    pub pitch: f32,
+    #[prost(float, tag = "7")]
+    pub temperature: f32,
+    #[prost(float, tag = "8")]
+    pub movement: f32,
+    #[prost(float, tag = "9")]
+    pub arousal: f32,
+}
+
+/// A pattern with its timestamp; see [`pattern::TimedPattern`].
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct TimedPattern {
+    #[prost(uint64, tag = "1")]
+    pub timestamp: u64,
+    #[prost(message, optional, tag = "2")]
+    pub pattern: Option<SubmodalityPattern>,
+}
+
+/// Keyed PRF used for derivation; see [`srt::DerivationAlgorithm`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum DerivationAlgorithm {
+    HmacSha256 = 0,
+    Blake3Keyed = 1,
+    HmacSha3256 = 2,
+}
+
+/// A derived target; see [`srt::DerivedTarget`].
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct DerivedTarget {
+    #[prost(enumeration = "DerivationAlgorithm", tag = "1")]
+    pub algorithm: i32,
+    #[prost(message, optional, tag = "2")]
+    pub pattern: Option<SubmodalityPattern>,
+}
+
+/// One matching decision; see `audit::MatchEvent`.
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct MatchEvent {
+    #[prost(uint64, tag = "1")]
+    pub index: u64,
+    #[prost(float, tag = "2")]
+    pub distance: f32,
+    #[prost(bool, tag = "3")]
+    pub matched: bool,
+}
+
+impl From<&pattern::SubmodalityPattern> for SubmodalityPattern {
+    fn from(pattern: &pattern::SubmodalityPattern) -> Self {
+        Self {
+            brightness: pattern.brightness,
+            color_temp: pattern.color_temp,
+            focal_distance: pattern.focal_distance,
+            volume: pattern.volume,
+            tempo: pattern.tempo,
+            pitch: pattern.pitch,
+            temperature: pattern.temperature,
+            movement: pattern.movement,
+            arousal: pattern.arousal,
+        }
+    }
+}
+
+impl From<SubmodalityPattern> for pattern::SubmodalityPattern {
+    fn from(message: SubmodalityPattern) -> Self {
+        Self {
+            brightness: message.brightness,
+            color_temp: message.color_temp,
+            focal_distance: message.focal_distance,
+            volume: message.volume,
+            tempo: message.tempo,
+            pitch: message.pitch,
+            temperature: message.temperature,
+            movement: message.movement,
+            arousal: message.arousal,
+        }
+    }
+}
+
+impl From<&pattern::TimedPattern> for TimedPattern {
+    fn from(timed: &pattern::TimedPattern) -> Self {
+        Self {
+            timestamp: timed.timestamp,
+            pattern: Some((&timed.pattern).into()),
+        }
+    }
+}
+
+impl TryFrom<TimedPattern> for pattern::TimedPattern {
+    type Error = ProtoError;
+
+    fn try_from(message: TimedPattern) -> Result<Self, Self::Error> {
+        let pattern = message.pattern.ok_or(ProtoError::MissingField("pattern"))?;
+        Ok(Self::new(message.timestamp, pattern.into()))
+    }
+}
+
+impl From<srt::DerivationAlgorithm> for DerivationAlgorithm {
+    fn from(algorithm: srt::DerivationAlgorithm) -> Self {
+        match algorithm {
+            srt::DerivationAlgorithm::HmacSha256 => Self::HmacSha256,
+            srt::DerivationAlgorithm::Blake3Keyed => Self::Blake3Keyed,
+            srt::DerivationAlgorithm::HmacSha3_256 => Self::HmacSha3256,
+        }
+    }
+}
+
+impl From<DerivationAlgorithm> for srt::DerivationAlgorithm {
+    fn from(algorithm: DerivationAlgorithm) -> Self {
+        match algorithm {
+            DerivationAlgorithm::HmacSha256 => Self::HmacSha256,
+            DerivationAlgorithm::Blake3Keyed => Self::Blake3Keyed,
+            DerivationAlgorithm::HmacSha3256 => Self::HmacSha3_256,
+        }
+    }
+}
+
+impl From<&srt::DerivedTarget> for DerivedTarget {
+    fn from(target: &srt::DerivedTarget) -> Self {
+        Self {
+            algorithm: DerivationAlgorithm::from(target.algorithm) as i32,
+            pattern: Some((&target.pattern).into()),
+        }
+    }
+}
+
+impl TryFrom<DerivedTarget> for srt::DerivedTarget {
+    type Error = ProtoError;
+
+    fn try_from(message: DerivedTarget) -> Result<Self, Self::Error> {
+        let algorithm = DerivationAlgorithm::try_from(message.algorithm)
+            .map_err(|_| ProtoError::UnknownAlgorithm(message.algorithm))?;
+        let pattern = message.pattern.ok_or(ProtoError::MissingField("pattern"))?;
+        Ok(Self {
+            algorithm: algorithm.into(),
+            pattern: pattern.into(),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<&crate::audit::MatchEvent> for MatchEvent {
+    fn from(event: &crate::audit::MatchEvent) -> Self {
+        Self {
+            index: event.index,
+            distance: event.distance,
+            matched: event.matched,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<MatchEvent> for crate::audit::MatchEvent {
+    fn from(message: MatchEvent) -> Self {
+        Self {
+            index: message.index,
+            distance: message.distance,
+            matched: message.matched,
+        }
+    }
+}
+
+/// Errors returned when converting a decoded message to a native type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtoError {
+    /// A required message field was absent.
+    MissingField(&'static str),
+    /// The derivation algorithm value is not one this crate knows.
+    UnknownAlgorithm(i32),
+}
+
+impl fmt::Display for ProtoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "protobuf message is missing '{field}'"),
+            Self::UnknownAlgorithm(value) => {
+                write!(
+                    f,
+                    "unknown derivation algorithm {value} in protobuf message"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProtoError {}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+
+    use super::*;
+
+    #[test]
+    fn messages_round_trip_through_the_wire_format() {
+        let srt = srt::SemanticRendezvousToken::from_bytes([3u8; 32]);
+        let target =
+            srt::DerivedTarget::derive(&srt, b"salt", srt::DerivationAlgorithm::Blake3Keyed);
+        let bytes = DerivedTarget::from(&target).encode_to_vec();
+        let decoded = DerivedTarget::decode(bytes.as_slice()).expect("decode");
+        assert_eq!(srt::DerivedTarget::try_from(decoded), Ok(target));
+
+        let timed =
+            pattern::TimedPattern::new(1_700_000_000_000, pattern::SubmodalityPattern::zeros());
+        let bytes = TimedPattern::from(&timed).encode_to_vec();
+        let decoded = TimedPattern::decode(bytes.as_slice()).expect("decode");
+        assert_eq!(pattern::TimedPattern::try_from(decoded), Ok(timed));
+    }
+
+    #[test]
+    fn field_numbers_match_the_proto_file() {
+        // Tag 1, wire type 5 (fixed32): brightness = 1.0.
+        let message = SubmodalityPattern {
+            brightness: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(message.encode_to_vec(), [0x0d, 0x00, 0x00, 0x80, 0x3f]);
+        // Tag 1 varint, tag 2 fixed32, tag 3 varint.
+        let event = MatchEvent {
+            index: 7,
+            distance: 0.0,
+            matched: true,
+        };
+        assert_eq!(event.encode_to_vec(), [0x08, 0x07, 0x18, 0x01]);
+    }
+
+    #[test]
+    fn decoding_rejects_incomplete_targets() {
+        let missing = DerivedTarget {
+            algorithm: 0,
+            pattern: None,
+        };
+        assert_eq!(
+            srt::DerivedTarget::try_from(missing),
+            Err(ProtoError::MissingField("pattern"))
+        );
+        let unknown = DerivedTarget {
+            algorithm: 9,
+            pattern: Some(SubmodalityPattern::default()),
+        };
+        assert_eq!(
+            srt::DerivedTarget::try_from(unknown),
+            Err(ProtoError::UnknownAlgorithm(9))
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Fixed-size rendezvous descriptors for anonymity networks.
+//!
+//! A descriptor bundles what a peer publishes to be found after a match: the
+//! beacon commitment and epoch, its capability flags, and an opaque contact
+//! blob (for example an onion address or I2P destination). Every encoded
+//! descriptor is exactly [`DESCRIPTOR_LEN`] bytes, so descriptors with short
+//! and long contact blobs are indistinguishable by size on the wire.
+//!
+//! Layout (big-endian integers):
+//!
+//! | Offset | Size | Contents                                |
+//! |--------|------|-----------------------------------------|
+//! | 0      | 3    | Magic bytes `PRD`                       |
+//! | 3      | 1    | Format version (`1`)                    |
+//! | 4      | 8    | Epoch                                   |
+//! | 12     | 32   | Beacon commitment                       |
+//! | 44     | 4    | Capability flags                        |
+//! | 48     | 2    | Contact length `n`                      |
+//! | 50     | n    | Contact blob                            |
+//! | 50 + n | rest | Zero padding                            |
+
+use std::fmt;
+
+use super::version::Capabilities;
+use crate::error::Result;
+use crate::srt::SemanticRendezvousToken;
+use crate::transport::beacon_commitment;
+
+/// Size of every encoded descriptor in bytes.
+pub const DESCRIPTOR_LEN: usize = 512;
+
+/// Magic bytes at the start of every descriptor.
+const MAGIC: [u8; 3] = *b"PRD";
+
+/// Descriptor format version written by this crate.
+const VERSION: u8 = 1;
+
+/// Bytes preceding the contact blob.
+const HEADER_LEN: usize = 50;
+
+/// Largest contact blob that fits in a descriptor.
+pub const MAX_CONTACT_LEN: usize = DESCRIPTOR_LEN - HEADER_LEN;
+
+/// Information a peer publishes so matched peers can contact it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RendezvousDescriptor {
+    /// Epoch the commitment belongs to.
+    pub epoch: u64,
+    /// Beacon commitment (see [`beacon_commitment`]).
+    pub commitment: [u8; 32],
+    /// Capabilities the publishing peer supports.
+    pub capabilities: Capabilities,
+    /// Opaque contact information, at most [`MAX_CONTACT_LEN`] bytes.
+    pub contact: Vec<u8>,
+}
+
+impl RendezvousDescriptor {
+    /// Build a descriptor whose commitment is derived from an SRT and salt.
+    pub fn for_srt(
+        srt: &SemanticRendezvousToken,
+        salt: &[u8],
+        epoch: u64,
+        capabilities: Capabilities,
+        contact: Vec<u8>,
+    ) -> Self {
+        Self {
+            epoch,
+            commitment: beacon_commitment(srt, salt),
+            capabilities,
+            contact,
+        }
+    }
+
+    /// Encode into a constant-size, zero-padded buffer.
+    pub fn encode(&self) -> Result<[u8; DESCRIPTOR_LEN]> {
+        if self.contact.len() > MAX_CONTACT_LEN {
+            return Err(DescriptorError::ContactTooLong(self.contact.len()).into());
+        }
+        let mut bytes = [0u8; DESCRIPTOR_LEN];
+        bytes[..3].copy_from_slice(&MAGIC);
+        bytes[3] = VERSION;
+        bytes[4..12].copy_from_slice(&self.epoch.to_be_bytes());
+        bytes[12..44].copy_from_slice(&self.commitment);
+        bytes[44..48].copy_from_slice(&self.capabilities.bits().to_be_bytes());
+        bytes[48..50].copy_from_slice(&(self.contact.len() as u16).to_be_bytes());
+        bytes[HEADER_LEN..HEADER_LEN + self.contact.len()].copy_from_slice(&self.contact);
+        Ok(bytes)
+    }
+
+    /// Decode a descriptor, rejecting wrong sizes and non-zero padding.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != DESCRIPTOR_LEN {
+            return Err(DescriptorError::WrongLength(bytes.len()).into());
+        }
+        if bytes[..3] != MAGIC {
+            return Err(DescriptorError::BadMagic.into());
+        }
+        if bytes[3] != VERSION {
+            return Err(DescriptorError::UnsupportedVersion(bytes[3]).into());
+        }
+        let contact_len = usize::from(u16::from_be_bytes([bytes[48], bytes[49]]));
+        if contact_len > MAX_CONTACT_LEN {
+            return Err(DescriptorError::ContactTooLong(contact_len).into());
+        }
+        let contact_end = HEADER_LEN + contact_len;
+        if bytes[contact_end..].iter().any(|&b| b != 0) {
+            return Err(DescriptorError::NonZeroPadding.into());
+        }
+
+        let mut epoch = [0u8; 8];
+        epoch.copy_from_slice(&bytes[4..12]);
+        let mut commitment = [0u8; 32];
+        commitment.copy_from_slice(&bytes[12..44]);
+        let mut capabilities = [0u8; 4];
+        capabilities.copy_from_slice(&bytes[44..48]);
+        Ok(Self {
+            epoch: u64::from_be_bytes(epoch),
+            commitment,
+            capabilities: Capabilities::from_bits(u32::from_be_bytes(capabilities)),
+            contact: bytes[HEADER_LEN..contact_end].to_vec(),
+        })
+    }
+
+    /// Whether this descriptor was published for the given SRT and salt.
+    pub fn matches(&self, srt: &SemanticRendezvousToken, salt: &[u8]) -> bool {
+        self.commitment == beacon_commitment(srt, salt)
+    }
+}
+
+/// Errors returned when encoding or decoding descriptors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DescriptorError {
+    /// The contact blob exceeds [`MAX_CONTACT_LEN`].
+    ContactTooLong(usize),
+    /// The input is not exactly [`DESCRIPTOR_LEN`] bytes.
+    WrongLength(usize),
+    /// The input does not start with the descriptor magic bytes.
+    BadMagic,
+    /// The descriptor uses a format version this crate cannot read.
+    UnsupportedVersion(u8),
+    /// Bytes after the contact blob are not zero.
+    NonZeroPadding,
+}
+
+impl fmt::Display for DescriptorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ContactTooLong(len) => write!(
+                f,
+                "contact blob of {len} bytes exceeds {MAX_CONTACT_LEN} bytes"
+            ),
+            Self::WrongLength(len) => {
+                write!(f, "descriptor must be {DESCRIPTOR_LEN} bytes, got {len}")
+            }
+            Self::BadMagic => write!(f, "not a rendezvous descriptor (bad magic bytes)"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported descriptor version {version}")
+            }
+            Self::NonZeroPadding => write!(f, "descriptor padding is not zero"),
+        }
+    }
+}
+
+impl std::error::Error for DescriptorError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    fn descriptor(contact: &[u8]) -> RendezvousDescriptor {
+        let srt = SemanticRendezvousToken::from_bytes([4u8; 32]);
+        RendezvousDescriptor::for_srt(
+            &srt,
+            b"salt",
+            7,
+            Capabilities::supported(),
+            contact.to_vec(),
+        )
+    }
+
+    #[test]
+    fn round_trip_has_constant_size() {
+        let short = descriptor(b"abc.onion");
+        let long = descriptor(&[0x5a; MAX_CONTACT_LEN]);
+        let short_bytes = short.encode().expect("encode");
+        let long_bytes = long.encode().expect("encode");
+        assert_eq!(short_bytes.len(), long_bytes.len());
+        assert_eq!(
+            RendezvousDescriptor::decode(&short_bytes).expect("decode"),
+            short
+        );
+        assert_eq!(
+            RendezvousDescriptor::decode(&long_bytes).expect("decode"),
+            long
+        );
+        let srt = SemanticRendezvousToken::from_bytes([4u8; 32]);
+        assert!(short.matches(&srt, b"salt"));
+    }
+
+    #[test]
+    fn malformed_descriptors_are_rejected() {
+        assert!(matches!(
+            descriptor(&[0; MAX_CONTACT_LEN + 1]).encode(),
+            Err(Error::Descriptor(DescriptorError::ContactTooLong(_)))
+        ));
+        let mut bytes = descriptor(b"x").encode().expect("encode");
+        assert!(RendezvousDescriptor::decode(&bytes[..100]).is_err());
+        bytes[DESCRIPTOR_LEN - 1] = 1;
+        assert!(matches!(
+            RendezvousDescriptor::decode(&bytes),
+            Err(Error::Descriptor(DescriptorError::NonZeroPadding))
+        ));
+    }
+}
@@ -0,0 +1,283 @@
+//! Multi-party rendezvous.
+//!
+//! When more than two peers share an SRT, a [`GroupRendezvous`] tracks a
+//! smoothed [`Matcher`] per member and declares rendezvous either for every
+//! pair of members that are simultaneously stable ([`GroupMode::Pairwise`])
+//! or only once the whole group is ([`GroupMode::WholeGroup`]).
+//!
+//! Groups derive their own salts with [`group_salt`] and [`pair_salt`]
+//! instead of reusing the oracle-state salt directly. Beacons and meeting
+//! points computed from these salts differ from the ones two members would
+//! publish for an ordinary pairwise rendezvous, and from those of every other
+//! pair in the group, so observers cannot link them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::error::Result;
+use crate::matching::{Matcher, MatchingConfig};
+use crate::pattern::SubmodalityPattern;
+use crate::srt::SemanticRendezvousToken;
+
+/// Domain-separation label for group salts.
+const GROUP_LABEL: &[u8] = b"phenorv-group-v1|";
+
+/// Domain-separation label for pair salts within a group.
+const PAIR_LABEL: &[u8] = b"phenorv-group-pair-v1|";
+
+/// Derive the salt a group uses in place of the oracle-state `salt`.
+///
+/// The result is HMAC-SHA256 keyed by the SRT over a label, the
+/// length-prefixed `group_id`, and `salt`. Rotating `salt` (for example per
+/// `TimeOracle` epoch) rotates the group salt.
+pub fn group_salt(srt: &SemanticRendezvousToken, group_id: &[u8], salt: &[u8]) -> Vec<u8> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(srt.as_bytes()).expect("HMAC can take a 32-byte key");
+    mac.update(GROUP_LABEL);
+    mac.update(&(group_id.len() as u32).to_be_bytes());
+    mac.update(group_id);
+    mac.update(salt);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Derive the salt two group members use for a pairwise rendezvous.
+///
+/// Member ids are sorted first, so both members compute the same salt
+/// regardless of argument order.
+pub fn pair_salt(srt: &SemanticRendezvousToken, group_salt: &[u8], a: &str, b: &str) -> Vec<u8> {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(srt.as_bytes()).expect("HMAC can take a 32-byte key");
+    mac.update(PAIR_LABEL);
+    mac.update(group_salt);
+    for id in [first, second] {
+        mac.update(&(id.len() as u32).to_be_bytes());
+        mac.update(id.as_bytes());
+    }
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// When a group declares rendezvous.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupMode {
+    /// Every pair of members that are stable at the same time rendezvous.
+    Pairwise,
+    /// Rendezvous only once every member is stable at the same time.
+    WholeGroup,
+}
+
+/// Current rendezvous state of a group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupOutcome {
+    /// No rendezvous has been declared.
+    Pending,
+    /// These member pairs rendezvous; each pair is sorted by id.
+    Pairs(Vec<(String, String)>),
+    /// The whole group rendezvous.
+    Group,
+}
+
+/// Tracks matching state for each member of a group sharing one SRT.
+#[derive(Debug, Clone)]
+pub struct GroupRendezvous {
+    /// When rendezvous is declared.
+    mode: GroupMode,
+    /// Per-member matcher and whether its latest observation was stable.
+    members: BTreeMap<String, (Matcher, bool)>,
+}
+
+impl GroupRendezvous {
+    /// Create a group of at least two distinct members.
+    pub fn new<I, S>(config: MatchingConfig, mode: GroupMode, members: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        config.validate()?;
+        let mut map = BTreeMap::new();
+        for id in members {
+            let id = id.into();
+            if map.contains_key(&id) {
+                return Err(GroupError::DuplicateMember(id).into());
+            }
+            map.insert(id, (Matcher::new(config), false));
+        }
+        if map.len() < 2 {
+            return Err(GroupError::TooFewMembers(map.len()).into());
+        }
+        Ok(Self { mode, members: map })
+    }
+
+    /// The group's rendezvous mode.
+    pub fn mode(&self) -> GroupMode {
+        self.mode
+    }
+
+    /// Member ids in sorted order.
+    pub fn members(&self) -> impl Iterator<Item = &str> {
+        self.members.keys().map(String::as_str)
+    }
+
+    /// Feed one member's measurement and return whether that member is stable.
+    pub fn observe(
+        &mut self,
+        member: &str,
+        measured: &SubmodalityPattern,
+        target: &SubmodalityPattern,
+    ) -> Result<bool> {
+        let (matcher, stable) = self
+            .members
+            .get_mut(member)
+            .ok_or_else(|| GroupError::UnknownMember(member.to_string()))?;
+        *stable = matcher.observe(measured, target);
+        Ok(*stable)
+    }
+
+    /// Sorted pairs of members that are currently stable together.
+    pub fn stable_pairs(&self) -> Vec<(String, String)> {
+        let stable: Vec<&String> = self
+            .members
+            .iter()
+            .filter(|(_, (_, stable))| *stable)
+            .map(|(id, _)| id)
+            .collect();
+        let mut pairs = Vec::new();
+        for (i, a) in stable.iter().enumerate() {
+            for b in &stable[i + 1..] {
+                pairs.push(((*a).clone(), (*b).clone()));
+            }
+        }
+        pairs
+    }
+
+    /// Whether every member is currently stable.
+    pub fn all_stable(&self) -> bool {
+        self.members.values().all(|(_, stable)| *stable)
+    }
+
+    /// Rendezvous state under the group's mode.
+    pub fn outcome(&self) -> GroupOutcome {
+        match self.mode {
+            GroupMode::WholeGroup if self.all_stable() => GroupOutcome::Group,
+            GroupMode::WholeGroup => GroupOutcome::Pending,
+            GroupMode::Pairwise => {
+                let pairs = self.stable_pairs();
+                if pairs.is_empty() {
+                    GroupOutcome::Pending
+                } else {
+                    GroupOutcome::Pairs(pairs)
+                }
+            }
+        }
+    }
+}
+
+/// Errors returned when building or feeding a group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupError {
+    /// Fewer than two members were supplied.
+    TooFewMembers(usize),
+    /// A member id was supplied more than once.
+    DuplicateMember(String),
+    /// An observation named a member outside the group.
+    UnknownMember(String),
+}
+
+impl fmt::Display for GroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooFewMembers(count) => {
+                write!(f, "a group needs at least 2 members, got {count}")
+            }
+            Self::DuplicateMember(id) => write!(f, "duplicate group member '{id}'"),
+            Self::UnknownMember(id) => write!(f, "'{id}' is not a member of this group"),
+        }
+    }
+}
+
+impl std::error::Error for GroupError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    fn group(mode: GroupMode) -> GroupRendezvous {
+        GroupRendezvous::new(
+            MatchingConfig::new(0.05, 1),
+            mode,
+            ["alice", "bob", "carol"],
+        )
+        .expect("group")
+    }
+
+    fn far() -> SubmodalityPattern {
+        let mut pattern = SubmodalityPattern::zeros();
+        pattern.arousal = 1.0;
+        pattern
+    }
+
+    #[test]
+    fn pairwise_mode_reports_stable_pairs() {
+        let target = SubmodalityPattern::zeros();
+        let mut group = group(GroupMode::Pairwise);
+        group.observe("carol", &target, &target).expect("carol");
+        assert_eq!(group.outcome(), GroupOutcome::Pending);
+        group.observe("alice", &target, &target).expect("alice");
+        group.observe("bob", &far(), &target).expect("bob");
+        assert_eq!(
+            group.outcome(),
+            GroupOutcome::Pairs(vec![("alice".to_string(), "carol".to_string())])
+        );
+    }
+
+    #[test]
+    fn whole_group_mode_waits_for_everyone() {
+        let target = SubmodalityPattern::zeros();
+        let mut group = group(GroupMode::WholeGroup);
+        group.observe("alice", &target, &target).expect("alice");
+        group.observe("bob", &target, &target).expect("bob");
+        assert_eq!(group.outcome(), GroupOutcome::Pending);
+        group.observe("carol", &target, &target).expect("carol");
+        assert_eq!(group.outcome(), GroupOutcome::Group);
+        group.observe("bob", &far(), &target).expect("bob");
+        assert_eq!(group.outcome(), GroupOutcome::Pending);
+    }
+
+    #[test]
+    fn membership_is_validated() {
+        let config = MatchingConfig::new(0.1, 1);
+        assert!(matches!(
+            GroupRendezvous::new(config, GroupMode::Pairwise, ["solo"]),
+            Err(Error::Group(GroupError::TooFewMembers(1)))
+        ));
+        assert!(matches!(
+            GroupRendezvous::new(config, GroupMode::Pairwise, ["a", "b", "a"]),
+            Err(Error::Group(GroupError::DuplicateMember(_)))
+        ));
+        let target = SubmodalityPattern::zeros();
+        assert!(matches!(
+            group(GroupMode::Pairwise).observe("mallory", &target, &target),
+            Err(Error::Group(GroupError::UnknownMember(_)))
+        ));
+    }
+
+    #[test]
+    fn salts_are_group_and_pair_specific() {
+        let srt = SemanticRendezvousToken::from_bytes([5u8; 32]);
+        let salt = group_salt(&srt, b"team", b"epoch-1");
+        assert_ne!(salt, b"epoch-1".to_vec());
+        assert_ne!(salt, group_salt(&srt, b"other", b"epoch-1"));
+        assert_eq!(
+            pair_salt(&srt, &salt, "alice", "bob"),
+            pair_salt(&srt, &salt, "bob", "alice")
+        );
+        assert_ne!(
+            pair_salt(&srt, &salt, "alice", "bob"),
+            pair_salt(&srt, &salt, "alice", "carol")
+        );
+    }
+}
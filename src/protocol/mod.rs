@@ -0,0 +1,62 @@
+//! Protocol-level definitions shared by peers.
+//!
+//! Nothing here performs I/O. These types describe what a peer speaks so that
+//! future networked components can agree on a common configuration before
+//! exchanging protocol messages, and how matched peers find each other.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::hex;
+use crate::srt::SemanticRendezvousToken;
+
+pub mod descriptor;
+pub mod group;
+pub mod version;
+
+/// Domain-separation label mixed into meeting-point keys.
+const MEETING_LABEL: &[u8] = b"phenorv-meeting-v1|";
+
+/// Derive the meeting-point key for an SRT and salt.
+///
+/// Once two peers have matched the target pattern for the same SRT and
+/// oracle-state salt, both can compute this key and use it to find each other
+/// on an existing DHT or pub/sub system. The key is HMAC-SHA256 keyed by the
+/// SRT over a label distinct from the beacon commitment's, so published
+/// beacons cannot be linked to meeting points. Rotating the salt (for example
+/// per `TimeOracle` epoch) rotates the key.
+pub fn meeting_point(srt: &SemanticRendezvousToken, salt: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(srt.as_bytes())
+        .expect("HMAC can take a 32-byte key");
+    mac.update(MEETING_LABEL);
+    mac.update(salt);
+    mac.finalize().into_bytes().into()
+}
+
+/// Pub/sub topic for a meeting point, as `<prefix>/meet/<hex key>`.
+pub fn meeting_topic(prefix: &str, srt: &SemanticRendezvousToken, salt: &[u8]) -> String {
+    format!("{prefix}/meet/{}", hex::encode(&meeting_point(srt, salt)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::beacon_commitment;
+
+    #[test]
+    fn meeting_point_is_deterministic_and_salted() {
+        let srt = SemanticRendezvousToken::from_bytes([9u8; 32]);
+        let a = meeting_point(&srt, b"phenorv-epoch-v1|42");
+        assert_eq!(a, meeting_point(&srt, b"phenorv-epoch-v1|42"));
+        assert_ne!(a, meeting_point(&srt, b"phenorv-epoch-v1|43"));
+        assert_ne!(a, beacon_commitment(&srt, b"phenorv-epoch-v1|42"));
+    }
+
+    #[test]
+    fn topic_embeds_hex_key() {
+        let srt = SemanticRendezvousToken::from_bytes([1u8; 32]);
+        let topic = meeting_topic("phenorv", &srt, b"salt");
+        assert!(topic.starts_with("phenorv/meet/"));
+        assert_eq!(topic.len(), "phenorv/meet/".len() + 64);
+    }
+}
@@ -0,0 +1,287 @@
+//! Protocol versioning and capability negotiation.
+//!
+//! Each peer advertises a [`Hello`] carrying the protocol versions it accepts
+//! and a [`Capabilities`] set. [`negotiate`] computes the configuration both
+//! sides support, picking one SRT scheme, one encoding, and one window policy
+//! in a fixed preference order so that both peers reach the same result
+//! independently.
+
+use std::fmt;
+use std::ops::{BitAnd, BitOr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Protocol version implemented by this crate.
+pub const PROTOCOL_VERSION: u16 = 1;
+
+/// Oldest protocol version this crate can still interoperate with.
+pub const MIN_SUPPORTED_VERSION: u16 = 1;
+
+/// A set of capability flags.
+///
+/// Flags are grouped into SRT derivation schemes, wire encodings, and
+/// temporal window policies. Unknown bits are preserved so that newer peers
+/// can advertise capabilities this version does not understand.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+    /// Target patterns derived with HMAC-SHA256 keyed by the SRT.
+    pub const SCHEME_HMAC_SHA256: Self = Self(1 << 0);
+    /// JSON messages.
+    pub const ENCODING_JSON: Self = Self(1 << 8);
+    /// Hex-encoded binary fields inside text messages.
+    pub const ENCODING_HEX: Self = Self(1 << 9);
+    /// Match after `window_size` consecutive observations within epsilon.
+    pub const WINDOW_CONSECUTIVE: Self = Self(1 << 16);
+
+    /// Mask covering scheme flags.
+    const SCHEME_MASK: u32 = 0x0000_00ff;
+    /// Mask covering encoding flags.
+    const ENCODING_MASK: u32 = 0x0000_ff00;
+    /// Mask covering window policy flags.
+    const WINDOW_MASK: u32 = 0x00ff_0000;
+
+    /// The empty set.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Every capability implemented by this crate.
+    pub const fn supported() -> Self {
+        Self(
+            Self::SCHEME_HMAC_SHA256.0
+                | Self::ENCODING_JSON.0
+                | Self::ENCODING_HEX.0
+                | Self::WINDOW_CONSECUTIVE.0,
+        )
+    }
+
+    /// Build a set from raw bits, keeping unknown bits.
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Raw bit representation.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Whether every flag in `other` is also set in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether no flags are set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Flags set in both `self` and `other`.
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Flags set in either `self` or `other`.
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Lowest set bit within `mask`, if any.
+    ///
+    /// Lower bits are preferred so that negotiation is deterministic.
+    fn preferred(self, mask: u32) -> Option<Self> {
+        let bits = self.0 & mask;
+        (bits != 0).then(|| Self(bits & bits.wrapping_neg()))
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+impl BitAnd for Capabilities {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        self.intersection(rhs)
+    }
+}
+
+/// Version and capability advertisement sent by a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Hello {
+    /// Newest protocol version the peer speaks.
+    pub version: u16,
+    /// Oldest protocol version the peer accepts.
+    pub min_version: u16,
+    /// Capabilities the peer supports.
+    pub capabilities: Capabilities,
+}
+
+impl Hello {
+    /// Advertisement for this crate's version and supported capabilities.
+    pub fn local() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            min_version: MIN_SUPPORTED_VERSION,
+            capabilities: Capabilities::supported(),
+        }
+    }
+}
+
+impl Default for Hello {
+    fn default() -> Self {
+        Self::local()
+    }
+}
+
+/// Configuration agreed on by two peers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Negotiated {
+    /// Protocol version both peers will use.
+    pub version: u16,
+    /// Selected SRT derivation scheme (a single flag).
+    pub scheme: Capabilities,
+    /// Selected encoding (a single flag).
+    pub encoding: Capabilities,
+    /// Selected window policy (a single flag).
+    pub window: Capabilities,
+    /// Every capability both peers advertised.
+    pub common: Capabilities,
+}
+
+/// Compute the mutually supported configuration for two advertisements.
+///
+/// The version is the newest one both peers accept. Within each capability
+/// group the lowest common flag wins, so both peers reach the same result
+/// regardless of which side runs the negotiation.
+pub fn negotiate(local: &Hello, remote: &Hello) -> Result<Negotiated, NegotiationError> {
+    let version = local.version.min(remote.version);
+    let floor = local.min_version.max(remote.min_version);
+    if version < floor {
+        return Err(NegotiationError::IncompatibleVersion {
+            local: local.version,
+            remote: remote.version,
+        });
+    }
+
+    let common = local.capabilities & remote.capabilities;
+    let scheme = common
+        .preferred(Capabilities::SCHEME_MASK)
+        .ok_or(NegotiationError::NoCommonScheme)?;
+    let encoding = common
+        .preferred(Capabilities::ENCODING_MASK)
+        .ok_or(NegotiationError::NoCommonEncoding)?;
+    let window = common
+        .preferred(Capabilities::WINDOW_MASK)
+        .ok_or(NegotiationError::NoCommonWindowPolicy)?;
+
+    Ok(Negotiated {
+        version,
+        scheme,
+        encoding,
+        window,
+        common,
+    })
+}
+
+/// Errors returned when peers cannot agree on a configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NegotiationError {
+    /// The peers' accepted version ranges do not overlap.
+    IncompatibleVersion {
+        /// Newest version the local peer speaks.
+        local: u16,
+        /// Newest version the remote peer speaks.
+        remote: u16,
+    },
+    /// No SRT derivation scheme is supported by both peers.
+    NoCommonScheme,
+    /// No encoding is supported by both peers.
+    NoCommonEncoding,
+    /// No window policy is supported by both peers.
+    NoCommonWindowPolicy,
+}
+
+impl fmt::Display for NegotiationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IncompatibleVersion { local, remote } => write!(
+                f,
+                "incompatible protocol versions (local {local}, remote {remote})"
+            ),
+            Self::NoCommonScheme => write!(f, "no common SRT scheme"),
+            Self::NoCommonEncoding => write!(f, "no common encoding"),
+            Self::NoCommonWindowPolicy => write!(f, "no common window policy"),
+        }
+    }
+}
+
+impl std::error::Error for NegotiationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_peers_agree() {
+        let negotiated = negotiate(&Hello::local(), &Hello::local()).expect("negotiate");
+        assert_eq!(negotiated.version, PROTOCOL_VERSION);
+        assert_eq!(negotiated.scheme, Capabilities::SCHEME_HMAC_SHA256);
+        assert_eq!(negotiated.encoding, Capabilities::ENCODING_JSON);
+        assert_eq!(negotiated.window, Capabilities::WINDOW_CONSECUTIVE);
+    }
+
+    #[test]
+    fn negotiation_is_symmetric() {
+        let newer = Hello {
+            version: 3,
+            min_version: 1,
+            capabilities: Capabilities::ENCODING_HEX
+                | Capabilities::SCHEME_HMAC_SHA256
+                | Capabilities::WINDOW_CONSECUTIVE
+                | Capabilities::from_bits(1 << 31),
+        };
+        let a = negotiate(&Hello::local(), &newer).expect("negotiate");
+        let b = negotiate(&newer, &Hello::local()).expect("negotiate");
+        assert_eq!(a, b);
+        assert_eq!(a.version, 1);
+        assert_eq!(a.encoding, Capabilities::ENCODING_HEX);
+        assert!(newer.capabilities.contains(Capabilities::from_bits(1 << 31)));
+        assert!(!a.common.contains(Capabilities::from_bits(1 << 31)));
+    }
+
+    #[test]
+    fn disjoint_peers_fail() {
+        let future = Hello {
+            version: 4,
+            min_version: 3,
+            capabilities: Capabilities::supported(),
+        };
+        assert!(matches!(
+            negotiate(&Hello::local(), &future),
+            Err(NegotiationError::IncompatibleVersion { .. })
+        ));
+        let no_window = Hello {
+            capabilities: Capabilities::SCHEME_HMAC_SHA256 | Capabilities::ENCODING_JSON,
+            ..Hello::local()
+        };
+        assert_eq!(
+            negotiate(&Hello::local(), &no_window),
+            Err(NegotiationError::NoCommonWindowPolicy)
+        );
+    }
+}
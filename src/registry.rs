@@ -0,0 +1,651 @@
+//! Runtime registry of pattern dimensions.
+//!
+//! `SubmodalityPattern` fixes the nine dimensions from the paper at compile
+//! time. Applications that want to experiment with additional submodalities
+//! can describe them in a [`DimensionRegistry`] instead and work with
+//! [`DynPattern`] values, which are normalized and compared using whatever
+//! dimensions the registry holds.
+//!
+//! Deployments that keep the nine core dimensions and only add a few can use
+//! [`PatternExt`] instead: a [`SubmodalityPattern`] plus named extension
+//! values, whose ranges and weights come from the registry.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::matching::{weighted_distance, DimensionWeights};
+use crate::pattern::{dimension, NormalizedPattern, SubmodalityPattern, DIMENSIONS};
+
+/// Name identifying a dimension in a registry and in [`DynPattern`] values.
+pub type DimensionId = String;
+
+/// How raw values of a dimension are mapped into `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Normalization {
+    /// `(value - min) / (max - min)`.
+    Linear,
+    /// `ln(value / min) / ln(max / min)`; requires `min > 0`.
+    Logarithmic,
+}
+
+/// Description of a single pattern dimension.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DimensionSpec {
+    /// Unique dimension name, used as the key in [`DynPattern`].
+    pub name: DimensionId,
+    /// Unit of raw values (e.g. `"Hz"`), or `None` for unitless ranges.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub unit: Option<String>,
+    /// Lower bound of the raw range.
+    pub min: f32,
+    /// Upper bound of the raw range.
+    pub max: f32,
+    /// Mapping from raw values into `[0, 1]`.
+    pub normalization: Normalization,
+    /// Relative weight of this dimension in distance calculations.
+    pub weight: f32,
+}
+
+impl DimensionSpec {
+    /// Create a linearly normalized dimension with unit weight.
+    pub fn linear(name: impl Into<String>, min: f32, max: f32) -> Self {
+        Self {
+            name: name.into(),
+            unit: None,
+            min,
+            max,
+            normalization: Normalization::Linear,
+            weight: 1.0,
+        }
+    }
+
+    /// Return this spec with a unit for its raw values.
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Return this spec with a different normalization.
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Return this spec with a different distance weight.
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Normalize a raw value into `[0, 1]`, clamping out-of-range input.
+    pub fn normalize(&self, value: f32) -> f32 {
+        let fraction = match self.normalization {
+            Normalization::Linear => (value - self.min) / (self.max - self.min),
+            Normalization::Logarithmic => {
+                (value.max(self.min) / self.min).ln() / (self.max / self.min).ln()
+            }
+        };
+        fraction.clamp(0.0, 1.0)
+    }
+
+    fn validate(&self) -> Result<(), RegistryError> {
+        if self.name.is_empty() {
+            return Err(RegistryError::EmptyName);
+        }
+        if !self.min.is_finite() || !self.max.is_finite() || self.min >= self.max {
+            return Err(RegistryError::InvalidRange(self.name.clone()));
+        }
+        if self.normalization == Normalization::Logarithmic && self.min <= 0.0 {
+            return Err(RegistryError::InvalidRange(self.name.clone()));
+        }
+        if !self.weight.is_finite() || self.weight < 0.0 {
+            return Err(RegistryError::InvalidWeight(self.name.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// An ordered set of dimension descriptions.
+///
+/// Dimensions keep their registration order, which is also the order used
+/// when iterating.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawDimensionRegistry"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DimensionRegistry {
+    /// Registered dimensions in registration order.
+    dimensions: Vec<DimensionSpec>,
+}
+
+impl DimensionRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry holding the nine core submodality dimensions.
+    ///
+    /// Names, ranges, and units come from [`DIMENSIONS`], and all weights are
+    /// `1.0`, so distances agree with [`euclidean_distance`](crate::matching::euclidean_distance).
+    pub fn core() -> Self {
+        let dimensions = DIMENSIONS
+            .iter()
+            .map(|info| {
+                let spec = DimensionSpec::linear(info.key, info.min, info.max);
+                match info.unit {
+                    Some(unit) => spec.with_unit(unit),
+                    None => spec,
+                }
+            })
+            .collect();
+        Self { dimensions }
+    }
+
+    /// Register a new dimension.
+    ///
+    /// Returns an error if the name is already taken or the spec is invalid
+    /// (empty name, empty or non-finite range, non-positive minimum for
+    /// logarithmic normalization, or negative weight).
+    pub fn register(&mut self, spec: DimensionSpec) -> Result<(), RegistryError> {
+        spec.validate()?;
+        if self.get(&spec.name).is_some() {
+            return Err(RegistryError::DuplicateDimension(spec.name));
+        }
+        self.dimensions.push(spec);
+        Ok(())
+    }
+
+    /// Look up a dimension by name.
+    pub fn get(&self, name: &str) -> Option<&DimensionSpec> {
+        self.dimensions.iter().find(|spec| spec.name == name)
+    }
+
+    /// Iterate over registered dimensions in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &DimensionSpec> {
+        self.dimensions.iter()
+    }
+
+    /// Number of registered dimensions.
+    pub fn len(&self) -> usize {
+        self.dimensions.len()
+    }
+
+    /// Whether the registry has no dimensions.
+    pub fn is_empty(&self) -> bool {
+        self.dimensions.is_empty()
+    }
+
+    /// Normalize every registered dimension of `pattern` into `[0, 1]`.
+    ///
+    /// Returns an error if the pattern is missing a registered dimension or
+    /// carries a dimension the registry does not know.
+    pub fn normalize(&self, pattern: &DynPattern) -> Result<DynPattern, RegistryError> {
+        self.check_keys(pattern)?;
+        let values = self
+            .dimensions
+            .iter()
+            .map(|spec| (spec.name.clone(), spec.normalize(pattern.values[&spec.name])))
+            .collect();
+        Ok(DynPattern { values })
+    }
+
+    /// Weighted Euclidean distance between two raw patterns.
+    ///
+    /// Both patterns are normalized first; each squared difference is scaled
+    /// by its dimension's weight.
+    pub fn distance(&self, a: &DynPattern, b: &DynPattern) -> Result<f32, RegistryError> {
+        self.check_keys(a)?;
+        self.check_keys(b)?;
+        let sum: f32 = self
+            .dimensions
+            .iter()
+            .map(|spec| {
+                let delta =
+                    spec.normalize(a.values[&spec.name]) - spec.normalize(b.values[&spec.name]);
+                spec.weight * delta * delta
+            })
+            .sum();
+        Ok(sum.sqrt())
+    }
+
+    /// Weighted distance between two patterns with extensions.
+    ///
+    /// The core dimensions contribute as in
+    /// [`weighted_distance`](crate::matching::weighted_distance) with
+    /// `weights`; each extension key present on both sides adds its squared
+    /// normalized difference times its registered weight. Keys supplied by
+    /// only one side are ignored, so peers with different sensor sets can
+    /// still be compared on what they share.
+    pub fn extended_distance(
+        &self,
+        a: &PatternExt,
+        b: &PatternExt,
+        weights: &DimensionWeights,
+    ) -> Result<f32, RegistryError> {
+        let a = a.normalize(self)?;
+        let b = b.normalize(self)?;
+        let extra: f32 = a
+            .extensions
+            .iter()
+            .filter_map(|(name, x)| {
+                let y = b.extensions.get(name)?;
+                let weight = self.get(name).map_or(1.0, |spec| spec.weight);
+                Some(weight * (x - y).powi(2))
+            })
+            .sum();
+        Ok((weighted_distance(&a.pattern, &b.pattern, weights).powi(2) + extra).sqrt())
+    }
+
+    /// Spec for an extension dimension, which must not be a core one.
+    fn extension(&self, name: &str) -> Result<&DimensionSpec, RegistryError> {
+        if dimension(name).is_some() {
+            return Err(RegistryError::DuplicateDimension(name.to_string()));
+        }
+        self.get(name)
+            .ok_or_else(|| RegistryError::UnknownDimension(name.to_string()))
+    }
+
+    fn check_keys(&self, pattern: &DynPattern) -> Result<(), RegistryError> {
+        for spec in &self.dimensions {
+            if !pattern.values.contains_key(&spec.name) {
+                return Err(RegistryError::MissingDimension(spec.name.clone()));
+            }
+        }
+        for name in pattern.values.keys() {
+            if self.get(name).is_none() {
+                return Err(RegistryError::UnknownDimension(name.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A pattern over an arbitrary set of named dimensions.
+///
+/// Serializes as a flat JSON object mapping dimension names to values.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct DynPattern {
+    /// Values keyed by dimension name.
+    values: BTreeMap<DimensionId, f32>,
+}
+
+impl DynPattern {
+    /// Create an empty pattern.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a dimension value, returning the previous value if any.
+    pub fn set(&mut self, name: impl Into<String>, value: f32) -> Option<f32> {
+        self.values.insert(name.into(), value)
+    }
+
+    /// Return this pattern with a dimension value set.
+    pub fn with(mut self, name: impl Into<String>, value: f32) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Get a dimension value by name.
+    pub fn get(&self, name: &str) -> Option<f32> {
+        self.values.get(name).copied()
+    }
+
+    /// Iterate over `(name, value)` pairs in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.values.iter().map(|(name, value)| (name.as_str(), *value))
+    }
+}
+
+impl From<SubmodalityPattern> for DynPattern {
+    fn from(pattern: SubmodalityPattern) -> Self {
+        Self::from(&pattern)
+    }
+}
+
+impl From<&SubmodalityPattern> for DynPattern {
+    fn from(pattern: &SubmodalityPattern) -> Self {
+        DynPattern::new()
+            .with("brightness", pattern.brightness)
+            .with("color_temp", pattern.color_temp)
+            .with("focal_distance", pattern.focal_distance)
+            .with("volume", pattern.volume)
+            .with("tempo", pattern.tempo)
+            .with("pitch", pattern.pitch)
+            .with("temperature", pattern.temperature)
+            .with("movement", pattern.movement)
+            .with("arousal", pattern.arousal)
+    }
+}
+
+/// Convert back to the fixed nine-field pattern.
+///
+/// Succeeds only when the pattern holds exactly the nine core dimensions, so
+/// a round trip through [`DynPattern`] is lossless.
+impl TryFrom<&DynPattern> for SubmodalityPattern {
+    type Error = RegistryError;
+
+    fn try_from(pattern: &DynPattern) -> Result<Self, Self::Error> {
+        DimensionRegistry::core().check_keys(pattern)?;
+        let value = |name: &str| pattern.values[name];
+        Ok(Self {
+            brightness: value("brightness"),
+            color_temp: value("color_temp"),
+            focal_distance: value("focal_distance"),
+            volume: value("volume"),
+            tempo: value("tempo"),
+            pitch: value("pitch"),
+            temperature: value("temperature"),
+            movement: value("movement"),
+            arousal: value("arousal"),
+        })
+    }
+}
+
+impl TryFrom<DynPattern> for SubmodalityPattern {
+    type Error = RegistryError;
+
+    fn try_from(pattern: DynPattern) -> Result<Self, Self::Error> {
+        Self::try_from(&pattern)
+    }
+}
+
+/// A core pattern with additional named dimensions.
+///
+/// Serializes as a [`SubmodalityPattern`] with an `extensions` object, so
+/// readers that only know the core fields can still load it. Extension
+/// values are raw; their ranges come from a [`DimensionRegistry`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PatternExt {
+    /// The nine core dimensions.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub pattern: SubmodalityPattern,
+    /// Raw extension values keyed by dimension name.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub extensions: BTreeMap<DimensionId, f32>,
+}
+
+impl PatternExt {
+    /// Wrap a core pattern with no extensions.
+    pub fn new(pattern: SubmodalityPattern) -> Self {
+        Self {
+            pattern,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    /// Return this pattern with an extension value set.
+    pub fn with_extension(mut self, name: impl Into<String>, value: f32) -> Self {
+        self.extensions.insert(name.into(), value);
+        self
+    }
+
+    /// Normalize the core dimensions with the reference ranges and each
+    /// extension with its spec in `registry`.
+    ///
+    /// Fails if an extension is not registered or shadows a core dimension.
+    pub fn normalize(
+        &self,
+        registry: &DimensionRegistry,
+    ) -> Result<NormalizedPatternExt, RegistryError> {
+        let extensions = self
+            .extensions
+            .iter()
+            .map(|(name, value)| {
+                let spec = registry.extension(name)?;
+                Ok((name.clone(), spec.normalize(*value)))
+            })
+            .collect::<Result<_, RegistryError>>()?;
+        Ok(NormalizedPatternExt {
+            pattern: self.pattern.normalize(),
+            extensions,
+        })
+    }
+}
+
+impl From<SubmodalityPattern> for PatternExt {
+    fn from(pattern: SubmodalityPattern) -> Self {
+        Self::new(pattern)
+    }
+}
+
+/// A [`PatternExt`] with every value mapped into `[0, 1]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedPatternExt {
+    /// The normalized core dimensions.
+    pub pattern: NormalizedPattern,
+    /// Normalized extension values keyed by dimension name.
+    pub extensions: BTreeMap<DimensionId, f32>,
+}
+
+/// Unchecked wire form of [`DimensionRegistry`], validated on conversion.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawDimensionRegistry {
+    dimensions: Vec<DimensionSpec>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawDimensionRegistry> for DimensionRegistry {
+    type Error = RegistryError;
+
+    fn try_from(raw: RawDimensionRegistry) -> Result<Self, Self::Error> {
+        let mut registry = Self::new();
+        for spec in raw.dimensions {
+            registry.register(spec)?;
+        }
+        Ok(registry)
+    }
+}
+
+/// Errors returned by registry operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    /// A dimension was registered with an empty name.
+    EmptyName,
+    /// A dimension with this name is already registered.
+    DuplicateDimension(String),
+    /// A dimension's range was empty, non-finite, or unsuitable for its
+    /// normalization.
+    InvalidRange(String),
+    /// A dimension's weight was negative or non-finite.
+    InvalidWeight(String),
+    /// A pattern was missing a registered dimension.
+    MissingDimension(String),
+    /// A pattern carried a dimension the registry does not know.
+    UnknownDimension(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyName => write!(f, "dimension name must not be empty"),
+            Self::DuplicateDimension(name) => write!(f, "dimension '{name}' already registered"),
+            Self::InvalidRange(name) => write!(f, "invalid range for dimension '{name}'"),
+            Self::InvalidWeight(name) => write!(f, "invalid weight for dimension '{name}'"),
+            Self::MissingDimension(name) => write!(f, "pattern is missing dimension '{name}'"),
+            Self::UnknownDimension(name) => write!(f, "unknown dimension '{name}'"),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::euclidean_distance;
+
+    #[test]
+    fn core_registry_agrees_with_fixed_distance() {
+        let registry = DimensionRegistry::core();
+        let a = SubmodalityPattern::zeros();
+        let b = SubmodalityPattern {
+            brightness: 0.9,
+            pitch: 1200.0,
+            ..SubmodalityPattern::zeros()
+        };
+        let fixed = euclidean_distance(&a.normalize(), &b.normalize());
+        let dynamic = registry
+            .distance(&DynPattern::from(&a), &DynPattern::from(&b))
+            .expect("core dimensions present");
+        assert!((fixed - dynamic).abs() < 1e-6);
+    }
+
+    #[test]
+    fn custom_dimension_participates_in_distance() {
+        let mut registry = DimensionRegistry::core();
+        registry
+            .register(DimensionSpec::linear("olfactory", 0.0, 10.0).with_weight(4.0))
+            .expect("register");
+
+        let base = DynPattern::from(&SubmodalityPattern::zeros()).with("olfactory", 0.0);
+        let shifted = base.clone().with("olfactory", 5.0);
+        let distance = registry.distance(&base, &shifted).expect("distance");
+        assert!((distance - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn extensions_shared_by_both_sides_add_to_distance() {
+        let mut registry = DimensionRegistry::new();
+        registry
+            .register(DimensionSpec::linear("olfactory", 0.0, 10.0))
+            .expect("register");
+        registry
+            .register(DimensionSpec::linear("proprioceptive_load", 0.0, 1.0).with_weight(4.0))
+            .expect("register");
+        let weights = DimensionWeights::default();
+
+        let core = PatternExt::new(SubmodalityPattern::zeros());
+        let a = core.clone().with_extension("olfactory", 2.0);
+        let b = core
+            .clone()
+            .with_extension("olfactory", 8.0)
+            .with_extension("proprioceptive_load", 0.5);
+        let distance = registry
+            .extended_distance(&a, &b, &weights)
+            .expect("distance");
+        assert!((distance - 0.6).abs() < 1e-6);
+
+        let c = a.clone().with_extension("proprioceptive_load", 1.0);
+        let distance = registry
+            .extended_distance(&b, &c, &weights)
+            .expect("distance");
+        assert!((distance - (0.36f32 + 1.0).sqrt()).abs() < 1e-6);
+        assert_eq!(registry.extended_distance(&core, &core, &weights), Ok(0.0));
+
+        assert_eq!(
+            core.clone()
+                .with_extension("gustatory", 1.0)
+                .normalize(&registry),
+            Err(RegistryError::UnknownDimension("gustatory".into()))
+        );
+        assert_eq!(
+            core.with_extension("pitch", 1.0)
+                .normalize(&DimensionRegistry::core()),
+            Err(RegistryError::DuplicateDimension("pitch".into()))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn extensions_serialize_beside_core_fields() {
+        let pattern = PatternExt::new(SubmodalityPattern::zeros()).with_extension("olfactory", 3.5);
+        let json = serde_json::to_value(&pattern).expect("serialize");
+        assert_eq!(json["pitch"], 440.0);
+        assert_eq!(json["extensions"]["olfactory"], 3.5);
+        let restored: PatternExt = serde_json::from_value(json.clone()).expect("deserialize");
+        assert_eq!(restored, pattern);
+        let core: SubmodalityPattern = serde_json::from_value(json).expect("core");
+        assert_eq!(core, pattern.pattern);
+        let plain = serde_json::to_value(PatternExt::from(core)).expect("serialize");
+        assert!(plain.get("extensions").is_none());
+    }
+
+    #[test]
+    fn registration_is_validated() {
+        let mut registry = DimensionRegistry::core();
+        assert_eq!(
+            registry.register(DimensionSpec::linear("pitch", 0.0, 1.0)),
+            Err(RegistryError::DuplicateDimension("pitch".into()))
+        );
+        assert_eq!(
+            registry.register(
+                DimensionSpec::linear("loudness", 0.0, 1.0)
+                    .with_normalization(Normalization::Logarithmic)
+            ),
+            Err(RegistryError::InvalidRange("loudness".into()))
+        );
+        assert_eq!(
+            registry.register(DimensionSpec::linear("load", 0.0, 1.0).with_weight(-1.0)),
+            Err(RegistryError::InvalidWeight("load".into()))
+        );
+    }
+
+    #[test]
+    fn core_patterns_round_trip_losslessly() {
+        let pattern = SubmodalityPattern {
+            color_temp: 6543.21,
+            pitch: 440.125,
+            arousal: 0.333,
+            ..SubmodalityPattern::zeros()
+        };
+        let dynamic = DynPattern::from(pattern.clone());
+        assert_eq!(SubmodalityPattern::try_from(&dynamic), Ok(pattern));
+        assert_eq!(
+            SubmodalityPattern::try_from(dynamic.with("olfactory", 1.0)),
+            Err(RegistryError::UnknownDimension("olfactory".into()))
+        );
+        assert_eq!(
+            DimensionRegistry::core()
+                .get("pitch")
+                .and_then(|spec| spec.unit.as_deref()),
+            Some("Hz")
+        );
+    }
+
+    #[test]
+    fn patterns_must_cover_registered_dimensions() {
+        let registry = DimensionRegistry::core();
+        let pattern = DynPattern::new().with("brightness", 0.5);
+        assert_eq!(
+            registry.normalize(&pattern),
+            Err(RegistryError::MissingDimension("color_temp".into()))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn registry_deserialization_checks_specs() {
+        let registry = DimensionRegistry::core();
+        let json = serde_json::to_value(&registry).unwrap();
+        let decoded: DimensionRegistry = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(decoded, registry);
+
+        let mut duplicated = json.clone();
+        let first = duplicated["dimensions"][0].clone();
+        duplicated["dimensions"].as_array_mut().unwrap().push(first);
+        assert!(serde_json::from_value::<DimensionRegistry>(duplicated).is_err());
+
+        let mut inverted = json;
+        inverted["dimensions"][0]["min"] = serde_json::json!(2.0);
+        assert!(serde_json::from_value::<DimensionRegistry>(inverted).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+//! Adaptive sensor sampling.
+//!
+//! Sampling every sensor at a fixed high rate wastes battery while a peer is
+//! nowhere near the target pattern. A [`Pacer`] asks a [`SamplingPolicy`]
+//! how long to wait before the next sample given the most recent distance to
+//! the target, so clients sample quickly when a match is plausible and back
+//! off when it is not. Time is passed in explicitly, which keeps pacing
+//! deterministic in tests and simulations.
+
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+
+/// Decides how long to wait before the next sample.
+pub trait SamplingPolicy {
+    /// Interval to wait after observing `distance` with matching threshold
+    /// `epsilon`.
+    fn interval(&self, distance: f32, epsilon: f32) -> Duration;
+}
+
+/// Always samples at the same interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedInterval(pub Duration);
+
+impl SamplingPolicy for FixedInterval {
+    fn interval(&self, _distance: f32, _epsilon: f32) -> Duration {
+        self.0
+    }
+}
+
+/// Samples faster as the distance to the target shrinks.
+///
+/// Distances are measured in multiples of `epsilon`. At or below
+/// `near_factor * epsilon` the pacer uses `min_interval`; at or beyond
+/// `far_factor * epsilon` it uses `max_interval`; in between the interval is
+/// interpolated linearly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProximityPolicy {
+    /// Interval used when close to the target.
+    min_interval: Duration,
+    /// Interval used when far from the target.
+    max_interval: Duration,
+    /// Distance, in multiples of epsilon, treated as close.
+    near_factor: f32,
+    /// Distance, in multiples of epsilon, treated as far.
+    far_factor: f32,
+}
+
+impl ProximityPolicy {
+    /// Create a policy, rejecting inverted intervals or factors.
+    pub fn new(
+        min_interval: Duration,
+        max_interval: Duration,
+        near_factor: f32,
+        far_factor: f32,
+    ) -> Result<Self> {
+        if min_interval > max_interval {
+            return Err(Error::InvalidConfig(
+                "min_interval must not exceed max_interval".to_string(),
+            ));
+        }
+        if !(near_factor.is_finite() && far_factor.is_finite())
+            || near_factor < 0.0
+            || near_factor >= far_factor
+        {
+            return Err(Error::InvalidConfig(format!(
+                "proximity factors must satisfy 0 <= near < far, got {near_factor} and {far_factor}"
+            )));
+        }
+        Ok(Self {
+            min_interval,
+            max_interval,
+            near_factor,
+            far_factor,
+        })
+    }
+}
+
+impl Default for ProximityPolicy {
+    /// 100 ms within 2·epsilon, 2 s beyond 10·epsilon.
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_millis(100),
+            max_interval: Duration::from_secs(2),
+            near_factor: 2.0,
+            far_factor: 10.0,
+        }
+    }
+}
+
+impl SamplingPolicy for ProximityPolicy {
+    fn interval(&self, distance: f32, epsilon: f32) -> Duration {
+        if !distance.is_finite() || epsilon <= 0.0 {
+            return self.max_interval;
+        }
+        let ratio = distance / epsilon;
+        if ratio <= self.near_factor {
+            return self.min_interval;
+        }
+        if ratio >= self.far_factor {
+            return self.max_interval;
+        }
+        let t = (ratio - self.near_factor) / (self.far_factor - self.near_factor);
+        self.min_interval + (self.max_interval - self.min_interval).mul_f32(t)
+    }
+}
+
+/// Tracks when the next sample is due under a [`SamplingPolicy`].
+#[derive(Debug, Clone)]
+pub struct Pacer<P> {
+    /// Policy mapping distance to interval.
+    policy: P,
+    /// Matching threshold passed to the policy.
+    epsilon: f32,
+    /// Time the next sample is due, or `None` before the first sample.
+    next_due: Option<Instant>,
+}
+
+impl<P: SamplingPolicy> Pacer<P> {
+    /// Create a pacer; the first sample is due immediately.
+    pub fn new(policy: P, epsilon: f32) -> Self {
+        Self {
+            policy,
+            epsilon,
+            next_due: None,
+        }
+    }
+
+    /// Whether a sample should be taken at `now`.
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.next_due.is_none_or(|due| now >= due)
+    }
+
+    /// Time remaining until the next sample is due (zero if already due).
+    pub fn time_until_due(&self, now: Instant) -> Duration {
+        self.next_due
+            .map_or(Duration::ZERO, |due| due.saturating_duration_since(now))
+    }
+
+    /// Record a sample taken at `now` and return when the next one is due.
+    pub fn record(&mut self, distance: f32, now: Instant) -> Instant {
+        let due = now + self.policy.interval(distance, self.epsilon);
+        self.next_due = Some(due);
+        due
+    }
+
+    /// The underlying policy.
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proximity_interpolates_between_bounds() {
+        let policy = ProximityPolicy::default();
+        assert_eq!(policy.interval(0.05, 0.1), Duration::from_millis(100));
+        assert_eq!(policy.interval(5.0, 0.1), Duration::from_secs(2));
+        assert_eq!(policy.interval(f32::NAN, 0.1), Duration::from_secs(2));
+        let mid = policy.interval(0.6, 0.1);
+        assert!(mid > Duration::from_millis(1000) && mid < Duration::from_millis(1100));
+    }
+
+    #[test]
+    fn invalid_policies_are_rejected() {
+        let short = Duration::from_millis(10);
+        let long = Duration::from_secs(1);
+        assert!(ProximityPolicy::new(long, short, 1.0, 2.0).is_err());
+        assert!(ProximityPolicy::new(short, long, 3.0, 2.0).is_err());
+        assert!(ProximityPolicy::new(short, long, 0.0, 2.0).is_ok());
+    }
+
+    #[test]
+    fn pacer_tracks_due_time() {
+        let start = Instant::now();
+        let mut pacer = Pacer::new(FixedInterval(Duration::from_millis(250)), 0.1);
+        assert!(pacer.is_due(start));
+
+        let due = pacer.record(0.4, start);
+        assert_eq!(due, start + Duration::from_millis(250));
+        assert!(!pacer.is_due(start + Duration::from_millis(100)));
+        assert_eq!(
+            pacer.time_until_due(start + Duration::from_millis(100)),
+            Duration::from_millis(150)
+        );
+        assert!(pacer.is_due(due));
+    }
+}
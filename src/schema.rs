@@ -0,0 +1,111 @@
+//! JSON Schemas for the crate's serialized types.
+//!
+//! Non-Rust implementations and API gateways can use these schemas to
+//! validate payloads (patterns, configs, results, and protocol messages)
+//! without reading the Rust sources.
+
+use std::collections::BTreeMap;
+
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+use crate::audit::{AuditEntry, MatchEvent};
+use crate::calibration::CalibrationProfile;
+use crate::datasets::{DatasetMapping, PatternTrajectory, PeerModel};
+use crate::matching::{DimensionWeights, DriftConfig, DuplicatePolicy, MatchingConfig};
+use crate::pattern::diff::PatternDiff;
+use crate::pattern::{
+    DimensionInfo, MeasuredPattern, NormalizedPattern, PatternCell, QuantizedPattern, SanitizePolicy,
+    SubmodalityPattern, TimedPattern,
+};
+use crate::privacy::AnonymizedDataset;
+use crate::protocol::version::{Hello, Negotiated};
+use crate::registry::{DimensionRegistry, DynPattern, PatternExt};
+use crate::series::{PatternRate, PatternSeries, TimedRate};
+use crate::sim::{EpsilonSolution, RocPoint, SimulationConfig, SimulationResult, SweepResult};
+use crate::srt::commitment::{CommitmentOpening, PatternCommitment};
+use crate::srt::group::{GroupCommitment, MemberCommitment, MembershipProof};
+use crate::srt::ratchet::SrtRatchet;
+use crate::srt::record::SrtRecord;
+use crate::srt::target::EncodedTarget;
+use crate::srt::{
+    ConfirmationTag, DerivationSpec, DerivedTarget, DimensionSource, SemanticRendezvousToken,
+};
+use crate::transport::Beacon;
+
+/// Generate schemas for every public serialized type, keyed by type name.
+pub fn all_schemas() -> BTreeMap<&'static str, RootSchema> {
+    BTreeMap::from([
+        ("AnonymizedDataset", schema_for!(AnonymizedDataset)),
+        ("AuditEntry", schema_for!(AuditEntry)),
+        ("Beacon", schema_for!(Beacon)),
+        ("CalibrationProfile", schema_for!(CalibrationProfile)),
+        ("CommitmentOpening", schema_for!(CommitmentOpening)),
+        ("ConfirmationTag", schema_for!(ConfirmationTag)),
+        ("DatasetMapping", schema_for!(DatasetMapping)),
+        ("DerivationSpec", schema_for!(DerivationSpec)),
+        ("DerivedTarget", schema_for!(DerivedTarget)),
+        ("DimensionInfo", schema_for!(DimensionInfo)),
+        ("DimensionRegistry", schema_for!(DimensionRegistry)),
+        ("DimensionSource", schema_for!(DimensionSource)),
+        ("DimensionWeights", schema_for!(DimensionWeights)),
+        ("DriftConfig", schema_for!(DriftConfig)),
+        ("DuplicatePolicy", schema_for!(DuplicatePolicy)),
+        ("DynPattern", schema_for!(DynPattern)),
+        ("EncodedTarget", schema_for!(EncodedTarget)),
+        ("EpsilonSolution", schema_for!(EpsilonSolution)),
+        ("GroupCommitment", schema_for!(GroupCommitment)),
+        ("Hello", schema_for!(Hello)),
+        ("MatchEvent", schema_for!(MatchEvent)),
+        ("MatchingConfig", schema_for!(MatchingConfig)),
+        ("MeasuredPattern", schema_for!(MeasuredPattern)),
+        ("MemberCommitment", schema_for!(MemberCommitment)),
+        ("MembershipProof", schema_for!(MembershipProof)),
+        ("Negotiated", schema_for!(Negotiated)),
+        ("NormalizedPattern", schema_for!(NormalizedPattern)),
+        ("PatternCell", schema_for!(PatternCell)),
+        ("PatternCommitment", schema_for!(PatternCommitment)),
+        ("PatternDiff", schema_for!(PatternDiff)),
+        ("PatternExt", schema_for!(PatternExt)),
+        ("PatternRate", schema_for!(PatternRate)),
+        ("PatternSeries", schema_for!(PatternSeries)),
+        ("PatternTrajectory", schema_for!(PatternTrajectory)),
+        ("PeerModel", schema_for!(PeerModel)),
+        ("QuantizedPattern", schema_for!(QuantizedPattern)),
+        ("RocPoint", schema_for!(RocPoint)),
+        ("SanitizePolicy", schema_for!(SanitizePolicy)),
+        ("SemanticRendezvousToken", schema_for!(SemanticRendezvousToken)),
+        ("SimulationConfig", schema_for!(SimulationConfig)),
+        ("SimulationResult", schema_for!(SimulationResult)),
+        ("SrtRatchet", schema_for!(SrtRatchet)),
+        ("SrtRecord", schema_for!(SrtRecord)),
+        ("SubmodalityPattern", schema_for!(SubmodalityPattern)),
+        ("SweepResult", schema_for!(SweepResult)),
+        ("TimedPattern", schema_for!(TimedPattern)),
+        ("TimedRate", schema_for!(TimedRate)),
+    ])
+}
+
+/// Generate the schema for a single type by name, if known.
+pub fn schema_by_name(name: &str) -> Option<RootSchema> {
+    all_schemas().remove(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern_schema_lists_all_fields() {
+        let schema = schema_by_name("SubmodalityPattern").expect("known type");
+        let json = serde_json::to_value(&schema).expect("serialize");
+        let properties = json["properties"].as_object().expect("properties");
+        assert_eq!(properties.len(), 9);
+        assert!(properties.contains_key("color_temperature_k"));
+    }
+
+    #[test]
+    fn unknown_type_has_no_schema() {
+        assert!(schema_by_name("NotAType").is_none());
+    }
+}
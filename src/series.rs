@@ -0,0 +1,656 @@
+//! Ordered series of timestamped patterns.
+//!
+//! Sensors sample at their own, often irregular, rates, while simulation,
+//! replay, and trajectory matching want patterns at chosen instants. A
+//! [`PatternSeries`] keeps [`TimedPattern`]s in strictly increasing timestamp
+//! order and provides linear or cubic interpolation between them, resampling
+//! to a fixed interval, windowing by time, detection of gaps where a sensor
+//! stopped reporting, and rates of change for matching on dynamics.
+
+use std::fmt;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::pattern::{
+    pattern_from_array, pattern_to_array, DimensionInfo, SubmodalityPattern, TimedPattern,
+    DIMENSIONS,
+};
+
+/// Timed patterns in strictly increasing timestamp order.
+///
+/// Serializes as a JSON array of [`TimedPattern`]s; deserialization rejects
+/// arrays that are out of order.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "Vec<TimedPattern>", into = "Vec<TimedPattern>")
+)]
+#[cfg_attr(feature = "schemars", schemars(transparent))]
+pub struct PatternSeries {
+    /// Samples sorted by timestamp, without duplicates.
+    samples: Vec<TimedPattern>,
+}
+
+/// An interval between two consecutive samples that exceeds the allowed gap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    /// Timestamp of the last sample before the gap.
+    pub start: u64,
+    /// Timestamp of the first sample after the gap.
+    pub end: u64,
+}
+
+impl Gap {
+    /// Length of the gap.
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.end - self.start)
+    }
+}
+
+/// Rate of change of every dimension, in raw units per second, in field
+/// declaration order.
+///
+/// Rates are signed and unbounded, so unlike a [`SubmodalityPattern`] they
+/// must not be normalized into the reference ranges or passed to a
+/// [`Matcher`](crate::matching::Matcher), which would clamp falling rates to
+/// zero. Compare them with [`distance`](Self::distance) instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PatternRate([f32; 9]);
+
+impl PatternRate {
+    /// Rates in raw units per second, in field declaration order.
+    pub fn new(values: [f32; 9]) -> Self {
+        Self(values)
+    }
+
+    /// Rates in raw units per second, in field declaration order.
+    pub fn values(&self) -> [f32; 9] {
+        self.0
+    }
+
+    /// Rates in reference ranges per second: each rate divided by the width
+    /// of its dimension's range, keeping its sign.
+    pub fn normalized(&self) -> [f32; 9] {
+        std::array::from_fn(|i| {
+            let DimensionInfo { min, max, .. } = DIMENSIONS[i];
+            self.0[i] / (max - min)
+        })
+    }
+
+    /// Euclidean distance between [`normalized`](Self::normalized) rates, so
+    /// every dimension counts in proportion to its range, as in matching.
+    pub fn distance(&self, other: &PatternRate) -> f32 {
+        self.normalized()
+            .into_iter()
+            .zip(other.normalized())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f32>()
+            .sqrt()
+    }
+}
+
+/// A [`PatternRate`] with the timestamp of the later sample it was taken
+/// from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TimedRate {
+    /// Milliseconds since an arbitrary epoch, as in [`TimedPattern`].
+    pub timestamp: u64,
+    /// Rate of change ending at `timestamp`.
+    pub rate: PatternRate,
+}
+
+/// Smoothing applied to sample values before [`PatternSeries::derivative`]
+/// differences them.
+///
+/// Differencing amplifies sensor noise, the more so the closer together the
+/// samples are; smoothing trades that noise for a little lag.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Smoothing {
+    /// Difference the raw values.
+    #[default]
+    None,
+    /// Trailing mean of the last `window` samples (fewer at the start).
+    MovingAverage {
+        /// Number of samples averaged; at least 1.
+        window: usize,
+    },
+    /// Exponential moving average, `alpha * value + (1 - alpha) * previous`.
+    Exponential {
+        /// Weight of the newest sample, in `(0, 1]`.
+        alpha: f32,
+    },
+}
+
+impl Smoothing {
+    fn apply(self, values: &[[f32; 9]]) -> Result<Vec<[f32; 9]>, SeriesError> {
+        match self {
+            Self::None => Ok(values.to_vec()),
+            Self::MovingAverage { window } => {
+                if window == 0 {
+                    return Err(SeriesError::InvalidSmoothing);
+                }
+                Ok((0..values.len())
+                    .map(|end| {
+                        let trailing = &values[(end + 1).saturating_sub(window)..=end];
+                        std::array::from_fn(|i| {
+                            trailing.iter().map(|value| value[i]).sum::<f32>()
+                                / trailing.len() as f32
+                        })
+                    })
+                    .collect())
+            }
+            Self::Exponential { alpha } => {
+                if !(alpha > 0.0 && alpha <= 1.0) {
+                    return Err(SeriesError::InvalidSmoothing);
+                }
+                let mut smoothed: Vec<[f32; 9]> = Vec::with_capacity(values.len());
+                for value in values {
+                    let next = match smoothed.last() {
+                        Some(previous) => {
+                            std::array::from_fn(|i| alpha * value[i] + (1.0 - alpha) * previous[i])
+                        }
+                        None => *value,
+                    };
+                    smoothed.push(next);
+                }
+                Ok(smoothed)
+            }
+        }
+    }
+}
+
+/// How [`PatternSeries::interpolate_at`] fills in values between samples.
+///
+/// Linear interpolation has a corner at every sample, which shows up as a
+/// step in [`PatternSeries::derivative`] of a resampled stream. The cubic
+/// methods pass through every sample with a continuous first derivative.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Straight lines between neighbouring samples.
+    #[default]
+    Linear,
+    /// Catmull-Rom spline: cubic Hermite segments whose slope at each sample
+    /// is that of the line through its two neighbours (one-sided at the
+    /// ends). Reproduces smooth curves closely but may overshoot near sharp
+    /// changes, even beyond the reference ranges.
+    CatmullRom,
+    /// Monotone cubic Hermite (Fritsch-Carlson) spline: never overshoots the
+    /// neighbouring samples, and is flat at local extrema.
+    MonotoneCubic,
+}
+
+impl PatternSeries {
+    /// Create an empty series.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a series from samples that must already be in strictly
+    /// increasing timestamp order.
+    pub fn from_samples(samples: Vec<TimedPattern>) -> Result<Self, SeriesError> {
+        if let Some(index) = samples
+            .windows(2)
+            .position(|pair| pair[1].timestamp <= pair[0].timestamp)
+        {
+            return Err(SeriesError::Unordered {
+                timestamp: samples[index + 1].timestamp,
+            });
+        }
+        Ok(Self { samples })
+    }
+
+    /// Append a sample later than every sample already in the series.
+    pub fn push(&mut self, sample: TimedPattern) -> Result<(), SeriesError> {
+        if self.end().is_some_and(|end| sample.timestamp <= end) {
+            return Err(SeriesError::Unordered {
+                timestamp: sample.timestamp,
+            });
+        }
+        self.samples.push(sample);
+        Ok(())
+    }
+
+    /// Samples in timestamp order.
+    pub fn samples(&self) -> &[TimedPattern] {
+        &self.samples
+    }
+
+    /// Patterns in timestamp order, without their timestamps; pass to the
+    /// functions in [`crate::pattern::stats`].
+    pub fn patterns(&self) -> impl Iterator<Item = &SubmodalityPattern> {
+        self.samples.iter().map(|sample| &sample.pattern)
+    }
+
+    /// Number of samples.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Whether the series holds no samples.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Timestamp of the first sample.
+    pub fn start(&self) -> Option<u64> {
+        self.samples.first().map(|sample| sample.timestamp)
+    }
+
+    /// Timestamp of the last sample.
+    pub fn end(&self) -> Option<u64> {
+        self.samples.last().map(|sample| sample.timestamp)
+    }
+
+    /// Pattern at `timestamp`, interpolated linearly between the samples
+    /// around it.
+    ///
+    /// Returns `None` outside `[start, end]`; the series is not extrapolated.
+    pub fn interpolate(&self, timestamp: u64) -> Option<SubmodalityPattern> {
+        self.interpolate_at(timestamp, Interpolation::Linear)
+    }
+
+    /// Pattern at `timestamp`, interpolated with `method` between the
+    /// samples around it.
+    ///
+    /// Returns `None` outside `[start, end]`; the series is not extrapolated.
+    /// Cubic methods also use the samples on either side of that interval.
+    pub fn interpolate_at(
+        &self,
+        timestamp: u64,
+        method: Interpolation,
+    ) -> Option<SubmodalityPattern> {
+        let after = self
+            .samples
+            .partition_point(|sample| sample.timestamp < timestamp);
+        let next = self.samples.get(after)?;
+        if next.timestamp == timestamp {
+            return Some(next.pattern.clone());
+        }
+        let before = after.checked_sub(1)?;
+        let prev = &self.samples[before];
+        let t =
+            ((timestamp - prev.timestamp) as f64 / (next.timestamp - prev.timestamp) as f64) as f32;
+        let a = pattern_to_array(&prev.pattern);
+        let b = pattern_to_array(&next.pattern);
+        if method == Interpolation::Linear {
+            return Some(pattern_from_array(std::array::from_fn(|i| {
+                a[i] + (b[i] - a[i]) * t
+            })));
+        }
+        let span = (next.timestamp - prev.timestamp) as f32;
+        let (m0, m1) = (self.tangent(before, method), self.tangent(after, method));
+        let (t2, t3) = (t * t, t * t * t);
+        Some(pattern_from_array(std::array::from_fn(|i| {
+            (2.0 * t3 - 3.0 * t2 + 1.0) * a[i]
+                + (t3 - 2.0 * t2 + t) * span * m0[i]
+                + (3.0 * t2 - 2.0 * t3) * b[i]
+                + (t3 - t2) * span * m1[i]
+        })))
+    }
+
+    /// Slope per millisecond of every dimension at sample `index` for a
+    /// cubic `method`; the series must hold at least two samples.
+    fn tangent(&self, index: usize, method: Interpolation) -> [f32; 9] {
+        let last = self.samples.len() - 1;
+        let slope = |from: usize, to: usize| -> [f32; 9] {
+            let (a, b) = (&self.samples[from], &self.samples[to]);
+            let dt = (b.timestamp - a.timestamp) as f32;
+            let (a, b) = (pattern_to_array(&a.pattern), pattern_to_array(&b.pattern));
+            std::array::from_fn(|i| (b[i] - a[i]) / dt)
+        };
+        if index == 0 {
+            return slope(0, 1);
+        }
+        if index == last {
+            return slope(last - 1, last);
+        }
+        match method {
+            Interpolation::MonotoneCubic => {
+                let h0 = (self.samples[index].timestamp - self.samples[index - 1].timestamp) as f32;
+                let h1 = (self.samples[index + 1].timestamp - self.samples[index].timestamp) as f32;
+                let (d0, d1) = (slope(index - 1, index), slope(index, index + 1));
+                let (w0, w1) = (2.0 * h1 + h0, h1 + 2.0 * h0);
+                std::array::from_fn(|i| {
+                    if d0[i] * d1[i] <= 0.0 {
+                        0.0
+                    } else {
+                        (w0 + w1) / (w0 / d0[i] + w1 / d1[i])
+                    }
+                })
+            }
+            _ => slope(index - 1, index + 1),
+        }
+    }
+
+    /// Resample at a fixed `interval` from the first sample to the last.
+    ///
+    /// The result has samples at `start`, `start + interval`, and so on up
+    /// to `end`, each interpolated linearly from this series. Fails if
+    /// `interval` is shorter than one millisecond.
+    pub fn resample(&self, interval: Duration) -> Result<Self, SeriesError> {
+        self.resample_with(interval, Interpolation::Linear)
+    }
+
+    /// [`resample`](Self::resample) with a chosen interpolation `method`.
+    pub fn resample_with(
+        &self,
+        interval: Duration,
+        method: Interpolation,
+    ) -> Result<Self, SeriesError> {
+        let step = u64::try_from(interval.as_millis()).unwrap_or(u64::MAX);
+        if step == 0 {
+            return Err(SeriesError::ZeroInterval);
+        }
+        let (Some(start), Some(end)) = (self.start(), self.end()) else {
+            return Ok(Self::new());
+        };
+        let samples = (0..=(end - start) / step)
+            .map(|index| {
+                let timestamp = start + index * step;
+                let pattern = self
+                    .interpolate_at(timestamp, method)
+                    .expect("timestamp lies within the series");
+                TimedPattern::new(timestamp, pattern)
+            })
+            .collect();
+        Ok(Self { samples })
+    }
+
+    /// Samples with `start <= timestamp < end`.
+    pub fn window(&self, start: u64, end: u64) -> &[TimedPattern] {
+        let from = self
+            .samples
+            .partition_point(|sample| sample.timestamp < start);
+        let to = self
+            .samples
+            .partition_point(|sample| sample.timestamp < end);
+        &self.samples[from..to.max(from)]
+    }
+
+    /// Rate of change per second of every dimension, after `smoothing`.
+    ///
+    /// Each output sample is the backward difference between two consecutive
+    /// (smoothed) samples, stamped with the later timestamp, so the result
+    /// has one sample fewer than the series and can be computed as samples
+    /// arrive. Rates are in raw units per second (e.g. Hz/s for pitch);
+    /// compare them with a target rate such as "brightness rising, tempo
+    /// falling" using [`PatternRate::distance`].
+    pub fn derivative(&self, smoothing: Smoothing) -> Result<Vec<TimedRate>, SeriesError> {
+        let values: Vec<[f32; 9]> = self.patterns().map(pattern_to_array).collect();
+        let smoothed = smoothing.apply(&values)?;
+        let samples = self
+            .samples
+            .windows(2)
+            .zip(smoothed.windows(2))
+            .map(|(pair, values)| {
+                let seconds = (pair[1].timestamp - pair[0].timestamp) as f32 / 1000.0;
+                TimedRate {
+                    timestamp: pair[1].timestamp,
+                    rate: PatternRate::new(std::array::from_fn(|i| {
+                        (values[1][i] - values[0][i]) / seconds
+                    })),
+                }
+            })
+            .collect();
+        Ok(samples)
+    }
+
+    /// Intervals between consecutive samples longer than `max_gap`.
+    pub fn gaps(&self, max_gap: Duration) -> Vec<Gap> {
+        self.samples
+            .windows(2)
+            .map(|pair| Gap {
+                start: pair[0].timestamp,
+                end: pair[1].timestamp,
+            })
+            .filter(|gap| gap.duration() > max_gap)
+            .collect()
+    }
+}
+
+impl TryFrom<Vec<TimedPattern>> for PatternSeries {
+    type Error = SeriesError;
+
+    fn try_from(samples: Vec<TimedPattern>) -> Result<Self, Self::Error> {
+        Self::from_samples(samples)
+    }
+}
+
+impl From<PatternSeries> for Vec<TimedPattern> {
+    fn from(series: PatternSeries) -> Self {
+        series.samples
+    }
+}
+
+/// Errors returned when building or resampling a [`PatternSeries`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeriesError {
+    /// A sample is not later than the one before it.
+    Unordered {
+        /// Timestamp of the offending sample.
+        timestamp: u64,
+    },
+    /// The resampling interval is shorter than one millisecond.
+    ZeroInterval,
+    /// A moving-average window of zero or an exponential `alpha` outside
+    /// `(0, 1]`.
+    InvalidSmoothing,
+}
+
+impl fmt::Display for SeriesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unordered { timestamp } => write!(
+                f,
+                "sample at {timestamp} ms is not later than the sample before it"
+            ),
+            Self::ZeroInterval => write!(f, "resampling interval must be at least 1 ms"),
+            Self::InvalidSmoothing => write!(
+                f,
+                "smoothing window must be at least 1 and alpha must lie in (0, 1]"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SeriesError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: u64, brightness: f32) -> TimedPattern {
+        TimedPattern::new(
+            timestamp,
+            SubmodalityPattern {
+                brightness,
+                ..SubmodalityPattern::zeros()
+            },
+        )
+    }
+
+    fn series() -> PatternSeries {
+        PatternSeries::from_samples(vec![
+            sample(0, 0.0),
+            sample(100, 1.0),
+            sample(250, 0.4),
+            sample(1000, 0.4),
+        ])
+        .expect("ordered")
+    }
+
+    #[test]
+    fn interpolation_and_resampling() {
+        let series = series();
+        assert_eq!(series.interpolate(50).unwrap().brightness, 0.5);
+        assert_eq!(series.interpolate(100).unwrap().brightness, 1.0);
+        assert!((series.interpolate(175).unwrap().brightness - 0.7).abs() < 1e-6);
+        assert_eq!(series.interpolate(1001), None);
+
+        let resampled = series.resample(Duration::from_millis(300)).unwrap();
+        let timestamps: Vec<u64> = resampled.samples().iter().map(|s| s.timestamp).collect();
+        assert_eq!(timestamps, [0, 300, 600, 900]);
+        assert_eq!(resampled.samples()[2].pattern.brightness, 0.4);
+        assert_eq!(
+            series.resample(Duration::from_micros(10)),
+            Err(SeriesError::ZeroInterval)
+        );
+        assert!(PatternSeries::new()
+            .resample(Duration::from_secs(1))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn cubic_interpolation_is_smooth() {
+        let curve = |t: u64| 0.2 + 0.6 * (t as f32 / 400.0).powi(2);
+        let parabola =
+            PatternSeries::from_samples((0..=4).map(|k| sample(k * 100, curve(k * 100))).collect())
+                .expect("ordered");
+        for method in [Interpolation::CatmullRom, Interpolation::MonotoneCubic] {
+            assert_eq!(
+                parabola.interpolate_at(200, method).unwrap().brightness,
+                curve(200)
+            );
+            assert_eq!(parabola.interpolate_at(401, method), None);
+        }
+        // Central differences are exact for a parabola on a uniform grid, so
+        // interior segments reproduce it.
+        let value = parabola
+            .interpolate_at(150, Interpolation::CatmullRom)
+            .unwrap();
+        assert!((value.brightness - curve(150)).abs() < 1e-6);
+
+        // Linear resampling turns every sample into a step in the rate of
+        // change; the splines keep it continuous.
+        let largest_step = |method| {
+            let rates = parabola
+                .resample_with(Duration::from_millis(5), method)
+                .unwrap()
+                .derivative(Smoothing::None)
+                .unwrap();
+            let brightness: Vec<f32> = rates.iter().map(|r| r.rate.values()[0]).collect();
+            brightness
+                .windows(2)
+                .map(|pair| (pair[1] - pair[0]).abs())
+                .fold(0.0, f32::max)
+        };
+        let linear = largest_step(Interpolation::Linear);
+        assert!(largest_step(Interpolation::CatmullRom) * 5.0 < linear);
+        assert!(largest_step(Interpolation::MonotoneCubic) * 5.0 < linear);
+    }
+
+    #[test]
+    fn monotone_cubic_does_not_overshoot() {
+        let step = PatternSeries::from_samples(vec![
+            sample(0, 0.0),
+            sample(100, 0.0),
+            sample(200, 1.0),
+            sample(300, 1.0),
+        ])
+        .expect("ordered");
+        let values = |method| -> Vec<f32> {
+            (0..=300)
+                .map(|t| step.interpolate_at(t, method).unwrap().brightness)
+                .collect()
+        };
+        let catmull_rom = values(Interpolation::CatmullRom);
+        assert!(catmull_rom.iter().any(|&v| !(0.0..=1.0).contains(&v)));
+        let monotone = values(Interpolation::MonotoneCubic);
+        assert!(monotone.iter().all(|&v| (0.0..=1.0).contains(&v)));
+        assert!(monotone.windows(2).all(|pair| pair[1] >= pair[0]));
+    }
+
+    #[test]
+    fn windows_gaps_and_ordering() {
+        let mut series = series();
+        assert_eq!(series.window(100, 1000).len(), 2);
+        assert_eq!(series.window(500, 100), &[]);
+        assert_eq!(
+            series.gaps(Duration::from_millis(200)),
+            [Gap {
+                start: 250,
+                end: 1000
+            }]
+        );
+        assert_eq!(
+            series.push(sample(1000, 0.0)),
+            Err(SeriesError::Unordered { timestamp: 1000 })
+        );
+        series.push(sample(1001, 0.0)).unwrap();
+        assert_eq!(series.end(), Some(1001));
+        assert!(PatternSeries::from_samples(vec![sample(5, 0.0), sample(1, 0.0)]).is_err());
+    }
+
+    #[test]
+    fn derivatives_are_per_second_and_smoothable() {
+        let rates = series().derivative(Smoothing::None).unwrap();
+        let timestamps: Vec<u64> = rates.iter().map(|r| r.timestamp).collect();
+        assert_eq!(timestamps, [100, 250, 1000]);
+        let brightness: Vec<f32> = rates.iter().map(|r| r.rate.values()[0]).collect();
+        assert_eq!(brightness[0], 10.0);
+        assert!((brightness[1] + 4.0).abs() < 1e-5);
+        assert_eq!(brightness[2], 0.0);
+        assert!(rates.iter().all(|r| r.rate.values()[5] == 0.0));
+
+        let averaged = series()
+            .derivative(Smoothing::MovingAverage { window: 2 })
+            .unwrap();
+        assert_eq!(averaged[0].rate.values()[0], 5.0);
+        assert!((averaged[1].rate.values()[0] - 1.333_333).abs() < 1e-4);
+        let exponential = series()
+            .derivative(Smoothing::Exponential { alpha: 0.5 })
+            .unwrap();
+        assert_eq!(exponential[0].rate.values()[0], 5.0);
+        assert_eq!(
+            series().derivative(Smoothing::Exponential { alpha: 1.0 }),
+            Ok(rates)
+        );
+
+        assert_eq!(
+            series().derivative(Smoothing::MovingAverage { window: 0 }),
+            Err(SeriesError::InvalidSmoothing)
+        );
+        assert!(series()
+            .derivative(Smoothing::Exponential { alpha: 0.0 })
+            .is_err());
+        assert!(PatternSeries::new()
+            .derivative(Smoothing::None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn falling_rates_keep_their_sign() {
+        let rates = series().derivative(Smoothing::None).unwrap();
+        let falling = rates[1].rate;
+        assert!(falling.normalized()[0] < -3.9);
+
+        let target = PatternRate::new([-4.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        let rising = PatternRate::new([4.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(falling.distance(&target) < 1e-4);
+        assert!((rising.distance(&target) - 8.0).abs() < 1e-4);
+        assert_eq!(PatternRate::default().distance(&PatternRate::default()), 0.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialization_rejects_unordered_samples() {
+        let json = serde_json::to_string(&series()).expect("serialize");
+        assert!(json.starts_with("[{\"timestamp\":0,"));
+        let restored: PatternSeries = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(restored, series());
+        let mut samples: Vec<TimedPattern> = series().into();
+        samples.swap(0, 1);
+        let json = serde_json::to_string(&samples).expect("serialize");
+        assert!(serde_json::from_str::<PatternSeries>(&json).is_err());
+    }
+}
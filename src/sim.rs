@@ -1,18 +1,30 @@
 //! Simulation tools for testing rendezvous dynamics.
 
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod noise;
+
 use rand::Rng;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::matching::{MatchingConfig, Matcher};
-use crate::pattern::{
-    SubmodalityPattern, AROUSAL_MAX, AROUSAL_MIN, BRIGHTNESS_MAX, BRIGHTNESS_MIN, COLOR_TEMP_MAX,
-    COLOR_TEMP_MIN, FOCAL_DISTANCE_MAX, FOCAL_DISTANCE_MIN, MOVEMENT_MAX, MOVEMENT_MIN, PITCH_MAX,
-    PITCH_MIN, TEMPERATURE_MAX, TEMPERATURE_MIN, TEMPO_MAX, TEMPO_MIN, VOLUME_MAX, VOLUME_MIN,
+use crate::batch::{NormalizedBatch, PatternBatch};
+use crate::datasets::PeerModel;
+use crate::error::{Error, Result};
+use crate::matching::{
+    check_epsilon, weighted_distance, DimensionWeights, MatchingConfig, Matcher,
 };
+use crate::pattern::sampling::{PatternDistribution, Uniform};
+use crate::pattern::{normalized_to_array, NormalizedPattern, SubmodalityPattern};
 use crate::srt::{pattern_from_srt, SemanticRendezvousToken};
+use noise::NoiseModel;
+
+pub use crate::pattern::sampling::random_pattern;
 
 /// Configuration for rendezvous simulations.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SimulationConfig {
     /// Number of peers sampled per trial.
     pub num_peers: usize,
@@ -26,10 +38,205 @@ pub struct SimulationConfig {
     pub apply_geo_filter: bool,
     /// Factor to reduce candidate pool size (e.g. 1e6).
     pub geo_filter_factor: f32,
+    /// Where uniform peer sampling runs.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub backend: SimulationBackend,
+    /// Whether to record per-trial data alongside the summary.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub detail: SimulationDetail,
+    /// Extra distance metrics evaluated against the same sampled peers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub metrics: Vec<MetricSpec>,
+    /// Per-dimension weights of the baseline distance, as used by
+    /// [`MatchingConfig::weights`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "DimensionWeights::is_uniform")
+    )]
+    pub weights: DimensionWeights,
+}
+
+/// Hardware that runs the simulation inner loop.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum SimulationBackend {
+    /// Sample peers on the CPU.
+    #[default]
+    Cpu,
+    /// Sample peers in a compute shader (requires the `gpu` feature).
+    ///
+    /// Only uniform sampling runs on the GPU; simulations with a fitted prior
+    /// always run on the CPU.
+    Gpu,
+}
+
+/// How much per-trial data a simulation keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum SimulationDetail {
+    /// Aggregate counts and probabilities only.
+    #[default]
+    Summary,
+    /// A [`TrialRecord`] for every trial.
+    Trials,
+    /// A uniform reservoir sample of at most this many trial records.
+    Sample(usize),
+}
+
+/// Distance functions available for metric comparisons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum DistanceMetric {
+    /// Square root of the weighted sum of squared differences.
+    Euclidean,
+    /// Weighted sum of absolute differences.
+    Manhattan,
+    /// Largest weighted absolute difference.
+    Chebyshev,
+}
+
+/// A named metric, weight set, and threshold to compare in a simulation.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MetricSpec {
+    /// Label reported in [`MetricResult::name`].
+    pub name: String,
+    /// Distance function.
+    pub metric: DistanceMetric,
+    /// Per-dimension weights.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "DimensionWeights::is_uniform")
+    )]
+    pub weights: DimensionWeights,
+    /// Threshold for this metric; defaults to [`SimulationConfig::epsilon`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub epsilon: Option<f32>,
+}
+
+impl MetricSpec {
+    /// An unweighted metric using the config's epsilon.
+    pub fn new(name: impl Into<String>, metric: DistanceMetric) -> Self {
+        Self {
+            name: name.into(),
+            metric,
+            weights: DimensionWeights::default(),
+            epsilon: None,
+        }
+    }
+
+    /// Set per-dimension weights.
+    pub fn with_weights(mut self, weights: DimensionWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Set a threshold specific to this metric.
+    pub fn with_epsilon(mut self, epsilon: f32) -> Self {
+        self.epsilon = Some(epsilon);
+        self
+    }
+
+    /// Distance between two normalized patterns under this metric.
+    pub fn distance(&self, a: &NormalizedPattern, b: &NormalizedPattern) -> f32 {
+        let diffs = normalized_to_array(a)
+            .into_iter()
+            .zip(normalized_to_array(b))
+            .zip(self.weights.values())
+            .map(|((a, b), weight)| (a - b, weight));
+        match self.metric {
+            DistanceMetric::Euclidean => diffs.map(|(d, w)| w * d * d).sum::<f32>().sqrt(),
+            DistanceMetric::Manhattan => diffs.map(|(d, w)| w * d.abs()).sum(),
+            DistanceMetric::Chebyshev => diffs.map(|(d, w)| w * d.abs()).fold(0.0, f32::max),
+        }
+    }
+
+    /// Distances from every pattern in `batch` to `target`, column by column.
+    fn batch_distances(&self, batch: &NormalizedBatch, target: &NormalizedPattern) -> Vec<f32> {
+        let mut acc = vec![0.0f32; batch.len()];
+        for (dimension, (center, weight)) in normalized_to_array(target)
+            .into_iter()
+            .zip(self.weights.values())
+            .enumerate()
+        {
+            for (acc, value) in acc.iter_mut().zip(batch.column(dimension)) {
+                let d = value - center;
+                match self.metric {
+                    DistanceMetric::Euclidean => *acc += weight * d * d,
+                    DistanceMetric::Manhattan => *acc += weight * d.abs(),
+                    DistanceMetric::Chebyshev => *acc = acc.max(weight * d.abs()),
+                }
+            }
+        }
+        if self.metric == DistanceMetric::Euclidean {
+            for acc in &mut acc {
+                *acc = acc.sqrt();
+            }
+        }
+        acc
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(Error::InvalidConfig(
+                "metric names must not be empty".to_string(),
+            ));
+        }
+        self.epsilon.map_or(Ok(()), check_epsilon)
+    }
+}
+
+/// Match counts for one [`MetricSpec`] in a simulation run.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MetricResult {
+    /// The metric's [`MetricSpec::name`].
+    pub name: String,
+    /// Threshold the metric was evaluated with.
+    pub epsilon: f32,
+    /// Count of single-peer matches within the threshold.
+    pub single_match_count: usize,
+    /// Count of trials where two independent peers both matched.
+    pub double_match_count: usize,
+    /// Estimated probability of a single random peer matching.
+    pub single_match_probability: f64,
+    /// Estimated probability of two independent peers both matching.
+    pub double_match_probability: f64,
+}
+
+/// Raw measurements from one simulation trial, for distribution plots.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TrialRecord {
+    /// Zero-based trial index.
+    pub trial: usize,
+    /// Peers in this trial within epsilon of the target.
+    pub match_count: usize,
+    /// Smallest normalized distance from any peer to the target.
+    pub nearest_distance: f32,
+    /// Smallest distance among peers that did not match, if any missed.
+    pub nearest_miss: Option<f32>,
+    /// Mean normalized distance from the trial's peers to the target.
+    pub mean_distance: f32,
+    /// Distances of the two independent peers used for the double match.
+    pub pair_distances: [f32; 2],
+    /// Whether both peers of the pair matched.
+    pub double_match: bool,
 }
 
 /// Output metrics from a simulation run.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SimulationResult {
     /// Number of trials executed.
     pub total_trials: usize,
@@ -49,35 +256,200 @@ pub struct SimulationResult {
     pub expected_matches_in_pool: f64,
     /// Probability that at least one match exists in the pool.
     pub pool_match_probability: f64,
+    /// Per-trial records in trial order, when requested by
+    /// [`SimulationConfig::detail`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub trials: Vec<TrialRecord>,
+    /// Results for each of [`SimulationConfig::metrics`], in order.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub metrics: Vec<MetricResult>,
+}
+
+impl SimulationConfig {
+    /// Start building a validated config.
+    pub fn builder() -> SimulationConfigBuilder {
+        SimulationConfigBuilder::default()
+    }
+
+    /// Check the config's invariants.
+    ///
+    /// Peer and trial counts must be at least one (a `window_size` of 0
+    /// behaves like 1, as in [`Matcher`]), `epsilon` must be finite and
+    /// non-negative, and when the geographic filter is enabled its factor
+    /// must be finite and at least one (a smaller factor would grow the pool
+    /// rather than filter it). Weighted distances only run on the CPU.
+    pub fn validate(&self) -> Result<()> {
+        for (name, value) in [("num_peers", self.num_peers), ("num_trials", self.num_trials)] {
+            if value == 0 {
+                return Err(Error::InvalidConfig(format!("{name} must be at least 1")));
+            }
+        }
+        check_epsilon(self.epsilon)?;
+        if self.apply_geo_filter
+            && !(self.geo_filter_factor.is_finite() && self.geo_filter_factor >= 1.0)
+        {
+            return Err(Error::InvalidConfig(format!(
+                "geo_filter_factor must be finite and at least 1 when the geo filter is \
+                 enabled, got {}",
+                self.geo_filter_factor
+            )));
+        }
+        if self.detail == SimulationDetail::Sample(0) {
+            return Err(Error::InvalidConfig(
+                "a trial sample must keep at least 1 record".to_string(),
+            ));
+        }
+        if self.backend == SimulationBackend::Gpu && self.detail != SimulationDetail::Summary {
+            return Err(Error::InvalidConfig(
+                "per-trial detail is only recorded by the cpu backend".to_string(),
+            ));
+        }
+        for (index, metric) in self.metrics.iter().enumerate() {
+            metric.validate()?;
+            if self.metrics[..index]
+                .iter()
+                .any(|other| other.name == metric.name)
+            {
+                return Err(Error::InvalidConfig(format!(
+                    "metric name '{}' is used more than once",
+                    metric.name
+                )));
+            }
+        }
+        if self.backend == SimulationBackend::Gpu && !self.weights.is_uniform() {
+            return Err(Error::InvalidConfig(
+                "weighted distances only run on the cpu backend".to_string(),
+            ));
+        }
+        if self.backend == SimulationBackend::Gpu && !self.metrics.is_empty() {
+            return Err(Error::InvalidConfig(
+                "metric comparisons only run on the cpu backend".to_string(),
+            ));
+        }
+        if self.backend == SimulationBackend::Gpu && !cfg!(feature = "gpu") {
+            return Err(Error::InvalidConfig(
+                "the gpu backend requires the `gpu` feature".to_string(),
+            ));
+        }
+        Ok(())
+    }
 }
 
-/// Generate a random submodality pattern using uniform sampling per dimension.
+/// Builder for [`SimulationConfig`] that rejects invalid combinations.
 ///
-/// This assumes independence and uniform distributions across the allowed
-/// ranges. These assumptions are for exploration only and do not reflect real
-/// sensor distributions.
-pub fn random_pattern<R: Rng + ?Sized>(rng: &mut R) -> SubmodalityPattern {
-    SubmodalityPattern {
-        brightness: rng.gen_range(BRIGHTNESS_MIN..=BRIGHTNESS_MAX),
-        color_temp: rng.gen_range(COLOR_TEMP_MIN..=COLOR_TEMP_MAX),
-        focal_distance: rng.gen_range(FOCAL_DISTANCE_MIN..=FOCAL_DISTANCE_MAX),
-        volume: rng.gen_range(VOLUME_MIN..=VOLUME_MAX),
-        tempo: rng.gen_range(TEMPO_MIN..=TEMPO_MAX),
-        pitch: rng.gen_range(PITCH_MIN..=PITCH_MAX),
-        temperature: rng.gen_range(TEMPERATURE_MIN..=TEMPERATURE_MAX),
-        movement: rng.gen_range(MOVEMENT_MIN..=MOVEMENT_MAX),
-        arousal: rng.gen_range(AROUSAL_MIN..=AROUSAL_MAX),
+/// Defaults match the `simulate` CLI command, with the geographic filter
+/// disabled.
+#[derive(Debug, Clone)]
+pub struct SimulationConfigBuilder {
+    config: SimulationConfig,
+}
+
+impl Default for SimulationConfigBuilder {
+    fn default() -> Self {
+        Self {
+            config: SimulationConfig {
+                num_peers: 1000,
+                num_trials: 1000,
+                epsilon: 0.1,
+                window_size: 3,
+                apply_geo_filter: false,
+                geo_filter_factor: 1e6,
+                backend: SimulationBackend::Cpu,
+                detail: SimulationDetail::Summary,
+                metrics: Vec::new(),
+                weights: DimensionWeights::default(),
+            },
+        }
+    }
+}
+
+impl SimulationConfigBuilder {
+    /// Set the number of peers sampled per trial.
+    pub fn num_peers(mut self, num_peers: usize) -> Self {
+        self.config.num_peers = num_peers;
+        self
+    }
+
+    /// Set the number of independent trials.
+    pub fn num_trials(mut self, num_trials: usize) -> Self {
+        self.config.num_trials = num_trials;
+        self
+    }
+
+    /// Set the matching threshold in normalized space.
+    pub fn epsilon(mut self, epsilon: f32) -> Self {
+        self.config.epsilon = epsilon;
+        self
+    }
+
+    /// Set the number of consecutive samples required to match.
+    pub fn window_size(mut self, window_size: usize) -> Self {
+        self.config.window_size = window_size;
+        self
+    }
+
+    /// Enable the geographic filter with the given reduction factor.
+    pub fn geo_filter(mut self, factor: f32) -> Self {
+        self.config.apply_geo_filter = true;
+        self.config.geo_filter_factor = factor;
+        self
+    }
+
+    /// Disable the geographic filter.
+    pub fn without_geo_filter(mut self) -> Self {
+        self.config.apply_geo_filter = false;
+        self
+    }
+
+    /// Select where peer sampling runs.
+    pub fn backend(mut self, backend: SimulationBackend) -> Self {
+        self.config.backend = backend;
+        self
+    }
+
+    /// Select how much per-trial data to record.
+    pub fn detail(mut self, detail: SimulationDetail) -> Self {
+        self.config.detail = detail;
+        self
+    }
+
+    /// Add a metric to evaluate alongside the default Euclidean distance.
+    pub fn metric(mut self, metric: MetricSpec) -> Self {
+        self.config.metrics.push(metric);
+        self
+    }
+
+    /// Set the per-dimension weights of the baseline distance.
+    pub fn weights(mut self, weights: DimensionWeights) -> Self {
+        self.config.weights = weights;
+        self
+    }
+
+    /// Validate and build the config.
+    pub fn build(self) -> Result<SimulationConfig> {
+        self.config.validate()?;
+        Ok(self.config)
     }
 }
 
+/// The matcher configuration a simulated peer is judged with.
+fn matching_config(config: &SimulationConfig) -> MatchingConfig {
+    MatchingConfig::new(config.epsilon, config.window_size).with_weights(config.weights)
+}
+
 fn matches_target(
     measured: &SubmodalityPattern,
     target: &SubmodalityPattern,
-    epsilon: f32,
-    window_size: usize,
+    config: &SimulationConfig,
 ) -> bool {
-    let mut matcher = Matcher::new(MatchingConfig::new(epsilon, window_size));
-    for _ in 0..window_size.max(1) {
+    let mut matcher = Matcher::new(matching_config(config));
+    for _ in 0..config.window_size.max(1) {
         if matcher.observe(measured, target) {
             return true;
         }
@@ -92,36 +464,285 @@ fn matches_target(
 ///
 /// This uses Monte Carlo sampling over uniformly generated patterns and does
 /// not attempt to model real sensor distributions.
+///
+/// With [`SimulationBackend::Gpu`], single-peer sampling runs in a compute
+/// shader; if no GPU is usable the run falls back to the CPU. Use
+/// [`try_run_simulation`] to surface GPU errors instead.
 pub fn run_simulation(
     config: &SimulationConfig,
     srt: &SemanticRendezvousToken,
     salt: &[u8],
 ) -> SimulationResult {
+    try_run_simulation(config, srt, salt)
+        .unwrap_or_else(|_| run_simulation_with_distribution(config, srt, salt, &Uniform))
+}
+
+/// Run a simulation on the configured backend, reporting backend failures.
+///
+/// Peers are static, so a peer matches exactly when its distance to the
+/// target is within epsilon, whatever the window size; the GPU backend relies
+/// on this and only evaluates distances.
+pub fn try_run_simulation(
+    config: &SimulationConfig,
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+) -> Result<SimulationResult> {
+    match config.backend {
+        SimulationBackend::Cpu => Ok(run_simulation_with_distribution(
+            config, srt, salt, &Uniform,
+        )),
+        #[cfg(feature = "gpu")]
+        SimulationBackend::Gpu => {
+            let target = pattern_from_srt(srt, salt);
+            let samples = config.num_trials.saturating_mul(config.num_peers);
+            let simulator = gpu::GpuSimulator::new()?;
+            let single_match_count =
+                simulator.count_matches(&target.normalize(), config.epsilon, samples as u64)?;
+            let mut rng = rand::thread_rng();
+            let double_match_count = (0..config.num_trials)
+                .filter(|_| {
+                    let peer_a = Uniform.sample(&mut rng);
+                    let peer_b = Uniform.sample(&mut rng);
+                    matches_target(&peer_a, &target, config)
+                        && matches_target(&peer_b, &target, config)
+                })
+                .count();
+            Ok(summarize(
+                config,
+                single_match_count as usize,
+                double_match_count,
+                samples,
+            ))
+        }
+        #[cfg(not(feature = "gpu"))]
+        SimulationBackend::Gpu => Err(Error::InvalidConfig(
+            "the gpu backend requires the `gpu` feature".to_string(),
+        )),
+    }
+}
+
+/// Run a simulation with peers drawn from a fitted prior.
+///
+/// Identical to [`run_simulation`] except that peer patterns are sampled from
+/// `prior` instead of uniformly, so estimates reflect observed population
+/// distributions. Dimensions are still treated as independent.
+pub fn run_simulation_with_prior(
+    config: &SimulationConfig,
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    prior: &PeerModel,
+) -> SimulationResult {
+    run_simulation_with_distribution(config, srt, salt, prior)
+}
+
+/// Run a simulation with peers drawn from any [`PatternDistribution`].
+///
+/// [`run_simulation`] uses `pattern::sampling::Uniform` and
+/// [`run_simulation_with_prior`] a fitted [`PeerModel`]; this accepts a
+/// Gaussian around a baseline, resampled recordings, or a custom
+/// distribution.
+pub fn run_simulation_with_distribution(
+    config: &SimulationConfig,
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    distribution: &dyn PatternDistribution,
+) -> SimulationResult {
+    run_simulation_with_sampler(config, srt, salt, |rng| distribution.sample(rng))
+}
+
+/// Run a simulation in which each peer is seen through a noisy sensor.
+///
+/// Peers are sampled uniformly as in [`run_simulation`], then each is
+/// replaced by one reading from `noise`, so the estimate reflects what a
+/// matcher would see rather than the population itself.
+pub fn run_simulation_with_noise(
+    config: &SimulationConfig,
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    noise: &dyn NoiseModel,
+) -> SimulationResult {
+    run_simulation_with_sampler(config, srt, salt, |rng| {
+        let peer = Uniform.sample(rng);
+        noise.perturb(&peer, rng)
+    })
+}
+
+/// Estimate how often a genuine peer, whose true pattern is exactly the
+/// target, matches despite sensor noise.
+///
+/// Each of `config.num_trials` trials feeds a fresh [`Matcher`]
+/// `window_size` (at least one) independent readings from `noise` and counts
+/// a match if it reports one. Together with the false match rate from
+/// [`run_simulation`] this gives both sides of the epsilon trade-off.
+pub fn genuine_match_probability(
+    config: &SimulationConfig,
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    noise: &dyn NoiseModel,
+) -> f64 {
     let target = pattern_from_srt(srt, salt);
     let mut rng = rand::thread_rng();
+    let matches = (0..config.num_trials)
+        .filter(|_| {
+            let mut matcher = Matcher::new(matching_config(config));
+            noise
+                .stream(&target, &mut rng, config.window_size.max(1))
+                .iter()
+                .any(|reading| matcher.observe(reading, &target))
+        })
+        .count();
+    matches as f64 / config.num_trials.max(1) as f64
+}
+
+fn run_simulation_with_sampler<F>(
+    config: &SimulationConfig,
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    mut sample_peer: F,
+) -> SimulationResult
+where
+    F: FnMut(&mut rand::rngs::ThreadRng) -> SubmodalityPattern,
+{
+    let target = pattern_from_srt(srt, salt);
+    let target_norm = target.normalize();
+    let mut rng = rand::thread_rng();
 
     let mut single_match_count = 0usize;
     let mut double_match_count = 0usize;
     let mut total_peer_samples = 0usize;
+    let mut trials = Vec::new();
+    let mut metric_counts = vec![(0usize, 0usize); config.metrics.len()];
+
+    for trial in 0..config.num_trials {
+        // Static peers match exactly when within epsilon, so the window does
+        // not affect the count and the batch kernel can be used directly.
+        let peers: PatternBatch = (0..config.num_peers)
+            .map(|_| sample_peer(&mut rng))
+            .collect();
+        let normalized = peers.normalize();
+        total_peer_samples += peers.len();
 
-    for _ in 0..config.num_trials {
-        for _ in 0..config.num_peers {
-            let peer = random_pattern(&mut rng);
-            if matches_target(&peer, &target, config.epsilon, config.window_size) {
-                single_match_count += 1;
+        let peer_a = sample_peer(&mut rng);
+        let peer_b = sample_peer(&mut rng);
+        let double_match =
+            matches_target(&peer_a, &target, config) && matches_target(&peer_b, &target, config);
+        if double_match {
+            double_match_count += 1;
+        }
+
+        if !config.metrics.is_empty() {
+            let (norm_a, norm_b) = (peer_a.normalize(), peer_b.normalize());
+            for (spec, (single, double)) in config.metrics.iter().zip(&mut metric_counts) {
+                let epsilon = spec.epsilon.unwrap_or(config.epsilon);
+                *single += spec
+                    .batch_distances(&normalized, &target_norm)
+                    .into_iter()
+                    .filter(|&distance| distance <= epsilon)
+                    .count();
+                if spec.distance(&norm_a, &target_norm) <= epsilon
+                    && spec.distance(&norm_b, &target_norm) <= epsilon
+                {
+                    *double += 1;
+                }
             }
-            total_peer_samples += 1;
         }
 
-        let peer_a = random_pattern(&mut rng);
-        let peer_b = random_pattern(&mut rng);
-        if matches_target(&peer_a, &target, config.epsilon, config.window_size)
-            && matches_target(&peer_b, &target, config.epsilon, config.window_size)
-        {
-            double_match_count += 1;
+        // Reservoir sampling keeps trial `trial` with probability
+        // `size / (trial + 1)`, replacing a random earlier record.
+        let slot = match config.detail {
+            SimulationDetail::Summary => None,
+            SimulationDetail::Trials => Some(trials.len()),
+            SimulationDetail::Sample(size) if trial < size => Some(trials.len()),
+            SimulationDetail::Sample(size) => {
+                Some(rng.gen_range(0..=trial)).filter(|&slot| slot < size)
+            }
+        };
+        let Some(slot) = slot else {
+            single_match_count +=
+                normalized.count_within_weighted(&target_norm, &config.weights, config.epsilon);
+            continue;
+        };
+        let record = trial_record(
+            trial,
+            &normalized.weighted_distances(&target_norm, &config.weights),
+            config.epsilon,
+            [
+                weighted_distance(&peer_a.normalize(), &target_norm, &config.weights),
+                weighted_distance(&peer_b.normalize(), &target_norm, &config.weights),
+            ],
+            double_match,
+        );
+        single_match_count += record.match_count;
+        if slot == trials.len() {
+            trials.push(record);
+        } else {
+            trials[slot] = record;
         }
     }
 
+    trials.sort_by_key(|record| record.trial);
+    let metrics = config
+        .metrics
+        .iter()
+        .zip(metric_counts)
+        .map(|(spec, (single, double))| MetricResult {
+            name: spec.name.clone(),
+            epsilon: spec.epsilon.unwrap_or(config.epsilon),
+            single_match_count: single,
+            double_match_count: double,
+            single_match_probability: single as f64 / total_peer_samples.max(1) as f64,
+            double_match_probability: double as f64 / config.num_trials.max(1) as f64,
+        })
+        .collect();
+    SimulationResult {
+        trials,
+        metrics,
+        ..summarize(
+            config,
+            single_match_count,
+            double_match_count,
+            total_peer_samples,
+        )
+    }
+}
+
+/// Summarize one trial's peer distances.
+fn trial_record(
+    trial: usize,
+    distances: &[f32],
+    epsilon: f32,
+    pair_distances: [f32; 2],
+    double_match: bool,
+) -> TrialRecord {
+    let nearest_distance = distances
+        .iter()
+        .copied()
+        .reduce(f32::min)
+        .unwrap_or(f32::INFINITY);
+    let nearest_miss = distances
+        .iter()
+        .copied()
+        .filter(|&distance| distance > epsilon)
+        .reduce(f32::min);
+    let mean_distance = distances.iter().sum::<f32>() / distances.len().max(1) as f32;
+    TrialRecord {
+        trial,
+        match_count: distances.iter().filter(|&&d| d <= epsilon).count(),
+        nearest_distance,
+        nearest_miss,
+        mean_distance,
+        pair_distances,
+        double_match,
+    }
+}
+
+/// Derive probabilities and pool estimates from raw match counts.
+fn summarize(
+    config: &SimulationConfig,
+    single_match_count: usize,
+    double_match_count: usize,
+    total_peer_samples: usize,
+) -> SimulationResult {
     let single_match_probability =
         (single_match_count as f64) / (total_peer_samples.max(1) as f64);
     let double_match_probability =
@@ -147,9 +768,208 @@ pub fn run_simulation(
         effective_peer_count,
         expected_matches_in_pool,
         pool_match_probability,
+        trials: Vec::new(),
+        metrics: Vec::new(),
     }
 }
 
+/// Results of a simulation parameter sweep.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SweepResult {
+    /// Epsilon values swept, in input order.
+    pub epsilons: Vec<f32>,
+    /// Window sizes swept, in input order.
+    pub window_sizes: Vec<usize>,
+    /// Simulation results indexed as `results[window_index][epsilon_index]`.
+    pub results: Vec<Vec<SimulationResult>>,
+}
+
+/// Run a simulation for every combination of `epsilons` and `window_sizes`.
+///
+/// All other parameters are taken from `base`. Each grid cell is an
+/// independent run, so cells do not share sampled peers.
+pub fn run_sweep(
+    base: &SimulationConfig,
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    epsilons: &[f32],
+    window_sizes: &[usize],
+) -> SweepResult {
+    let results = window_sizes
+        .iter()
+        .map(|&window_size| {
+            epsilons
+                .iter()
+                .map(|&epsilon| {
+                    let config = SimulationConfig {
+                        epsilon,
+                        window_size,
+                        ..base.clone()
+                    };
+                    run_simulation(&config, srt, salt)
+                })
+                .collect()
+        })
+        .collect();
+
+    SweepResult {
+        epsilons: epsilons.to_vec(),
+        window_sizes: window_sizes.to_vec(),
+        results,
+    }
+}
+
+/// Two-sided 95% normal quantile used for confidence intervals.
+const Z_95: f64 = 1.959_964;
+
+/// Epsilon found by [`solve_epsilon`] and the false-rendezvous rate it achieves.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct EpsilonSolution {
+    /// Largest epsilon whose estimated false-rendezvous rate does not exceed
+    /// the target.
+    pub epsilon: f32,
+    /// Requested false-rendezvous probability.
+    pub target_fpr: f64,
+    /// Estimated false-rendezvous probability at `epsilon`.
+    pub achieved_fpr: f64,
+    /// Lower bound of the 95% Wilson confidence interval for `achieved_fpr`.
+    pub ci_low: f64,
+    /// Upper bound of the 95% Wilson confidence interval for `achieved_fpr`.
+    pub ci_high: f64,
+    /// Number of random peer/target pairs sampled.
+    pub samples: usize,
+    /// Bisection steps performed.
+    pub iterations: usize,
+}
+
+/// Find the epsilon giving a requested false-rendezvous probability.
+///
+/// Draws `num_peers * num_trials` independent pairs of a uniformly random
+/// target and peer, then bisects over epsilon on that fixed sample (common
+/// random numbers), so each step is exact for the sample and the search
+/// converges monotonically. The probability is averaged over targets, i.e.
+/// over SRTs. Distances use `config.weights`. Peers are static, so
+/// `window_size` does not change the rate and `config.epsilon` is ignored.
+///
+/// Rates much smaller than `1 / samples` cannot be resolved; the reported
+/// confidence interval shows how precise the estimate is.
+pub fn solve_epsilon(config: &SimulationConfig, target_fpr: f64) -> Result<EpsilonSolution> {
+    config.validate()?;
+    if !(target_fpr > 0.0 && target_fpr < 1.0) {
+        return Err(Error::InvalidConfig(format!(
+            "target false-positive rate must be in (0, 1), got {target_fpr}"
+        )));
+    }
+    let samples = config.num_peers.saturating_mul(config.num_trials);
+    let distances = random_pair_distances(&config.weights, samples);
+    let rate =
+        |epsilon: f32| distances.iter().filter(|&&d| d <= epsilon).count() as f64 / samples as f64;
+
+    let (mut low, mut high) = (0.0f32, config.weights.max_distance());
+    let mut iterations = 0;
+    while high - low > 1e-5 && iterations < 64 {
+        let mid = (low + high) / 2.0;
+        if rate(mid) <= target_fpr {
+            low = mid;
+        } else {
+            high = mid;
+        }
+        iterations += 1;
+    }
+
+    let achieved_fpr = rate(low);
+    let (ci_low, ci_high) = wilson_interval(achieved_fpr, samples);
+    Ok(EpsilonSolution {
+        epsilon: low,
+        target_fpr,
+        achieved_fpr,
+        ci_low,
+        ci_high,
+        samples,
+        iterations,
+    })
+}
+
+/// Estimate the probability that a random peer matches a random target
+/// within `epsilon` under `weights`, from `samples` uniformly drawn pairs.
+///
+/// This is the per-observation false-rendezvous rate that [`solve_epsilon`]
+/// inverts; it ignores the temporal window.
+pub fn false_match_rate(epsilon: f32, weights: &DimensionWeights, samples: usize) -> f64 {
+    let within = random_pair_distances(weights, samples)
+        .into_iter()
+        .filter(|&d| d <= epsilon)
+        .count();
+    within as f64 / samples.max(1) as f64
+}
+
+/// Weighted distances between `samples` independent uniform peer/target pairs.
+fn random_pair_distances(weights: &DimensionWeights, samples: usize) -> Vec<f32> {
+    let mut rng = rand::thread_rng();
+    (0..samples)
+        .map(|_| {
+            let target = Uniform.sample(&mut rng).normalize();
+            let peer = Uniform.sample(&mut rng).normalize();
+            weighted_distance(&peer, &target, weights)
+        })
+        .collect()
+}
+
+/// 95% Wilson score interval for a proportion `p` observed over `n` trials.
+fn wilson_interval(p: f64, n: usize) -> (f64, f64) {
+    let n = n as f64;
+    let z2 = Z_95 * Z_95;
+    let denom = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denom;
+    let half = Z_95 * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denom;
+    ((center - half).max(0.0), (center + half).min(1.0))
+}
+
+/// A point on a receiver operating characteristic curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RocPoint {
+    /// Distance threshold (epsilon) this point was evaluated at.
+    pub threshold: f32,
+    /// Fraction of impostor distances within the threshold.
+    pub false_positive_rate: f64,
+    /// Fraction of genuine distances within the threshold.
+    pub true_positive_rate: f64,
+}
+
+/// Compute an ROC curve from genuine and impostor distance samples.
+///
+/// `genuine` holds distances for observations that should match (e.g. noisy
+/// measurements of the target) and `impostor` holds distances for ones that
+/// should not (e.g. random peers). Points are returned in ascending threshold
+/// order, starting at threshold `0.0`.
+pub fn roc_curve(genuine: &[f32], impostor: &[f32]) -> Vec<RocPoint> {
+    let mut thresholds: Vec<f32> = genuine.iter().chain(impostor).copied().collect();
+    thresholds.push(0.0);
+    thresholds.retain(|t| t.is_finite());
+    thresholds.sort_by(f32::total_cmp);
+    thresholds.dedup();
+
+    let rate = |distances: &[f32], threshold: f32| {
+        let within = distances.iter().filter(|&&d| d <= threshold).count();
+        within as f64 / distances.len().max(1) as f64
+    };
+
+    thresholds
+        .into_iter()
+        .map(|threshold| RocPoint {
+            threshold,
+            false_positive_rate: rate(impostor, threshold),
+            true_positive_rate: rate(genuine, threshold),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,6 +983,10 @@ mod tests {
             window_size: 1,
             apply_geo_filter: false,
             geo_filter_factor: 1e6,
+            backend: SimulationBackend::Cpu,
+            detail: SimulationDetail::Summary,
+            metrics: Vec::new(),
+            weights: DimensionWeights::default(),
         };
         let srt = SemanticRendezvousToken::from_bytes([1u8; 32]);
         let result = run_simulation(&config, &srt, b"salt");
@@ -173,4 +997,248 @@ mod tests {
         assert!(result.double_match_probability >= 0.0);
         assert!(result.double_match_probability <= 1.0);
     }
+
+    #[test]
+    fn builder_validates_config() {
+        let config = SimulationConfig::builder()
+            .num_peers(10)
+            .epsilon(0.3)
+            .geo_filter(100.0)
+            .build()
+            .expect("valid");
+        assert_eq!(config.num_peers, 10);
+        assert!(config.apply_geo_filter);
+
+        assert!(SimulationConfig::builder().num_trials(0).build().is_err());
+        assert!(SimulationConfig::builder().epsilon(f32::INFINITY).build().is_err());
+        let err = SimulationConfig::builder()
+            .geo_filter(0.5)
+            .build()
+            .expect_err("factor below one");
+        assert!(err.to_string().contains("geo_filter_factor"));
+        assert!(SimulationConfig::builder()
+            .geo_filter(0.5)
+            .without_geo_filter()
+            .build()
+            .is_ok());
+        assert_eq!(
+            SimulationConfig::builder()
+                .backend(SimulationBackend::Gpu)
+                .build()
+                .is_ok(),
+            cfg!(feature = "gpu")
+        );
+    }
+
+    #[test]
+    fn noise_lowers_genuine_matches() {
+        use noise::{GaussianNoise, UniformNoise};
+
+        let srt = SemanticRendezvousToken::from_bytes([5u8; 32]);
+        let config = SimulationConfig::builder()
+            .num_peers(20)
+            .num_trials(200)
+            .epsilon(0.1)
+            .window_size(3)
+            .build()
+            .expect("valid");
+        let still = UniformNoise::new(0.0).expect("noise");
+        let quiet = genuine_match_probability(&config, &srt, b"salt", &still);
+        assert_eq!(quiet, 1.0);
+        let noisy = genuine_match_probability(&config, &srt, b"salt", &GaussianNoise::new(0.05));
+        assert!(noisy < 0.5, "genuine match rate {noisy}");
+
+        let result = run_simulation_with_noise(&config, &srt, b"salt", &GaussianNoise::new(0.05));
+        assert_eq!(result.total_peer_samples, 20 * 200);
+    }
+
+    #[test]
+    fn distributions_drive_peer_sampling() {
+        use crate::pattern::sampling::{Empirical, Gaussian};
+
+        let srt = SemanticRendezvousToken::from_bytes([6u8; 32]);
+        let config = SimulationConfig::builder()
+            .num_peers(20)
+            .num_trials(50)
+            .epsilon(0.1)
+            .build()
+            .expect("valid");
+        let target = pattern_from_srt(&srt, b"salt");
+        let near = Gaussian::normalized(target.clone(), 0.01);
+        let result = run_simulation_with_distribution(&config, &srt, b"salt", &near);
+        assert!(result.single_match_probability > 0.9, "{result:?}");
+
+        let recorded = Empirical::new(vec![target]).expect("empirical");
+        let result = run_simulation_with_distribution(&config, &srt, b"salt", &recorded);
+        assert_eq!(result.single_match_count, 20 * 50);
+        assert_eq!(result.double_match_count, 50);
+    }
+
+    #[test]
+    fn detail_records_trials_and_samples() {
+        let srt = SemanticRendezvousToken::from_bytes([3u8; 32]);
+        let config = SimulationConfig::builder()
+            .num_peers(20)
+            .num_trials(50)
+            .epsilon(0.8)
+            .detail(SimulationDetail::Trials)
+            .build()
+            .expect("valid");
+        let result = run_simulation(&config, &srt, b"salt");
+        assert_eq!(result.trials.len(), 50);
+        assert_eq!(
+            result.trials.iter().map(|r| r.match_count).sum::<usize>(),
+            result.single_match_count
+        );
+        for record in &result.trials {
+            assert!(record.nearest_distance <= record.mean_distance);
+            assert!(record.nearest_miss.is_none_or(|miss| miss > 0.8));
+            assert_eq!(
+                record.double_match,
+                record.pair_distances.iter().all(|&d| d <= 0.8)
+            );
+        }
+
+        let sampled = SimulationConfig {
+            detail: SimulationDetail::Sample(7),
+            ..config.clone()
+        };
+        let trials = run_simulation(&sampled, &srt, b"salt").trials;
+        assert_eq!(trials.len(), 7);
+        assert!(trials.windows(2).all(|pair| pair[0].trial < pair[1].trial));
+
+        let summary = SimulationConfig {
+            detail: SimulationDetail::Summary,
+            ..config
+        };
+        assert!(run_simulation(&summary, &srt, b"salt").trials.is_empty());
+        assert!(SimulationConfig::builder()
+            .detail(SimulationDetail::Sample(0))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn metrics_share_sampled_peers() {
+        let srt = SemanticRendezvousToken::from_bytes([4u8; 32]);
+        let config = SimulationConfig::builder()
+            .num_peers(50)
+            .num_trials(40)
+            .epsilon(0.7)
+            .metric(MetricSpec::new("euclidean", DistanceMetric::Euclidean))
+            .metric(MetricSpec::new("chebyshev", DistanceMetric::Chebyshev))
+            .metric(MetricSpec::new("manhattan", DistanceMetric::Manhattan).with_epsilon(10.0))
+            .build()
+            .expect("valid");
+        let result = run_simulation(&config, &srt, b"salt");
+        let [euclidean, chebyshev, manhattan] = &result.metrics[..] else {
+            panic!("expected three metric results");
+        };
+        // Same peers: unit-weight Euclidean reproduces the baseline exactly,
+        // and Chebyshev never exceeds Euclidean distance.
+        assert_eq!(euclidean.single_match_count, result.single_match_count);
+        assert!(chebyshev.single_match_count >= euclidean.single_match_count);
+        assert_eq!(manhattan.epsilon, 10.0);
+        assert_eq!(manhattan.single_match_probability, 1.0);
+
+        let a = SubmodalityPattern::zeros().normalize();
+        let mut b = a.clone();
+        b.brightness += 0.3;
+        b.tempo += 0.4;
+        let weights = DimensionWeights::new([2.0; 9]).expect("weights");
+        let spec = MetricSpec::new("w", DistanceMetric::Manhattan).with_weights(weights);
+        assert!((spec.distance(&a, &b) - 1.4).abs() < 1e-6);
+
+        let duplicate = SimulationConfig::builder()
+            .metric(MetricSpec::new("m", DistanceMetric::Euclidean))
+            .metric(MetricSpec::new("m", DistanceMetric::Manhattan))
+            .build();
+        assert!(duplicate.is_err());
+        let negative = MetricSpec::new("n", DistanceMetric::Euclidean).with_epsilon(-1.0);
+        assert!(SimulationConfig::builder().metric(negative).build().is_err());
+    }
+
+    #[test]
+    fn sweep_covers_grid() {
+        let base = SimulationConfig {
+            num_peers: 10,
+            num_trials: 5,
+            epsilon: 0.0,
+            window_size: 0,
+            apply_geo_filter: false,
+            geo_filter_factor: 1.0,
+            backend: SimulationBackend::Cpu,
+            detail: SimulationDetail::Summary,
+            metrics: Vec::new(),
+            weights: DimensionWeights::default(),
+        };
+        let srt = SemanticRendezvousToken::from_bytes([2u8; 32]);
+        let sweep = run_sweep(&base, &srt, b"salt", &[0.1, 0.5, 3.0], &[1, 2]);
+        assert_eq!(sweep.results.len(), 2);
+        assert_eq!(sweep.results[0].len(), 3);
+        assert_eq!(sweep.results[1][2].single_match_probability, 1.0);
+    }
+
+    #[test]
+    fn roc_curve_is_monotone() {
+        let genuine = [0.05, 0.1, 0.2, 0.3];
+        let impostor = [0.25, 0.5, 0.9, 1.2];
+        let points = roc_curve(&genuine, &impostor);
+        assert_eq!(points[0].threshold, 0.0);
+        assert!(points.windows(2).all(|w| {
+            w[0].false_positive_rate <= w[1].false_positive_rate
+                && w[0].true_positive_rate <= w[1].true_positive_rate
+        }));
+        let last = points.last().expect("points");
+        assert_eq!((last.false_positive_rate, last.true_positive_rate), (1.0, 1.0));
+    }
+
+    #[test]
+    fn solved_epsilon_hits_target_rate() {
+        let config = SimulationConfig::builder()
+            .num_peers(200)
+            .num_trials(100)
+            .build()
+            .expect("config");
+        let solution = solve_epsilon(&config, 0.05).expect("solve");
+        assert_eq!(solution.samples, 20_000);
+        assert!(solution.achieved_fpr <= 0.05);
+        assert!(
+            solution.ci_low <= solution.achieved_fpr && solution.achieved_fpr <= solution.ci_high
+        );
+        assert!((solution.achieved_fpr - 0.05).abs() < 0.005);
+        assert!(solution.epsilon > 0.0 && solution.epsilon < 3.0);
+
+        assert!(solve_epsilon(&config, 0.0).is_err());
+        assert!(solve_epsilon(&config, 1.5).is_err());
+    }
+
+    #[test]
+    fn simulation_respects_weights() {
+        let mut values = [0.0; 9];
+        values[0] = 1.0;
+        let weights = DimensionWeights::new(values).expect("weights");
+        let config = SimulationConfig::builder()
+            .num_peers(20)
+            .num_trials(10)
+            .epsilon(1.0)
+            .detail(SimulationDetail::Trials)
+            .weights(weights)
+            .build()
+            .expect("config");
+        let srt = SemanticRendezvousToken::from_bytes([4u8; 32]);
+        let result = run_simulation(&config, &srt, b"salt");
+        assert_eq!(result.single_match_probability, 1.0);
+        assert_eq!(result.double_match_probability, 1.0);
+        assert!(result.trials.iter().all(|record| record.nearest_miss.is_none()));
+        assert_eq!(false_match_rate(1.0, &weights, 100), 1.0);
+        let solution = solve_epsilon(&config, 0.5).expect("solve");
+        assert!(solution.epsilon < 1.0);
+
+        let gpu = SimulationConfig::builder()
+            .backend(SimulationBackend::Gpu)
+            .weights(weights)
+            .build();
+        assert!(gpu.is_err());
+    }
 }
@@ -0,0 +1,329 @@
+//! Compute-shader backend for the simulation inner loop.
+//!
+//! [`GpuSimulator`] samples uniform peers directly on the GPU and counts how
+//! many fall within epsilon of a normalized target, so rare-event studies can
+//! evaluate billions of peers without transferring patterns to the host.
+//! Each invocation runs its own xoshiro128** stream seeded from the invocation
+//! index and a per-dispatch seed drawn on the host.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::mpsc;
+
+use rand::RngCore;
+use wgpu::util::DeviceExt;
+
+use crate::pattern::{normalized_to_array, NormalizedPattern};
+
+/// Invocations per workgroup; must match `@workgroup_size` in the shader.
+const WORKGROUP_SIZE: u32 = 256;
+
+/// Peers sampled by each invocation.
+const SAMPLES_PER_INVOCATION: u32 = 64;
+
+/// Largest number of peers sampled per dispatch.
+///
+/// Keeps per-dispatch counts well inside `u32` and the workgroup count inside
+/// the default dispatch limit.
+const MAX_SAMPLES_PER_DISPATCH: u64 = (WORKGROUP_SIZE * SAMPLES_PER_INVOCATION) as u64 * 4096;
+
+/// Size of the uniform parameter block in bytes.
+const PARAMS_LEN: u64 = 80;
+
+const SHADER: &str = r#"
+struct Params {
+    center: array<vec4<f32>, 3>,
+    epsilon_sq: f32,
+    seed_lo: u32,
+    seed_hi: u32,
+    samples: u32,
+    samples_per_invocation: u32,
+};
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> matches: atomic<u32>;
+
+var<workgroup> local_matches: atomic<u32>;
+var<private> rng: vec4<u32>;
+
+fn pcg(v: u32) -> u32 {
+    let state = v * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn rotl(x: u32, k: u32) -> u32 {
+    return (x << k) | (x >> (32u - k));
+}
+
+fn next_unit() -> f32 {
+    let result = rotl(rng.y * 5u, 7u) * 9u;
+    let t = rng.y << 9u;
+    rng.z ^= rng.x;
+    rng.w ^= rng.y;
+    rng.y ^= rng.z;
+    rng.x ^= rng.w;
+    rng.z ^= t;
+    rng.w = rotl(rng.w, 11u);
+    return f32(result >> 8u) * (1.0 / 16777216.0);
+}
+
+@compute @workgroup_size(256)
+fn main(
+    @builtin(global_invocation_id) gid: vec3<u32>,
+    @builtin(local_invocation_index) lid: u32,
+) {
+    if (lid == 0u) {
+        atomicStore(&local_matches, 0u);
+    }
+    workgroupBarrier();
+
+    let start = gid.x * params.samples_per_invocation;
+    let end = min(start + params.samples_per_invocation, params.samples);
+    rng = vec4<u32>(
+        pcg(gid.x ^ params.seed_lo),
+        pcg(gid.x + params.seed_hi),
+        pcg(pcg(gid.x) ^ params.seed_hi),
+        pcg(pcg(gid.x) + params.seed_lo) | 1u,
+    );
+    var count = 0u;
+    for (var i = start; i < end; i++) {
+        var sum = 0.0;
+        for (var d = 0u; d < 9u; d++) {
+            let diff = next_unit() - params.center[d / 4u][d % 4u];
+            sum += diff * diff;
+        }
+        if (sum <= params.epsilon_sq) {
+            count += 1u;
+        }
+    }
+    atomicAdd(&local_matches, count);
+    workgroupBarrier();
+
+    if (lid == 0u) {
+        atomicAdd(&matches, atomicLoad(&local_matches));
+    }
+}
+"#;
+
+/// A GPU device and compiled sampling pipeline.
+///
+/// Creating a simulator selects an adapter and compiles the shader, which
+/// takes a noticeable fraction of a second; reuse one for many runs.
+#[derive(Debug)]
+pub struct GpuSimulator {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    adapter_name: String,
+}
+
+impl GpuSimulator {
+    /// Select the default high-performance adapter and build the pipeline.
+    pub fn new() -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            compatible_surface: None,
+        }))
+        .ok_or(GpuError::NoAdapter)?;
+        let adapter_name = adapter.get_info().name;
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("phenorv-sim"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::downlevel_defaults(),
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            None,
+        ))
+        .map_err(|err| GpuError::Device(err.to_string()))?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("phenorv-sim"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(SHADER)),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("phenorv-sim"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        Ok(Self {
+            device,
+            queue,
+            pipeline,
+            adapter_name,
+        })
+    }
+
+    /// Name of the selected adapter, for reporting.
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// Count how many of `samples` uniform random peers lie within `epsilon`
+    /// of `target` in normalized space.
+    pub fn count_matches(
+        &self,
+        target: &NormalizedPattern,
+        epsilon: f32,
+        samples: u64,
+    ) -> Result<u64, GpuError> {
+        let params = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("phenorv-sim-params"),
+            size: PARAMS_LEN,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let counter = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("phenorv-sim-counter"),
+                contents: &[0; 4],
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_SRC
+                    | wgpu::BufferUsages::COPY_DST,
+            });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("phenorv-sim-readback"),
+            size: 4,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("phenorv-sim"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: counter.as_entire_binding(),
+                },
+            ],
+        });
+
+        let center = normalized_to_array(target);
+        let mut rng = rand::thread_rng();
+        let mut total = 0u64;
+        let mut remaining = samples;
+        while remaining > 0 {
+            let batch = remaining.min(MAX_SAMPLES_PER_DISPATCH) as u32;
+            remaining -= u64::from(batch);
+
+            let mut bytes = Vec::with_capacity(PARAMS_LEN as usize);
+            for i in 0..12 {
+                bytes.extend(center.get(i).copied().unwrap_or(0.0).to_le_bytes());
+            }
+            bytes.extend((epsilon * epsilon).to_le_bytes());
+            for word in [rng.next_u32(), rng.next_u32(), batch, SAMPLES_PER_INVOCATION, 0, 0, 0] {
+                bytes.extend(u32::to_le_bytes(word));
+            }
+            self.queue.write_buffer(&params, 0, &bytes);
+            self.queue.write_buffer(&counter, 0, &[0; 4]);
+
+            let invocations = batch.div_ceil(SAMPLES_PER_INVOCATION);
+            let mut encoder = self
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+                pass.set_pipeline(&self.pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.dispatch_workgroups(invocations.div_ceil(WORKGROUP_SIZE), 1, 1);
+            }
+            encoder.copy_buffer_to_buffer(&counter, 0, &readback, 0, 4);
+            self.queue.submit([encoder.finish()]);
+
+            total += u64::from(self.read_counter(&readback)?);
+        }
+        Ok(total)
+    }
+
+    /// Map the readback buffer and return the match count it holds.
+    fn read_counter(&self, readback: &wgpu::Buffer) -> Result<u32, GpuError> {
+        let slice = readback.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|err| GpuError::Readback(err.to_string()))?
+            .map_err(|err| GpuError::Readback(err.to_string()))?;
+        let count = {
+            let data = slice.get_mapped_range();
+            u32::from_le_bytes([data[0], data[1], data[2], data[3]])
+        };
+        readback.unmap();
+        Ok(count)
+    }
+}
+
+/// Errors returned by the GPU backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GpuError {
+    /// No compatible GPU adapter is available.
+    NoAdapter,
+    /// The adapter refused to create a device.
+    Device(String),
+    /// Match counts could not be read back from the device.
+    Readback(String),
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAdapter => write!(f, "no compatible GPU adapter found"),
+            Self::Device(err) => write!(f, "failed to create GPU device: {err}"),
+            Self::Readback(err) => write!(f, "failed to read GPU results: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for GpuError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::euclidean_distance;
+    use crate::pattern::SubmodalityPattern;
+    use crate::pattern::sampling::random_pattern;
+
+    #[test]
+    fn gpu_rate_agrees_with_cpu() {
+        let simulator = match GpuSimulator::new() {
+            Ok(simulator) => simulator,
+            // Nothing to check on machines without a GPU.
+            Err(GpuError::NoAdapter) => return,
+            Err(err) => panic!("{err}"),
+        };
+        let mut target = SubmodalityPattern::zeros().normalize();
+        target.brightness = 0.5;
+        target.arousal = 0.5;
+        let samples = 1_000_003;
+        let matches = simulator
+            .count_matches(&target, 1.2, samples)
+            .expect("count");
+        let gpu_rate = matches as f64 / samples as f64;
+
+        let mut rng = rand::thread_rng();
+        let cpu_samples = 200_000;
+        let cpu_matches = (0..cpu_samples)
+            .filter(|_| euclidean_distance(&random_pattern(&mut rng).normalize(), &target) <= 1.2)
+            .count();
+        let cpu_rate = cpu_matches as f64 / cpu_samples as f64;
+        assert!(
+            (gpu_rate - cpu_rate).abs() < 0.01,
+            "gpu {gpu_rate}, cpu {cpu_rate}"
+        );
+        assert_eq!(simulator.count_matches(&target, 0.0, 1000), Ok(0));
+    }
+}
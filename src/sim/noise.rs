@@ -0,0 +1,208 @@
+//! Sensor noise models.
+//!
+//! A [`NoiseModel`] turns a true pattern into one noisy sensor reading.
+//! Simulations use it to model measurement error on top of the sampled
+//! population ([`run_simulation_with_noise`](super::run_simulation_with_noise))
+//! and to estimate how often a peer standing exactly on the target still
+//! matches ([`genuine_match_probability`](super::genuine_match_probability)).
+//! Tests use [`NoiseModel::stream`] to generate realistic near-target
+//! streams. All models clamp readings to the reference ranges, as a real
+//! sensor's output would be.
+
+use rand::{Rng, RngCore};
+
+use crate::error::{Error, Result};
+use crate::pattern::sampling::{Gaussian, PatternDistribution};
+use crate::pattern::{
+    pattern_from_array, pattern_to_array, standard_normal, DimensionInfo, SubmodalityPattern,
+    DIMENSIONS,
+};
+
+/// Source of per-reading sensor noise.
+///
+/// Takes `&mut dyn RngCore` so models can be boxed and chosen at runtime.
+pub trait NoiseModel {
+    /// One noisy reading of `pattern`.
+    fn perturb(&self, pattern: &SubmodalityPattern, rng: &mut dyn RngCore) -> SubmodalityPattern;
+
+    /// `len` independent noisy readings of `target`.
+    fn stream(
+        &self,
+        target: &SubmodalityPattern,
+        rng: &mut dyn RngCore,
+        len: usize,
+    ) -> Vec<SubmodalityPattern> {
+        (0..len).map(|_| self.perturb(target, rng)).collect()
+    }
+}
+
+/// Gaussian noise with the same standard deviation on every dimension,
+/// expressed as a fraction of each dimension's reference range.
+///
+/// Each reading is one draw from [`Gaussian::normalized`] around the true
+/// pattern.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GaussianNoise {
+    /// Standard deviation in normalized units.
+    pub sigma: f32,
+}
+
+impl GaussianNoise {
+    /// Noise with standard deviation `sigma` in normalized units.
+    pub fn new(sigma: f32) -> Self {
+        Self { sigma }
+    }
+}
+
+impl NoiseModel for GaussianNoise {
+    fn perturb(&self, pattern: &SubmodalityPattern, rng: &mut dyn RngCore) -> SubmodalityPattern {
+        Gaussian::normalized(pattern.clone(), self.sigma).sample(rng)
+    }
+}
+
+/// Uniform noise in `±half_width` on every dimension, expressed as a fraction
+/// of each dimension's reference range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UniformNoise {
+    /// Half the width of the noise interval in normalized units.
+    half_width: f32,
+}
+
+impl UniformNoise {
+    /// Noise uniform in `±half_width` normalized units.
+    ///
+    /// `half_width` must be finite and non-negative.
+    pub fn new(half_width: f32) -> Result<Self> {
+        if !(half_width.is_finite() && half_width >= 0.0) {
+            return Err(Error::InvalidConfig(format!(
+                "noise half-width must be finite and non-negative, got {half_width}"
+            )));
+        }
+        Ok(Self { half_width })
+    }
+
+    /// Half the width of the noise interval in normalized units.
+    pub fn half_width(&self) -> f32 {
+        self.half_width
+    }
+}
+
+impl NoiseModel for UniformNoise {
+    fn perturb(&self, pattern: &SubmodalityPattern, rng: &mut dyn RngCore) -> SubmodalityPattern {
+        let values = pattern_to_array(pattern);
+        pattern_from_array(std::array::from_fn(|i| {
+            let DimensionInfo { min, max, .. } = DIMENSIONS[i];
+            let offset = if self.half_width > 0.0 {
+                rng.gen_range(-self.half_width..=self.half_width)
+            } else {
+                0.0
+            };
+            (values[i] + offset * (max - min)).clamp(min, max)
+        }))
+    }
+}
+
+/// Gaussian noise whose standard deviation differs per dimension and grows
+/// with the reading: `absolute + relative * |value|`.
+///
+/// Models sensors such as microphones whose pitch error scales with
+/// frequency, alongside dimensions with a fixed noise floor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeteroscedasticNoise {
+    /// Noise floor per dimension, in raw units.
+    pub absolute: SubmodalityPattern,
+    /// Additional standard deviation per unit of the true value.
+    pub relative: SubmodalityPattern,
+}
+
+impl HeteroscedasticNoise {
+    /// Noise with a per-dimension floor in raw units and no relative term.
+    pub fn new(absolute: SubmodalityPattern) -> Self {
+        Self {
+            absolute,
+            relative: pattern_from_array([0.0; 9]),
+        }
+    }
+
+    /// Return this model with a per-dimension relative term.
+    pub fn with_relative(mut self, relative: SubmodalityPattern) -> Self {
+        self.relative = relative;
+        self
+    }
+}
+
+impl NoiseModel for HeteroscedasticNoise {
+    fn perturb(&self, pattern: &SubmodalityPattern, rng: &mut dyn RngCore) -> SubmodalityPattern {
+        let values = pattern_to_array(pattern);
+        let absolute = pattern_to_array(&self.absolute);
+        let relative = pattern_to_array(&self.relative);
+        pattern_from_array(std::array::from_fn(|i| {
+            let DimensionInfo { min, max, .. } = DIMENSIONS[i];
+            let sigma = absolute[i] + relative[i] * values[i].abs();
+            (values[i] + sigma * standard_normal(rng)).clamp(min, max)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::pattern::stats;
+
+    #[test]
+    fn models_scale_noise_as_documented() {
+        let mut rng = StdRng::seed_from_u64(88);
+        let target = SubmodalityPattern::zeros();
+
+        let readings = GaussianNoise::new(0.01).stream(&target, &mut rng, 2000);
+        let variance = stats::variance(&readings).expect("variance");
+        assert!((variance.color_temp.sqrt() - 80.0).abs() < 8.0);
+        assert!((variance.pitch.sqrt() - 199.8).abs() < 20.0);
+
+        let readings = UniformNoise::new(0.05)
+            .expect("noise")
+            .stream(&target, &mut rng, 500);
+        assert!(readings
+            .iter()
+            .all(|reading| (reading.temperature - 20.0).abs() <= 1.5));
+        for half_width in [f32::INFINITY, f32::NAN, -0.1] {
+            assert!(UniformNoise::new(half_width).is_err());
+        }
+
+        let noise = HeteroscedasticNoise::new(SubmodalityPattern {
+            brightness: 0.01,
+            ..pattern_from_array([0.0; 9])
+        })
+        .with_relative(SubmodalityPattern {
+            pitch: 0.01,
+            ..pattern_from_array([0.0; 9])
+        });
+        let low = noise.stream(&target, &mut rng, 2000);
+        let high = noise.stream(
+            &SubmodalityPattern {
+                pitch: 4400.0,
+                ..target.clone()
+            },
+            &mut rng,
+            2000,
+        );
+        let low_sd = stats::variance(&low).expect("variance").pitch.sqrt();
+        let high_sd = stats::variance(&high).expect("variance").pitch.sqrt();
+        assert!((low_sd - 4.4).abs() < 0.5);
+        assert!((high_sd - 44.0).abs() < 5.0);
+        assert!(low.iter().all(|reading| reading.tempo == 0.0));
+    }
+
+    #[test]
+    fn readings_stay_in_range() {
+        let mut rng = StdRng::seed_from_u64(89);
+        let edge = pattern_from_array(DIMENSIONS.map(|info| info.max));
+        let boxed: Box<dyn NoiseModel> = Box::new(GaussianNoise::new(0.2));
+        for reading in boxed.stream(&edge, &mut rng, 100) {
+            assert_eq!(reading.validate(), Ok(()));
+        }
+    }
+}
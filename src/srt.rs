@@ -1,30 +1,80 @@
 //! Semantic Rendezvous Token (SRT) encoding primitives.
 
+#[cfg(feature = "agreement")]
+pub mod agreement;
+pub mod armor;
+pub mod commitment;
+#[cfg(feature = "encryption")]
+pub mod envelope;
+pub mod group;
+pub mod keychain;
+#[cfg(feature = "mnemonic")]
+pub mod mnemonic;
+pub mod oracle;
+pub mod path;
+pub mod provider;
+#[cfg(feature = "qr")]
+pub mod qr;
+pub mod ratchet;
+pub mod record;
+pub mod stream;
+pub mod target;
+#[cfg(feature = "serde")]
+pub mod test_vectors;
+pub mod uri;
+
+pub use oracle::SaltSchedule;
+pub use provider::SrtProvider;
+pub use stream::TargetStream;
+pub use target::EncodedTarget;
+pub use crate::embedded::{pattern_from_srt, SemanticRendezvousToken};
+pub(crate) use crate::embedded::pattern_from_digest;
+
 use std::fmt;
 use std::str::FromStr;
 
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bech32::{Bech32m, Hrp};
+use hkdf::Hkdf;
 use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
+use sha3::Sha3_256;
 
+use crate::embedded;
+use crate::matching::Tolerances;
 use crate::pattern::{
-    quantize_u16_to_range, SubmodalityPattern, AROUSAL_MAX, AROUSAL_MIN, BRIGHTNESS_MAX,
-    BRIGHTNESS_MIN, COLOR_TEMP_MAX, COLOR_TEMP_MIN, FOCAL_DISTANCE_MAX, FOCAL_DISTANCE_MIN,
-    MOVEMENT_MAX, MOVEMENT_MIN, PITCH_MAX, PITCH_MIN, TEMPERATURE_MAX, TEMPERATURE_MIN, TEMPO_MAX,
-    TEMPO_MIN, VOLUME_MAX, VOLUME_MIN,
+    pattern_from_array, pattern_to_array, quantize_u16_to_range, SubmodalityPattern, AROUSAL_MAX,
+    AROUSAL_MIN, BRIGHTNESS_MAX, BRIGHTNESS_MIN, COLOR_TEMP_MAX, COLOR_TEMP_MIN,
+    FOCAL_DISTANCE_MAX, FOCAL_DISTANCE_MIN, MOVEMENT_MAX, MOVEMENT_MIN, PITCH_MAX, PITCH_MIN,
+    TEMPERATURE_MAX, TEMPERATURE_MIN, TEMPO_MAX, TEMPO_MIN, VOLUME_MAX, VOLUME_MIN,
 };
+use crate::registry::{DimensionRegistry, DynPattern};
 
-/// A Semantic Rendezvous Token (SRT).
-///
-/// An SRT is a shared secret key used for HMAC-based derivation of target
-/// patterns during rendezvous. We treat it as an opaque 32-byte value and do
-/// not attempt to derive it from passwords or other human inputs here.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct SemanticRendezvousToken([u8; 32]);
+/// Human-readable prefix of bech32-encoded SRTs.
+pub const BECH32_HRP: &str = "srt";
 
+// The token type and HMAC-SHA256 derivation live in `embedded` so that
+// `no_std` builds can derive targets; encodings and generation need `std`.
 impl SemanticRendezvousToken {
-    /// Create an SRT from raw 32-byte input.
-    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+    /// Generate a fresh SRT from the operating system's CSPRNG.
+    pub fn generate() -> Self {
+        Self::generate_with(&mut OsRng)
+    }
+
+    /// Generate a fresh SRT from a caller-supplied cryptographic RNG.
+    ///
+    /// Useful for deterministic tests with a seeded CSPRNG such as
+    /// `rand::rngs::StdRng`; production code should prefer [`generate`].
+    ///
+    /// [`generate`]: Self::generate
+    pub fn generate_with<R: CryptoRng + RngCore>(rng: &mut R) -> Self {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
         Self(bytes)
     }
 
@@ -40,66 +90,604 @@ impl SemanticRendezvousToken {
         Ok(Self(raw))
     }
 
-    /// Borrow the underlying bytes.
-    pub fn as_bytes(&self) -> &[u8; 32] {
-        &self.0
-    }
-
     /// Parse an SRT from a hex string.
     ///
     /// The string must contain exactly 64 hex characters.
     pub fn from_hex(hex: &str) -> Result<Self, SrtParseError> {
         hex.parse()
     }
+
+    /// Parse an SRT from hex copied out of other tools.
+    ///
+    /// Accepts everything [`from_hex`](Self::from_hex) does, plus a `0x`
+    /// prefix on the whole string or on each group, and groups separated by
+    /// whitespace, `:`, `-`, or `,` (as in `AB:CD:...` or `0xab, 0xcd, ...`).
+    /// Case may be mixed. The digits must still encode exactly 32 bytes.
+    pub fn from_hex_lenient(hex: &str) -> Result<Self, SrtParseError> {
+        let digits: String = hex
+            .split(|c: char| c.is_whitespace() || matches!(c, ':' | '-' | ','))
+            .map(|group| {
+                group
+                    .strip_prefix("0x")
+                    .or_else(|| group.strip_prefix("0X"))
+                    .unwrap_or(group)
+            })
+            .collect();
+        digits.parse()
+    }
+
+    /// Short fingerprint for verifying verbally that two tokens are equal.
+    ///
+    /// The fingerprint is a hash of the token, not a truncation of it, so it
+    /// reveals nothing about the token and different tokens collide only with
+    /// probability 2^-64.
+    pub fn fingerprint(&self) -> Fingerprint {
+        let digest = Sha256::new()
+            .chain_update(FINGERPRINT_DOMAIN)
+            .chain_update(self.0)
+            .finalize();
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        Fingerprint(bytes)
+    }
+
+    /// Combine this token with another party's into a joint token.
+    ///
+    /// The joint token is HKDF-SHA256 over both tokens in sorted order with
+    /// [`SUBKEY_COMBINE`] as info, so `a.combine(&b) == b.combine(&a)` and
+    /// neither party alone chooses the result: it changes whenever either
+    /// contribution does. Each party should generate its contribution
+    /// freshly and exchange it only after receiving a commitment to the
+    /// other's, or the second party can pick theirs after seeing the first.
+    pub fn combine(&self, other: &Self) -> Self {
+        let (first, second) = if self.0 <= other.0 {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let ikm = [first.0, second.0].concat();
+        let mut okm = [0u8; 32];
+        Hkdf::<Sha256>::new(None, &ikm)
+            .expand(SUBKEY_COMBINE, &mut okm)
+            .expect("32 bytes is a valid HKDF output length");
+        Self(okm)
+    }
+
+    /// Encode as a bech32m string with the `srt` prefix, e.g. `srt1...`.
+    ///
+    /// The checksum detects any single typo and most transpositions, which
+    /// makes this the preferred form for tokens copied by hand.
+    pub fn to_bech32(&self) -> String {
+        let hrp = Hrp::parse(BECH32_HRP).expect("valid HRP");
+        bech32::encode::<Bech32m>(hrp, &self.0).expect("32 bytes fit in bech32")
+    }
+
+    /// Parse a bech32m string produced by [`to_bech32`](Self::to_bech32).
+    ///
+    /// Upper-case input is accepted, but mixed case is not.
+    pub fn from_bech32(text: &str) -> Result<Self, SrtParseError> {
+        let (hrp, data) = bech32::decode(text.trim()).map_err(|err| match err {
+            bech32::DecodeError::Checksum(_) => SrtParseError::InvalidChecksum,
+            other => SrtParseError::InvalidBech32(other.to_string()),
+        })?;
+        if hrp.to_lowercase() != BECH32_HRP {
+            return Err(SrtParseError::WrongPrefix(hrp.to_lowercase()));
+        }
+        Self::from_slice(&data)
+    }
+
+    /// Encode as unpadded base64url (43 characters).
+    pub fn to_base64url(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.0)
+    }
+
+    /// Parse unpadded base64url produced by [`to_base64url`](Self::to_base64url).
+    ///
+    /// Base64url carries no checksum; prefer bech32 for hand-copied tokens.
+    pub fn from_base64url(text: &str) -> Result<Self, SrtParseError> {
+        let data = URL_SAFE_NO_PAD
+            .decode(text.trim())
+            .map_err(|err| SrtParseError::InvalidBase64(err.to_string()))?;
+        Self::from_slice(&data)
+    }
+}
+
+/// Domain separator hashed before the token when computing fingerprints.
+const FINGERPRINT_DOMAIN: &[u8] = b"phenorv-fingerprint-v1|";
+
+/// Short hash of an SRT for comparing tokens by voice or by eye.
+///
+/// Displays as four dash-separated groups of four hex digits, e.g.
+/// `3f9a-04c2-b71e-5d08`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint([u8; 8]);
+
+impl Fingerprint {
+    /// Borrow the fingerprint bytes.
+    pub fn as_bytes(&self) -> &[u8; 8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, pair) in self.0.chunks(2).enumerate() {
+            if i > 0 {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02x}{:02x}", pair[0], pair[1])?;
+        }
+        Ok(())
+    }
+}
+
+/// Keyed PRF used to turn an SRT and salt into the 32-byte digest behind
+/// patterns, tolerances, and confirmation tags.
+///
+/// Peers must agree on the algorithm; record it alongside derived targets
+/// (see [`DerivedTarget`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum DerivationAlgorithm {
+    /// HMAC-SHA256 (the original scheme).
+    #[default]
+    HmacSha256,
+    /// BLAKE3 in keyed-hash mode with the SRT as key.
+    Blake3Keyed,
+    /// HMAC-SHA3-256.
+    HmacSha3_256,
+}
+
+impl DerivationAlgorithm {
+    /// Compute the 32-byte digest of `salt` keyed by the SRT.
+    pub fn digest(self, srt: &SemanticRendezvousToken, salt: &[u8]) -> [u8; 32] {
+        match self {
+            Self::HmacSha256 => embedded::hmac_sha256(srt, salt),
+            Self::Blake3Keyed => blake3::keyed_hash(srt.as_bytes(), salt).into(),
+            Self::HmacSha3_256 => {
+                let mut mac = Hmac::<Sha3_256>::new_from_slice(srt.as_bytes())
+                    .expect("HMAC can take a 32-byte key");
+                mac.update(salt);
+                mac.finalize().into_bytes().into()
+            }
+        }
+    }
+}
+
+/// A target pattern together with the algorithm that derived it.
+///
+/// Serializes as the pattern's fields plus an `algorithm` field, so readers
+/// that only expect a `SubmodalityPattern` can still load it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DerivedTarget {
+    /// Algorithm used to derive the pattern.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub algorithm: DerivationAlgorithm,
+    /// The derived pattern.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub pattern: SubmodalityPattern,
+}
+
+impl DerivedTarget {
+    /// Derive the target for an SRT and salt with `algorithm`.
+    pub fn derive(
+        srt: &SemanticRendezvousToken,
+        salt: &[u8],
+        algorithm: DerivationAlgorithm,
+    ) -> Self {
+        Self {
+            algorithm,
+            pattern: pattern_from_srt_with(srt, salt, algorithm),
+        }
+    }
+}
+
+/// Derive a `SubmodalityPattern` with a chosen [`DerivationAlgorithm`].
+///
+/// The digest layout is the same as for [`pattern_from_srt`].
+pub fn pattern_from_srt_with(
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    algorithm: DerivationAlgorithm,
+) -> SubmodalityPattern {
+    pattern_from_digest(&algorithm.digest(srt, salt))
+}
+
+/// Where one dimension of a target pattern comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum DimensionSource {
+    /// Derived from the SRT and salt.
+    #[default]
+    Derived,
+    /// Pinned to a raw value, e.g. a measured ambient level both peers share.
+    Fixed(f32),
+    /// Excluded from matching; see [`Matcher::with_derivation_spec`].
+    ///
+    /// [`Matcher::with_derivation_spec`]: crate::matching::Matcher::with_derivation_spec
+    Ignored,
+}
+
+/// Per-dimension choice of [`DimensionSource`] for target derivation.
+///
+/// The default derives every dimension, matching [`pattern_from_srt`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DerivationSpec {
+    /// Source of `brightness`.
+    pub brightness: DimensionSource,
+    /// Source of `color_temp`.
+    pub color_temp: DimensionSource,
+    /// Source of `focal_distance`.
+    pub focal_distance: DimensionSource,
+    /// Source of `volume`.
+    pub volume: DimensionSource,
+    /// Source of `tempo`.
+    pub tempo: DimensionSource,
+    /// Source of `pitch`.
+    pub pitch: DimensionSource,
+    /// Source of `temperature`.
+    pub temperature: DimensionSource,
+    /// Source of `movement`.
+    pub movement: DimensionSource,
+    /// Source of `arousal`.
+    pub arousal: DimensionSource,
+}
+
+impl DerivationSpec {
+    /// Sources in field declaration order.
+    pub fn sources(&self) -> [DimensionSource; 9] {
+        [
+            self.brightness,
+            self.color_temp,
+            self.focal_distance,
+            self.volume,
+            self.tempo,
+            self.pitch,
+            self.temperature,
+            self.movement,
+            self.arousal,
+        ]
+    }
+
+    /// Which dimensions are [`DimensionSource::Ignored`], in field
+    /// declaration order.
+    pub fn ignored(&self) -> [bool; 9] {
+        self.sources()
+            .map(|source| source == DimensionSource::Ignored)
+    }
 }
 
-/// Derive a `SubmodalityPattern` from an SRT and salt (oracle-state).
+/// Derive a target pattern with per-dimension sources.
 ///
-/// This uses HMAC-SHA256 with the SRT as key and `salt` as the message.
-/// The resulting 32-byte digest is partitioned into 16-bit chunks:
+/// Derived dimensions take the values [`pattern_from_srt`] would give, fixed
+/// dimensions take their pinned value, and ignored dimensions keep the
+/// derived value as a placeholder that a matcher built with
+/// [`Matcher::with_derivation_spec`] never compares.
 ///
-/// - `digest[0..2]`  -> brightness
-/// - `digest[2..4]`  -> color_temp
-/// - `digest[4..6]`  -> focal_distance
-/// - `digest[6..8]`  -> volume
-/// - `digest[8..10]` -> tempo
-/// - `digest[10..12]` -> pitch
-/// - `digest[12..14]` -> temperature
-/// - `digest[14..16]` -> movement
-/// - `digest[16..18]` -> arousal
+/// [`Matcher::with_derivation_spec`]: crate::matching::Matcher::with_derivation_spec
+pub fn pattern_from_srt_with_spec(
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    spec: &DerivationSpec,
+) -> SubmodalityPattern {
+    let mut values = pattern_to_array(&pattern_from_srt(srt, salt));
+    for (value, source) in values.iter_mut().zip(spec.sources()) {
+        if let DimensionSource::Fixed(fixed) = source {
+            *value = fixed;
+        }
+    }
+    pattern_from_array(values)
+}
+
+/// Build the derivation message for a context label and salt.
 ///
-/// Remaining bytes are reserved for future extensions.
+/// The message is the context length as a 4-byte big-endian integer, the
+/// UTF-8 context, then the salt. The length prefix keeps `("ab", "c")` and
+/// `("a", "bc")` apart. Pass the result as the salt to any derivation
+/// function (patterns, tolerances, tags, or the `_with` variants) to scope it
+/// to one application.
+pub fn context_salt(context: &str, salt: &[u8]) -> Vec<u8> {
+    let len = u32::try_from(context.len()).expect("context label fits in u32");
+    let mut message = Vec::with_capacity(4 + context.len() + salt.len());
+    message.extend_from_slice(&len.to_be_bytes());
+    message.extend_from_slice(context.as_bytes());
+    message.extend_from_slice(salt);
+    message
+}
+
+/// Derive a `SubmodalityPattern` scoped to an application context.
 ///
-/// `salt` is an oracle-state or context binding, and should be provided in the
-/// same format for all peers that need to rendezvous.
-pub fn pattern_from_srt(
+/// Deployments that share an SRT but use different `context` labels (e.g.
+/// `"com.example.meetup"`) get independent targets for the same salt. The
+/// HMAC message is [`context_salt`]`(context, salt)`.
+pub fn pattern_from_srt_with_context(
     srt: &SemanticRendezvousToken,
+    context: &str,
     salt: &[u8],
 ) -> SubmodalityPattern {
-    let mut mac = Hmac::<Sha256>::new_from_slice(srt.as_bytes())
-        .expect("HMAC can take a 32-byte key");
-    mac.update(salt);
-    let digest = mac.finalize().into_bytes();
+    pattern_from_srt(srt, &context_salt(context, salt))
+}
+
+/// Domain prefix of cell-ID derivation messages.
+///
+/// Distinct from the `phenorv-geo-v1` prefix of [`oracle::geohash_salt`], so
+/// a cell ID can never encode to the same message as a geohash salt.
+pub const GEO_DOMAIN: &[u8] = b"phenorv-geocell-v1|";
+
+/// Build the derivation message for a geographic cell and salt.
+///
+/// The message is [`GEO_DOMAIN`], the cell index as an 8-byte big-endian
+/// integer, then the salt. Like [`context_salt`], the result can be passed as
+/// the salt to any derivation function.
+pub fn geo_salt(cell_id: u64, salt: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(GEO_DOMAIN.len() + 8 + salt.len());
+    message.extend_from_slice(GEO_DOMAIN);
+    message.extend_from_slice(&cell_id.to_be_bytes());
+    message.extend_from_slice(salt);
+    message
+}
+
+/// Derive a `SubmodalityPattern` scoped to a geographic cell.
+///
+/// `cell_id` is a 64-bit S2 cell ID or H3 index at whatever resolution the
+/// deployment agrees on. The same SRT and salt yield unrelated targets in
+/// different cells, so a remote party holding the SRT still has to know the
+/// local cell to precompute a target. Peers near a cell boundary may sit in
+/// different cells; derive for each neighbouring cell if that matters. The
+/// HMAC message is [`geo_salt`]`(cell_id, salt)`.
+pub fn pattern_from_srt_geo(
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    cell_id: u64,
+) -> SubmodalityPattern {
+    pattern_from_srt(srt, &geo_salt(cell_id, salt))
+}
+
+/// Domain prefix of sequence (moving-target) derivation messages.
+pub const SEQUENCE_DOMAIN: &[u8] = b"phenorv-sequence-v1|";
+
+/// Build the derivation message for step `counter` of a target sequence.
+///
+/// The message is [`SEQUENCE_DOMAIN`], the salt length as an 8-byte
+/// big-endian integer, the salt, then `counter` as a 4-byte big-endian
+/// integer. The domain keeps sequence targets apart from plain
+/// [`pattern_from_srt`] targets, and the length prefix keeps `(salt, i)`
+/// pairs from colliding when one salt ends with the bytes of another's
+/// counter.
+pub fn sequence_salt(salt: &[u8], counter: u32) -> Vec<u8> {
+    let mut message = Vec::with_capacity(SEQUENCE_DOMAIN.len() + 8 + salt.len() + 4);
+    message.extend_from_slice(SEQUENCE_DOMAIN);
+    message.extend_from_slice(&(salt.len() as u64).to_be_bytes());
+    message.extend_from_slice(salt);
+    message.extend_from_slice(&counter.to_be_bytes());
+    message
+}
+
+/// Derive a deterministic chain of `count` target patterns.
+///
+/// Pattern `i` is derived like [`pattern_from_srt`] with the message
+/// [`sequence_salt`]`(salt, i)`, so the target moves through the chain
+/// during a session. Step through it with
+/// [`SequenceMatcher`](crate::matching::SequenceMatcher). For open-ended
+/// sessions, iterate a [`TargetStream`] instead.
+pub fn pattern_sequence_from_srt(
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    count: u32,
+) -> Vec<SubmodalityPattern> {
+    TargetStream::new(srt, salt).take(count as usize).collect()
+}
+
+/// Domain prefix of per-dimension (v2) derivation messages.
+pub const DIMENSION_DOMAIN_V2: &[u8] = b"phenorv-dimension-v2|";
 
-    let read = |start: usize| -> u16 {
-        let hi = digest[start] as u16;
-        let lo = digest[start + 1] as u16;
-        (hi << 8) | lo
-    };
+/// Derive one dimension's value from its own digest (the v2 scheme).
+///
+/// The HMAC-SHA256 message is [`DIMENSION_DOMAIN_V2`] followed by
+/// [`context_salt`]`(label, salt)`, and the first two digest bytes are
+/// quantized onto `min..=max`. Because each label gets its own digest, the
+/// value for `"brightness"` does not depend on which other dimensions exist.
+pub fn dimension_from_srt_v2(
+    srt: &SemanticRendezvousToken,
+    label: &str,
+    salt: &[u8],
+    min: f32,
+    max: f32,
+) -> f32 {
+    let message = [DIMENSION_DOMAIN_V2, &context_salt(label, salt)].concat();
+    let digest = DerivationAlgorithm::HmacSha256.digest(srt, &message);
+    quantize_u16_to_range(u16::from_be_bytes([digest[0], digest[1]]), min, max)
+}
 
+/// Derive a `SubmodalityPattern` with independent per-dimension digests.
+///
+/// Unlike [`pattern_from_srt`] (v1), which slices one digest at fixed
+/// offsets, every field is derived by [`dimension_from_srt_v2`] with its
+/// field name as label. New dimensions can be added later without changing
+/// the values of existing ones. v1 and v2 give different targets for the same
+/// inputs, so peers must agree on the version.
+pub fn pattern_from_srt_v2(srt: &SemanticRendezvousToken, salt: &[u8]) -> SubmodalityPattern {
+    let dim = |label, min, max| dimension_from_srt_v2(srt, label, salt, min, max);
     SubmodalityPattern {
-        brightness: quantize_u16_to_range(read(0), BRIGHTNESS_MIN, BRIGHTNESS_MAX),
-        color_temp: quantize_u16_to_range(read(2), COLOR_TEMP_MIN, COLOR_TEMP_MAX),
-        focal_distance: quantize_u16_to_range(read(4), FOCAL_DISTANCE_MIN, FOCAL_DISTANCE_MAX),
-        volume: quantize_u16_to_range(read(6), VOLUME_MIN, VOLUME_MAX),
-        tempo: quantize_u16_to_range(read(8), TEMPO_MIN, TEMPO_MAX),
-        pitch: quantize_u16_to_range(read(10), PITCH_MIN, PITCH_MAX),
-        temperature: quantize_u16_to_range(read(12), TEMPERATURE_MIN, TEMPERATURE_MAX),
-        movement: quantize_u16_to_range(read(14), MOVEMENT_MIN, MOVEMENT_MAX),
-        arousal: quantize_u16_to_range(read(16), AROUSAL_MIN, AROUSAL_MAX),
+        brightness: dim("brightness", BRIGHTNESS_MIN, BRIGHTNESS_MAX),
+        color_temp: dim("color_temp", COLOR_TEMP_MIN, COLOR_TEMP_MAX),
+        focal_distance: dim("focal_distance", FOCAL_DISTANCE_MIN, FOCAL_DISTANCE_MAX),
+        volume: dim("volume", VOLUME_MIN, VOLUME_MAX),
+        tempo: dim("tempo", TEMPO_MIN, TEMPO_MAX),
+        pitch: dim("pitch", PITCH_MIN, PITCH_MAX),
+        temperature: dim("temperature", TEMPERATURE_MIN, TEMPERATURE_MAX),
+        movement: dim("movement", MOVEMENT_MIN, MOVEMENT_MAX),
+        arousal: dim("arousal", AROUSAL_MIN, AROUSAL_MAX),
     }
 }
 
+/// Derive a [`DynPattern`] over every dimension in `registry` with the v2
+/// scheme.
+///
+/// Values are spread linearly over each dimension's `min..=max`. For the
+/// [`DimensionRegistry::core`] registry the result holds the same values as
+/// [`pattern_from_srt_v2`].
+pub fn dyn_pattern_from_srt_v2(
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    registry: &DimensionRegistry,
+) -> DynPattern {
+    registry.iter().fold(DynPattern::new(), |pattern, spec| {
+        let value = dimension_from_srt_v2(srt, &spec.name, salt, spec.min, spec.max);
+        pattern.with(spec.name.clone(), value)
+    })
+}
+
+/// Smallest derived per-dimension tolerance, in normalized units.
+pub const TOLERANCE_MIN: f32 = 0.02;
+
+/// Largest derived per-dimension tolerance, in normalized units.
+pub const TOLERANCE_MAX: f32 = 0.2;
+
+/// Derive per-dimension matching tolerances from an SRT and salt.
+///
+/// Each of `digest[18..27]` (one byte per dimension, in field declaration
+/// order) is mapped linearly onto `TOLERANCE_MIN..=TOLERANCE_MAX`, so peers
+/// sharing an SRT and salt agree on tolerances without exchanging
+/// configuration. Use the result with [`Matcher::with_tolerances`].
+///
+/// [`Matcher::with_tolerances`]: crate::matching::Matcher::with_tolerances
+pub fn tolerances_from_srt(srt: &SemanticRendezvousToken, salt: &[u8]) -> Tolerances {
+    tolerances_from_srt_with(srt, salt, DerivationAlgorithm::HmacSha256)
+}
+
+/// Derive per-dimension tolerances with a chosen [`DerivationAlgorithm`].
+pub fn tolerances_from_srt_with(
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    algorithm: DerivationAlgorithm,
+) -> Tolerances {
+    tolerances_from_digest(&algorithm.digest(srt, salt))
+}
+
+/// Map `digest[18..27]` onto tolerances as documented on [`tolerances_from_srt`].
+pub(crate) fn tolerances_from_digest(digest: &[u8; 32]) -> Tolerances {
+    let values = std::array::from_fn(|i| {
+        TOLERANCE_MIN + f32::from(digest[18 + i]) / 255.0 * (TOLERANCE_MAX - TOLERANCE_MIN)
+    });
+    Tolerances::new(values).expect("derived tolerances are within bounds")
+}
+
+/// Short tag that peers exchange after a candidate match.
+///
+/// Both sides derive the tag from the same SRT and salt as the target
+/// pattern. A peer that matched the pattern by chance, without the SRT, cannot
+/// produce it, so comparing tags weeds out false rendezvous. The tag is four
+/// digest bytes and reveals nothing useful about the SRT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConfirmationTag([u8; 4]);
+
+impl ConfirmationTag {
+    /// Wrap raw tag bytes, e.g. as received from a peer.
+    pub fn from_bytes(bytes: [u8; 4]) -> Self {
+        Self(bytes)
+    }
+
+    /// Borrow the tag bytes.
+    pub fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+
+    /// Compare with a tag received from a peer in constant time.
+    pub fn verify(&self, received: &ConfirmationTag) -> bool {
+        self.0
+            .iter()
+            .zip(received.0)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+    }
+}
+
+impl fmt::Display for ConfirmationTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Derive the confirmation tag for an SRT and salt from `digest[27..31]`.
+pub fn confirmation_tag_from_srt(srt: &SemanticRendezvousToken, salt: &[u8]) -> ConfirmationTag {
+    confirmation_tag_from_srt_with(srt, salt, DerivationAlgorithm::HmacSha256)
+}
+
+/// Derive the confirmation tag with a chosen [`DerivationAlgorithm`].
+pub fn confirmation_tag_from_srt_with(
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+    algorithm: DerivationAlgorithm,
+) -> ConfirmationTag {
+    let digest = algorithm.digest(srt, salt);
+    ConfirmationTag([digest[27], digest[28], digest[29], digest[30]])
+}
+
+/// Derive the target pattern and its confirmation tag in one step.
+pub fn pattern_and_tag_from_srt(
+    srt: &SemanticRendezvousToken,
+    salt: &[u8],
+) -> (SubmodalityPattern, ConfirmationTag) {
+    (
+        pattern_from_srt(srt, salt),
+        confirmation_tag_from_srt(srt, salt),
+    )
+}
+
+/// Info label for keys that derive per-epoch target patterns.
+///
+/// Append the epoch salt, e.g. `[SUBKEY_TARGET, b"|", &epoch_salt(n)].concat()`,
+/// to get one independent key per epoch.
+pub const SUBKEY_TARGET: &[u8] = b"phenorv-subkey-v1|target";
+
+/// Info label for the key that authenticates match confirmations.
+pub const SUBKEY_CONFIRMATION: &[u8] = b"phenorv-subkey-v1|confirmation";
+
+/// Info label for the key that protects the post-rendezvous transport.
+pub const SUBKEY_TRANSPORT: &[u8] = b"phenorv-subkey-v1|transport";
+
+/// Info label for joint tokens built by [`SemanticRendezvousToken::combine`].
+pub const SUBKEY_COMBINE: &[u8] = b"phenorv-subkey-v1|combine";
+
+/// Longest subkey HKDF-SHA256 can produce, in bytes.
+pub const SUBKEY_MAX_LEN: usize = 255 * 32;
+
+/// Derive a `len`-byte subkey from an SRT with HKDF-SHA256.
+///
+/// The SRT is the input key material and `info` separates uses, so keys for
+/// different labels are independent and never reveal the token itself. Use
+/// the `SUBKEY_*` labels for the uses this crate knows about; applications
+/// should pick their own distinct labels for anything else.
+pub fn derive_subkey(
+    srt: &SemanticRendezvousToken,
+    info: &[u8],
+    len: usize,
+) -> Result<Vec<u8>, SubkeyError> {
+    if len == 0 || len > SUBKEY_MAX_LEN {
+        return Err(SubkeyError::InvalidLength(len));
+    }
+    let mut okm = vec![0u8; len];
+    Hkdf::<Sha256>::new(None, srt.as_bytes())
+        .expand(info, &mut okm)
+        .map_err(|_| SubkeyError::InvalidLength(len))?;
+    Ok(okm)
+}
+
 impl fmt::Display for SemanticRendezvousToken {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for byte in &self.0 {
@@ -127,6 +715,86 @@ impl FromStr for SemanticRendezvousToken {
     }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for SemanticRendezvousToken {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for SemanticRendezvousToken {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(TokenVisitor)
+        } else {
+            deserializer.deserialize_bytes(TokenVisitor)
+        }
+    }
+}
+
+/// Accepts hex strings, byte strings, and legacy byte arrays.
+#[cfg(feature = "serde")]
+struct TokenVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for TokenVisitor {
+    type Value = SemanticRendezvousToken;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a 64-character hex string or 32 bytes")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+        SemanticRendezvousToken::from_slice(value).map_err(E::custom)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        use serde::de::Error;
+
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(i, &"32 bytes"))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(A::Error::invalid_length(33, &"32 bytes"));
+        }
+        Ok(SemanticRendezvousToken(bytes))
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for SemanticRendezvousToken {
+    fn schema_name() -> String {
+        "SemanticRendezvousToken".to_string()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::schema::Schema {
+        use schemars::schema::{InstanceType, SchemaObject, StringValidation};
+
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                max_length: Some(64),
+                min_length: Some(64),
+                pattern: Some("^[0-9a-fA-F]{64}$".to_string()),
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
 fn decode_hex_nibble(byte: u8) -> Result<u8, SrtParseError> {
     match byte {
         b'0'..=b'9' => Ok(byte - b'0'),
@@ -145,6 +813,15 @@ pub enum SrtParseError {
     InvalidLength(usize),
     /// The input included a non-hex character.
     InvalidHexCharacter(char),
+    /// A bech32 string was malformed (bad character, mixed case, or missing
+    /// separator).
+    InvalidBech32(String),
+    /// A bech32 checksum did not match, usually because of a typo.
+    InvalidChecksum,
+    /// A bech32 string had a prefix other than [`BECH32_HRP`].
+    WrongPrefix(String),
+    /// A base64url string was malformed.
+    InvalidBase64(String),
 }
 
 impl fmt::Display for SrtParseError {
@@ -153,12 +830,40 @@ impl fmt::Display for SrtParseError {
             Self::InvalidHexLength(len) => write!(f, "expected 64 hex chars, got {len}"),
             Self::InvalidLength(len) => write!(f, "expected 32 bytes, got {len}"),
             Self::InvalidHexCharacter(ch) => write!(f, "invalid hex character '{ch}'"),
+            Self::InvalidBech32(reason) => write!(f, "invalid bech32 token: {reason}"),
+            Self::InvalidChecksum => write!(f, "bech32 checksum mismatch (check for typos)"),
+            Self::WrongPrefix(hrp) => {
+                write!(f, "expected '{BECH32_HRP}' prefix, got '{hrp}'")
+            }
+            Self::InvalidBase64(reason) => write!(f, "invalid base64url token: {reason}"),
         }
     }
 }
 
 impl std::error::Error for SrtParseError {}
 
+/// Errors returned when deriving subkeys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubkeyError {
+    /// The requested length was zero or above [`SUBKEY_MAX_LEN`].
+    InvalidLength(usize),
+}
+
+impl fmt::Display for SubkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength(len) => {
+                write!(
+                    f,
+                    "subkey length must be 1..={SUBKEY_MAX_LEN} bytes, got {len}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SubkeyError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,6 +880,299 @@ mod tests {
         assert_eq!(srt, decoded);
     }
 
+    #[test]
+    fn lenient_hex_accepts_copied_formats() {
+        let srt = SemanticRendezvousToken::from_bytes(std::array::from_fn(|i| (i * 7) as u8));
+        let hex = srt.to_string();
+        let bytes: Vec<String> = srt.as_bytes().iter().map(|b| format!("{b:02X}")).collect();
+        let words: Vec<String> = bytes.chunks(4).map(|word| word.concat()).collect();
+        let prefixed: Vec<String> = bytes.iter().map(|b| format!("0x{b}")).collect();
+        let accepted = [
+            hex.clone(),
+            format!("0x{hex}"),
+            format!("0X{}", hex.to_uppercase()),
+            bytes.join(":"),
+            bytes.join(" "),
+            bytes.join("-"),
+            format!("  {}\n", words.join(" ")),
+            prefixed.join(", "),
+            format!("{}{}", &hex[..32], hex[32..].to_uppercase()),
+        ];
+        for text in accepted {
+            assert_eq!(
+                SemanticRendezvousToken::from_hex_lenient(&text),
+                Ok(srt.clone()),
+                "{text}"
+            );
+        }
+
+        assert!(SemanticRendezvousToken::from_hex(&format!("0x{hex}")).is_err());
+        assert!(SemanticRendezvousToken::from_hex(&bytes.join(":")).is_err());
+        assert_eq!(
+            SemanticRendezvousToken::from_hex_lenient(&bytes[..31].join(":")),
+            Err(SrtParseError::InvalidHexLength(62))
+        );
+        assert_eq!(
+            SemanticRendezvousToken::from_hex_lenient(&format!("{}zz", &hex[..62])),
+            Err(SrtParseError::InvalidHexCharacter('z'))
+        );
+    }
+
+    #[test]
+    fn geo_derivation_depends_on_cell() {
+        let srt = SemanticRendezvousToken::from_bytes([5u8; 32]);
+        // H3 resolution-9 cells, as produced by `latLngToCell`.
+        let here = 0x8928308280fffff;
+        let there = 0x8928308280bffff;
+        let target = pattern_from_srt_geo(&srt, b"epoch-1", here);
+        assert_eq!(target, pattern_from_srt_geo(&srt, b"epoch-1", here));
+        assert_ne!(target, pattern_from_srt_geo(&srt, b"epoch-1", there));
+        assert_ne!(target, pattern_from_srt(&srt, b"epoch-1"));
+        assert_eq!(target, pattern_from_srt(&srt, &geo_salt(here, b"epoch-1")));
+    }
+
+    #[test]
+    fn geo_salt_is_separate_from_geohash_salts() {
+        // A cell ID whose bytes spell a geohash cell and epoch.
+        let cell = u64::from_be_bytes(*b"u4pru|42");
+        let geohash = oracle::geohash_salt(57.64911, 10.40744, 5, 42).expect("valid coordinates");
+        assert_ne!(geo_salt(cell, b""), geohash);
+    }
+
+    #[test]
+    fn combine_is_symmetric_and_depends_on_both() {
+        let alice = SemanticRendezvousToken::from_bytes([1u8; 32]);
+        let bob = SemanticRendezvousToken::from_bytes([2u8; 32]);
+        let joint = alice.combine(&bob);
+        assert_eq!(joint, bob.combine(&alice));
+        assert_ne!(joint, alice);
+        assert_ne!(joint, bob);
+
+        let carol = SemanticRendezvousToken::from_bytes([3u8; 32]);
+        assert_ne!(alice.combine(&carol), joint);
+        assert_ne!(carol.combine(&bob), joint);
+    }
+
+    #[test]
+    fn spec_pins_and_ignores_dimensions() {
+        let srt = SemanticRendezvousToken::from_bytes([6u8; 32]);
+        let spec = DerivationSpec {
+            volume: DimensionSource::Fixed(0.4),
+            pitch: DimensionSource::Ignored,
+            ..DerivationSpec::default()
+        };
+        let derived = pattern_from_srt(&srt, b"salt");
+        let pattern = pattern_from_srt_with_spec(&srt, b"salt", &spec);
+        assert_eq!(pattern.volume, 0.4);
+        assert_eq!(pattern.brightness, derived.brightness);
+        assert_eq!(pattern.pitch, derived.pitch);
+        assert_eq!(
+            spec.ignored(),
+            [false, false, false, false, false, true, false, false, false]
+        );
+        assert_eq!(
+            pattern_from_srt_with_spec(&srt, b"salt", &DerivationSpec::default()),
+            derived
+        );
+    }
+
+    #[test]
+    fn v2_derivation_is_per_dimension() {
+        use crate::registry::DimensionSpec;
+
+        let srt = SemanticRendezvousToken::from_bytes([5u8; 32]);
+        let pattern = pattern_from_srt_v2(&srt, b"salt");
+        assert_ne!(pattern, pattern_from_srt(&srt, b"salt"));
+        assert_eq!(
+            pattern.tempo,
+            dimension_from_srt_v2(&srt, "tempo", b"salt", TEMPO_MIN, TEMPO_MAX)
+        );
+
+        let mut registry = DimensionRegistry::core();
+        let core = dyn_pattern_from_srt_v2(&srt, b"salt", &registry);
+        registry
+            .register(DimensionSpec::linear("humidity", 0.0, 100.0))
+            .expect("register");
+        let extended = dyn_pattern_from_srt_v2(&srt, b"salt", &registry);
+        assert_eq!(extended.get("brightness"), Some(pattern.brightness));
+        assert_eq!(extended.get("arousal"), core.get("arousal"));
+        assert!(extended.get("humidity").is_some());
+    }
+
+    #[test]
+    fn text_encodings_round_trip_and_detect_typos() {
+        let srt = SemanticRendezvousToken::from_bytes(std::array::from_fn(|i| i as u8));
+        let bech = srt.to_bech32();
+        assert!(bech.starts_with("srt1"));
+        assert_eq!(SemanticRendezvousToken::from_bech32(&bech), Ok(srt.clone()));
+        assert_eq!(
+            SemanticRendezvousToken::from_bech32(&bech.to_uppercase()),
+            Ok(srt.clone())
+        );
+
+        let mut typo = bech.clone().into_bytes();
+        typo[10] = if typo[10] == b'q' { b'p' } else { b'q' };
+        let typo = String::from_utf8(typo).expect("ascii");
+        assert_eq!(
+            SemanticRendezvousToken::from_bech32(&typo),
+            Err(SrtParseError::InvalidChecksum)
+        );
+        let other = bech32::encode::<Bech32m>(Hrp::parse("key").unwrap(), srt.as_bytes()).unwrap();
+        assert_eq!(
+            SemanticRendezvousToken::from_bech32(&other),
+            Err(SrtParseError::WrongPrefix("key".to_string()))
+        );
+
+        let b64 = srt.to_base64url();
+        assert_eq!(b64.len(), 43);
+        assert_eq!(SemanticRendezvousToken::from_base64url(&b64), Ok(srt));
+        assert!(matches!(
+            SemanticRendezvousToken::from_base64url("not base64!"),
+            Err(SrtParseError::InvalidBase64(_))
+        ));
+    }
+
+    #[test]
+    fn fingerprint_is_short_and_hashed() {
+        let srt = SemanticRendezvousToken::from_bytes([0u8; 32]);
+        let fingerprint = srt.fingerprint();
+        let text = fingerprint.to_string();
+        assert_eq!(text.len(), 19);
+        assert_eq!(text.matches('-').count(), 3);
+        assert_ne!(fingerprint.as_bytes(), &[0u8; 8]);
+        assert_eq!(fingerprint, srt.clone().fingerprint());
+        let mut other = *srt.as_bytes();
+        other[31] = 1;
+        assert_ne!(
+            SemanticRendezvousToken::from_bytes(other).fingerprint(),
+            fingerprint
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_uses_hex_and_accepts_legacy_arrays() {
+        let srt = SemanticRendezvousToken::from_bytes(std::array::from_fn(|i| i as u8));
+        let json = serde_json::to_string(&srt).expect("serialize");
+        assert_eq!(json, format!("\"{srt}\""));
+        let restored: SemanticRendezvousToken = serde_json::from_str(&json).expect("hex");
+        assert_eq!(restored, srt);
+
+        let legacy = serde_json::to_string(srt.as_bytes()).expect("array");
+        let migrated: SemanticRendezvousToken = serde_json::from_str(&legacy).expect("legacy");
+        assert_eq!(migrated, srt);
+
+        assert!(serde_json::from_str::<SemanticRendezvousToken>("\"abcd\"").is_err());
+        assert!(serde_json::from_str::<SemanticRendezvousToken>("[1, 2, 3]").is_err());
+    }
+
+    #[test]
+    fn generated_tokens_are_random() {
+        use rand::SeedableRng;
+
+        assert_ne!(
+            SemanticRendezvousToken::generate(),
+            SemanticRendezvousToken::generate()
+        );
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let token = SemanticRendezvousToken::generate_with(&mut rng);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        assert_eq!(token, SemanticRendezvousToken::generate_with(&mut rng));
+    }
+
+    #[test]
+    fn confirmation_tag_separates_tokens() {
+        let srt = SemanticRendezvousToken::from_bytes([1u8; 32]);
+        let (pattern, tag) = pattern_and_tag_from_srt(&srt, b"salt");
+        assert_eq!(pattern, pattern_from_srt(&srt, b"salt"));
+        assert!(tag.verify(&confirmation_tag_from_srt(&srt, b"salt")));
+
+        let digest = DerivationAlgorithm::HmacSha256.digest(&srt, b"salt");
+        assert_eq!(tag.as_bytes(), &digest[27..31]);
+        assert_eq!(tag.to_string().len(), 8);
+
+        let impostor = SemanticRendezvousToken::from_bytes([2u8; 32]);
+        assert!(!tag.verify(&confirmation_tag_from_srt(&impostor, b"salt")));
+        assert!(!tag.verify(&confirmation_tag_from_srt(&srt, b"other")));
+    }
+
+    #[test]
+    fn context_labels_separate_deployments() {
+        let srt = SemanticRendezvousToken::from_bytes([4u8; 32]);
+        let alpha = pattern_from_srt_with_context(&srt, "alpha", b"salt");
+        assert_eq!(alpha, pattern_from_srt_with_context(&srt, "alpha", b"salt"));
+        assert_ne!(alpha, pattern_from_srt_with_context(&srt, "beta", b"salt"));
+        assert_ne!(alpha, pattern_from_srt(&srt, b"salt"));
+        assert_ne!(context_salt("ab", b"c"), context_salt("a", b"bc"));
+        assert_eq!(context_salt("a", b"s"), [0, 0, 0, 1, b'a', b's']);
+    }
+
+    #[test]
+    fn pattern_sequence_is_deterministic_and_moves() {
+        let srt = SemanticRendezvousToken::from_bytes([6u8; 32]);
+        let sequence = pattern_sequence_from_srt(&srt, b"salt", 3);
+        assert_eq!(sequence.len(), 3);
+        assert_eq!(sequence, pattern_sequence_from_srt(&srt, b"salt", 3));
+        assert_ne!(sequence[0], sequence[1]);
+        assert_eq!(sequence[1], pattern_from_srt(&srt, &sequence_salt(b"salt", 1)));
+        assert_ne!(sequence[1], pattern_from_srt(&srt, b"salt\0\0\0\x01"));
+        assert_ne!(
+            pattern_sequence_from_srt(&srt, b"salt\0\0\0\x01", 1),
+            pattern_sequence_from_srt(&srt, b"salt", 2)[1..]
+        );
+    }
+
+    #[test]
+    fn derivation_algorithms_are_distinct() {
+        let srt = SemanticRendezvousToken::from_bytes([5u8; 32]);
+        let sha256 = DerivedTarget::derive(&srt, b"salt", DerivationAlgorithm::HmacSha256);
+        let blake3 = DerivedTarget::derive(&srt, b"salt", DerivationAlgorithm::Blake3Keyed);
+        let sha3 = DerivedTarget::derive(&srt, b"salt", DerivationAlgorithm::HmacSha3_256);
+        assert_eq!(sha256.pattern, pattern_from_srt(&srt, b"salt"));
+        assert_ne!(sha256.pattern, blake3.pattern);
+        assert_ne!(sha256.pattern, sha3.pattern);
+        assert_ne!(blake3.pattern, sha3.pattern);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn derived_target_records_algorithm() {
+        let srt = SemanticRendezvousToken::from_bytes([5u8; 32]);
+        let target = DerivedTarget::derive(&srt, b"salt", DerivationAlgorithm::Blake3Keyed);
+        let json = serde_json::to_value(&target).expect("serialize");
+        assert_eq!(json["algorithm"], "blake3_keyed");
+        assert!(json.get("brightness").is_some());
+        let pattern: SubmodalityPattern = serde_json::from_value(json.clone()).expect("pattern");
+        assert_eq!(pattern, target.pattern);
+        let restored: DerivedTarget = serde_json::from_value(json).expect("target");
+        assert_eq!(restored, target);
+    }
+
+    #[test]
+    fn subkeys_are_separated_by_label() {
+        let srt = SemanticRendezvousToken::from_bytes([7u8; 32]);
+        let confirmation = derive_subkey(&srt, SUBKEY_CONFIRMATION, 32).expect("derive");
+        let transport = derive_subkey(&srt, SUBKEY_TRANSPORT, 32).expect("derive");
+        assert_eq!(confirmation.len(), 32);
+        assert_ne!(confirmation, transport);
+        assert_ne!(confirmation.as_slice(), srt.as_bytes());
+        assert_eq!(
+            derive_subkey(&srt, SUBKEY_CONFIRMATION, 32).expect("derive"),
+            confirmation
+        );
+        // A shorter key is a prefix of the longer one for the same label.
+        let short = derive_subkey(&srt, SUBKEY_CONFIRMATION, 16).expect("derive");
+        assert_eq!(short, confirmation[..16]);
+        assert_eq!(
+            derive_subkey(&srt, SUBKEY_TARGET, 0),
+            Err(SubkeyError::InvalidLength(0))
+        );
+        assert_eq!(
+            derive_subkey(&srt, SUBKEY_TARGET, SUBKEY_MAX_LEN + 1),
+            Err(SubkeyError::InvalidLength(SUBKEY_MAX_LEN + 1))
+        );
+    }
+
     #[test]
     fn srt_pattern_is_deterministic() {
         let srt = SemanticRendezvousToken::from_bytes([7u8; 32]);
@@ -197,4 +1195,16 @@ mod tests {
         }
         assert!(different >= 2);
     }
+
+    #[test]
+    fn derived_tolerances_are_bounded_and_shared() {
+        let srt = SemanticRendezvousToken::from_bytes([5u8; 32]);
+        let tolerances = tolerances_from_srt(&srt, b"oracle-state");
+        assert_eq!(tolerances, tolerances_from_srt(&srt, b"oracle-state"));
+        assert_ne!(tolerances, tolerances_from_srt(&srt, b"other-state"));
+        assert!(tolerances
+            .values()
+            .iter()
+            .all(|t| (TOLERANCE_MIN..=TOLERANCE_MAX).contains(t)));
+    }
 }
@@ -0,0 +1,136 @@
+//! X25519 key agreement for establishing an SRT.
+//!
+//! Peers without a prior shared secret each create an [`AgreementKey`],
+//! exchange public keys over an authenticated channel, and call
+//! [`AgreementKey::agree`] with the other side's public key. The X25519
+//! shared secret is passed through HKDF-SHA256, salted with both public keys
+//! in a canonical order and bound to a caller-supplied context, so both peers
+//! arrive at the same [`SemanticRendezvousToken`].
+//!
+//! X25519 alone does not authenticate the peer: an attacker who can replace
+//! public keys in transit can run separate agreements with each side.
+//! Authenticate the exchange (e.g. by signing the public keys or comparing
+//! fingerprints in person) before relying on the token.
+
+use std::fmt;
+
+use hkdf::Hkdf;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use sha2::Sha256;
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+use super::SemanticRendezvousToken;
+
+/// Info prefix for agreement-derived SRTs; the caller's context follows it.
+pub const AGREEMENT_INFO: &[u8] = b"phenorv-agreement-v1|srt|";
+
+/// One side of an X25519 key agreement.
+///
+/// The secret is ephemeral: [`agree`](Self::agree) consumes it, so each key
+/// establishes at most one token.
+pub struct AgreementKey {
+    secret: EphemeralSecret,
+    public: PublicKey,
+}
+
+impl AgreementKey {
+    /// Create a key pair from the operating system's CSPRNG.
+    pub fn generate() -> Self {
+        Self::generate_with(&mut OsRng)
+    }
+
+    /// Create a key pair from a caller-supplied cryptographic RNG.
+    pub fn generate_with<R: CryptoRng + RngCore>(rng: &mut R) -> Self {
+        let secret = EphemeralSecret::random_from_rng(rng);
+        let public = PublicKey::from(&secret);
+        Self { secret, public }
+    }
+
+    /// Public key to send to the peer.
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public.to_bytes()
+    }
+
+    /// Combine with the peer's public key to derive the shared SRT.
+    ///
+    /// `context` binds the token to its purpose (for example a session or
+    /// pairing identifier) and must be identical on both sides. Fails if the
+    /// peer's key is a low-order point that would force a predictable secret.
+    pub fn agree(
+        self,
+        peer_public: &[u8; 32],
+        context: &[u8],
+    ) -> Result<SemanticRendezvousToken, AgreementError> {
+        let shared = self.secret.diffie_hellman(&PublicKey::from(*peer_public));
+        if !shared.was_contributory() {
+            return Err(AgreementError::NonContributory);
+        }
+        let own_public = self.public.to_bytes();
+        let (first, second) = if own_public <= *peer_public {
+            (own_public, *peer_public)
+        } else {
+            (*peer_public, own_public)
+        };
+        let salt = [first, second].concat();
+        let info = [AGREEMENT_INFO, context].concat();
+        let mut okm = [0u8; 32];
+        Hkdf::<Sha256>::new(Some(&salt), shared.as_bytes())
+            .expand(&info, &mut okm)
+            .expect("32 bytes is a valid HKDF output length");
+        Ok(SemanticRendezvousToken::from_bytes(okm))
+    }
+}
+
+impl fmt::Debug for AgreementKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AgreementKey")
+            .field("public", &self.public)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Errors returned by key agreement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgreementError {
+    /// The peer's public key produced an all-zero shared secret.
+    NonContributory,
+}
+
+impl fmt::Display for AgreementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonContributory => write!(f, "peer public key is a low-order point"),
+        }
+    }
+}
+
+impl std::error::Error for AgreementError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peers_derive_the_same_token() {
+        let alice = AgreementKey::generate();
+        let bob = AgreementKey::generate();
+        let (alice_public, bob_public) = (alice.public_key(), bob.public_key());
+        let alice_srt = alice.agree(&bob_public, b"pairing-1").expect("agree");
+        let bob_srt = bob.agree(&alice_public, b"pairing-1").expect("agree");
+        assert_eq!(alice_srt, bob_srt);
+
+        let carol = AgreementKey::generate();
+        let other = carol.agree(&bob_public, b"pairing-1").expect("agree");
+        assert_ne!(other, alice_srt);
+    }
+
+    #[test]
+    fn rejects_low_order_public_key() {
+        let key = AgreementKey::generate();
+        assert_eq!(
+            key.agree(&[0u8; 32], b""),
+            Err(AgreementError::NonContributory)
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! ASCII-armored SRT text format.
+//!
+//! Armored tokens survive email bodies, chat messages, and configuration
+//! management templates that would mangle or wrap raw hex. The layout follows
+//! PEM and OpenPGP armor:
+//!
+//! ```text
+//! -----BEGIN PHENORV TOKEN-----
+//! Label: alice-bob
+//!
+//! AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=
+//! =Yw3N
+//! -----END PHENORV TOKEN-----
+//! ```
+//!
+//! Optional `Name: value` headers come first, followed by a blank line. The
+//! body is the standard base64 encoding of the 32 token bytes, and the line
+//! starting with `=` is the base64 encoding of the first three bytes of the
+//! body's SHA-256 digest, so copy errors are detected before the token is
+//! used. Headers are informational and are not covered by the checksum.
+
+use std::fmt;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+use super::SemanticRendezvousToken;
+
+/// First line of an armored token.
+pub const BEGIN_LINE: &str = "-----BEGIN PHENORV TOKEN-----";
+
+/// Last line of an armored token.
+pub const END_LINE: &str = "-----END PHENORV TOKEN-----";
+
+/// An SRT together with optional armor headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArmoredToken {
+    /// The wrapped token.
+    token: SemanticRendezvousToken,
+    /// Headers in emission order.
+    headers: Vec<(String, String)>,
+}
+
+impl ArmoredToken {
+    /// Wrap a token without headers.
+    pub fn new(token: SemanticRendezvousToken) -> Self {
+        Self {
+            token,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Append a `name: value` header.
+    ///
+    /// Names must be non-empty and may not contain `:` or whitespace; values
+    /// may not contain line breaks.
+    pub fn with_header(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Result<Self, ArmorError> {
+        let name = name.into();
+        let value = value.into();
+        if name.is_empty() || name.contains(|ch: char| ch == ':' || ch.is_whitespace()) {
+            return Err(ArmorError::InvalidHeader(name));
+        }
+        if value.contains(['\r', '\n']) {
+            return Err(ArmorError::InvalidHeader(name));
+        }
+        self.headers.push((name, value.trim().to_string()));
+        Ok(self)
+    }
+
+    /// The wrapped token.
+    pub fn token(&self) -> &SemanticRendezvousToken {
+        &self.token
+    }
+
+    /// Unwrap the token, discarding headers.
+    pub fn into_token(self) -> SemanticRendezvousToken {
+        self.token
+    }
+
+    /// Headers in the order they were added or parsed.
+    pub fn headers(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Value of the first header named `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Parse an armored token, verifying its checksum.
+    ///
+    /// Leading and trailing text outside the BEGIN/END lines is ignored, so
+    /// a whole email body can be passed in. Line endings may be `\n` or
+    /// `\r\n`, and lines may be indented.
+    pub fn parse(text: &str) -> Result<Self, ArmorError> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .skip_while(|line| *line != BEGIN_LINE);
+        if lines.next().is_none() {
+            return Err(ArmorError::MissingBegin);
+        }
+
+        let mut headers = Vec::new();
+        let mut body = String::new();
+        let mut checksum = None;
+        let mut in_headers = true;
+        let mut ended = false;
+        for line in lines {
+            if line == END_LINE {
+                ended = true;
+                break;
+            }
+            if in_headers {
+                if line.is_empty() {
+                    in_headers = false;
+                    continue;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    headers.push((name.trim().to_string(), value.trim().to_string()));
+                    continue;
+                }
+                in_headers = false;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(sum) = line.strip_prefix('=') {
+                checksum = Some(sum.to_string());
+            } else if checksum.is_some() {
+                return Err(ArmorError::MalformedBody);
+            } else {
+                body.push_str(line);
+            }
+        }
+        if !ended {
+            return Err(ArmorError::MissingEnd);
+        }
+
+        let bytes = STANDARD
+            .decode(&body)
+            .map_err(|_| ArmorError::MalformedBody)?;
+        let checksum = checksum.ok_or(ArmorError::MissingChecksum)?;
+        if checksum != body_checksum(&bytes) {
+            return Err(ArmorError::ChecksumMismatch);
+        }
+        let token = SemanticRendezvousToken::from_slice(&bytes)
+            .map_err(|_| ArmorError::WrongLength(bytes.len()))?;
+        Ok(Self { token, headers })
+    }
+}
+
+impl fmt::Display for ArmoredToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{BEGIN_LINE}")?;
+        for (name, value) in &self.headers {
+            writeln!(f, "{name}: {value}")?;
+        }
+        if !self.headers.is_empty() {
+            writeln!(f)?;
+        }
+        writeln!(f, "{}", STANDARD.encode(self.token.as_bytes()))?;
+        writeln!(f, "={}", body_checksum(self.token.as_bytes()))?;
+        writeln!(f, "{END_LINE}")
+    }
+}
+
+impl SemanticRendezvousToken {
+    /// Encode the token in armored text form without headers.
+    pub fn to_armored(&self) -> String {
+        ArmoredToken::new(self.clone()).to_string()
+    }
+
+    /// Parse an armored token, ignoring any headers.
+    pub fn from_armored(text: &str) -> Result<Self, ArmorError> {
+        ArmoredToken::parse(text).map(ArmoredToken::into_token)
+    }
+}
+
+/// Base64 of the first three bytes of the SHA-256 digest of `bytes`.
+fn body_checksum(bytes: &[u8]) -> String {
+    STANDARD.encode(&Sha256::digest(bytes)[..3])
+}
+
+/// Errors returned when parsing or building armored tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArmorError {
+    /// No `BEGIN PHENORV TOKEN` line was found.
+    MissingBegin,
+    /// The armor was not terminated by an `END PHENORV TOKEN` line.
+    MissingEnd,
+    /// The body was not valid base64 or text followed the checksum line.
+    MalformedBody,
+    /// The checksum line was missing.
+    MissingChecksum,
+    /// The checksum did not match the body; the token was mistyped or damaged.
+    ChecksumMismatch,
+    /// The body decoded to the wrong number of bytes.
+    WrongLength(usize),
+    /// A header name or value cannot be represented in armor.
+    InvalidHeader(String),
+}
+
+impl fmt::Display for ArmorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingBegin => write!(f, "no '{BEGIN_LINE}' line found"),
+            Self::MissingEnd => write!(f, "no '{END_LINE}' line found"),
+            Self::MalformedBody => write!(f, "armored token body is not valid base64"),
+            Self::MissingChecksum => write!(f, "armored token has no checksum line"),
+            Self::ChecksumMismatch => {
+                write!(
+                    f,
+                    "armored token checksum does not match; it may be mistyped"
+                )
+            }
+            Self::WrongLength(len) => write!(f, "armored token holds {len} bytes, expected 32"),
+            Self::InvalidHeader(name) => write!(f, "invalid armor header '{name}'"),
+        }
+    }
+}
+
+impl std::error::Error for ArmorError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token() -> SemanticRendezvousToken {
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        SemanticRendezvousToken::from_bytes(bytes)
+    }
+
+    #[test]
+    fn armor_round_trips_with_headers() {
+        let armored = ArmoredToken::new(token())
+            .with_header("Label", "alice-bob")
+            .expect("header")
+            .with_header("Created", "2026-10-17")
+            .expect("header");
+        let text = format!(
+            "Hi,\r\n\r\n  {}\r\nThanks\r\n",
+            armored.to_string().replace('\n', "\r\n  ")
+        );
+        let parsed = ArmoredToken::parse(&text).expect("parse");
+        assert_eq!(parsed, armored);
+        assert_eq!(parsed.header("label"), Some("alice-bob"));
+
+        let plain = token().to_armored();
+        assert!(plain.starts_with(&format!("{BEGIN_LINE}\nAAECAwQF")));
+        assert!(plain.contains("\n=Yw3N\n"));
+        assert_eq!(SemanticRendezvousToken::from_armored(&plain), Ok(token()));
+    }
+
+    #[test]
+    fn damaged_armor_is_rejected() {
+        let text = token().to_armored();
+        let typo = text.replacen("AAEC", "AAED", 1);
+        assert_eq!(
+            SemanticRendezvousToken::from_armored(&typo),
+            Err(ArmorError::ChecksumMismatch)
+        );
+        let truncated = text.replace(END_LINE, "");
+        assert_eq!(
+            SemanticRendezvousToken::from_armored(&truncated),
+            Err(ArmorError::MissingEnd)
+        );
+        assert_eq!(
+            SemanticRendezvousToken::from_armored("not armor"),
+            Err(ArmorError::MissingBegin)
+        );
+        assert!(ArmoredToken::new(token())
+            .with_header("Bad Name", "x")
+            .is_err());
+    }
+}
@@ -0,0 +1,151 @@
+//! Commit-and-reveal for measured patterns.
+//!
+//! During rendezvous confirmation a peer that sees the other side's pattern
+//! first could adapt its own claim. Each peer therefore publishes a
+//! [`PatternCommitment`] to its measured pattern, and only after both
+//! commitments are exchanged reveals the [`CommitmentOpening`].
+//!
+//! The commitment is HMAC-SHA256 over the pattern's canonical encoding (the
+//! nine fields as little-endian `f32`, as in binary traces), keyed by a fresh
+//! random 32-byte blinding key. The random key hides the pattern until it is
+//! revealed, and opening to a different pattern would require a SHA-256
+//! collision. The encoding covers exact bit patterns, so the opener must
+//! reveal the very values it committed to.
+
+use std::fmt;
+
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::pattern::SubmodalityPattern;
+use crate::trace::encode_record;
+
+/// Domain separator hashed before the encoded pattern.
+const COMMITMENT_DOMAIN: &[u8] = b"phenorv-commitment-v1|";
+
+/// Binding, hiding commitment to a `SubmodalityPattern`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PatternCommitment([u8; 32]);
+
+/// Values revealed to open a [`PatternCommitment`].
+///
+/// Keep the opening private until the peer's commitment has been received.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CommitmentOpening {
+    /// The committed pattern.
+    pub pattern: SubmodalityPattern,
+    /// Random key that blinds the commitment.
+    pub blinding: [u8; 32],
+}
+
+/// Commit to a pattern with a blinding key from the operating system's CSPRNG.
+pub fn commit(pattern: &SubmodalityPattern) -> (PatternCommitment, CommitmentOpening) {
+    commit_with(pattern, &mut OsRng)
+}
+
+/// Commit to a pattern with a blinding key from a caller-supplied RNG.
+pub fn commit_with<R: CryptoRng + RngCore>(
+    pattern: &SubmodalityPattern,
+    rng: &mut R,
+) -> (PatternCommitment, CommitmentOpening) {
+    let mut blinding = [0u8; 32];
+    rng.fill_bytes(&mut blinding);
+    let opening = CommitmentOpening {
+        pattern: pattern.clone(),
+        blinding,
+    };
+    (PatternCommitment(commitment_tag(&opening)), opening)
+}
+
+impl PatternCommitment {
+    /// Wrap raw commitment bytes, e.g. as received from a peer.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Borrow the commitment bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Check a revealed opening and return the committed pattern.
+    ///
+    /// The comparison runs in constant time.
+    pub fn open<'a>(
+        &self,
+        opening: &'a CommitmentOpening,
+    ) -> Result<&'a SubmodalityPattern, CommitmentError> {
+        let expected = commitment_tag(opening);
+        let diff = self
+            .0
+            .iter()
+            .zip(expected)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        if diff != 0 {
+            return Err(CommitmentError::Mismatch);
+        }
+        Ok(&opening.pattern)
+    }
+}
+
+impl fmt::Display for PatternCommitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+fn commitment_tag(opening: &CommitmentOpening) -> [u8; 32] {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(&opening.blinding).expect("HMAC can take a 32-byte key");
+    mac.update(COMMITMENT_DOMAIN);
+    mac.update(&encode_record(&opening.pattern));
+    mac.finalize().into_bytes().into()
+}
+
+/// Errors returned when opening a commitment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitmentError {
+    /// The opening does not match the commitment.
+    Mismatch,
+}
+
+impl fmt::Display for CommitmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mismatch => write!(f, "opening does not match the commitment"),
+        }
+    }
+}
+
+impl std::error::Error for CommitmentError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commitment_opens_only_to_committed_pattern() {
+        let mut pattern = SubmodalityPattern::zeros();
+        pattern.tempo = 90.0;
+        let (commitment, opening) = commit(&pattern);
+        assert_eq!(commitment.open(&opening), Ok(&pattern));
+
+        let mut altered = opening.clone();
+        altered.pattern.tempo = 91.0;
+        assert_eq!(commitment.open(&altered), Err(CommitmentError::Mismatch));
+
+        let (again, _) = commit(&pattern);
+        assert_ne!(again, commitment, "fresh blinding hides equal patterns");
+    }
+}
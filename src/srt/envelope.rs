@@ -0,0 +1,296 @@
+//! Passphrase-encrypted storage for SRT material.
+//!
+//! Tokens written to disk should not be readable by anyone who copies the
+//! file. A sealed envelope encrypts its contents with ChaCha20-Poly1305 under
+//! a key stretched from a passphrase with Argon2id. The binary layout is:
+//!
+//! ```text
+//! magic "PRVS" | version (1) | m_cost, t_cost, p_cost (u32 BE) | salt (16) | nonce (12) | ciphertext
+//! ```
+//!
+//! The Argon2 parameters travel with the envelope so they can be raised in
+//! later releases without breaking old files. Everything before the
+//! ciphertext, together with a label naming the kind of content, is
+//! authenticated as associated data: tampering with the header or opening a
+//! keychain envelope as something else fails like a wrong passphrase.
+//!
+//! A single token can also be exported as armored text for backups or for
+//! moving it between devices through untrusted storage:
+//!
+//! ```text
+//! -----BEGIN PHENORV ENCRYPTED TOKEN-----
+//! UFJWUwEAAEwAAAAAAgAAAAE...
+//! -----END PHENORV ENCRYPTED TOKEN-----
+//! ```
+//!
+//! The body is the standard base64 encoding of the envelope, wrapped at 64
+//! columns. No separate checksum is needed: the authentication tag rejects
+//! any damage.
+
+use std::fmt;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use super::SemanticRendezvousToken;
+
+/// Leading bytes of every sealed envelope.
+const MAGIC: &[u8; 4] = b"PRVS";
+
+/// First line of an encrypted armored token.
+pub const ENCRYPTED_BEGIN_LINE: &str = "-----BEGIN PHENORV ENCRYPTED TOKEN-----";
+
+/// Last line of an encrypted armored token.
+pub const ENCRYPTED_END_LINE: &str = "-----END PHENORV ENCRYPTED TOKEN-----";
+
+/// Label binding envelopes to a single token.
+const TOKEN_LABEL: &[u8] = b"phenorv-token-v1";
+
+/// Base64 characters per armor line.
+const LINE_WIDTH: usize = 64;
+
+/// Current envelope version.
+pub const ENVELOPE_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 12 + SALT_LEN + NONCE_LEN;
+
+/// Largest Argon2 memory cost accepted when opening, in KiB (1 GiB).
+///
+/// Bounds the work an untrusted envelope can demand before decryption fails.
+const MAX_M_COST: u32 = 1024 * 1024;
+
+/// Largest Argon2 iteration count accepted when opening.
+const MAX_T_COST: u32 = 64;
+
+impl SemanticRendezvousToken {
+    /// Encrypt the token under `passphrase` as armored text.
+    ///
+    /// Each call uses a fresh salt and nonce, so exporting the same token
+    /// twice gives different text.
+    pub fn export_encrypted(&self, passphrase: &str) -> String {
+        let body = STANDARD.encode(seal(passphrase, TOKEN_LABEL, self.as_bytes()));
+        let mut text = format!("{ENCRYPTED_BEGIN_LINE}\n");
+        for line in body.as_bytes().chunks(LINE_WIDTH) {
+            text.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+            text.push('\n');
+        }
+        text.push_str(ENCRYPTED_END_LINE);
+        text.push('\n');
+        text
+    }
+
+    /// Decrypt armored text produced by [`export_encrypted`](Self::export_encrypted).
+    ///
+    /// Text outside the BEGIN/END lines is ignored and lines may be
+    /// indented, as for plain armored tokens.
+    pub fn import_encrypted(text: &str, passphrase: &str) -> Result<Self, EnvelopeError> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .skip_while(|line| *line != ENCRYPTED_BEGIN_LINE);
+        if lines.next().is_none() {
+            return Err(EnvelopeError::Malformed);
+        }
+        let mut body = String::new();
+        let mut ended = false;
+        for line in lines {
+            if line == ENCRYPTED_END_LINE {
+                ended = true;
+                break;
+            }
+            body.push_str(line);
+        }
+        if !ended {
+            return Err(EnvelopeError::Malformed);
+        }
+        let envelope = STANDARD
+            .decode(&body)
+            .map_err(|_| EnvelopeError::Malformed)?;
+        let plaintext = open(passphrase, TOKEN_LABEL, &envelope)?;
+        Self::from_slice(&plaintext).map_err(|_| EnvelopeError::Malformed)
+    }
+}
+
+/// Encrypt `plaintext` under `passphrase`, binding the envelope to `label`.
+pub(crate) fn seal(passphrase: &str, label: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let params = Params::default();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut envelope = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+    envelope.extend_from_slice(MAGIC);
+    envelope.push(ENVELOPE_VERSION);
+    envelope.extend_from_slice(&params.m_cost().to_be_bytes());
+    envelope.extend_from_slice(&params.t_cost().to_be_bytes());
+    envelope.extend_from_slice(&params.p_cost().to_be_bytes());
+    envelope.extend_from_slice(&salt);
+    envelope.extend_from_slice(&nonce);
+
+    let key = stretch(passphrase, &salt, params).expect("default Argon2 parameters are valid");
+    let aad = [&envelope[..], label].concat();
+    let ciphertext = ChaCha20Poly1305::new(&key)
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &aad,
+            },
+        )
+        .expect("ChaCha20-Poly1305 encryption does not fail for in-memory buffers");
+    envelope.extend_from_slice(&ciphertext);
+    envelope
+}
+
+/// Decrypt an envelope produced by [`seal`] with the same `label`.
+pub(crate) fn open(
+    passphrase: &str,
+    label: &[u8],
+    envelope: &[u8],
+) -> Result<Vec<u8>, EnvelopeError> {
+    if envelope.len() < MAGIC.len() + 1 || &envelope[..MAGIC.len()] != MAGIC {
+        return Err(EnvelopeError::Malformed);
+    }
+    let version = envelope[MAGIC.len()];
+    if version != ENVELOPE_VERSION {
+        return Err(EnvelopeError::UnsupportedVersion(version));
+    }
+    if envelope.len() < HEADER_LEN {
+        return Err(EnvelopeError::Malformed);
+    }
+    let (header, ciphertext) = envelope.split_at(HEADER_LEN);
+    let read_u32 = |offset: usize| {
+        u32::from_be_bytes(header[offset..offset + 4].try_into().expect("4-byte slice"))
+    };
+    let m_cost = read_u32(5);
+    let t_cost = read_u32(9);
+    let p_cost = read_u32(13);
+    if m_cost > MAX_M_COST || t_cost > MAX_T_COST {
+        return Err(EnvelopeError::InvalidParameters);
+    }
+    let params =
+        Params::new(m_cost, t_cost, p_cost, None).map_err(|_| EnvelopeError::InvalidParameters)?;
+    let salt = &header[17..17 + SALT_LEN];
+    let nonce = &header[17 + SALT_LEN..];
+
+    let key = stretch(passphrase, salt, params)?;
+    let aad = [header, label].concat();
+    ChaCha20Poly1305::new(&key)
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| EnvelopeError::Decryption)
+}
+
+fn stretch(passphrase: &str, salt: &[u8], params: Params) -> Result<Key, EnvelopeError> {
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|_| EnvelopeError::InvalidParameters)?;
+    Ok(key)
+}
+
+/// Errors returned when opening an encrypted envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvelopeError {
+    /// The input is not an envelope, is truncated, or is not valid armor.
+    Malformed,
+    /// The envelope version is not supported by this implementation.
+    UnsupportedVersion(u8),
+    /// The stored Argon2 parameters are invalid or exceed the accepted limits.
+    InvalidParameters,
+    /// Authentication failed: wrong passphrase, or the envelope was altered.
+    Decryption,
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "not a valid encrypted envelope"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported envelope version {version}")
+            }
+            Self::InvalidParameters => write!(f, "envelope key-derivation parameters rejected"),
+            Self::Decryption => write!(f, "wrong passphrase or damaged envelope"),
+        }
+    }
+}
+
+impl std::error::Error for EnvelopeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_data_opens_only_with_passphrase_and_label() {
+        let sealed = seal("correct horse", b"test", b"secret");
+        assert_eq!(sealed.len(), HEADER_LEN + 6 + 16);
+        assert_eq!(
+            open("correct horse", b"test", &sealed),
+            Ok(b"secret".to_vec())
+        );
+        assert_eq!(
+            open("wrong horse", b"test", &sealed),
+            Err(EnvelopeError::Decryption)
+        );
+        assert_eq!(
+            open("correct horse", b"other", &sealed),
+            Err(EnvelopeError::Decryption)
+        );
+
+        let mut future = sealed.clone();
+        future[4] = 2;
+        assert_eq!(
+            open("correct horse", b"test", &future),
+            Err(EnvelopeError::UnsupportedVersion(2))
+        );
+        let mut greedy = sealed.clone();
+        greedy[5..9].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(
+            open("correct horse", b"test", &greedy),
+            Err(EnvelopeError::InvalidParameters)
+        );
+        assert_eq!(
+            open("correct horse", b"test", &sealed[..20]),
+            Err(EnvelopeError::Malformed)
+        );
+    }
+
+    #[test]
+    fn encrypted_token_round_trips_through_armor() {
+        let srt = SemanticRendezvousToken::from_bytes([7u8; 32]);
+        let text = srt.export_encrypted("passphrase");
+        assert!(text.starts_with(ENCRYPTED_BEGIN_LINE));
+        assert!(text.lines().all(|line| line.len() <= LINE_WIDTH
+            || line == ENCRYPTED_BEGIN_LINE
+            || line == ENCRYPTED_END_LINE));
+        assert_ne!(text, srt.export_encrypted("passphrase"));
+
+        let pasted = format!("Backup:\n  {}\n", text.replace('\n', "\n  "));
+        assert_eq!(
+            SemanticRendezvousToken::import_encrypted(&pasted, "passphrase"),
+            Ok(srt.clone())
+        );
+        assert_eq!(
+            SemanticRendezvousToken::import_encrypted(&text, "Passphrase"),
+            Err(EnvelopeError::Decryption)
+        );
+        assert_eq!(
+            SemanticRendezvousToken::import_encrypted(&srt.to_armored(), "passphrase"),
+            Err(EnvelopeError::Malformed)
+        );
+    }
+}
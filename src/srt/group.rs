@@ -0,0 +1,301 @@
+//! Group tokens for k-party rendezvous.
+//!
+//! When several parties each hold their own SRT, [`derive_group_token`]
+//! combines them into one token from which every member derives the same
+//! target. The result does not depend on the order in which members are
+//! listed.
+//!
+//! Each member is represented by a [`MemberCommitment`], a domain-separated
+//! hash of their token. The commitments are sorted into a Merkle tree whose
+//! root is the public [`GroupCommitment`]; a member can later show they were
+//! part of the group with a [`MembershipProof`] against that root without
+//! revealing the other members' commitments. The group token itself is keyed
+//! by the members' tokens as well as the root, so publishing the root or
+//! proofs does not reveal it.
+
+use std::fmt;
+
+use hkdf::Hkdf;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::SemanticRendezvousToken;
+
+/// Domain separator for member commitments.
+const MEMBER_DOMAIN: &[u8] = b"phenorv-group-member-v1|";
+
+/// Domain separator for inner Merkle nodes.
+const NODE_DOMAIN: &[u8] = b"phenorv-group-node-v1|";
+
+/// HKDF info label for the group token; the group commitment follows it.
+pub const SUBKEY_GROUP: &[u8] = b"phenorv-subkey-v1|group|";
+
+/// Public commitment to one member's token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MemberCommitment([u8; 32]);
+
+impl MemberCommitment {
+    /// Commit to a member's token.
+    pub fn of(token: &SemanticRendezvousToken) -> Self {
+        Self(
+            Sha256::new()
+                .chain_update(MEMBER_DOMAIN)
+                .chain_update(token.as_bytes())
+                .finalize()
+                .into(),
+        )
+    }
+
+    /// Wrap raw commitment bytes, e.g. as received from a peer.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Borrow the commitment bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+/// Merkle root over the sorted member commitments of a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct GroupCommitment([u8; 32]);
+
+impl GroupCommitment {
+    /// Wrap raw root bytes, e.g. as published by the group.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Borrow the root bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for GroupCommitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Sibling hashes from a member's leaf up to the group commitment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MembershipProof {
+    /// Sibling node at each level that had one, leaf level first.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl MembershipProof {
+    /// Whether `member` is a leaf of the tree with root `group`.
+    ///
+    /// This shows the commitment is in the group, not that the presenter
+    /// holds the matching token; a verifier who needs that must also see the
+    /// commitment opened with [`MemberCommitment::of`].
+    pub fn verify(&self, group: &GroupCommitment, member: &MemberCommitment) -> bool {
+        let root = self
+            .siblings
+            .iter()
+            .fold(member.0, |node, sibling| hash_pair(&node, sibling));
+        root == group.0
+    }
+}
+
+/// A group token together with the commitments it was built from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupToken {
+    token: SemanticRendezvousToken,
+    commitment: GroupCommitment,
+    members: Vec<MemberCommitment>,
+}
+
+impl GroupToken {
+    /// The shared token every member derives targets from.
+    pub fn token(&self) -> &SemanticRendezvousToken {
+        &self.token
+    }
+
+    /// Public Merkle root over the member commitments.
+    pub fn commitment(&self) -> GroupCommitment {
+        self.commitment
+    }
+
+    /// Member commitments in tree (sorted) order.
+    pub fn members(&self) -> &[MemberCommitment] {
+        &self.members
+    }
+
+    /// Build the proof that `member` belongs to the group, if it does.
+    pub fn prove(&self, member: &SemanticRendezvousToken) -> Option<MembershipProof> {
+        let mut index = self
+            .members
+            .binary_search(&MemberCommitment::of(member))
+            .ok()?;
+        let mut level: Vec<[u8; 32]> = self.members.iter().map(|member| member.0).collect();
+        let mut siblings = Vec::new();
+        while level.len() > 1 {
+            if let Some(sibling) = level.get(index ^ 1) {
+                siblings.push(*sibling);
+            }
+            level = next_level(&level);
+            index /= 2;
+        }
+        Some(MembershipProof { siblings })
+    }
+}
+
+/// Combine member tokens into one group token.
+///
+/// Members are committed to, sorted, and hashed into a Merkle tree; the
+/// token is HKDF-SHA256 over the sorted member tokens with
+/// [`SUBKEY_GROUP`] and the root as info. Every permutation of `members`
+/// yields the same token. At least two distinct members are required.
+pub fn derive_group_token(
+    members: &[SemanticRendezvousToken],
+) -> Result<GroupToken, GroupTokenError> {
+    if members.len() < 2 {
+        return Err(GroupTokenError::TooFewMembers(members.len()));
+    }
+    let mut sorted: Vec<(MemberCommitment, &SemanticRendezvousToken)> = members
+        .iter()
+        .map(|token| (MemberCommitment::of(token), token))
+        .collect();
+    sorted.sort_by_key(|(commitment, _)| *commitment);
+    if sorted.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+        return Err(GroupTokenError::DuplicateMember);
+    }
+
+    let mut level: Vec<[u8; 32]> = sorted.iter().map(|(commitment, _)| commitment.0).collect();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    let commitment = GroupCommitment(level[0]);
+
+    let ikm: Vec<u8> = sorted
+        .iter()
+        .flat_map(|(_, token)| token.as_bytes().iter().copied())
+        .collect();
+    let info = [SUBKEY_GROUP, commitment.as_bytes()].concat();
+    let mut okm = [0u8; 32];
+    Hkdf::<Sha256>::new(None, &ikm)
+        .expand(&info, &mut okm)
+        .expect("32 bytes is a valid HKDF length");
+
+    Ok(GroupToken {
+        token: SemanticRendezvousToken::from_bytes(okm),
+        commitment,
+        members: sorted
+            .into_iter()
+            .map(|(commitment, _)| commitment)
+            .collect(),
+    })
+}
+
+/// Hash adjacent pairs; an unpaired last node is carried up unchanged.
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_pair(left, right),
+            [single] => *single,
+            _ => unreachable!("chunks(2) yields one or two nodes"),
+        })
+        .collect()
+}
+
+/// Inner node over two children, ordered so proofs need no direction bits.
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    Sha256::new()
+        .chain_update(NODE_DOMAIN)
+        .chain_update(low)
+        .chain_update(high)
+        .finalize()
+        .into()
+}
+
+/// Errors returned when deriving a group token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupTokenError {
+    /// Fewer than two member tokens were supplied.
+    TooFewMembers(usize),
+    /// The same token was supplied more than once.
+    DuplicateMember,
+}
+
+impl fmt::Display for GroupTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooFewMembers(count) => {
+                write!(f, "a group token needs at least 2 members, got {count}")
+            }
+            Self::DuplicateMember => write!(f, "a member token was supplied more than once"),
+        }
+    }
+}
+
+impl std::error::Error for GroupTokenError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(count: u8) -> Vec<SemanticRendezvousToken> {
+        (1..=count)
+            .map(|byte| SemanticRendezvousToken::from_bytes([byte; 32]))
+            .collect()
+    }
+
+    #[test]
+    fn group_token_is_order_independent() {
+        let mut tokens = members(5);
+        let group = derive_group_token(&tokens).expect("group");
+        tokens.reverse();
+        tokens.swap(0, 2);
+        assert_eq!(derive_group_token(&tokens), Ok(group.clone()));
+        assert!(tokens.iter().all(|token| token != group.token()));
+
+        let smaller = derive_group_token(&tokens[..4]).expect("group");
+        assert_ne!(smaller.token(), group.token());
+        assert_ne!(smaller.commitment(), group.commitment());
+    }
+
+    #[test]
+    fn members_prove_inclusion() {
+        for count in 2..=7 {
+            let tokens = members(count);
+            let group = derive_group_token(&tokens).expect("group");
+            for token in &tokens {
+                let proof = group.prove(token).expect("member");
+                assert!(proof.verify(&group.commitment(), &MemberCommitment::of(token)));
+            }
+            let outsider = SemanticRendezvousToken::from_bytes([0xee; 32]);
+            assert_eq!(group.prove(&outsider), None);
+            let proof = group.prove(&tokens[0]).expect("member");
+            assert!(!proof.verify(&group.commitment(), &MemberCommitment::of(&outsider)));
+        }
+    }
+
+    #[test]
+    fn group_token_rejects_degenerate_groups() {
+        assert_eq!(
+            derive_group_token(&members(1)),
+            Err(GroupTokenError::TooFewMembers(1))
+        );
+        let tokens = members(2);
+        assert_eq!(
+            derive_group_token(&[tokens[0].clone(), tokens[1].clone(), tokens[0].clone()]),
+            Err(GroupTokenError::DuplicateMember)
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Labeled collections of SRTs.
+//!
+//! A device that meets several peers holds one token per peer or group.
+//! [`SrtKeychain`] maps labels such as peer IDs to tokens and tracks how many
+//! times each token has been rotated. Rotation applies the one-way
+//! [`ratchet`](SemanticRendezvousToken::ratchet) step, so peers that rotate
+//! the same entry the same number of times keep matching tokens without
+//! exchanging anything new.
+//!
+//! With the `encryption` feature the keychain can be exported to, and
+//! imported from, a passphrase-encrypted envelope (see [`super::envelope`]).
+//! The plaintext inside is a `u32` big-endian entry count followed by, for
+//! each entry in label order, a `u16` big-endian label length, the UTF-8
+//! label, the `u64` big-endian generation, and the 32 token bytes.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::SemanticRendezvousToken;
+
+/// Label binding envelopes to keychain contents.
+#[cfg(feature = "encryption")]
+const ENVELOPE_LABEL: &[u8] = b"phenorv-keychain-v1";
+
+/// Token held under one keychain label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeychainEntry {
+    token: SemanticRendezvousToken,
+    generation: u64,
+}
+
+impl KeychainEntry {
+    /// Current token.
+    pub fn token(&self) -> &SemanticRendezvousToken {
+        &self.token
+    }
+
+    /// Number of rotations applied since the token was added.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+/// Tokens indexed by label.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SrtKeychain {
+    entries: BTreeMap<String, KeychainEntry>,
+}
+
+impl SrtKeychain {
+    /// Create an empty keychain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a token under `label`.
+    ///
+    /// Labels must be non-empty and at most `u16::MAX` bytes long. Fails if
+    /// the label is already in use; [`remove`](Self::remove) it first to
+    /// replace the token.
+    pub fn add(
+        &mut self,
+        label: impl Into<String>,
+        token: SemanticRendezvousToken,
+    ) -> Result<(), KeychainError> {
+        let label = label.into();
+        if label.is_empty() || label.len() > usize::from(u16::MAX) {
+            return Err(KeychainError::InvalidLabel(label));
+        }
+        if self.entries.contains_key(&label) {
+            return Err(KeychainError::DuplicateLabel(label));
+        }
+        self.entries.insert(
+            label,
+            KeychainEntry {
+                token,
+                generation: 0,
+            },
+        );
+        Ok(())
+    }
+
+    /// Current token for `label`.
+    pub fn get(&self, label: &str) -> Option<&SemanticRendezvousToken> {
+        self.entries.get(label).map(KeychainEntry::token)
+    }
+
+    /// Entry for `label`, including its generation.
+    pub fn entry(&self, label: &str) -> Option<&KeychainEntry> {
+        self.entries.get(label)
+    }
+
+    /// Remove and return the entry for `label`.
+    pub fn remove(&mut self, label: &str) -> Option<KeychainEntry> {
+        self.entries.remove(label)
+    }
+
+    /// Ratchet the token for `label` forward one generation.
+    pub fn rotate(&mut self, label: &str) -> Result<&SemanticRendezvousToken, KeychainError> {
+        let entry = self
+            .entries
+            .get_mut(label)
+            .ok_or_else(|| KeychainError::UnknownLabel(label.to_string()))?;
+        entry.token = entry.token.ratchet();
+        entry.generation += 1;
+        Ok(&entry.token)
+    }
+
+    /// Labels in sorted order.
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Entries in label order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &KeychainEntry)> {
+        self.entries
+            .iter()
+            .map(|(label, entry)| (label.as_str(), entry))
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the keychain holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Encrypt the keychain under `passphrase` for storage.
+    #[cfg(feature = "encryption")]
+    pub fn export_encrypted(&self, passphrase: &str) -> Vec<u8> {
+        super::envelope::seal(passphrase, ENVELOPE_LABEL, &self.encode())
+    }
+
+    /// Decrypt a keychain written by [`export_encrypted`](Self::export_encrypted).
+    #[cfg(feature = "encryption")]
+    pub fn import_encrypted(envelope: &[u8], passphrase: &str) -> crate::Result<Self> {
+        let plaintext = super::envelope::open(passphrase, ENVELOPE_LABEL, envelope)?;
+        Ok(Self::decode(&plaintext)?)
+    }
+
+    #[cfg(feature = "encryption")]
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&(self.entries.len() as u32).to_be_bytes());
+        for (label, entry) in &self.entries {
+            out.extend_from_slice(&(label.len() as u16).to_be_bytes());
+            out.extend_from_slice(label.as_bytes());
+            out.extend_from_slice(&entry.generation.to_be_bytes());
+            out.extend_from_slice(entry.token.as_bytes());
+        }
+        out
+    }
+
+    #[cfg(feature = "encryption")]
+    fn decode(mut bytes: &[u8]) -> Result<Self, KeychainError> {
+        fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], KeychainError> {
+            if bytes.len() < len {
+                return Err(KeychainError::Malformed("truncated entry".to_string()));
+            }
+            let (head, rest) = bytes.split_at(len);
+            *bytes = rest;
+            Ok(head)
+        }
+
+        let count = u32::from_be_bytes(take(&mut bytes, 4)?.try_into().expect("4 bytes"));
+        let mut keychain = Self::new();
+        for _ in 0..count {
+            let label_len = u16::from_be_bytes(take(&mut bytes, 2)?.try_into().expect("2 bytes"));
+            let label = std::str::from_utf8(take(&mut bytes, usize::from(label_len))?)
+                .map_err(|_| KeychainError::Malformed("label is not UTF-8".to_string()))?;
+            let generation = u64::from_be_bytes(take(&mut bytes, 8)?.try_into().expect("8 bytes"));
+            let token =
+                SemanticRendezvousToken::from_slice(take(&mut bytes, 32)?).expect("32 bytes");
+            keychain.add(label, token)?;
+            keychain
+                .entries
+                .get_mut(label)
+                .expect("entry was just added")
+                .generation = generation;
+        }
+        if !bytes.is_empty() {
+            return Err(KeychainError::Malformed(format!(
+                "{} trailing bytes",
+                bytes.len()
+            )));
+        }
+        Ok(keychain)
+    }
+}
+
+/// Errors returned by [`SrtKeychain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeychainError {
+    /// A token is already stored under the label.
+    DuplicateLabel(String),
+    /// No token is stored under the label.
+    UnknownLabel(String),
+    /// The label is empty or longer than `u16::MAX` bytes.
+    InvalidLabel(String),
+    /// Decrypted keychain contents could not be decoded.
+    Malformed(String),
+}
+
+impl fmt::Display for KeychainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateLabel(label) => write!(f, "keychain already has label '{label}'"),
+            Self::UnknownLabel(label) => write!(f, "keychain has no label '{label}'"),
+            Self::InvalidLabel(label) => write!(f, "invalid keychain label '{label}'"),
+            Self::Malformed(reason) => write!(f, "malformed keychain: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for KeychainError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keychain() -> SrtKeychain {
+        let mut keychain = SrtKeychain::new();
+        keychain
+            .add("bob", SemanticRendezvousToken::from_bytes([2u8; 32]))
+            .expect("add");
+        keychain
+            .add("alice", SemanticRendezvousToken::from_bytes([1u8; 32]))
+            .expect("add");
+        keychain
+    }
+
+    #[test]
+    fn keychain_adds_rotates_and_removes() {
+        let mut keychain = keychain();
+        assert_eq!(keychain.labels().collect::<Vec<_>>(), ["alice", "bob"]);
+        assert_eq!(
+            keychain.add("bob", SemanticRendezvousToken::from_bytes([0u8; 32])),
+            Err(KeychainError::DuplicateLabel("bob".to_string()))
+        );
+        assert_eq!(
+            keychain.add("", SemanticRendezvousToken::from_bytes([0u8; 32])),
+            Err(KeychainError::InvalidLabel(String::new()))
+        );
+
+        let rotated = keychain.rotate("bob").expect("rotate").clone();
+        assert_eq!(
+            rotated,
+            SemanticRendezvousToken::from_bytes([2u8; 32]).ratchet()
+        );
+        assert_eq!(
+            keychain.entry("bob").map(KeychainEntry::generation),
+            Some(1)
+        );
+        assert_eq!(
+            keychain.rotate("carol"),
+            Err(KeychainError::UnknownLabel("carol".to_string()))
+        );
+
+        let removed = keychain.remove("alice").expect("present");
+        assert_eq!(removed.generation(), 0);
+        assert_eq!(keychain.get("alice"), None);
+        assert_eq!(keychain.len(), 1);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypted_export_round_trips() {
+        let mut keychain = keychain();
+        keychain.rotate("alice").expect("rotate");
+        let envelope = keychain.export_encrypted("hunter2");
+        assert!(!envelope
+            .windows(32)
+            .any(|window| window == keychain.get("bob").unwrap().as_bytes()));
+
+        let restored = SrtKeychain::import_encrypted(&envelope, "hunter2").expect("import");
+        assert_eq!(restored, keychain);
+        assert!(matches!(
+            SrtKeychain::import_encrypted(&envelope, "hunter3"),
+            Err(crate::Error::Envelope(
+                super::super::envelope::EnvelopeError::Decryption
+            ))
+        ));
+    }
+}
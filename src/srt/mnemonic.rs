@@ -0,0 +1,157 @@
+//! BIP39 mnemonic encoding of SRTs.
+//!
+//! A 32-byte token maps to 24 words from a BIP39 word list, with the last
+//! word carrying an 8-bit checksum. Two people can read the words to each
+//! other instead of comparing 64 hex characters. Only the entropy encoding
+//! is used: no passphrase or seed stretching is applied, so the words decode
+//! back to exactly the original token.
+
+use std::fmt;
+
+use bip39::{Language, Mnemonic};
+
+use super::SemanticRendezvousToken;
+
+/// Number of words in an SRT mnemonic.
+pub const MNEMONIC_WORDS: usize = 24;
+
+/// Word list used for a mnemonic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MnemonicLanguage {
+    /// English (the BIP39 default).
+    #[default]
+    English,
+    /// Simplified Chinese.
+    ChineseSimplified,
+    /// Traditional Chinese.
+    ChineseTraditional,
+    /// Czech.
+    Czech,
+    /// French.
+    French,
+    /// Italian.
+    Italian,
+    /// Japanese.
+    Japanese,
+    /// Korean.
+    Korean,
+    /// Portuguese.
+    Portuguese,
+    /// Spanish.
+    Spanish,
+}
+
+impl MnemonicLanguage {
+    fn word_list(self) -> Language {
+        match self {
+            Self::English => Language::English,
+            Self::ChineseSimplified => Language::SimplifiedChinese,
+            Self::ChineseTraditional => Language::TraditionalChinese,
+            Self::Czech => Language::Czech,
+            Self::French => Language::French,
+            Self::Italian => Language::Italian,
+            Self::Japanese => Language::Japanese,
+            Self::Korean => Language::Korean,
+            Self::Portuguese => Language::Portuguese,
+            Self::Spanish => Language::Spanish,
+        }
+    }
+}
+
+impl SemanticRendezvousToken {
+    /// Encode the token as a 24-word BIP39 mnemonic.
+    pub fn to_mnemonic(&self, language: MnemonicLanguage) -> String {
+        Mnemonic::from_entropy_in(language.word_list(), self.as_bytes())
+            .expect("32 bytes is valid BIP39 entropy")
+            .to_string()
+    }
+
+    /// Decode a 24-word BIP39 mnemonic, verifying its checksum.
+    ///
+    /// Words may be separated by any whitespace; case and Unicode
+    /// normalization follow BIP39.
+    pub fn from_mnemonic(phrase: &str, language: MnemonicLanguage) -> Result<Self, MnemonicError> {
+        let words = phrase.split_whitespace().count();
+        if words != MNEMONIC_WORDS {
+            return Err(MnemonicError::WordCount(words));
+        }
+        let mnemonic =
+            Mnemonic::parse_in(language.word_list(), phrase).map_err(|err| match err {
+                bip39::Error::UnknownWord(index) => MnemonicError::UnknownWord(
+                    phrase
+                        .split_whitespace()
+                        .nth(index)
+                        .unwrap_or_default()
+                        .to_string(),
+                ),
+                bip39::Error::InvalidChecksum => MnemonicError::InvalidChecksum,
+                _ => MnemonicError::WordCount(words),
+            })?;
+        Ok(Self::from_slice(&mnemonic.to_entropy()).expect("24 words carry 32 bytes"))
+    }
+}
+
+/// Errors returned when decoding an SRT mnemonic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MnemonicError {
+    /// The phrase did not have [`MNEMONIC_WORDS`] words.
+    WordCount(usize),
+    /// A word is not in the selected word list.
+    UnknownWord(String),
+    /// The checksum in the last word did not match, usually because of a
+    /// typo or swapped words.
+    InvalidChecksum,
+}
+
+impl fmt::Display for MnemonicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WordCount(count) => {
+                write!(f, "expected {MNEMONIC_WORDS} words, got {count}")
+            }
+            Self::UnknownWord(word) => write!(f, "unknown mnemonic word '{word}'"),
+            Self::InvalidChecksum => write!(f, "mnemonic checksum mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for MnemonicError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mnemonic_round_trips_and_validates() {
+        let srt = SemanticRendezvousToken::from_bytes(std::array::from_fn(|i| i as u8 * 7));
+        let phrase = srt.to_mnemonic(MnemonicLanguage::English);
+        assert_eq!(phrase.split_whitespace().count(), MNEMONIC_WORDS);
+        assert_eq!(
+            SemanticRendezvousToken::from_mnemonic(&phrase, MnemonicLanguage::English),
+            Ok(srt.clone())
+        );
+
+        let spanish = srt.to_mnemonic(MnemonicLanguage::Spanish);
+        assert_ne!(spanish, phrase);
+        assert_eq!(
+            SemanticRendezvousToken::from_mnemonic(&spanish, MnemonicLanguage::Spanish),
+            Ok(srt.clone())
+        );
+
+        // The all-zero token ends in "art"; "abandon" there breaks the checksum.
+        let bad = ["abandon"; MNEMONIC_WORDS].join(" ");
+        assert_eq!(
+            SemanticRendezvousToken::from_mnemonic(&bad, MnemonicLanguage::English),
+            Err(MnemonicError::InvalidChecksum)
+        );
+        assert_eq!(
+            SemanticRendezvousToken::from_mnemonic("abandon ability", MnemonicLanguage::English),
+            Err(MnemonicError::WordCount(2))
+        );
+        let unknown = phrase.replacen(phrase.split(' ').next().unwrap(), "zzzz", 1);
+        assert_eq!(
+            SemanticRendezvousToken::from_mnemonic(&unknown, MnemonicLanguage::English),
+            Err(MnemonicError::UnknownWord("zzzz".to_string()))
+        );
+    }
+}
@@ -0,0 +1,527 @@
+//! Oracle-state helpers for producing canonical salts.
+//!
+//! Peers that want to rendezvous must feed identical salts into
+//! [`pattern_from_srt`](crate::srt::pattern_from_srt). The helpers here build
+//! those salts from shared, coarse context (a location cell and a time bucket)
+//! so peers can compute them independently, tolerating small disagreements in
+//! position or clock.
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Base32 alphabet used by the standard geohash encoding.
+const GEOHASH_ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Maximum supported geohash precision (characters).
+pub const GEOHASH_MAX_PRECISION: usize = 12;
+
+/// Prefix for geohash-based salts, versioned so the format can evolve.
+const GEOHASH_SALT_PREFIX: &str = "phenorv-geo-v1";
+
+/// Prefix for time-epoch salts, versioned so the format can evolve.
+const EPOCH_SALT_PREFIX: &str = "phenorv-epoch-v1";
+
+/// Build the canonical salt for a time epoch.
+///
+/// The salt is the UTF-8 string `phenorv-epoch-v1|<epoch>` with `epoch` as a
+/// decimal index.
+pub fn epoch_salt(epoch: u64) -> Vec<u8> {
+    format!("{EPOCH_SALT_PREFIX}|{epoch}").into_bytes()
+}
+
+/// Time oracle that maps wall-clock time to epoch salts.
+///
+/// Time is divided into fixed-length epochs counted from the Unix epoch. Peers
+/// with slightly desynchronized clocks may disagree on the current epoch near a
+/// boundary, so the oracle accepts the current epoch plus `tolerance` epochs on
+/// either side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeOracle {
+    /// Epoch length in seconds.
+    epoch_length_secs: u64,
+    /// Number of adjacent epochs accepted on each side of the current one.
+    tolerance: u64,
+}
+
+impl TimeOracle {
+    /// Create a time oracle with an epoch length and skew tolerance.
+    ///
+    /// Returns an error if `epoch_length_secs` is zero.
+    pub fn new(epoch_length_secs: u64, tolerance: u64) -> Result<Self, OracleError> {
+        if epoch_length_secs == 0 {
+            return Err(OracleError::InvalidEpochLength);
+        }
+        Ok(Self {
+            epoch_length_secs,
+            tolerance,
+        })
+    }
+
+    /// Epoch length in seconds.
+    pub fn epoch_length_secs(&self) -> u64 {
+        self.epoch_length_secs
+    }
+
+    /// Number of adjacent epochs accepted on each side of the current one.
+    pub fn tolerance(&self) -> u64 {
+        self.tolerance
+    }
+
+    /// Epoch index containing the Unix timestamp `unix_secs`.
+    pub fn epoch_at(&self, unix_secs: u64) -> u64 {
+        unix_secs / self.epoch_length_secs
+    }
+
+    /// Unix timestamp at which `epoch` begins.
+    pub fn epoch_start(&self, epoch: u64) -> u64 {
+        epoch.saturating_mul(self.epoch_length_secs)
+    }
+
+    /// Seconds from `unix_secs` until the next epoch begins.
+    pub fn secs_until_next_epoch(&self, unix_secs: u64) -> u64 {
+        self.epoch_length_secs - unix_secs % self.epoch_length_secs
+    }
+
+    /// Epochs a peer should accept at `unix_secs`.
+    ///
+    /// The current epoch comes first, followed by neighbors in order of
+    /// increasing distance (earlier before later). Epochs before zero are
+    /// omitted.
+    pub fn accepted_epochs(&self, unix_secs: u64) -> Vec<u64> {
+        self.epochs_around(unix_secs).collect()
+    }
+
+    /// Lazy form of [`accepted_epochs`](Self::accepted_epochs).
+    fn epochs_around(&self, unix_secs: u64) -> impl Iterator<Item = u64> {
+        let current = self.epoch_at(unix_secs);
+        std::iter::once(current).chain((1..=self.tolerance).flat_map(move |offset| {
+            let earlier = current.checked_sub(offset);
+            let later = current.checked_add(offset);
+            earlier.into_iter().chain(later)
+        }))
+    }
+
+    /// Salts a peer should accept at `unix_secs`, in [`accepted_epochs`] order.
+    ///
+    /// [`accepted_epochs`]: Self::accepted_epochs
+    pub fn salts_at(&self, unix_secs: u64) -> Vec<Vec<u8>> {
+        self.accepted_epochs(unix_secs)
+            .into_iter()
+            .map(epoch_salt)
+            .collect()
+    }
+
+    /// Salts a peer should accept right now, based on the system clock.
+    pub fn salts_now(&self) -> Vec<Vec<u8>> {
+        self.salts_at(unix_now())
+    }
+}
+
+/// Salt schedule that rotates the canonical epoch salt with wall-clock time.
+///
+/// Built on a [`TimeOracle`], but the skew tolerance is in seconds rather
+/// than whole epochs: a neighboring epoch is a candidate only when it starts
+/// or ends within `skew_secs` of the timestamp. Away from epoch boundaries a
+/// single salt is returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaltSchedule {
+    /// Oracle accepting every epoch `skew_secs` can reach.
+    oracle: TimeOracle,
+    /// Maximum clock disagreement between peers, in seconds.
+    skew_secs: u64,
+}
+
+impl SaltSchedule {
+    /// Create a schedule with an epoch length and clock-skew tolerance.
+    ///
+    /// Returns an error if `epoch_length_secs` is zero.
+    pub fn new(epoch_length_secs: u64, skew_secs: u64) -> Result<Self, OracleError> {
+        let reach = skew_secs.div_ceil(epoch_length_secs.max(1));
+        Ok(Self {
+            oracle: TimeOracle::new(epoch_length_secs, reach)?,
+            skew_secs,
+        })
+    }
+
+    /// Underlying oracle, whose tolerance covers every epoch within
+    /// `skew_secs`.
+    pub fn oracle(&self) -> &TimeOracle {
+        &self.oracle
+    }
+
+    /// Epoch length in seconds.
+    pub fn epoch_length_secs(&self) -> u64 {
+        self.oracle.epoch_length_secs()
+    }
+
+    /// Clock-skew tolerance in seconds.
+    pub fn skew_secs(&self) -> u64 {
+        self.skew_secs
+    }
+
+    /// Epoch index containing the Unix timestamp `unix_secs`.
+    pub fn epoch_at(&self, unix_secs: u64) -> u64 {
+        self.oracle.epoch_at(unix_secs)
+    }
+
+    /// Unix timestamp at which `epoch` begins.
+    pub fn epoch_start(&self, epoch: u64) -> u64 {
+        self.oracle.epoch_start(epoch)
+    }
+
+    /// Canonical salt for the epoch containing `unix_secs`.
+    pub fn salt_at(&self, unix_secs: u64) -> Vec<u8> {
+        epoch_salt(self.epoch_at(unix_secs))
+    }
+
+    /// Epochs a peer within `skew_secs` of `unix_secs` might be in.
+    ///
+    /// The oracle's accepted epochs, in the same order, restricted to those
+    /// overlapping `unix_secs ± skew_secs`.
+    pub fn candidate_epochs(&self, unix_secs: u64) -> impl Iterator<Item = u64> {
+        let earliest = self.epoch_at(unix_secs.saturating_sub(self.skew_secs));
+        let latest = self.epoch_at(unix_secs.saturating_add(self.skew_secs));
+        self.oracle
+            .epochs_around(unix_secs)
+            .filter(move |epoch| (earliest..=latest).contains(epoch))
+    }
+
+    /// Salts for [`candidate_epochs`](Self::candidate_epochs), in the same order.
+    pub fn candidate_salts(&self, unix_secs: u64) -> impl Iterator<Item = Vec<u8>> {
+        self.candidate_epochs(unix_secs).map(epoch_salt)
+    }
+
+    /// Epochs starting with the one containing `unix_secs`, with their salts.
+    ///
+    /// Useful for precomputing upcoming targets.
+    pub fn upcoming(&self, unix_secs: u64) -> impl Iterator<Item = (u64, Vec<u8>)> {
+        (self.epoch_at(unix_secs)..).map(|epoch| (epoch, epoch_salt(epoch)))
+    }
+
+    /// Candidate salts right now, based on the system clock.
+    pub fn candidate_salts_now(&self) -> impl Iterator<Item = Vec<u8>> {
+        self.candidate_salts(unix_now())
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// Encode a latitude/longitude pair as a geohash string.
+///
+/// `precision` is the number of base32 characters in the output and must be in
+/// `1..=GEOHASH_MAX_PRECISION`.
+pub fn geohash(lat: f64, lon: f64, precision: usize) -> Result<String, OracleError> {
+    validate_coordinates(lat, lon)?;
+    validate_precision(precision)?;
+
+    let mut lat_range = (-90.0f64, 90.0f64);
+    let mut lon_range = (-180.0f64, 180.0f64);
+    let mut hash = String::with_capacity(precision);
+    let mut even_bit = true;
+    let mut bits = 0u8;
+    let mut bit_count = 0;
+
+    while hash.len() < precision {
+        let (range, value) = if even_bit {
+            (&mut lon_range, lon)
+        } else {
+            (&mut lat_range, lat)
+        };
+        let mid = (range.0 + range.1) / 2.0;
+        bits <<= 1;
+        if value >= mid {
+            bits |= 1;
+            range.0 = mid;
+        } else {
+            range.1 = mid;
+        }
+        even_bit = !even_bit;
+        bit_count += 1;
+
+        if bit_count == 5 {
+            hash.push(GEOHASH_ALPHABET[bits as usize] as char);
+            bits = 0;
+            bit_count = 0;
+        }
+    }
+
+    Ok(hash)
+}
+
+/// Build the canonical salt for a geohash cell and time epoch.
+///
+/// The salt is the UTF-8 string `phenorv-geo-v1|<geohash>|<epoch>`, where the
+/// geohash is lowercase and `epoch` is the decimal time-bucket index.
+pub fn geohash_salt(
+    lat: f64,
+    lon: f64,
+    precision: usize,
+    epoch: u64,
+) -> Result<Vec<u8>, OracleError> {
+    let cell = geohash(lat, lon, precision)?;
+    Ok(salt_for_cell(&cell, epoch))
+}
+
+/// Build the canonical salts for a location's cell and its adjacent cells.
+///
+/// The first entry is always the salt for the cell containing `(lat, lon)`;
+/// the remaining entries cover the neighboring cells returned by
+/// [`geohash_neighbors`]. Peers close to a cell boundary can try each salt in
+/// turn so a small position difference does not prevent a rendezvous.
+pub fn geohash_salt_candidates(
+    lat: f64,
+    lon: f64,
+    precision: usize,
+    epoch: u64,
+) -> Result<Vec<Vec<u8>>, OracleError> {
+    let cell = geohash(lat, lon, precision)?;
+    let mut salts = vec![salt_for_cell(&cell, epoch)];
+    for neighbor in geohash_neighbors(&cell)? {
+        salts.push(salt_for_cell(&neighbor, epoch));
+    }
+    Ok(salts)
+}
+
+/// Enumerate the cells adjacent to a geohash cell.
+///
+/// Returns up to eight neighbors in row-major order (north-west to
+/// south-east). Longitude wraps around the antimeridian; cells beyond the
+/// poles are omitted, so polar cells yield fewer neighbors. Input is
+/// case-insensitive; neighbors are returned in lowercase.
+pub fn geohash_neighbors(hash: &str) -> Result<Vec<String>, OracleError> {
+    let hash = hash.to_ascii_lowercase();
+    let (lat_range, lon_range) = decode_bounds(&hash)?;
+    let lat_center = (lat_range.0 + lat_range.1) / 2.0;
+    let lon_center = (lon_range.0 + lon_range.1) / 2.0;
+    let lat_step = lat_range.1 - lat_range.0;
+    let lon_step = lon_range.1 - lon_range.0;
+    let precision = hash.len();
+
+    let mut neighbors = Vec::with_capacity(8);
+    for dlat in [1.0, 0.0, -1.0] {
+        for dlon in [-1.0, 0.0, 1.0] {
+            if dlat == 0.0 && dlon == 0.0 {
+                continue;
+            }
+            let lat = lat_center + dlat * lat_step;
+            if !(-90.0..=90.0).contains(&lat) {
+                continue;
+            }
+            let lon = wrap_longitude(lon_center + dlon * lon_step);
+            let cell = geohash(lat, lon, precision)?;
+            if cell != hash && !neighbors.contains(&cell) {
+                neighbors.push(cell);
+            }
+        }
+    }
+    Ok(neighbors)
+}
+
+/// Closed-open coordinate interval `(min, max)` covered by a geohash cell.
+type Interval = (f64, f64);
+
+fn salt_for_cell(cell: &str, epoch: u64) -> Vec<u8> {
+    format!("{GEOHASH_SALT_PREFIX}|{cell}|{epoch}").into_bytes()
+}
+
+fn decode_bounds(hash: &str) -> Result<(Interval, Interval), OracleError> {
+    validate_precision(hash.len())?;
+
+    let mut lat_range = (-90.0f64, 90.0f64);
+    let mut lon_range = (-180.0f64, 180.0f64);
+    let mut even_bit = true;
+
+    for ch in hash.chars() {
+        let lower = ch.to_ascii_lowercase();
+        let index = GEOHASH_ALPHABET
+            .iter()
+            .position(|&c| c as char == lower)
+            .ok_or(OracleError::InvalidGeohashCharacter(ch))?;
+        for shift in (0..5).rev() {
+            let bit = (index >> shift) & 1;
+            let range = if even_bit {
+                &mut lon_range
+            } else {
+                &mut lat_range
+            };
+            let mid = (range.0 + range.1) / 2.0;
+            if bit == 1 {
+                range.0 = mid;
+            } else {
+                range.1 = mid;
+            }
+            even_bit = !even_bit;
+        }
+    }
+
+    Ok((lat_range, lon_range))
+}
+
+fn wrap_longitude(lon: f64) -> f64 {
+    if lon >= 180.0 {
+        lon - 360.0
+    } else if lon < -180.0 {
+        lon + 360.0
+    } else {
+        lon
+    }
+}
+
+fn validate_coordinates(lat: f64, lon: f64) -> Result<(), OracleError> {
+    if !lat.is_finite() || !(-90.0..=90.0).contains(&lat) {
+        return Err(OracleError::InvalidLatitude(lat));
+    }
+    if !lon.is_finite() || !(-180.0..=180.0).contains(&lon) {
+        return Err(OracleError::InvalidLongitude(lon));
+    }
+    Ok(())
+}
+
+fn validate_precision(precision: usize) -> Result<(), OracleError> {
+    if precision == 0 || precision > GEOHASH_MAX_PRECISION {
+        return Err(OracleError::InvalidPrecision(precision));
+    }
+    Ok(())
+}
+
+/// Errors returned when building oracle-state salts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OracleError {
+    /// Latitude was not finite or outside `[-90, 90]`.
+    InvalidLatitude(f64),
+    /// Longitude was not finite or outside `[-180, 180]`.
+    InvalidLongitude(f64),
+    /// Geohash precision was zero or above [`GEOHASH_MAX_PRECISION`].
+    InvalidPrecision(usize),
+    /// A geohash contained a character outside the base32 alphabet.
+    InvalidGeohashCharacter(char),
+    /// A time oracle was configured with a zero-length epoch.
+    InvalidEpochLength,
+}
+
+impl fmt::Display for OracleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLatitude(lat) => write!(f, "latitude out of range: {lat}"),
+            Self::InvalidLongitude(lon) => write!(f, "longitude out of range: {lon}"),
+            Self::InvalidPrecision(precision) => write!(
+                f,
+                "geohash precision must be 1..={GEOHASH_MAX_PRECISION}, got {precision}"
+            ),
+            Self::InvalidGeohashCharacter(ch) => write!(f, "invalid geohash character '{ch}'"),
+            Self::InvalidEpochLength => write!(f, "epoch length must be greater than zero"),
+        }
+    }
+}
+
+impl std::error::Error for OracleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geohash_matches_reference_value() {
+        // Reference cell for 57.64911, 10.40744 from the original geohash spec.
+        let hash = geohash(57.64911, 10.40744, 11).expect("valid coordinates");
+        assert_eq!(hash, "u4pruydqqvj");
+    }
+
+    #[test]
+    fn geohash_salt_is_canonical() {
+        let salt = geohash_salt(57.64911, 10.40744, 5, 42).expect("valid coordinates");
+        assert_eq!(salt, b"phenorv-geo-v1|u4pru|42".to_vec());
+    }
+
+    #[test]
+    fn neighbors_surround_the_cell() {
+        let neighbors = geohash_neighbors("u4pru").expect("valid geohash");
+        assert_eq!(neighbors.len(), 8);
+        assert!(neighbors.iter().all(|n| n.len() == 5 && n != "u4pru"));
+        for neighbor in &neighbors {
+            let back = geohash_neighbors(neighbor).expect("valid geohash");
+            assert!(back.contains(&"u4pru".to_string()));
+        }
+    }
+
+    #[test]
+    fn neighbors_ignore_input_case() {
+        let lower = geohash_neighbors("u4pru").expect("valid geohash");
+        assert_eq!(geohash_neighbors("U4pRU").expect("valid geohash"), lower);
+        assert!(!lower.contains(&"u4pru".to_string()));
+    }
+
+    #[test]
+    fn candidates_start_with_own_cell() {
+        let candidates = geohash_salt_candidates(57.64911, 10.40744, 5, 7).expect("valid");
+        assert_eq!(candidates.len(), 9);
+        assert_eq!(candidates[0], geohash_salt(57.64911, 10.40744, 5, 7).unwrap());
+    }
+
+    #[test]
+    fn time_oracle_accepts_adjacent_epochs() {
+        let oracle = TimeOracle::new(600, 1).expect("valid oracle");
+        assert_eq!(oracle.epoch_at(1_799), 2);
+        assert_eq!(oracle.accepted_epochs(1_799), vec![2, 1, 3]);
+        assert_eq!(oracle.salts_at(1_799)[0], b"phenorv-epoch-v1|2".to_vec());
+        assert_eq!(oracle.secs_until_next_epoch(1_799), 1);
+        assert_eq!(oracle.secs_until_next_epoch(1_800), 600);
+    }
+
+    #[test]
+    fn salt_schedule_includes_neighbors_only_near_boundaries() {
+        let schedule = SaltSchedule::new(600, 30).expect("valid");
+        assert_eq!(schedule.salt_at(6_300), epoch_salt(10));
+        assert_eq!(schedule.candidate_epochs(6_300).collect::<Vec<_>>(), [10]);
+        assert_eq!(schedule.candidate_epochs(6_010).collect::<Vec<_>>(), [10, 9]);
+        assert_eq!(schedule.candidate_epochs(6_590).collect::<Vec<_>>(), [10, 11]);
+        assert_eq!(schedule.candidate_epochs(10).collect::<Vec<_>>(), [0]);
+        assert_eq!(
+            schedule.candidate_salts(6_590).collect::<Vec<_>>(),
+            [epoch_salt(10), epoch_salt(11)]
+        );
+        let upcoming: Vec<u64> = schedule.upcoming(6_300).take(3).map(|(e, _)| e).collect();
+        assert_eq!(upcoming, [10, 11, 12]);
+        assert_eq!(schedule.epoch_start(11), 6_600);
+
+        let wide = SaltSchedule::new(60, 130).expect("valid");
+        assert_eq!(
+            wide.candidate_epochs(6_030).collect::<Vec<_>>(),
+            [100, 99, 101, 98, 102]
+        );
+        assert_eq!(wide.oracle().tolerance(), 3);
+        assert_eq!(SaltSchedule::new(0, 5), Err(OracleError::InvalidEpochLength));
+    }
+
+    #[test]
+    fn time_oracle_skips_negative_epochs() {
+        let oracle = TimeOracle::new(60, 2).expect("valid oracle");
+        assert_eq!(oracle.accepted_epochs(30), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn skewed_peers_share_a_salt() {
+        let oracle = TimeOracle::new(300, 1).expect("valid oracle");
+        let ahead = oracle.salts_at(1_201);
+        let behind = oracle.salts_at(1_199);
+        assert_ne!(ahead[0], behind[0]);
+        assert!(ahead.contains(&behind[0]));
+        assert!(behind.contains(&ahead[0]));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(geohash(91.0, 0.0, 5), Err(OracleError::InvalidLatitude(91.0)));
+        assert_eq!(geohash(0.0, 0.0, 0), Err(OracleError::InvalidPrecision(0)));
+        assert_eq!(
+            geohash_neighbors("u4pra"),
+            Err(OracleError::InvalidGeohashCharacter('a'))
+        );
+        assert_eq!(TimeOracle::new(0, 1), Err(OracleError::InvalidEpochLength));
+    }
+}
@@ -0,0 +1,185 @@
+//! Hierarchical SRT derivation.
+//!
+//! One master token can serve many contexts by deriving a child token per
+//! context along a slash-separated path such as `home/kitchen/0`, in the
+//! spirit of BIP32. Each path component is one HKDF-SHA256 step keyed by the
+//! parent token, so a child reveals nothing about its parent or siblings and
+//! can be handed to a device that should only reach that branch. Deriving
+//! `a/b` from the master equals deriving `b` from the child at `a`.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{derive_subkey, SemanticRendezvousToken};
+
+/// Info prefix for child derivation; the path component follows it.
+pub const SUBKEY_CHILD: &[u8] = b"phenorv-subkey-v1|child|";
+
+/// A parsed derivation path such as `home/kitchen/0`.
+///
+/// Components are non-empty and separated by `/`; surrounding whitespace and
+/// a single leading or trailing `/` are ignored. With the `serde` feature the
+/// path serializes as its string form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<String>);
+
+impl DerivationPath {
+    /// Path components from the root down.
+    pub fn components(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    /// Number of derivation steps.
+    pub fn depth(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Return this path extended by one component.
+    pub fn child(mut self, component: &str) -> Result<Self, PathError> {
+        if component.is_empty() || component.contains('/') {
+            return Err(PathError::InvalidComponent(component.to_string()));
+        }
+        self.0.push(component.to_string());
+        Ok(self)
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = PathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let trimmed = trimmed.strip_prefix('/').unwrap_or(trimmed);
+        let trimmed = trimmed.strip_suffix('/').unwrap_or(trimmed);
+        if trimmed.is_empty() {
+            return Err(PathError::Empty);
+        }
+        let components = trimmed
+            .split('/')
+            .map(|component| {
+                if component.is_empty() {
+                    Err(PathError::InvalidComponent(component.to_string()))
+                } else {
+                    Ok(component.to_string())
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self(components))
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.join("/"))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for DerivationPath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for DerivationPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl SemanticRendezvousToken {
+    /// Derive the child token at a slash-separated `path`.
+    pub fn derive_child(&self, path: &str) -> Result<Self, PathError> {
+        Ok(self.derive_path(&path.parse()?))
+    }
+
+    /// Derive the child token at a parsed path.
+    pub fn derive_path(&self, path: &DerivationPath) -> Self {
+        path.components().fold(self.clone(), |parent, component| {
+            let info = [SUBKEY_CHILD, component.as_bytes()].concat();
+            let bytes = derive_subkey(&parent, &info, 32).expect("32 bytes is a valid length");
+            Self::from_slice(&bytes).expect("derived subkey is 32 bytes")
+        })
+    }
+}
+
+/// Errors returned when parsing derivation paths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathError {
+    /// The path has no components.
+    Empty,
+    /// A component is empty or contains `/`.
+    InvalidComponent(String),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "derivation path is empty"),
+            Self::InvalidComponent(component) => {
+                write!(f, "invalid derivation path component '{component}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn children_compose_and_do_not_reveal_parents() {
+        let master = SemanticRendezvousToken::from_bytes([2u8; 32]);
+        let kitchen = master.derive_child("home/kitchen/0").expect("derive");
+        let home = master.derive_child("home").expect("derive");
+        assert_eq!(home.derive_child("kitchen/0"), Ok(kitchen.clone()));
+        assert_ne!(
+            master.derive_child("home/kitchen/1"),
+            Ok(kitchen.clone()),
+            "siblings differ"
+        );
+
+        // Derivation only walks down: no child path leads back to an ancestor.
+        for path in ["home", "kitchen", "0", "..", "home/kitchen"] {
+            let descendant = kitchen.derive_child(path).expect("derive");
+            assert_ne!(descendant, master);
+            assert_ne!(descendant, home);
+            assert_ne!(descendant, kitchen);
+        }
+        assert_ne!(home, master);
+    }
+
+    #[test]
+    fn paths_parse_and_format() {
+        let path: DerivationPath = " /home/kitchen/0/ ".parse().expect("parse");
+        assert_eq!(path.depth(), 3);
+        assert_eq!(path.to_string(), "home/kitchen/0");
+        assert_eq!(path.clone().child("lamp").expect("child").depth(), 4);
+        assert_eq!("".parse::<DerivationPath>(), Err(PathError::Empty));
+        assert_eq!(
+            "home//0".parse::<DerivationPath>(),
+            Err(PathError::InvalidComponent(String::new()))
+        );
+        assert!(path.child("a/b").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn paths_serialize_as_strings() {
+        let path: DerivationPath = "home/kitchen".parse().expect("parse");
+        let json = serde_json::to_string(&path).expect("serialize");
+        assert_eq!(json, "\"home/kitchen\"");
+        assert_eq!(
+            serde_json::from_str::<DerivationPath>(&json).expect("deserialize"),
+            path
+        );
+        assert!(serde_json::from_str::<DerivationPath>("\"\"").is_err());
+    }
+}
@@ -0,0 +1,236 @@
+//! Target derivation backed by external key storage.
+//!
+//! Deployments that keep the SRT in a platform keystore, hardware token, or
+//! remote signer never hold the raw bytes in process memory. Every
+//! derivation in this crate only needs a keyed digest of a message, so an
+//! [`SrtProvider`] exposes exactly that operation and builds patterns and
+//! tolerances on top of it. [`SemanticRendezvousToken`] is itself a provider,
+//! and [`CommandProvider`] delegates to an external program.
+
+use std::fmt;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use super::{
+    pattern_from_digest, tolerances_from_digest, DerivationAlgorithm, SemanticRendezvousToken,
+};
+use crate::matching::Tolerances;
+use crate::pattern::SubmodalityPattern;
+
+/// Source of SRT-keyed digests.
+pub trait SrtProvider {
+    /// Compute the 32-byte digest of `message` keyed by the held SRT, as
+    /// [`DerivationAlgorithm::digest`] would.
+    ///
+    /// Providers that cannot run `algorithm` return
+    /// [`ProviderError::UnsupportedAlgorithm`].
+    fn digest(
+        &self,
+        algorithm: DerivationAlgorithm,
+        message: &[u8],
+    ) -> Result<[u8; 32], ProviderError>;
+
+    /// Derive the target pattern for `salt`, as
+    /// [`pattern_from_srt`](super::pattern_from_srt) would.
+    fn derive_pattern(&self, salt: &[u8]) -> Result<SubmodalityPattern, ProviderError> {
+        self.derive_pattern_with(salt, DerivationAlgorithm::HmacSha256)
+    }
+
+    /// Derive the target pattern with a chosen algorithm.
+    fn derive_pattern_with(
+        &self,
+        salt: &[u8],
+        algorithm: DerivationAlgorithm,
+    ) -> Result<SubmodalityPattern, ProviderError> {
+        Ok(pattern_from_digest(&self.digest(algorithm, salt)?))
+    }
+
+    /// Derive per-dimension tolerances with a chosen algorithm.
+    fn derive_tolerances_with(
+        &self,
+        salt: &[u8],
+        algorithm: DerivationAlgorithm,
+    ) -> Result<Tolerances, ProviderError> {
+        Ok(tolerances_from_digest(&self.digest(algorithm, salt)?))
+    }
+}
+
+impl SrtProvider for SemanticRendezvousToken {
+    fn digest(
+        &self,
+        algorithm: DerivationAlgorithm,
+        message: &[u8],
+    ) -> Result<[u8; 32], ProviderError> {
+        Ok(algorithm.digest(self, message))
+    }
+}
+
+/// Provider that runs an external program for each HMAC-SHA256 digest.
+///
+/// The program receives the message bytes on stdin and must print the
+/// 32-byte MAC as 64 hex characters. Only the last whitespace-separated word
+/// of its output is read, so tools that print a label first, such as
+/// `openssl dgst -sha256 -mac HMAC -macopt hexkey:...`, work unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandProvider {
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandProvider {
+    /// Run `program` with no arguments.
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Append an argument.
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Split a command line on whitespace into a program and arguments.
+    ///
+    /// Returns `None` for an empty command line. Quoting is not supported.
+    pub fn from_command_line(command: &str) -> Option<Self> {
+        let mut words = command.split_whitespace();
+        let provider = Self::new(words.next()?);
+        Some(words.fold(provider, Self::arg))
+    }
+}
+
+impl SrtProvider for CommandProvider {
+    fn digest(
+        &self,
+        algorithm: DerivationAlgorithm,
+        message: &[u8],
+    ) -> Result<[u8; 32], ProviderError> {
+        if algorithm != DerivationAlgorithm::HmacSha256 {
+            return Err(ProviderError::UnsupportedAlgorithm(algorithm));
+        }
+        let unavailable = |err: std::io::Error| {
+            ProviderError::Unavailable(format!("running '{}': {err}", self.program))
+        };
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(unavailable)?;
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(message)
+            .map_err(unavailable)?;
+        let output = child.wait_with_output().map_err(unavailable)?;
+        if !output.status.success() {
+            return Err(ProviderError::Unavailable(format!(
+                "'{}' exited with {}",
+                self.program, output.status
+            )));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mac = stdout.split_whitespace().last().unwrap_or_default();
+        SemanticRendezvousToken::from_hex(mac)
+            .map(|parsed| *parsed.as_bytes())
+            .map_err(|err| ProviderError::InvalidResponse(err.to_string()))
+    }
+}
+
+/// Errors returned by an [`SrtProvider`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderError {
+    /// The provider does not implement the requested algorithm.
+    UnsupportedAlgorithm(DerivationAlgorithm),
+    /// The backing store could not be reached or refused the operation.
+    Unavailable(String),
+    /// The backing store returned something other than a 32-byte digest.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedAlgorithm(algorithm) => {
+                write!(f, "SRT provider does not support {algorithm:?}")
+            }
+            Self::Unavailable(reason) => write!(f, "SRT provider unavailable: {reason}"),
+            Self::InvalidResponse(reason) => {
+                write!(f, "SRT provider returned an invalid digest: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::srt::{pattern_from_srt, tolerances_from_srt_with};
+
+    /// Provider that hides its token behind the trait, like a keystore.
+    struct Opaque(SemanticRendezvousToken);
+
+    impl SrtProvider for Opaque {
+        fn digest(
+            &self,
+            algorithm: DerivationAlgorithm,
+            message: &[u8],
+        ) -> Result<[u8; 32], ProviderError> {
+            match algorithm {
+                DerivationAlgorithm::HmacSha256 => Ok(algorithm.digest(&self.0, message)),
+                other => Err(ProviderError::UnsupportedAlgorithm(other)),
+            }
+        }
+    }
+
+    #[test]
+    fn providers_derive_like_the_token() {
+        let srt = SemanticRendezvousToken::from_bytes([8u8; 32]);
+        let opaque = Opaque(srt.clone());
+        assert_eq!(
+            opaque.derive_pattern(b"salt"),
+            Ok(pattern_from_srt(&srt, b"salt"))
+        );
+        assert_eq!(
+            srt.derive_tolerances_with(b"salt", DerivationAlgorithm::Blake3Keyed),
+            Ok(tolerances_from_srt_with(
+                &srt,
+                b"salt",
+                DerivationAlgorithm::Blake3Keyed
+            ))
+        );
+        assert_eq!(
+            opaque.derive_pattern_with(b"salt", DerivationAlgorithm::Blake3Keyed),
+            Err(ProviderError::UnsupportedAlgorithm(
+                DerivationAlgorithm::Blake3Keyed
+            ))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn command_provider_reads_hex_from_stdout() {
+        let mac = "ab".repeat(32);
+        let provider = CommandProvider::new("sh")
+            .arg("-c")
+            .arg(format!("cat >/dev/null; echo 'HMAC(stdin)= {mac}'"));
+        let digest = provider
+            .digest(DerivationAlgorithm::HmacSha256, b"salt")
+            .expect("digest");
+        assert_eq!(digest, [0xab; 32]);
+
+        let failing = CommandProvider::from_command_line("false").expect("command");
+        assert!(matches!(
+            failing.derive_pattern(b"salt"),
+            Err(ProviderError::Unavailable(_))
+        ));
+        assert_eq!(CommandProvider::from_command_line("  "), None);
+    }
+}
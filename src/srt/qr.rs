@@ -0,0 +1,162 @@
+//! QR code transfer of SRTs.
+//!
+//! Showing a QR code on one device and scanning it with the other is the
+//! usual out-of-band channel for sharing a token in person. The code carries
+//! a text payload of the form `PHENORV1...`: the upper-case bech32m encoding
+//! (HRP `phenorv`) of a version byte, the 32 token bytes, and a 4-byte
+//! fingerprint. Upper-case bech32 fits QR alphanumeric mode, keeping the code
+//! small, and its checksum rejects misreads. The embedded fingerprint is the
+//! first four bytes of [`SemanticRendezvousToken::fingerprint`], which should
+//! be shown next to the code so both people can confirm they hold the same
+//! token.
+//!
+//! Rendering produces SVG or terminal text. Decoding takes the payload
+//! string returned by any QR scanner; image recognition is left to the
+//! platform.
+
+use std::fmt;
+
+use bech32::{Bech32m, Hrp};
+use qrcode::render::{svg, unicode};
+use qrcode::{EcLevel, QrCode};
+
+use super::SemanticRendezvousToken;
+
+/// Current QR payload version.
+pub const QR_PAYLOAD_VERSION: u8 = 1;
+
+/// Human-readable prefix of QR payloads.
+const QR_HRP: &str = "phenorv";
+
+/// Fingerprint bytes embedded in the payload.
+const FINGERPRINT_LEN: usize = 4;
+
+impl SemanticRendezvousToken {
+    /// Text payload stored in the QR code.
+    pub fn to_qr_payload(&self) -> String {
+        let mut data = Vec::with_capacity(1 + 32 + FINGERPRINT_LEN);
+        data.push(QR_PAYLOAD_VERSION);
+        data.extend_from_slice(self.as_bytes());
+        data.extend_from_slice(&fingerprint_bytes(self));
+        let hrp = Hrp::parse(QR_HRP).expect("valid HRP");
+        bech32::encode_upper::<Bech32m>(hrp, &data).expect("payload fits in bech32")
+    }
+
+    /// Parse a scanned QR payload, checking its version and fingerprint.
+    pub fn from_qr_payload(payload: &str) -> Result<Self, QrError> {
+        let (hrp, data) = bech32::decode(payload.trim())
+            .map_err(|err| QrError::InvalidPayload(err.to_string()))?;
+        if hrp.to_lowercase() != QR_HRP {
+            return Err(QrError::InvalidPayload(format!(
+                "unexpected prefix '{}'",
+                hrp.to_lowercase()
+            )));
+        }
+        let Some((&version, rest)) = data.split_first() else {
+            return Err(QrError::InvalidPayload("empty payload".to_string()));
+        };
+        if version != QR_PAYLOAD_VERSION {
+            return Err(QrError::UnsupportedVersion(version));
+        }
+        if rest.len() != 32 + FINGERPRINT_LEN {
+            return Err(QrError::InvalidPayload(format!(
+                "expected {} payload bytes, got {}",
+                32 + FINGERPRINT_LEN,
+                rest.len()
+            )));
+        }
+        let (token, fingerprint) = rest.split_at(32);
+        let srt = Self::from_slice(token).expect("length checked above");
+        if fingerprint != fingerprint_bytes(&srt) {
+            return Err(QrError::FingerprintMismatch);
+        }
+        Ok(srt)
+    }
+
+    /// Render the QR code as an SVG document.
+    pub fn to_qr_svg(&self) -> String {
+        qr_code(self)
+            .render::<svg::Color>()
+            .min_dimensions(256, 256)
+            .build()
+    }
+
+    /// Render the QR code with Unicode half blocks for display in a terminal.
+    pub fn to_qr_text(&self) -> String {
+        qr_code(self)
+            .render::<unicode::Dense1x2>()
+            .dark_color(unicode::Dense1x2::Light)
+            .light_color(unicode::Dense1x2::Dark)
+            .build()
+    }
+}
+
+fn fingerprint_bytes(srt: &SemanticRendezvousToken) -> [u8; FINGERPRINT_LEN] {
+    let mut fingerprint = [0u8; FINGERPRINT_LEN];
+    fingerprint.copy_from_slice(&srt.fingerprint().as_bytes()[..FINGERPRINT_LEN]);
+    fingerprint
+}
+
+fn qr_code(srt: &SemanticRendezvousToken) -> QrCode {
+    QrCode::with_error_correction_level(srt.to_qr_payload(), EcLevel::M)
+        .expect("payload fits in a QR code")
+}
+
+/// Errors returned when parsing a QR payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QrError {
+    /// The payload was not a well-formed `PHENORV1...` string.
+    InvalidPayload(String),
+    /// The payload version is not supported by this implementation.
+    UnsupportedVersion(u8),
+    /// The embedded fingerprint does not match the embedded token.
+    FingerprintMismatch,
+}
+
+impl fmt::Display for QrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPayload(reason) => write!(f, "invalid QR payload: {reason}"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported QR payload version {version}")
+            }
+            Self::FingerprintMismatch => write!(f, "QR payload fingerprint does not match token"),
+        }
+    }
+}
+
+impl std::error::Error for QrError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qr_payload_round_trips_and_checks_fingerprint() {
+        let srt = SemanticRendezvousToken::from_bytes(std::array::from_fn(|i| i as u8));
+        let payload = srt.to_qr_payload();
+        assert!(payload.starts_with("PHENORV1"));
+        assert_eq!(
+            SemanticRendezvousToken::from_qr_payload(&payload),
+            Ok(srt.clone())
+        );
+        assert!(srt.to_qr_svg().starts_with("<?xml"));
+        assert!(!srt.to_qr_text().is_empty());
+
+        let hrp = Hrp::parse(QR_HRP).unwrap();
+        let mut data = vec![QR_PAYLOAD_VERSION];
+        data.extend_from_slice(srt.as_bytes());
+        data.extend_from_slice(&[0, 0, 0, 0]);
+        let forged = bech32::encode_upper::<Bech32m>(hrp, &data).unwrap();
+        assert_eq!(
+            SemanticRendezvousToken::from_qr_payload(&forged),
+            Err(QrError::FingerprintMismatch)
+        );
+        data[0] = 9;
+        let future = bech32::encode_upper::<Bech32m>(hrp, &data).unwrap();
+        assert_eq!(
+            SemanticRendezvousToken::from_qr_payload(&future),
+            Err(QrError::UnsupportedVersion(9))
+        );
+    }
+}
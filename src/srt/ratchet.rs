@@ -0,0 +1,196 @@
+//! Forward-secure SRT ratcheting.
+//!
+//! A ratchet replaces the long-lived SRT with a chain of per-epoch tokens,
+//! each derived from the previous one through HKDF-SHA256. Stepping forward
+//! is cheap, but recovering an earlier token from a later one would require
+//! inverting the hash, so a device compromised in epoch `n` reveals nothing
+//! about the targets of epochs before `n`. Peers that start from the same
+//! token and epoch stay in lockstep by advancing to the same epoch.
+
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{derive_subkey, SemanticRendezvousToken};
+
+/// Info label for the ratchet step.
+pub const SUBKEY_RATCHET: &[u8] = b"phenorv-subkey-v1|ratchet";
+
+/// Most steps [`SrtRatchet::advance_to`] takes in one call.
+///
+/// Each step is an HKDF derivation, so this bounds a single call to about a
+/// second of work even when the requested epoch comes from untrusted input.
+/// Callers that really need to skip further can call it repeatedly.
+pub const MAX_ADVANCE_STEPS: u64 = 1 << 20;
+
+impl SemanticRendezvousToken {
+    /// Derive the next token in the ratchet chain.
+    ///
+    /// The step is one-way: the returned token does not reveal `self`.
+    pub fn ratchet(&self) -> Self {
+        let bytes = derive_subkey(self, SUBKEY_RATCHET, 32).expect("32 bytes is a valid length");
+        Self::from_slice(&bytes).expect("derived subkey is 32 bytes")
+    }
+}
+
+/// Ratchet state: the token for the current epoch.
+///
+/// Persist this value (it is serializable with the `serde` feature) instead
+/// of the original SRT, and drop older states once advanced, so past tokens
+/// cannot be recovered from storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SrtRatchet {
+    /// Epoch whose token is held.
+    epoch: u64,
+    /// Token for `epoch`.
+    token: SemanticRendezvousToken,
+}
+
+impl SrtRatchet {
+    /// Start a ratchet with `token` as the token for `epoch`.
+    pub fn new(token: SemanticRendezvousToken, epoch: u64) -> Self {
+        Self { epoch, token }
+    }
+
+    /// Current epoch.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Token for the current epoch.
+    pub fn token(&self) -> &SemanticRendezvousToken {
+        &self.token
+    }
+
+    /// Step to the next epoch.
+    ///
+    /// Fails without changing state if the epoch counter would overflow.
+    pub fn advance(&mut self) -> Result<(), RatchetError> {
+        self.epoch = self
+            .epoch
+            .checked_add(1)
+            .ok_or(RatchetError::EpochOverflow)?;
+        self.token = self.token.ratchet();
+        Ok(())
+    }
+
+    /// Step forward until the current epoch is `epoch`.
+    ///
+    /// Fails without changing state if `epoch` is earlier than the current
+    /// epoch, since past tokens cannot be recovered, or more than
+    /// [`MAX_ADVANCE_STEPS`] ahead of it.
+    pub fn advance_to(&mut self, epoch: u64) -> Result<&SemanticRendezvousToken, RatchetError> {
+        if epoch < self.epoch {
+            return Err(RatchetError::EpochInPast {
+                current: self.epoch,
+                requested: epoch,
+            });
+        }
+        if epoch - self.epoch > MAX_ADVANCE_STEPS {
+            return Err(RatchetError::TooFarAhead {
+                current: self.epoch,
+                requested: epoch,
+            });
+        }
+        while self.epoch < epoch {
+            self.advance()?;
+        }
+        Ok(&self.token)
+    }
+}
+
+/// Errors returned by [`SrtRatchet`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RatchetError {
+    /// The requested epoch precedes the ratchet's current epoch.
+    EpochInPast {
+        /// Current ratchet epoch.
+        current: u64,
+        /// Epoch that was requested.
+        requested: u64,
+    },
+    /// The requested epoch is more than [`MAX_ADVANCE_STEPS`] ahead.
+    TooFarAhead {
+        /// Current ratchet epoch.
+        current: u64,
+        /// Epoch that was requested.
+        requested: u64,
+    },
+    /// The epoch counter is at `u64::MAX`.
+    EpochOverflow,
+}
+
+impl fmt::Display for RatchetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EpochInPast { current, requested } => write!(
+                f,
+                "cannot ratchet back to epoch {requested} from epoch {current}"
+            ),
+            Self::TooFarAhead { current, requested } => write!(
+                f,
+                "epoch {requested} is more than {MAX_ADVANCE_STEPS} steps ahead of epoch {current}"
+            ),
+            Self::EpochOverflow => write!(f, "ratchet epoch counter overflowed"),
+        }
+    }
+}
+
+impl std::error::Error for RatchetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratchet_is_deterministic_and_forward_only() {
+        let srt = SemanticRendezvousToken::from_bytes([3u8; 32]);
+        let mut alice = SrtRatchet::new(srt.clone(), 10);
+        let mut bob = SrtRatchet::new(srt.clone(), 10);
+
+        alice.advance().expect("advance");
+        alice.advance().expect("advance");
+        let token = bob.advance_to(12).expect("forward").clone();
+        assert_eq!(alice, bob);
+        assert_eq!(token, srt.ratchet().ratchet());
+        assert_ne!(token, srt);
+        assert_eq!(
+            bob.advance_to(11),
+            Err(RatchetError::EpochInPast {
+                current: 12,
+                requested: 11
+            })
+        );
+    }
+
+    #[test]
+    fn ratchet_bounds_steps_and_epoch() {
+        let srt = SemanticRendezvousToken::from_bytes([3u8; 32]);
+        let mut ratchet = SrtRatchet::new(srt.clone(), 0);
+        assert_eq!(
+            ratchet.advance_to(u64::MAX),
+            Err(RatchetError::TooFarAhead {
+                current: 0,
+                requested: u64::MAX
+            })
+        );
+        assert_eq!(ratchet.epoch(), 0);
+
+        let mut last = SrtRatchet::new(srt.clone(), u64::MAX);
+        assert_eq!(last.advance(), Err(RatchetError::EpochOverflow));
+        assert_eq!(last, SrtRatchet::new(srt, u64::MAX));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn ratchet_state_round_trips() {
+        let mut ratchet = SrtRatchet::new(SemanticRendezvousToken::from_bytes([9u8; 32]), 0);
+        ratchet.advance().expect("advance");
+        let json = serde_json::to_string(&ratchet).expect("serialize");
+        let restored: SrtRatchet = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(restored, ratchet);
+    }
+}
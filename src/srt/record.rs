@@ -0,0 +1,215 @@
+//! Validity periods and revocation for stored SRTs.
+//!
+//! A token that leaks has to be retired on every device that holds it. An
+//! [`SrtRecord`] wraps a token with an optional validity window and a
+//! revocation flag, and hands the token out only while it is usable.
+//! Timestamps are Unix seconds, as for [`TimeOracle`](super::oracle::TimeOracle).
+//!
+//! Records implement [`SrtProvider`], so a matcher or the CLI deriving
+//! targets through a record refuses expired or revoked tokens.
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::provider::{ProviderError, SrtProvider};
+use super::{DerivationAlgorithm, SemanticRendezvousToken};
+
+/// An SRT with validity metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SrtRecord {
+    /// The wrapped token.
+    token: SemanticRendezvousToken,
+    /// First second at which the token is valid, if limited.
+    #[cfg_attr(feature = "serde", serde(default))]
+    not_before: Option<u64>,
+    /// Last second at which the token is valid, if limited.
+    #[cfg_attr(feature = "serde", serde(default))]
+    not_after: Option<u64>,
+    /// Whether the token has been revoked.
+    #[cfg_attr(feature = "serde", serde(default))]
+    revoked: bool,
+}
+
+impl SrtRecord {
+    /// Wrap a token that is valid indefinitely.
+    pub fn new(token: SemanticRendezvousToken) -> Self {
+        Self {
+            token,
+            not_before: None,
+            not_after: None,
+            revoked: false,
+        }
+    }
+
+    /// Return this record valid from `unix_secs` onwards.
+    pub fn with_not_before(mut self, unix_secs: u64) -> Self {
+        self.not_before = Some(unix_secs);
+        self
+    }
+
+    /// Return this record valid up to and including `unix_secs`.
+    pub fn with_not_after(mut self, unix_secs: u64) -> Self {
+        self.not_after = Some(unix_secs);
+        self
+    }
+
+    /// Mark the token as revoked. Revocation cannot be undone.
+    pub fn revoke(&mut self) {
+        self.revoked = true;
+    }
+
+    /// Whether the token has been revoked.
+    pub fn is_revoked(&self) -> bool {
+        self.revoked
+    }
+
+    /// Start of the validity window, if any.
+    pub fn not_before(&self) -> Option<u64> {
+        self.not_before
+    }
+
+    /// End of the validity window, if any.
+    pub fn not_after(&self) -> Option<u64> {
+        self.not_after
+    }
+
+    /// Check whether the token is usable at `now` (Unix seconds).
+    pub fn validate_at(&self, now: u64) -> Result<(), RecordError> {
+        if self.revoked {
+            return Err(RecordError::Revoked);
+        }
+        if let Some(not_before) = self.not_before.filter(|start| now < *start) {
+            return Err(RecordError::NotYetValid { not_before, now });
+        }
+        if let Some(not_after) = self.not_after.filter(|end| now > *end) {
+            return Err(RecordError::Expired { not_after, now });
+        }
+        Ok(())
+    }
+
+    /// The token, if it is usable at `now` (Unix seconds).
+    pub fn token_at(&self, now: u64) -> Result<&SemanticRendezvousToken, RecordError> {
+        self.validate_at(now)?;
+        Ok(&self.token)
+    }
+
+    /// The token, if it is usable according to the system clock.
+    pub fn token_now(&self) -> Result<&SemanticRendezvousToken, RecordError> {
+        self.token_at(unix_now())
+    }
+}
+
+impl SrtProvider for SrtRecord {
+    fn digest(
+        &self,
+        algorithm: DerivationAlgorithm,
+        message: &[u8],
+    ) -> Result<[u8; 32], ProviderError> {
+        let token = self
+            .token_now()
+            .map_err(|err| ProviderError::Unavailable(err.to_string()))?;
+        token.digest(algorithm, message)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// Reasons an [`SrtRecord`] refuses to release its token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    /// The validity window has not started.
+    NotYetValid {
+        /// Start of the validity window.
+        not_before: u64,
+        /// Time of the check.
+        now: u64,
+    },
+    /// The validity window has ended.
+    Expired {
+        /// End of the validity window.
+        not_after: u64,
+        /// Time of the check.
+        now: u64,
+    },
+    /// The token was revoked.
+    Revoked,
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotYetValid { not_before, now } => {
+                write!(f, "SRT is not valid until {not_before} (now {now})")
+            }
+            Self::Expired { not_after, now } => {
+                write!(f, "SRT expired at {not_after} (now {now})")
+            }
+            Self::Revoked => write!(f, "SRT has been revoked"),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_releases_token_only_while_valid() {
+        let token = SemanticRendezvousToken::from_bytes([1u8; 32]);
+        let mut record = SrtRecord::new(token.clone())
+            .with_not_before(100)
+            .with_not_after(200);
+        assert_eq!(record.token_at(100), Ok(&token));
+        assert_eq!(record.token_at(200), Ok(&token));
+        assert_eq!(
+            record.validate_at(99),
+            Err(RecordError::NotYetValid {
+                not_before: 100,
+                now: 99
+            })
+        );
+        assert_eq!(
+            record.validate_at(201),
+            Err(RecordError::Expired {
+                not_after: 200,
+                now: 201
+            })
+        );
+        assert!(matches!(
+            record.derive_pattern(b"salt"),
+            Err(ProviderError::Unavailable(_))
+        ));
+
+        record.revoke();
+        assert_eq!(record.validate_at(150), Err(RecordError::Revoked));
+
+        let open = SrtRecord::new(token.clone());
+        assert_eq!(
+            open.derive_pattern(b"salt"),
+            Ok(crate::srt::pattern_from_srt(&token, b"salt"))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn record_fields_default_when_missing() {
+        let json = format!("{{\"token\": \"{}\"}}", "00".repeat(32));
+        let record: SrtRecord = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(
+            record,
+            SrtRecord::new(SemanticRendezvousToken::from_bytes([0u8; 32]))
+        );
+    }
+}
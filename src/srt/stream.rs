@@ -0,0 +1,109 @@
+//! Lazy derivation of successive targets.
+//!
+//! Long sessions that move the target every few seconds derive thousands of
+//! patterns per hour from the same SRT and salt. [`TargetStream`] keys the
+//! HMAC once, absorbs the salt once, and clones that state for each counter,
+//! so each step costs a single HMAC finalization instead of a full re-key.
+
+use std::fmt;
+use std::iter::FusedIterator;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::{pattern_from_digest, SemanticRendezvousToken, SEQUENCE_DOMAIN};
+use crate::pattern::SubmodalityPattern;
+
+/// Iterator over the targets for successive counters.
+///
+/// The target for counter `i` equals `pattern_from_srt(srt,
+/// &sequence_salt(salt, i))`, so the stream yields the same patterns as
+/// [`pattern_sequence_from_srt`](super::pattern_sequence_from_srt) without a
+/// fixed length. The stream ends after counter `u32::MAX`.
+#[derive(Clone)]
+pub struct TargetStream {
+    /// HMAC state keyed with the SRT, with the domain and length-prefixed
+    /// salt already absorbed.
+    prefix: Hmac<Sha256>,
+    /// Next counter to yield, or `None` once exhausted.
+    next: Option<u32>,
+}
+
+impl TargetStream {
+    /// Start a stream at counter 0.
+    pub fn new(srt: &SemanticRendezvousToken, salt: &[u8]) -> Self {
+        Self::starting_at(srt, salt, 0)
+    }
+
+    /// Start a stream at `counter`, e.g. the current epoch of a resumed
+    /// session.
+    pub fn starting_at(srt: &SemanticRendezvousToken, salt: &[u8], counter: u32) -> Self {
+        let mut prefix =
+            Hmac::<Sha256>::new_from_slice(srt.as_bytes()).expect("HMAC can take a 32-byte key");
+        prefix.update(SEQUENCE_DOMAIN);
+        prefix.update(&(salt.len() as u64).to_be_bytes());
+        prefix.update(salt);
+        Self {
+            prefix,
+            next: Some(counter),
+        }
+    }
+
+    /// Counter of the next target, or `None` once the stream is exhausted.
+    pub fn position(&self) -> Option<u32> {
+        self.next
+    }
+
+    /// Target for an arbitrary counter, without moving the stream.
+    pub fn target_at(&self, counter: u32) -> SubmodalityPattern {
+        let mut mac = self.prefix.clone();
+        mac.update(&counter.to_be_bytes());
+        pattern_from_digest(&mac.finalize().into_bytes().into())
+    }
+}
+
+impl Iterator for TargetStream {
+    type Item = SubmodalityPattern;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let counter = self.next?;
+        self.next = counter.checked_add(1);
+        Some(self.target_at(counter))
+    }
+}
+
+impl FusedIterator for TargetStream {}
+
+impl fmt::Debug for TargetStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TargetStream")
+            .field("next", &self.next)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::srt::{pattern_from_srt, pattern_sequence_from_srt, sequence_salt};
+
+    #[test]
+    fn stream_matches_sequence_derivation() {
+        let srt = SemanticRendezvousToken::from_bytes([4u8; 32]);
+        let streamed: Vec<_> = TargetStream::new(&srt, b"session").take(5).collect();
+        assert_eq!(streamed, pattern_sequence_from_srt(&srt, b"session", 5));
+        assert_eq!(
+            streamed[2],
+            pattern_from_srt(&srt, &sequence_salt(b"session", 2))
+        );
+
+        let mut resumed = TargetStream::starting_at(&srt, b"session", 3);
+        assert_eq!(resumed.next().as_ref(), Some(&streamed[3]));
+        assert_eq!(resumed.position(), Some(4));
+
+        let mut last = TargetStream::starting_at(&srt, b"session", u32::MAX);
+        assert!(last.next().is_some());
+        assert_eq!(last.next(), None);
+        assert_eq!(last.position(), None);
+    }
+}
@@ -0,0 +1,194 @@
+//! Target files with integrity protection.
+//!
+//! A target written to disk and loaded later can be edited in between, which
+//! would silently move the rendezvous point. An [`EncodedTarget`] stores the
+//! derived pattern with its derivation metadata and an HMAC tag keyed by the
+//! SRT, so only someone holding the token can produce a file that passes
+//! [`EncodedTarget::verify`].
+
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::provider::{ProviderError, SrtProvider};
+use super::{DerivationAlgorithm, DerivedTarget};
+use crate::hex;
+use crate::pattern::SubmodalityPattern;
+use crate::trace::encode_record;
+
+/// Domain separator prefixed to the tagged message.
+pub const TARGET_TAG_DOMAIN: &[u8] = b"phenorv-target-tag-v1|";
+
+/// A derived target with its salt and an SRT-keyed integrity tag.
+///
+/// Serializes as a [`DerivedTarget`] plus hex `salt` and `tag` fields, so
+/// readers that only expect a pattern can still load it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct EncodedTarget {
+    /// The pattern and the algorithm that derived it.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub target: DerivedTarget,
+    /// Hex-encoded salt the target was derived from.
+    pub salt: String,
+    /// Hex-encoded HMAC-SHA256 tag over the algorithm, salt, and pattern.
+    pub tag: String,
+}
+
+impl EncodedTarget {
+    /// Derive the target for `salt` and tag it, both through `provider`.
+    pub fn seal<P: SrtProvider + ?Sized>(
+        provider: &P,
+        salt: &[u8],
+        algorithm: DerivationAlgorithm,
+    ) -> Result<Self, ProviderError> {
+        let target = DerivedTarget {
+            algorithm,
+            pattern: provider.derive_pattern_with(salt, algorithm)?,
+        };
+        let tag = provider.digest(DerivationAlgorithm::HmacSha256, &tag_message(&target, salt))?;
+        Ok(Self {
+            target,
+            salt: hex::encode(salt),
+            tag: hex::encode(&tag),
+        })
+    }
+
+    /// The tagged pattern. Call [`verify`](Self::verify) before trusting it.
+    pub fn pattern(&self) -> &SubmodalityPattern {
+        &self.target.pattern
+    }
+
+    /// Decode the salt bytes, if well formed.
+    pub fn salt_bytes(&self) -> Option<Vec<u8>> {
+        hex::decode(&self.salt).ok()
+    }
+
+    /// Check the tag against the SRT held by `provider`.
+    ///
+    /// Fails with [`TargetError::Tampered`] if the pattern, algorithm, salt,
+    /// or tag were changed, or the file was sealed under a different SRT.
+    pub fn verify<P: SrtProvider + ?Sized>(&self, provider: &P) -> Result<(), TargetError> {
+        let salt = self.salt_bytes().ok_or(TargetError::Malformed("salt"))?;
+        let tag: [u8; 32] = hex::decode(&self.tag)
+            .ok()
+            .and_then(|tag| tag.try_into().ok())
+            .ok_or(TargetError::Malformed("tag"))?;
+        let expected = provider.digest(
+            DerivationAlgorithm::HmacSha256,
+            &tag_message(&self.target, &salt),
+        )?;
+        let diff = expected
+            .iter()
+            .zip(tag)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        if diff == 0 {
+            Ok(())
+        } else {
+            Err(TargetError::Tampered)
+        }
+    }
+}
+
+/// Domain, algorithm, length-prefixed salt, then the encoded pattern.
+fn tag_message(target: &DerivedTarget, salt: &[u8]) -> Vec<u8> {
+    let algorithm: u8 = match target.algorithm {
+        DerivationAlgorithm::HmacSha256 => 0,
+        DerivationAlgorithm::Blake3Keyed => 1,
+        DerivationAlgorithm::HmacSha3_256 => 2,
+    };
+    let len = u32::try_from(salt.len()).expect("salt fits in u32");
+    let mut message = TARGET_TAG_DOMAIN.to_vec();
+    message.push(algorithm);
+    message.extend_from_slice(&len.to_be_bytes());
+    message.extend_from_slice(salt);
+    message.extend_from_slice(&encode_record(&target.pattern));
+    message
+}
+
+/// Errors returned when verifying an [`EncodedTarget`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetError {
+    /// The named hex field could not be decoded.
+    Malformed(&'static str),
+    /// The tag does not match the contents under this SRT.
+    Tampered,
+    /// The SRT provider failed.
+    Provider(ProviderError),
+}
+
+impl From<ProviderError> for TargetError {
+    fn from(err: ProviderError) -> Self {
+        Self::Provider(err)
+    }
+}
+
+impl fmt::Display for TargetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(field) => write!(f, "target file has a malformed {field}"),
+            Self::Tampered => write!(
+                f,
+                "target file failed verification: modified or sealed with a different SRT"
+            ),
+            Self::Provider(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for TargetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::srt::SemanticRendezvousToken;
+
+    #[test]
+    fn verify_detects_tampering() {
+        let srt = SemanticRendezvousToken::from_bytes([6u8; 32]);
+        let sealed =
+            EncodedTarget::seal(&srt, b"oracle-state", DerivationAlgorithm::Blake3Keyed).unwrap();
+        assert_eq!(sealed.verify(&srt), Ok(()));
+        assert_eq!(
+            sealed.pattern(),
+            &crate::srt::pattern_from_srt_with(
+                &srt,
+                b"oracle-state",
+                DerivationAlgorithm::Blake3Keyed
+            )
+        );
+
+        let mut moved = sealed.clone();
+        moved.target.pattern.tempo += 1.0;
+        assert_eq!(moved.verify(&srt), Err(TargetError::Tampered));
+
+        let mut resalted = sealed.clone();
+        resalted.salt = hex::encode(b"other-state");
+        assert_eq!(resalted.verify(&srt), Err(TargetError::Tampered));
+
+        let mut realgo = sealed.clone();
+        realgo.target.algorithm = DerivationAlgorithm::HmacSha256;
+        assert_eq!(realgo.verify(&srt), Err(TargetError::Tampered));
+
+        let other = SemanticRendezvousToken::from_bytes([7u8; 32]);
+        assert_eq!(sealed.verify(&other), Err(TargetError::Tampered));
+
+        let mut truncated = sealed;
+        truncated.tag.truncate(10);
+        assert_eq!(truncated.verify(&srt), Err(TargetError::Malformed("tag")));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn encoded_target_reads_as_a_pattern() {
+        let srt = SemanticRendezvousToken::from_bytes([6u8; 32]);
+        let sealed = EncodedTarget::seal(&srt, b"salt", DerivationAlgorithm::HmacSha256).unwrap();
+        let json = serde_json::to_string(&sealed).expect("serialize");
+        let restored: EncodedTarget = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(restored, sealed);
+        let pattern: SubmodalityPattern = serde_json::from_str(&json).expect("pattern");
+        assert_eq!(&pattern, sealed.pattern());
+    }
+}
@@ -0,0 +1,99 @@
+//! Canonical test vectors for SRT target derivation.
+//!
+//! Ports of the protocol to other languages verify conformance by deriving
+//! the same pattern from each vector's SRT and salt. [`generate`] produces a
+//! deterministic set of [`DerivationVector`]s from a seed, and [`bundle`]
+//! wraps it in a [`VectorBundle`] in the format of the files under
+//! `vectors/`, so the conformance harness checks it like any other bundle.
+//!
+//! Seeded inputs come from SHA-256 rather than a library RNG, so another
+//! implementation can regenerate the inputs for a seed without depending on
+//! this crate: vector `i` uses `SHA-256(domain || seed || i || 0)` as its SRT
+//! and the first `b % 33` bytes of `SHA-256(domain || seed || i || 1)` as its
+//! salt, where `b` is the first byte of that digest, `seed` is a big-endian
+//! `u64`, and `i` a big-endian `u32`.
+
+use sha2::{Digest, Sha256};
+
+use super::{pattern_from_srt, SemanticRendezvousToken};
+use crate::conformance::{DerivationVector, VectorBundle, DEFAULT_TOLERANCE};
+use crate::hex;
+
+/// Domain separator for seeded vector inputs.
+pub const TEST_VECTOR_DOMAIN: &[u8] = b"phenorv-test-vectors-v1";
+
+/// Number of seeded vectors [`generate`] appends to the fixed cases.
+pub const SEEDED_VECTORS: u32 = 16;
+
+/// Seed of the bundle shipped as `vectors/generated-seed-0.json`.
+pub const DEFAULT_SEED: u64 = 0;
+
+/// Build a vector by deriving the expected pattern for `srt` and `salt`.
+pub fn derivation_vector(srt: &SemanticRendezvousToken, salt: &[u8]) -> DerivationVector {
+    DerivationVector {
+        srt: srt.to_string(),
+        salt: hex::encode(salt),
+        expected: pattern_from_srt(srt, salt),
+    }
+}
+
+/// Generate the vector set for `seed`.
+///
+/// The set starts with fixed edge cases (all-zero and all-one tokens, the
+/// counting token with short text salts) that are the same for every seed,
+/// followed by [`SEEDED_VECTORS`] vectors with seeded inputs.
+pub fn generate(seed: u64) -> Vec<DerivationVector> {
+    let counting = SemanticRendezvousToken::from_bytes(std::array::from_fn(|i| i as u8));
+    let mut vectors = vec![
+        derivation_vector(&SemanticRendezvousToken::from_bytes([0u8; 32]), b""),
+        derivation_vector(&SemanticRendezvousToken::from_bytes([0xff; 32]), b""),
+        derivation_vector(&counting, b"alpha"),
+        derivation_vector(&counting, b"beta"),
+    ];
+    vectors.extend((0..SEEDED_VECTORS).map(|index| {
+        let srt = SemanticRendezvousToken::from_bytes(seeded_digest(seed, index, 0));
+        let salt = seeded_digest(seed, index, 1);
+        let len = usize::from(salt[0]) % (salt.len() + 1);
+        derivation_vector(&srt, &salt[..len])
+    }));
+    vectors
+}
+
+/// Wrap [`generate`]`(seed)` in a bundle named `generated-seed-<seed>`.
+///
+/// Serialize it with `serde_json::to_string_pretty` to get the file shipped
+/// in `vectors/`; the output is byte-stable for a given seed.
+pub fn bundle(seed: u64) -> VectorBundle {
+    VectorBundle {
+        name: format!("generated-seed-{seed}"),
+        tolerance: DEFAULT_TOLERANCE,
+        derivation: generate(seed),
+        normalization: Vec::new(),
+        distance: Vec::new(),
+        matching: Vec::new(),
+    }
+}
+
+fn seeded_digest(seed: u64, index: u32, role: u8) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(TEST_VECTOR_DOMAIN);
+    hasher.update(seed.to_be_bytes());
+    hasher.update(index.to_be_bytes());
+    hasher.update([role]);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generation_is_deterministic_per_seed() {
+        let vectors = generate(DEFAULT_SEED);
+        assert_eq!(vectors.len(), 4 + SEEDED_VECTORS as usize);
+        assert_eq!(vectors, generate(DEFAULT_SEED));
+        assert_ne!(vectors, generate(DEFAULT_SEED + 1));
+        assert_eq!(vectors[..4], generate(DEFAULT_SEED + 1)[..4]);
+        assert!(vectors.iter().all(|vector| vector.salt.len() <= 64));
+    }
+}
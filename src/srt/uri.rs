@@ -0,0 +1,309 @@
+//! `phenorv://` links carrying an SRT and rendezvous parameters.
+//!
+//! A single link (or the QR code of one) can hand a peer everything needed
+//! to rendezvous:
+//!
+//! ```text
+//! phenorv://v1/srt1...?salt=oracle-state&epsilon=0.1&window=3&context=com.example.meetup
+//! ```
+//!
+//! The path holds the protocol version and the bech32m-encoded token (see
+//! [`SemanticRendezvousToken::to_bech32`]). All query parameters are
+//! optional: `salt` is percent-encoded bytes, `epsilon` and `window` fill a
+//! [`MatchingConfig`], and `context` is the application label for
+//! [`context_salt`](super::context_salt). Unknown or repeated parameters are
+//! rejected so that typos do not silently change the rendezvous.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::{SemanticRendezvousToken, SrtParseError};
+use crate::matching::MatchingConfig;
+
+/// URI scheme of rendezvous links.
+pub const URI_SCHEME: &str = "phenorv";
+
+/// Link format version written by this crate.
+pub const URI_VERSION: &str = "v1";
+
+/// A parsed `phenorv://` link.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RendezvousUri {
+    /// The shared token.
+    pub token: SemanticRendezvousToken,
+    /// Oracle-state salt, if the link fixes one.
+    pub salt: Option<Vec<u8>>,
+    /// Matching threshold, if the link fixes one.
+    pub epsilon: Option<f32>,
+    /// Matching window size, if the link fixes one.
+    pub window_size: Option<usize>,
+    /// Application context label, if any.
+    pub context: Option<String>,
+}
+
+impl RendezvousUri {
+    /// A link carrying only `token`.
+    pub fn new(token: SemanticRendezvousToken) -> Self {
+        Self {
+            token,
+            salt: None,
+            epsilon: None,
+            window_size: None,
+            context: None,
+        }
+    }
+
+    /// Matching configuration, if the link sets both `epsilon` and `window`.
+    pub fn matching_config(&self) -> Option<MatchingConfig> {
+        Some(MatchingConfig::new(self.epsilon?, self.window_size?))
+    }
+}
+
+impl fmt::Display for RendezvousUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{URI_SCHEME}://{URI_VERSION}/{}", self.token.to_bech32())?;
+        let mut params = Vec::new();
+        if let Some(salt) = &self.salt {
+            params.push(format!("salt={}", percent_encode(salt)));
+        }
+        if let Some(epsilon) = self.epsilon {
+            params.push(format!("epsilon={epsilon}"));
+        }
+        if let Some(window_size) = self.window_size {
+            params.push(format!("window={window_size}"));
+        }
+        if let Some(context) = &self.context {
+            params.push(format!("context={}", percent_encode(context.as_bytes())));
+        }
+        if !params.is_empty() {
+            write!(f, "?{}", params.join("&"))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RendezvousUri {
+    type Err = UriError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (scheme, rest) = s.split_once("://").ok_or(UriError::MissingScheme)?;
+        if !scheme.eq_ignore_ascii_case(URI_SCHEME) {
+            return Err(UriError::WrongScheme(scheme.to_string()));
+        }
+        let (path, query) = match rest.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (rest, None),
+        };
+        let (version, token) = path.split_once('/').unwrap_or((path, ""));
+        if !version.eq_ignore_ascii_case(URI_VERSION) {
+            return Err(UriError::UnsupportedVersion(version.to_string()));
+        }
+        let token = token.trim_end_matches('/');
+        if token.is_empty() {
+            return Err(UriError::MissingToken);
+        }
+        let mut uri = Self::new(SemanticRendezvousToken::from_bech32(token)?);
+
+        for pair in query.unwrap_or_default().split('&') {
+            if pair.is_empty() {
+                continue;
+            }
+            let (name, raw) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(raw).ok_or_else(|| UriError::InvalidParameter {
+                name: name.to_string(),
+                reason: "invalid percent-encoding".to_string(),
+            })?;
+            let invalid = |reason: &str| UriError::InvalidParameter {
+                name: name.to_string(),
+                reason: reason.to_string(),
+            };
+            let text = || String::from_utf8(value.clone()).map_err(|_| invalid("not UTF-8"));
+            let duplicate = match name {
+                "salt" => uri.salt.replace(value.clone()).is_some(),
+                "epsilon" => {
+                    let epsilon = text()?
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|epsilon| epsilon.is_finite() && *epsilon >= 0.0)
+                        .ok_or_else(|| invalid("expected a finite non-negative number"))?;
+                    uri.epsilon.replace(epsilon).is_some()
+                }
+                "window" => {
+                    let window = text()?
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|window| *window > 0)
+                        .ok_or_else(|| invalid("expected a positive integer"))?;
+                    uri.window_size.replace(window).is_some()
+                }
+                "context" => uri.context.replace(text()?).is_some(),
+                _ => return Err(UriError::UnknownParameter(name.to_string())),
+            };
+            if duplicate {
+                return Err(UriError::DuplicateParameter(name.to_string()));
+            }
+        }
+        Ok(uri)
+    }
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters.
+fn percent_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+fn percent_decode(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = text.get(i + 1..i + 3)?;
+                out.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    Some(out)
+}
+
+/// Errors returned when parsing a `phenorv://` link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UriError {
+    /// The text has no `scheme://` prefix.
+    MissingScheme,
+    /// The scheme is not `phenorv`.
+    WrongScheme(String),
+    /// The link version is not supported by this implementation.
+    UnsupportedVersion(String),
+    /// The path has no token after the version.
+    MissingToken,
+    /// The token is not a valid bech32 SRT.
+    InvalidToken(SrtParseError),
+    /// A query parameter is not recognized.
+    UnknownParameter(String),
+    /// A query parameter appears more than once.
+    DuplicateParameter(String),
+    /// A query parameter has an invalid value.
+    InvalidParameter {
+        /// Parameter name.
+        name: String,
+        /// Why the value was rejected.
+        reason: String,
+    },
+}
+
+impl From<SrtParseError> for UriError {
+    fn from(err: SrtParseError) -> Self {
+        Self::InvalidToken(err)
+    }
+}
+
+impl fmt::Display for UriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingScheme => write!(f, "expected a link starting with '{URI_SCHEME}://'"),
+            Self::WrongScheme(scheme) => {
+                write!(f, "unexpected scheme '{scheme}', expected '{URI_SCHEME}'")
+            }
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported link version '{version}', expected '{URI_VERSION}'"
+            ),
+            Self::MissingToken => write!(f, "link has no token after '{URI_VERSION}/'"),
+            Self::InvalidToken(err) => write!(f, "invalid token in link: {err}"),
+            Self::UnknownParameter(name) => write!(f, "unknown link parameter '{name}'"),
+            Self::DuplicateParameter(name) => write!(f, "link parameter '{name}' is repeated"),
+            Self::InvalidParameter { name, reason } => {
+                write!(f, "invalid link parameter '{name}': {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UriError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token() -> SemanticRendezvousToken {
+        SemanticRendezvousToken::from_bytes(std::array::from_fn(|i| i as u8))
+    }
+
+    #[test]
+    fn uri_round_trips_with_parameters() {
+        let uri = RendezvousUri {
+            token: token(),
+            salt: Some(b"oracle state/1".to_vec()),
+            epsilon: Some(0.1),
+            window_size: Some(3),
+            context: Some("com.example.meetup".to_string()),
+        };
+        let text = uri.to_string();
+        assert!(text.starts_with("phenorv://v1/srt1"));
+        assert!(text.contains("?salt=oracle%20state%2F1&epsilon=0.1&window=3&context="));
+        assert_eq!(text.parse::<RendezvousUri>(), Ok(uri.clone()));
+        assert_eq!(uri.matching_config(), Some(MatchingConfig::new(0.1, 3)));
+
+        let bare = RendezvousUri::new(token());
+        assert_eq!(bare.to_string().parse::<RendezvousUri>(), Ok(bare.clone()));
+        assert_eq!(bare.matching_config(), None);
+        let shouted = format!("PHENORV://V1/{}", token().to_bech32().to_uppercase());
+        assert_eq!(shouted.parse::<RendezvousUri>(), Ok(bare));
+    }
+
+    #[test]
+    fn uri_errors_name_the_problem() {
+        let bech = token().to_bech32();
+        let parse = |text: String| text.parse::<RendezvousUri>();
+        assert_eq!(
+            parse(format!("https://v1/{bech}")),
+            Err(UriError::WrongScheme("https".to_string()))
+        );
+        assert_eq!(
+            parse(format!("phenorv://v9/{bech}")),
+            Err(UriError::UnsupportedVersion("v9".to_string()))
+        );
+        assert_eq!(
+            parse("phenorv://v1/".to_string()),
+            Err(UriError::MissingToken)
+        );
+        assert!(matches!(
+            parse("phenorv://v1/srt1qqqq".to_string()),
+            Err(UriError::InvalidToken(_))
+        ));
+        assert_eq!(
+            parse(format!("phenorv://v1/{bech}?epsln=0.1")),
+            Err(UriError::UnknownParameter("epsln".to_string()))
+        );
+        assert_eq!(
+            parse(format!("phenorv://v1/{bech}?window=3&window=4")),
+            Err(UriError::DuplicateParameter("window".to_string()))
+        );
+        let err = parse(format!("phenorv://v1/{bech}?epsilon=-1")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid link parameter 'epsilon': expected a finite non-negative number"
+        );
+        assert!(parse(format!("phenorv://v1/{bech}?salt=%zz")).is_err());
+    }
+}
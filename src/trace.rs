@@ -0,0 +1,310 @@
+//! Compact binary pattern traces.
+//!
+//! Long field recordings are too large to replay efficiently as JSON lines.
+//! A trace file is an 8-byte header followed by fixed-size records:
+//!
+//! | Offset | Size | Contents                                        |
+//! |--------|------|-------------------------------------------------|
+//! | 0      | 4    | Magic bytes `PRVT`                              |
+//! | 4      | 2    | Format version, little-endian (currently `1`)   |
+//! | 6      | 2    | Record length in bytes, little-endian (`36`)    |
+//! | 8      | 36·n | Records: nine little-endian `f32` raw values    |
+//!
+//! Record values follow `SubmodalityPattern` field order (see
+//! `pattern::DIMENSIONS`). [`TraceRecords`] decodes records straight out
+//! of a byte slice without allocating, and [`BinaryTraceReader`] (feature
+//! `mmap`) memory-maps a file so that slice is backed by the page cache.
+
+use std::fmt;
+use std::io::Write;
+#[cfg(feature = "mmap")]
+use std::path::Path;
+
+use crate::error::Result;
+use crate::pattern::{pattern_from_array, pattern_to_array, SubmodalityPattern};
+
+/// Magic bytes at the start of every trace.
+pub const TRACE_MAGIC: [u8; 4] = *b"PRVT";
+
+/// Trace format version written by this crate.
+pub const TRACE_VERSION: u16 = 1;
+
+/// Length of the trace header in bytes.
+pub const HEADER_LEN: usize = 8;
+
+/// Length of one record in bytes.
+pub const RECORD_LEN: usize = 9 * 4;
+
+/// Encode the trace header.
+fn header() -> [u8; HEADER_LEN] {
+    let mut bytes = [0u8; HEADER_LEN];
+    bytes[..4].copy_from_slice(&TRACE_MAGIC);
+    bytes[4..6].copy_from_slice(&TRACE_VERSION.to_le_bytes());
+    bytes[6..].copy_from_slice(&(RECORD_LEN as u16).to_le_bytes());
+    bytes
+}
+
+/// Encode a single record.
+pub(crate) fn encode_record(pattern: &SubmodalityPattern) -> [u8; RECORD_LEN] {
+    let mut bytes = [0u8; RECORD_LEN];
+    for (chunk, value) in bytes.chunks_exact_mut(4).zip(pattern_to_array(pattern)) {
+        chunk.copy_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+/// Decode a single record; `bytes` must be exactly [`RECORD_LEN`] long.
+fn decode_record(bytes: &[u8]) -> SubmodalityPattern {
+    let mut values = [0.0f32; 9];
+    for (value, chunk) in values.iter_mut().zip(bytes.chunks_exact(4)) {
+        *value = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+    pattern_from_array(values)
+}
+
+/// Zero-copy view over the records of an in-memory trace.
+#[derive(Debug, Clone, Copy)]
+pub struct TraceRecords<'a> {
+    /// Record bytes following the header.
+    records: &'a [u8],
+}
+
+impl<'a> TraceRecords<'a> {
+    /// Validate the header of `bytes` and view the records that follow it.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self> {
+        if bytes.len() < HEADER_LEN || bytes[..4] != TRACE_MAGIC {
+            return Err(TraceError::BadMagic.into());
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != TRACE_VERSION {
+            return Err(TraceError::UnsupportedVersion(version).into());
+        }
+        let record_len = u16::from_le_bytes([bytes[6], bytes[7]]);
+        if usize::from(record_len) != RECORD_LEN {
+            return Err(TraceError::UnsupportedRecordLength(record_len).into());
+        }
+        let records = &bytes[HEADER_LEN..];
+        if !records.len().is_multiple_of(RECORD_LEN) {
+            return Err(TraceError::TruncatedRecord {
+                offset: HEADER_LEN + records.len() / RECORD_LEN * RECORD_LEN,
+            }
+            .into());
+        }
+        Ok(Self { records })
+    }
+
+    /// Number of records.
+    pub fn len(&self) -> usize {
+        self.records.len() / RECORD_LEN
+    }
+
+    /// Whether the trace holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Decode the record at `index`, if present.
+    pub fn get(&self, index: usize) -> Option<SubmodalityPattern> {
+        let start = index.checked_mul(RECORD_LEN)?;
+        let end = start.checked_add(RECORD_LEN)?;
+        self.records.get(start..end).map(decode_record)
+    }
+
+    /// Iterate over decoded records in file order.
+    pub fn iter(&self) -> TraceIter<'a> {
+        TraceIter {
+            chunks: self.records.chunks_exact(RECORD_LEN),
+        }
+    }
+}
+
+impl<'a> IntoIterator for TraceRecords<'a> {
+    type Item = SubmodalityPattern;
+    type IntoIter = TraceIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over decoded trace records.
+#[derive(Debug, Clone)]
+pub struct TraceIter<'a> {
+    chunks: std::slice::ChunksExact<'a, u8>,
+}
+
+impl Iterator for TraceIter<'_> {
+    type Item = SubmodalityPattern;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(decode_record)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl ExactSizeIterator for TraceIter<'_> {}
+
+/// Memory-mapped reader for trace files.
+///
+/// The file is mapped read-only; records are decoded on demand, so replaying
+/// a multi-gigabyte trace touches each page once and never copies the file
+/// into the heap. The file must not be truncated or modified while mapped.
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub struct BinaryTraceReader {
+    /// The mapped file, including the header.
+    map: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl BinaryTraceReader {
+    /// Map a trace file and validate its header.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the map is read-only. Concurrent modification of the file
+        // by another process is documented as unsupported above.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        TraceRecords::parse(&map)?;
+        Ok(Self { map })
+    }
+
+    /// View the mapped records.
+    pub fn records(&self) -> TraceRecords<'_> {
+        TraceRecords::parse(&self.map).expect("header validated in open")
+    }
+
+    /// Number of records.
+    pub fn len(&self) -> usize {
+        self.records().len()
+    }
+
+    /// Whether the trace holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.records().is_empty()
+    }
+
+    /// Iterate over decoded records in file order.
+    pub fn iter(&self) -> TraceIter<'_> {
+        self.records().iter()
+    }
+}
+
+/// Streaming writer for trace files.
+#[derive(Debug)]
+pub struct BinaryTraceWriter<W: Write> {
+    /// Destination for the header and records.
+    writer: W,
+}
+
+impl<W: Write> BinaryTraceWriter<W> {
+    /// Write the trace header and return a writer for records.
+    pub fn new(mut writer: W) -> Result<Self> {
+        writer.write_all(&header())?;
+        Ok(Self { writer })
+    }
+
+    /// Append a record.
+    pub fn write(&mut self, pattern: &SubmodalityPattern) -> Result<()> {
+        self.writer.write_all(&encode_record(pattern))?;
+        Ok(())
+    }
+
+    /// Flush and return the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Errors returned when a trace is malformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceError {
+    /// The data does not start with [`TRACE_MAGIC`].
+    BadMagic,
+    /// The header names a format version this crate cannot read.
+    UnsupportedVersion(u16),
+    /// The header names a record length other than [`RECORD_LEN`].
+    UnsupportedRecordLength(u16),
+    /// The data ends partway through a record.
+    TruncatedRecord {
+        /// Byte offset of the incomplete record.
+        offset: usize,
+    },
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic => write!(f, "not a pattern trace (bad magic bytes)"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported trace format version {version}")
+            }
+            Self::UnsupportedRecordLength(len) => {
+                write!(f, "unsupported trace record length {len}")
+            }
+            Self::TruncatedRecord { offset } => {
+                write!(f, "trace ends inside the record at byte {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    fn sample_trace() -> Vec<u8> {
+        let mut writer = BinaryTraceWriter::new(Vec::new()).expect("header");
+        let mut pattern = SubmodalityPattern::zeros();
+        for step in 0..4 {
+            pattern.tempo = 60.0 + step as f32;
+            writer.write(&pattern).expect("record");
+        }
+        writer.finish().expect("finish")
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let bytes = sample_trace();
+        assert_eq!(bytes.len(), HEADER_LEN + 4 * RECORD_LEN);
+        let records = TraceRecords::parse(&bytes).expect("parse");
+        assert_eq!(records.len(), 4);
+        let tempos: Vec<f32> = records.iter().map(|p| p.tempo).collect();
+        assert_eq!(tempos, vec![60.0, 61.0, 62.0, 63.0]);
+        assert_eq!(records.get(2).map(|p| p.tempo), Some(62.0));
+        assert!(records.get(4).is_none());
+        assert!(records.get(usize::MAX / RECORD_LEN).is_none());
+        assert!(records.get(usize::MAX).is_none());
+    }
+
+    #[test]
+    fn malformed_traces_are_rejected() {
+        let mut bytes = sample_trace();
+        bytes.pop();
+        assert!(matches!(
+            TraceRecords::parse(&bytes),
+            Err(Error::Trace(TraceError::TruncatedRecord { offset })) if offset == HEADER_LEN + 3 * RECORD_LEN
+        ));
+        assert!(matches!(
+            TraceRecords::parse(b"JSON{}\n\n"),
+            Err(Error::Trace(TraceError::BadMagic))
+        ));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn reader_maps_file() {
+        let path = std::env::temp_dir().join(format!("phenorv-trace-{}.bin", std::process::id()));
+        std::fs::write(&path, sample_trace()).expect("write");
+        let reader = BinaryTraceReader::open(&path).expect("open");
+        assert_eq!(reader.len(), 4);
+        assert_eq!(reader.iter().last().map(|p| p.tempo), Some(63.0));
+        std::fs::remove_file(&path).expect("cleanup");
+    }
+}
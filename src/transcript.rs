@@ -0,0 +1,366 @@
+//! Reproducible, signed transcripts of matching sessions.
+//!
+//! A [`SessionTranscript`] captures what is needed to reproduce a matching
+//! session: the crate version, matching config, calibration data, salt
+//! schedule, and per-observation hashes, distances, and decisions. Raw
+//! measurements are not stored; each observation is represented by the
+//! SHA-256 of its binary trace record, so a published transcript can be
+//! checked against a privately held trace without disclosing it.
+//!
+//! [`SessionTranscript::sign`] wraps a transcript and an Ed25519 signature
+//! into a single JSON document suitable for supplementary material.
+//!
+//! The signature does not cover the JSON text, whose float formatting and
+//! key order could change in transit, but a fixed binary encoding of the
+//! transcript's fields in declaration order:
+//!
+//! ```text
+//! label "phenorv-transcript-v2|" | format_version (u32) | crate_version
+//! | epsilon (f32 bits) | window_size (u64) | weights (9 × f32 bits)
+//! | calibration: 0, or 1 and compact JSON with object keys sorted
+//! | salt count (u64), then per salt: epoch (u64) | salt
+//! | observation count (u64), then per observation:
+//!   index (u64) | hash | distance (f32 bits) | matched (u8)
+//! ```
+//!
+//! Integers are big-endian and strings are a `u64` byte length followed by
+//! UTF-8. JSON cannot carry NaN or infinities, so transcripts holding them
+//! are refused at signing time rather than failing verification later.
+
+use std::fmt;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::Result;
+use crate::hex;
+use crate::matching::{euclidean_distance, Matcher, MatchingConfig};
+use crate::pattern::SubmodalityPattern;
+use crate::trace::encode_record;
+
+/// Transcript format version written by this crate.
+pub const TRANSCRIPT_FORMAT_VERSION: u32 = 2;
+
+/// Domain-separation label prefixed to the signed message.
+const SIGNATURE_LABEL: &[u8] = b"phenorv-transcript-v2|";
+
+/// A salt in effect from `epoch` onward.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaltScheduleEntry {
+    /// First epoch the salt applies to.
+    pub epoch: u64,
+    /// Hex-encoded salt.
+    pub salt: String,
+}
+
+/// A single observation in a transcript.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObservationRecord {
+    /// Position of the observation in the measured stream.
+    pub index: u64,
+    /// Hex-encoded SHA-256 of the observation's binary trace record.
+    pub hash: String,
+    /// Distance to the target in normalized space.
+    pub distance: f32,
+    /// Whether the matcher reported a stable match after this observation.
+    pub matched: bool,
+}
+
+/// Everything needed to reproduce a matching session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTranscript {
+    /// Transcript format version.
+    pub format_version: u32,
+    /// Version of this crate that produced the transcript.
+    pub crate_version: String,
+    /// Matching configuration used for the session.
+    pub config: MatchingConfig,
+    /// Sensor calibration data, if any, in whatever form the client uses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<serde_json::Value>,
+    /// Salts used during the session, ordered by starting epoch.
+    pub salt_schedule: Vec<SaltScheduleEntry>,
+    /// Observations in stream order.
+    pub observations: Vec<ObservationRecord>,
+}
+
+impl SessionTranscript {
+    /// Start an empty transcript for `config`.
+    pub fn new(config: MatchingConfig) -> Self {
+        Self {
+            format_version: TRANSCRIPT_FORMAT_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            config,
+            calibration: None,
+            salt_schedule: Vec::new(),
+            observations: Vec::new(),
+        }
+    }
+
+    /// Canonical bytes covered by the signature (see the module docs).
+    fn signed_message(&self) -> Result<Vec<u8>> {
+        fn put_str(message: &mut Vec<u8>, text: &str) {
+            message.extend_from_slice(&(text.len() as u64).to_be_bytes());
+            message.extend_from_slice(text.as_bytes());
+        }
+
+        let mut message = SIGNATURE_LABEL.to_vec();
+        message.extend_from_slice(&self.format_version.to_be_bytes());
+        put_str(&mut message, &self.crate_version);
+        message.extend_from_slice(&self.config.epsilon.to_bits().to_be_bytes());
+        message.extend_from_slice(&(self.config.window_size as u64).to_be_bytes());
+        for weight in self.config.weights.values() {
+            message.extend_from_slice(&weight.to_bits().to_be_bytes());
+        }
+        match &self.calibration {
+            // `serde_json::Map` keeps keys sorted, so this is canonical.
+            Some(calibration) => {
+                message.push(1);
+                put_str(&mut message, &serde_json::to_string(calibration)?);
+            }
+            None => message.push(0),
+        }
+        message.extend_from_slice(&(self.salt_schedule.len() as u64).to_be_bytes());
+        for entry in &self.salt_schedule {
+            message.extend_from_slice(&entry.epoch.to_be_bytes());
+            put_str(&mut message, &entry.salt);
+        }
+        message.extend_from_slice(&(self.observations.len() as u64).to_be_bytes());
+        for observation in &self.observations {
+            message.extend_from_slice(&observation.index.to_be_bytes());
+            put_str(&mut message, &observation.hash);
+            message.extend_from_slice(&observation.distance.to_bits().to_be_bytes());
+            message.push(u8::from(observation.matched));
+        }
+        Ok(message)
+    }
+
+    /// Fail if a float field is NaN or infinite, which JSON cannot carry.
+    fn check_finite(&self) -> Result<(), TranscriptError> {
+        if !self.config.epsilon.is_finite() {
+            return Err(TranscriptError::NonFinite("config.epsilon"));
+        }
+        if !self.config.weights.values().iter().all(|w| w.is_finite()) {
+            return Err(TranscriptError::NonFinite("config.weights"));
+        }
+        if !self.observations.iter().all(|o| o.distance.is_finite()) {
+            return Err(TranscriptError::NonFinite("observations.distance"));
+        }
+        Ok(())
+    }
+
+    /// Sign the transcript with an Ed25519 key.
+    ///
+    /// Fails with [`TranscriptError::NonFinite`] if a recorded value is NaN
+    /// or infinite, since the signed JSON could not be read back.
+    pub fn sign(self, key: &SigningKey) -> Result<SignedTranscript> {
+        self.check_finite()?;
+        let signature = key.sign(&self.signed_message()?);
+        Ok(SignedTranscript {
+            public_key: hex::encode(key.verifying_key().as_bytes()),
+            signature: hex::encode(&signature.to_bytes()),
+            transcript: self,
+        })
+    }
+}
+
+/// Hash an observation the way transcripts record it.
+pub fn observation_hash(pattern: &SubmodalityPattern) -> [u8; 32] {
+    Sha256::digest(encode_record(pattern)).into()
+}
+
+/// Records a transcript while matching a measured stream.
+#[derive(Debug, Clone)]
+pub struct TranscriptRecorder {
+    /// Matcher producing the recorded decisions.
+    matcher: Matcher,
+    /// Transcript being built.
+    transcript: SessionTranscript,
+}
+
+impl TranscriptRecorder {
+    /// Start recording a session with `config`.
+    pub fn new(config: MatchingConfig) -> Self {
+        Self {
+            matcher: Matcher::new(config),
+            transcript: SessionTranscript::new(config),
+        }
+    }
+
+    /// Attach calibration data to the transcript.
+    pub fn set_calibration(&mut self, calibration: serde_json::Value) {
+        self.transcript.calibration = Some(calibration);
+    }
+
+    /// Record the salt that takes effect at `epoch`.
+    pub fn add_salt(&mut self, epoch: u64, salt: &[u8]) {
+        self.transcript.salt_schedule.push(SaltScheduleEntry {
+            epoch,
+            salt: hex::encode(salt),
+        });
+    }
+
+    /// Observe a measurement, record it, and return the match decision.
+    pub fn observe(&mut self, measured: &SubmodalityPattern, target: &SubmodalityPattern) -> bool {
+        let matched = self.matcher.observe(measured, target);
+        let index = self.transcript.observations.len() as u64;
+        self.transcript.observations.push(ObservationRecord {
+            index,
+            hash: hex::encode(&observation_hash(measured)),
+            distance: euclidean_distance(&measured.normalize(), &target.normalize()),
+            matched,
+        });
+        matched
+    }
+
+    /// Finish recording and return the transcript.
+    pub fn finish(self) -> SessionTranscript {
+        self.transcript
+    }
+}
+
+/// A transcript with an Ed25519 signature over its canonical binary encoding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedTranscript {
+    /// The signed transcript.
+    pub transcript: SessionTranscript,
+    /// Hex-encoded Ed25519 public key of the signer.
+    pub public_key: String,
+    /// Hex-encoded Ed25519 signature.
+    pub signature: String,
+}
+
+impl SignedTranscript {
+    /// Check the signature against the embedded public key.
+    ///
+    /// This only proves the transcript is unmodified since it was signed by
+    /// the holder of `public_key`; use [`verify_with`](Self::verify_with) to
+    /// also check who signed it.
+    pub fn verify(&self) -> Result<()> {
+        let key = hex::decode(&self.public_key)
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+            .ok_or(TranscriptError::MalformedKey)?;
+        self.verify_with(&key)
+    }
+
+    /// Check the signature against a trusted public key.
+    pub fn verify_with(&self, key: &VerifyingKey) -> Result<()> {
+        if self.transcript.format_version != TRANSCRIPT_FORMAT_VERSION {
+            return Err(TranscriptError::UnsupportedVersion(self.transcript.format_version).into());
+        }
+        let signature = hex::decode(&self.signature)
+            .ok()
+            .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+            .map(|bytes| Signature::from_bytes(&bytes))
+            .ok_or(TranscriptError::MalformedSignature)?;
+        key.verify(&self.transcript.signed_message()?, &signature)
+            .map_err(|_| TranscriptError::BadSignature.into())
+    }
+}
+
+/// Errors returned when a signed transcript fails verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptError {
+    /// The transcript uses a format version this crate cannot verify.
+    UnsupportedVersion(u32),
+    /// The public key is not a valid hex-encoded Ed25519 key.
+    MalformedKey,
+    /// The signature is not 64 hex-encoded bytes.
+    MalformedSignature,
+    /// The signature does not match the transcript and key.
+    BadSignature,
+    /// A float field is NaN or infinite, so the transcript cannot be signed.
+    NonFinite(&'static str),
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported transcript format version {version}")
+            }
+            Self::MalformedKey => write!(f, "transcript public key is malformed"),
+            Self::MalformedSignature => write!(f, "transcript signature is malformed"),
+            Self::BadSignature => write!(f, "transcript signature does not verify"),
+            Self::NonFinite(field) => write!(f, "transcript field {field} is not finite"),
+        }
+    }
+}
+
+impl std::error::Error for TranscriptError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    fn signed() -> SignedTranscript {
+        let mut recorder = TranscriptRecorder::new(MatchingConfig::new(0.1, 2));
+        recorder.add_salt(0, b"epoch-0");
+        recorder.set_calibration(serde_json::json!({ "brightness_gain": 1.02 }));
+        let target = SubmodalityPattern::zeros();
+        assert!(!recorder.observe(&target, &target));
+        assert!(recorder.observe(&target, &target));
+        recorder
+            .finish()
+            .sign(&SigningKey::from_bytes(&[9u8; 32]))
+            .expect("sign")
+    }
+
+    #[test]
+    fn signed_transcript_round_trips_and_verifies() {
+        let signed = signed();
+        let json = serde_json::to_string(&signed).expect("serialize");
+        let parsed: SignedTranscript = serde_json::from_str(&json).expect("parse");
+        parsed.verify().expect("verify");
+        assert_eq!(parsed.transcript.observations.len(), 2);
+        assert_eq!(
+            parsed.transcript.observations[0].hash,
+            hex::encode(&observation_hash(&SubmodalityPattern::zeros()))
+        );
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let mut signed = signed();
+        signed.transcript.observations[0].matched = true;
+        assert!(matches!(
+            signed.verify(),
+            Err(Error::Transcript(TranscriptError::BadSignature))
+        ));
+
+        let signed = self::signed();
+        let other = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        assert!(signed.verify_with(&other).is_err());
+    }
+
+    #[test]
+    fn signature_ignores_json_formatting_and_rejects_nan() {
+        let signed = signed();
+        let reordered = serde_json::json!({
+            "signature": signed.signature,
+            "public_key": signed.public_key,
+            "transcript": serde_json::to_value(&signed.transcript).expect("value"),
+        });
+        let pretty = serde_json::to_string_pretty(&reordered).expect("serialize");
+        let parsed: SignedTranscript = serde_json::from_str(&pretty).expect("parse");
+        parsed.verify().expect("verify");
+
+        let mut recorder = TranscriptRecorder::new(MatchingConfig::new(0.1, 2));
+        let target = SubmodalityPattern::zeros();
+        let broken = SubmodalityPattern {
+            pitch: f32::NAN,
+            ..target.clone()
+        };
+        recorder.observe(&broken, &target);
+        assert!(matches!(
+            recorder.finish().sign(&SigningKey::from_bytes(&[9u8; 32])),
+            Err(Error::Transcript(TranscriptError::NonFinite(
+                "observations.distance"
+            )))
+        ));
+    }
+}
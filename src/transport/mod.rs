@@ -0,0 +1,80 @@
+//! Transport-facing message types and optional broker integrations.
+//!
+//! The core protocol never sends anything over a network. This module defines
+//! the small messages peers may choose to publish, such as beacon commitments,
+//! and hosts feature-gated adapters for existing messaging infrastructure.
+
+use hmac::{Hmac, Mac};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::hex;
+use crate::srt::SemanticRendezvousToken;
+
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+
+/// Domain-separation label mixed into beacon commitments.
+const BEACON_LABEL: &[u8] = b"phenorv-beacon-v1|";
+
+/// Compute a beacon commitment for an SRT and salt.
+///
+/// The commitment is HMAC-SHA256 keyed by the SRT over a fixed label followed
+/// by `salt`. Peers holding the same SRT and salt compute identical
+/// commitments, while observers without the SRT learn nothing about the
+/// target pattern.
+pub fn beacon_commitment(srt: &SemanticRendezvousToken, salt: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(srt.as_bytes())
+        .expect("HMAC can take a 32-byte key");
+    mac.update(BEACON_LABEL);
+    mac.update(salt);
+    mac.finalize().into_bytes().into()
+}
+
+/// A beacon announcing that a peer is attempting rendezvous in an epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Beacon {
+    /// Epoch index the beacon belongs to.
+    pub epoch: u64,
+    /// Hex-encoded [`beacon_commitment`].
+    pub commitment: String,
+}
+
+impl Beacon {
+    /// Build a beacon for an SRT, salt, and epoch.
+    pub fn new(srt: &SemanticRendezvousToken, salt: &[u8], epoch: u64) -> Self {
+        Self {
+            epoch,
+            commitment: hex::encode(&beacon_commitment(srt, salt)),
+        }
+    }
+
+    /// Decode the commitment bytes, if well formed.
+    pub fn commitment_bytes(&self) -> Option<[u8; 32]> {
+        hex::decode(&self.commitment).ok()?.try_into().ok()
+    }
+
+    /// Whether this beacon was produced from the given SRT and salt.
+    pub fn matches(&self, srt: &SemanticRendezvousToken, salt: &[u8]) -> bool {
+        self.commitment_bytes() == Some(beacon_commitment(srt, salt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beacon_matches_same_srt_and_salt() {
+        let srt = SemanticRendezvousToken::from_bytes([3u8; 32]);
+        let beacon = Beacon::new(&srt, b"epoch-1", 1);
+        assert!(beacon.matches(&srt, b"epoch-1"));
+        assert!(!beacon.matches(&srt, b"epoch-2"));
+
+        let other = SemanticRendezvousToken::from_bytes([4u8; 32]);
+        assert!(!beacon.matches(&other, b"epoch-1"));
+    }
+}
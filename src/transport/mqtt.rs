@@ -0,0 +1,223 @@
+//! MQTT publisher/subscriber adapter.
+//!
+//! Publishes beacons and match events as JSON payloads and subscribes to
+//! peers' beacons through an existing MQTT broker. Topics are laid out as:
+//!
+//! - `<prefix>/beacons/<epoch>` for [`Beacon`] messages
+//! - `<prefix>/events/<client_id>` for [`MatchEvent`] messages
+//!
+//! The adapter uses the blocking `rumqttc` client and does not enable TLS;
+//! brokers reachable only over TLS need a different client configuration.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use rumqttc::{Client, Connection, Event, MqttOptions, Packet, QoS, RecvTimeoutError};
+
+use crate::audit::MatchEvent;
+use crate::transport::Beacon;
+
+/// Connection and topic settings for [`MqttTransport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MqttConfig {
+    /// Broker host name or address.
+    pub host: String,
+    /// Broker port.
+    pub port: u16,
+    /// MQTT client identifier, also used in the event topic.
+    pub client_id: String,
+    /// Prefix shared by all topics.
+    pub topic_prefix: String,
+    /// Keep-alive interval in seconds.
+    pub keep_alive_secs: u64,
+}
+
+impl MqttConfig {
+    /// Create a config with the default `phenorv` topic prefix.
+    pub fn new(host: impl Into<String>, port: u16, client_id: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            client_id: client_id.into(),
+            topic_prefix: "phenorv".to_string(),
+            keep_alive_secs: 30,
+        }
+    }
+
+    /// Topic for beacons in a given epoch.
+    pub fn beacon_topic(&self, epoch: u64) -> String {
+        format!("{}/beacons/{epoch}", self.topic_prefix)
+    }
+
+    /// Wildcard topic matching beacons in every epoch.
+    pub fn beacon_wildcard(&self) -> String {
+        format!("{}/beacons/+", self.topic_prefix)
+    }
+
+    /// Topic this client publishes match events to.
+    pub fn event_topic(&self) -> String {
+        format!("{}/events/{}", self.topic_prefix, self.client_id)
+    }
+
+    fn is_beacon_topic(&self, topic: &str) -> bool {
+        topic
+            .strip_prefix(&self.topic_prefix)
+            .and_then(|rest| rest.strip_prefix("/beacons/"))
+            .is_some_and(|epoch| !epoch.is_empty() && !epoch.contains('/'))
+    }
+}
+
+/// Blocking MQTT transport for beacons and match events.
+pub struct MqttTransport {
+    /// Topic and connection settings.
+    config: MqttConfig,
+    /// Handle used to send requests to the broker.
+    client: Client,
+    /// Event loop driving the connection; must be polled to make progress.
+    connection: Connection,
+}
+
+impl MqttTransport {
+    /// Create a transport for the given broker configuration.
+    ///
+    /// The connection is established lazily while polling
+    /// [`next_beacon`](Self::next_beacon).
+    pub fn new(config: MqttConfig) -> Self {
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(config.keep_alive_secs));
+        let (client, connection) = Client::new(options, 16);
+        Self {
+            config,
+            client,
+            connection,
+        }
+    }
+
+    /// Topic and connection settings.
+    pub fn config(&self) -> &MqttConfig {
+        &self.config
+    }
+
+    /// Publish a beacon to its epoch topic.
+    pub fn publish_beacon(&self, beacon: &Beacon) -> Result<(), MqttError> {
+        let payload = serde_json::to_vec(beacon)?;
+        self.client.publish(
+            self.config.beacon_topic(beacon.epoch),
+            QoS::AtLeastOnce,
+            false,
+            payload,
+        )?;
+        Ok(())
+    }
+
+    /// Publish a match event to this client's event topic.
+    pub fn publish_event(&self, event: &MatchEvent) -> Result<(), MqttError> {
+        let payload = serde_json::to_vec(event)?;
+        self.client
+            .publish(self.config.event_topic(), QoS::AtLeastOnce, false, payload)?;
+        Ok(())
+    }
+
+    /// Subscribe to beacons for one epoch, or for all epochs if `None`.
+    pub fn subscribe_beacons(&self, epoch: Option<u64>) -> Result<(), MqttError> {
+        let topic = match epoch {
+            Some(epoch) => self.config.beacon_topic(epoch),
+            None => self.config.beacon_wildcard(),
+        };
+        self.client.subscribe(topic, QoS::AtLeastOnce)?;
+        Ok(())
+    }
+
+    /// Drive the connection until a peer beacon arrives or `timeout` elapses.
+    ///
+    /// Returns `Ok(None)` once `timeout` has passed since the call, however
+    /// much other traffic arrives meanwhile. Messages on other topics are
+    /// skipped, as are beacon payloads that fail to decode (these are logged
+    /// at warn level). A closed connection is reported as
+    /// [`MqttError::Disconnected`].
+    pub fn next_beacon(&mut self, timeout: Duration) -> Result<Option<Beacon>, MqttError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let event = match self.connection.recv_timeout(remaining) {
+                Ok(event) => event?,
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => return Err(MqttError::Disconnected),
+            };
+            if let Event::Incoming(Packet::Publish(publish)) = event
+                && self.config.is_beacon_topic(&publish.topic)
+            {
+                match serde_json::from_slice(&publish.payload) {
+                    Ok(beacon) => return Ok(Some(beacon)),
+                    Err(err) => log::warn!("skipping malformed beacon on {}: {err}", publish.topic),
+                }
+            }
+        }
+    }
+
+    /// Ask the broker to close the connection.
+    pub fn disconnect(&self) -> Result<(), MqttError> {
+        self.client.disconnect()?;
+        Ok(())
+    }
+}
+
+/// Errors returned by the MQTT transport.
+#[derive(Debug)]
+pub enum MqttError {
+    /// The client request queue rejected a request.
+    Client(Box<rumqttc::ClientError>),
+    /// The broker connection failed.
+    Connection(Box<rumqttc::ConnectionError>),
+    /// A payload could not be encoded.
+    Json(serde_json::Error),
+    /// The connection's event loop has shut down.
+    Disconnected,
+}
+
+impl fmt::Display for MqttError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Client(err) => write!(f, "{err}"),
+            Self::Connection(err) => write!(f, "{err}"),
+            Self::Json(err) => write!(f, "{err}"),
+            Self::Disconnected => write!(f, "MQTT connection closed"),
+        }
+    }
+}
+
+impl std::error::Error for MqttError {}
+
+impl From<rumqttc::ClientError> for MqttError {
+    fn from(err: rumqttc::ClientError) -> Self {
+        Self::Client(Box::new(err))
+    }
+}
+
+impl From<rumqttc::ConnectionError> for MqttError {
+    fn from(err: rumqttc::ConnectionError) -> Self {
+        Self::Connection(Box::new(err))
+    }
+}
+
+impl From<serde_json::Error> for MqttError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topics_follow_prefix_layout() {
+        let config = MqttConfig::new("localhost", 1883, "peer-a");
+        assert_eq!(config.beacon_topic(7), "phenorv/beacons/7");
+        assert_eq!(config.beacon_wildcard(), "phenorv/beacons/+");
+        assert_eq!(config.event_topic(), "phenorv/events/peer-a");
+        assert!(config.is_beacon_topic("phenorv/beacons/7"));
+        assert!(!config.is_beacon_topic("phenorv/events/peer-a"));
+        assert!(!config.is_beacon_topic("phenorv/beacons/7/extra"));
+    }
+}
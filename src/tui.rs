@@ -0,0 +1,295 @@
+//! Terminal dashboard for live matching sessions.
+//!
+//! A [`Dashboard`] accumulates what a field operator needs to see during a
+//! rendezvous attempt (per-dimension distance to the target, window fill,
+//! recent match state transitions, and time left in the current salt epoch)
+//! and draws it with `ratatui`. The `phenorv tui` command feeds it from a
+//! live stream; the type itself holds no terminal state, so it can be
+//! rendered into any `ratatui` backend.
+
+use std::collections::VecDeque;
+
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph};
+use ratatui::Frame;
+
+use crate::matching::MatchWindow;
+use crate::pattern::{normalized_to_array, NormalizedPattern, DIMENSIONS};
+
+/// Number of match state transitions kept for display.
+pub const TRANSITION_HISTORY: usize = 8;
+
+/// A change between searching and matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    /// Zero-based observation index at which the state changed.
+    pub index: usize,
+    /// Whether the matcher became matched (`true`) or lost the match.
+    pub matched: bool,
+}
+
+/// Salt epoch shown in the dashboard header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochStatus {
+    /// Current epoch index.
+    pub epoch: u64,
+    /// Seconds until the next epoch begins.
+    pub secs_remaining: u64,
+}
+
+/// State displayed by the matching dashboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dashboard {
+    /// Matching threshold in normalized space.
+    epsilon: f32,
+    /// Absolute per-dimension differences of the latest observation.
+    differences: Option<[f32; 9]>,
+    /// Euclidean distance of the latest observation.
+    distance: Option<f32>,
+    /// Matches in the window and window capacity.
+    window_fill: (usize, usize),
+    /// Whether the latest observation completed a stable match.
+    matched: bool,
+    /// Observations seen so far.
+    observations: usize,
+    /// Most recent transitions, newest last.
+    transitions: VecDeque<Transition>,
+    /// Current salt epoch, if salts rotate.
+    epoch: Option<EpochStatus>,
+    /// Free-form status line, e.g. the end of the stream.
+    status: Option<String>,
+}
+
+impl Dashboard {
+    /// Create an empty dashboard for a threshold and window size.
+    pub fn new(epsilon: f32, window_size: usize) -> Self {
+        Self {
+            epsilon,
+            differences: None,
+            distance: None,
+            window_fill: (0, window_size),
+            matched: false,
+            observations: 0,
+            transitions: VecDeque::with_capacity(TRANSITION_HISTORY),
+            epoch: None,
+            status: None,
+        }
+    }
+
+    /// Record one observation and the matcher's verdict on it.
+    pub fn record(
+        &mut self,
+        measured: &NormalizedPattern,
+        target: &NormalizedPattern,
+        matched: bool,
+        window: &MatchWindow<Vec<u64>>,
+    ) {
+        let mut differences = [0.0f32; 9];
+        for ((diff, m), t) in differences
+            .iter_mut()
+            .zip(normalized_to_array(measured))
+            .zip(normalized_to_array(target))
+        {
+            *diff = (m - t).abs();
+        }
+        self.distance = Some(differences.iter().map(|d| d * d).sum::<f32>().sqrt());
+        self.differences = Some(differences);
+        self.window_fill = (window.matches(), window.capacity());
+        if matched != self.matched {
+            if self.transitions.len() == TRANSITION_HISTORY {
+                self.transitions.pop_front();
+            }
+            self.transitions.push_back(Transition {
+                index: self.observations,
+                matched,
+            });
+        }
+        self.matched = matched;
+        self.observations += 1;
+    }
+
+    /// Set the salt epoch shown in the header.
+    pub fn set_epoch(&mut self, epoch: EpochStatus) {
+        self.epoch = Some(epoch);
+    }
+
+    /// Set a status message shown in the header, such as a stream error.
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = Some(status.into());
+    }
+
+    /// Whether the latest observation completed a stable match.
+    pub fn is_matched(&self) -> bool {
+        self.matched
+    }
+
+    /// Observations recorded so far.
+    pub fn observations(&self) -> usize {
+        self.observations
+    }
+
+    /// Recent match state transitions, oldest first.
+    pub fn transitions(&self) -> impl Iterator<Item = &Transition> {
+        self.transitions.iter()
+    }
+
+    /// Draw the dashboard over the whole frame.
+    pub fn render(&self, frame: &mut Frame) {
+        let [header, dimensions, window, transitions] = Layout::vertical([
+            Constraint::Length(4),
+            Constraint::Length(9 + 2),
+            Constraint::Length(3),
+            Constraint::Min(3),
+        ])
+        .areas(frame.area());
+        self.render_header(frame, header);
+        self.render_dimensions(frame, dimensions);
+        self.render_window(frame, window);
+        self.render_transitions(frame, transitions);
+    }
+
+    fn render_header(&self, frame: &mut Frame, area: Rect) {
+        let state = if self.matched {
+            Span::styled(
+                " MATCH ",
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Span::styled(" SEARCHING ", Style::default().fg(Color::Yellow))
+        };
+        let distance = match self.distance {
+            Some(distance) => format!("distance {distance:.4} / epsilon {:.4}", self.epsilon),
+            None => format!("waiting for data / epsilon {:.4}", self.epsilon),
+        };
+        let epoch = match self.epoch {
+            Some(epoch) => format!(
+                "epoch {} ends in {}:{:02}",
+                epoch.epoch,
+                epoch.secs_remaining / 60,
+                epoch.secs_remaining % 60
+            ),
+            None => "fixed salt".to_string(),
+        };
+        let mut lines = vec![
+            Line::from(vec![
+                state,
+                Span::raw(format!("  {distance}  observations {}", self.observations)),
+            ]),
+            Line::from(epoch),
+        ];
+        if let Some(status) = &self.status {
+            lines[1].spans.push(Span::raw(format!("  {status}")));
+        }
+        let block = Block::default().borders(Borders::ALL).title("phenorv");
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn render_dimensions(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("distance to target per dimension");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let rows = Layout::vertical([Constraint::Length(1); 9]).split(inner);
+        let differences = self.differences.unwrap_or_default();
+        for ((row, info), diff) in rows.iter().zip(DIMENSIONS).zip(differences) {
+            let color = if diff <= self.epsilon {
+                Color::Green
+            } else {
+                Color::Red
+            };
+            let gauge = Gauge::default()
+                .gauge_style(Style::default().fg(color))
+                .ratio(f64::from(diff.clamp(0.0, 1.0)))
+                .label(format!("{:<15}{diff:.3}", info.key));
+            frame.render_widget(gauge, *row);
+        }
+    }
+
+    fn render_window(&self, frame: &mut Frame, area: Rect) {
+        let (matches, capacity) = self.window_fill;
+        let gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title("window"))
+            .gauge_style(Style::default().fg(Color::Cyan))
+            .ratio(matches as f64 / capacity.max(1) as f64)
+            .label(format!("{matches}/{capacity} within epsilon"));
+        frame.render_widget(gauge, area);
+    }
+
+    fn render_transitions(&self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .transitions
+            .iter()
+            .rev()
+            .map(|transition| {
+                let text = if transition.matched {
+                    format!("#{}: match acquired", transition.index)
+                } else {
+                    format!("#{}: match lost", transition.index)
+                };
+                ListItem::new(text)
+            })
+            .collect();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("transitions (q to quit)");
+        frame.render_widget(List::new(items).block(block), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::{Matcher, MatchingConfig};
+    use crate::pattern::SubmodalityPattern;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn dashboard_tracks_transitions_and_renders() {
+        let target = SubmodalityPattern::zeros();
+        let mut far = target.clone();
+        far.brightness = 1.0;
+        let mut matcher = Matcher::new(MatchingConfig::new(0.1, 2));
+        let mut dashboard = Dashboard::new(0.1, 2);
+        for measured in [&target, &target, &far, &target, &target] {
+            let matched = matcher.observe(measured, &target);
+            dashboard.record(
+                &measured.normalize(),
+                &target.normalize(),
+                matched,
+                matcher.window(),
+            );
+        }
+        let transitions: Vec<_> = dashboard
+            .transitions()
+            .map(|t| (t.index, t.matched))
+            .collect();
+        assert_eq!(transitions, [(1, true), (2, false), (4, true)]);
+        dashboard.set_epoch(EpochStatus {
+            epoch: 7,
+            secs_remaining: 75,
+        });
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 30)).expect("terminal");
+        terminal
+            .draw(|frame| dashboard.render(frame))
+            .expect("draw");
+        let text: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(text.contains("MATCH"));
+        assert!(text.contains("epoch 7 ends in 1:15"));
+        assert!(text.contains("2/2 within epsilon"));
+        assert!(text.contains("brightness"));
+    }
+}
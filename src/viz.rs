@@ -0,0 +1,274 @@
+//! SVG charts for matching sessions and simulation output.
+//!
+//! Every function renders into an in-memory SVG document and returns it as a
+//! string, leaving file handling to the caller. Charts are deliberately plain
+//! so they can be embedded in papers or notebooks without restyling.
+
+use std::fmt;
+
+use plotters::prelude::*;
+
+use crate::sim::{RocPoint, SweepResult};
+
+/// Default chart size in pixels.
+pub const DEFAULT_SIZE: (u32, u32) = (800, 480);
+
+/// Fill used for the within-epsilon band.
+const BAND: RGBAColor = RGBAColor(46, 139, 87, 0.15);
+
+/// Plot per-observation distance with the `epsilon` acceptance band shaded.
+pub fn distance_over_time_svg(distances: &[f32], epsilon: f32) -> Result<String, VizError> {
+    if distances.is_empty() {
+        return Err(VizError::EmptyData);
+    }
+    let x_max = distances.len().max(2) as f32 - 1.0;
+    let y_max = distances.iter().copied().fold(epsilon, f32::max) * 1.1;
+
+    render(|root| {
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Distance to target", ("sans-serif", 20))
+            .margin(12)
+            .x_label_area_size(36)
+            .y_label_area_size(48)
+            .build_cartesian_2d(0f32..x_max, 0f32..y_max)?;
+        chart
+            .configure_mesh()
+            .x_desc("observation")
+            .y_desc("distance")
+            .draw()?;
+        chart.draw_series(std::iter::once(Rectangle::new(
+            [(0.0, 0.0), (x_max, epsilon)],
+            BAND.filled(),
+        )))?;
+        chart.draw_series(LineSeries::new(
+            [(0.0, epsilon), (x_max, epsilon)],
+            GREEN.stroke_width(1),
+        ))?;
+        chart.draw_series(LineSeries::new(
+            distances.iter().enumerate().map(|(i, d)| (i as f32, *d)),
+            BLUE.stroke_width(2),
+        ))?;
+        Ok(())
+    })
+}
+
+/// Plot a receiver operating characteristic curve.
+pub fn roc_curve_svg(points: &[RocPoint]) -> Result<String, VizError> {
+    if points.is_empty() {
+        return Err(VizError::EmptyData);
+    }
+
+    render(|root| {
+        let mut chart = ChartBuilder::on(&root)
+            .caption("ROC curve", ("sans-serif", 20))
+            .margin(12)
+            .x_label_area_size(36)
+            .y_label_area_size(48)
+            .build_cartesian_2d(0f64..1f64, 0f64..1f64)?;
+        chart
+            .configure_mesh()
+            .x_desc("false positive rate")
+            .y_desc("true positive rate")
+            .draw()?;
+        chart.draw_series(LineSeries::new([(0.0, 0.0), (1.0, 1.0)], BLACK.mix(0.3)))?;
+        chart.draw_series(LineSeries::new(
+            points
+                .iter()
+                .map(|p| (p.false_positive_rate, p.true_positive_rate)),
+            BLUE.stroke_width(2),
+        ))?;
+        Ok(())
+    })
+}
+
+/// Plot a histogram of distances, optionally marking `epsilon`.
+pub fn distance_histogram_svg(
+    distances: &[f32],
+    bins: usize,
+    epsilon: Option<f32>,
+) -> Result<String, VizError> {
+    if distances.is_empty() {
+        return Err(VizError::EmptyData);
+    }
+    if bins == 0 {
+        return Err(VizError::InvalidBins);
+    }
+    let max = distances
+        .iter()
+        .copied()
+        .chain(epsilon)
+        .fold(0.0f32, f32::max)
+        .max(f32::EPSILON);
+    let width = max / bins as f32;
+    let mut counts = vec![0u32; bins];
+    for &distance in distances {
+        let bin = ((distance / width) as usize).min(bins - 1);
+        counts[bin] += 1;
+    }
+    let y_max = counts.iter().copied().max().unwrap_or(1) as f32 * 1.1;
+
+    render(|root| {
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Distance histogram", ("sans-serif", 20))
+            .margin(12)
+            .x_label_area_size(36)
+            .y_label_area_size(48)
+            .build_cartesian_2d(0f32..max, 0f32..y_max)?;
+        chart
+            .configure_mesh()
+            .x_desc("distance")
+            .y_desc("count")
+            .draw()?;
+        chart.draw_series(counts.iter().enumerate().map(|(i, &count)| {
+            let x0 = i as f32 * width;
+            Rectangle::new([(x0, 0.0), (x0 + width, count as f32)], BLUE.mix(0.6).filled())
+        }))?;
+        if let Some(epsilon) = epsilon {
+            chart.draw_series(LineSeries::new(
+                [(epsilon, 0.0), (epsilon, y_max)],
+                GREEN.stroke_width(2),
+            ))?;
+        }
+        Ok(())
+    })
+}
+
+/// Plot single-peer match probability over an epsilon × window-size sweep.
+///
+/// Cell shading is proportional to the probability relative to the largest
+/// value in the sweep.
+pub fn sweep_heatmap_svg(sweep: &SweepResult) -> Result<String, VizError> {
+    if sweep.epsilons.is_empty() || sweep.window_sizes.is_empty() {
+        return Err(VizError::EmptyData);
+    }
+    let columns = sweep.epsilons.len();
+    let rows = sweep.window_sizes.len();
+    let max = sweep
+        .results
+        .iter()
+        .flatten()
+        .map(|r| r.single_match_probability)
+        .fold(0.0f64, f64::max)
+        .max(f64::MIN_POSITIVE);
+
+    let epsilon_label = |x: &f64| {
+        sweep
+            .epsilons
+            .get(*x as usize)
+            .map(|e| format!("{e}"))
+            .unwrap_or_default()
+    };
+    let window_label = |y: &f64| {
+        sweep
+            .window_sizes
+            .get(*y as usize)
+            .map(|w| format!("{w}"))
+            .unwrap_or_default()
+    };
+
+    render(|root| {
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Single-peer match probability", ("sans-serif", 20))
+            .margin(12)
+            .x_label_area_size(36)
+            .y_label_area_size(48)
+            .build_cartesian_2d(0f64..columns as f64, 0f64..rows as f64)?;
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .x_labels(columns + 1)
+            .y_labels(rows + 1)
+            .x_label_formatter(&epsilon_label)
+            .y_label_formatter(&window_label)
+            .x_desc("epsilon")
+            .y_desc("window size")
+            .draw()?;
+        chart.draw_series(sweep.results.iter().enumerate().flat_map(|(row, cells)| {
+            cells.iter().enumerate().map(move |(column, result)| {
+                let intensity = result.single_match_probability / max;
+                let shade = (255.0 * (1.0 - intensity)) as u8;
+                Rectangle::new(
+                    [
+                        (column as f64, row as f64),
+                        (column as f64 + 1.0, row as f64 + 1.0),
+                    ],
+                    RGBColor(shade, shade, 255).filled(),
+                )
+            })
+        }))?;
+        Ok(())
+    })
+}
+
+fn render<F>(draw: F) -> Result<String, VizError>
+where
+    F: FnOnce(DrawingArea<SVGBackend<'_>, plotters::coord::Shift>) -> Result<(), VizError>,
+{
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, DEFAULT_SIZE).into_drawing_area();
+        root.fill(&WHITE)?;
+        draw(root.clone())?;
+        root.present()?;
+    }
+    Ok(svg)
+}
+
+/// Errors returned while rendering charts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VizError {
+    /// There was nothing to plot.
+    EmptyData,
+    /// A histogram was requested with zero bins.
+    InvalidBins,
+    /// The drawing backend reported an error.
+    Drawing(String),
+}
+
+impl fmt::Display for VizError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyData => write!(f, "no data to plot"),
+            Self::InvalidBins => write!(f, "histogram needs at least one bin"),
+            Self::Drawing(msg) => write!(f, "chart rendering failed: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for VizError {}
+
+impl<E: std::error::Error + Send + Sync> From<DrawingAreaErrorKind<E>> for VizError {
+    fn from(err: DrawingAreaErrorKind<E>) -> Self {
+        Self::Drawing(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::roc_curve;
+
+    #[test]
+    fn distance_chart_is_svg() {
+        let svg = distance_over_time_svg(&[0.4, 0.2, 0.05, 0.04], 0.1).expect("render");
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("Distance to target"));
+    }
+
+    #[test]
+    fn histogram_and_roc_render() {
+        let distances = [0.1, 0.2, 0.2, 0.7, 1.1];
+        assert!(distance_histogram_svg(&distances, 4, Some(0.3)).is_ok());
+        assert_eq!(
+            distance_histogram_svg(&distances, 0, None),
+            Err(VizError::InvalidBins)
+        );
+        let points = roc_curve(&distances[..3], &distances[3..]);
+        assert!(roc_curve_svg(&points).expect("render").contains("ROC curve"));
+    }
+
+    #[test]
+    fn empty_input_is_rejected() {
+        assert_eq!(distance_over_time_svg(&[], 0.1), Err(VizError::EmptyData));
+    }
+}
@@ -0,0 +1,10 @@
+#![cfg(feature = "serde")]
+
+use phenomenological_rendezvous::conformance::run_dir;
+
+#[test]
+fn shipped_vectors_pass() {
+    let report = run_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/vectors")).expect("load vectors");
+    assert!(report.passed > 0);
+    assert!(report.all_passed(), "failed cases: {:?}", report.cases);
+}
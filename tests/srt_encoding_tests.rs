@@ -62,3 +62,16 @@ fn srt_encoding_vector_beta() {
     };
     assert_pattern_close(&actual, &expected, 1e-3);
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn generated_vector_bundle_matches_shipped_file() {
+    use phenomenological_rendezvous::srt::test_vectors::{bundle, DEFAULT_SEED};
+
+    let shipped = include_str!("../vectors/generated-seed-0.json");
+    assert_eq!(
+        serde_json::to_string_pretty(&bundle(DEFAULT_SEED)).expect("serialize"),
+        shipped.trim_end(),
+        "regenerate vectors/generated-seed-0.json from test_vectors::bundle(DEFAULT_SEED)"
+    );
+}